@@ -0,0 +1,115 @@
+//! Reports the memory used to hold a large set of HTTPRoutes.
+//!
+//! Controllers commonly cache every HTTPRoute in a cluster, so the in-memory
+//! size of the route types matters more than their (de)serialization speed.
+//! This benchmark counts the bytes allocated while decoding and holding
+//! `ROUTES` representative routes and reports the per-route cost alongside
+//! the inline sizes of the types that make up a route:
+//!
+//! ```sh
+//! cargo bench --bench memory
+//! ```
+
+use k8s_gateway_api::*;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    mem::size_of,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+const ROUTES: usize = 10_000;
+
+/// Tracks the number of bytes currently allocated.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn main() {
+    println!("inline sizes (bytes):");
+    println!("  HttpRoute           {:>5}", size_of::<HttpRoute>());
+    println!("  HttpRouteRule       {:>5}", size_of::<HttpRouteRule>());
+    println!("  HttpRouteMatch      {:>5}", size_of::<HttpRouteMatch>());
+    println!("  HttpRouteFilter     {:>5}", size_of::<HttpRouteFilter>());
+    println!("  HttpBackendRef      {:>5}", size_of::<HttpBackendRef>());
+
+    // Routes are decoded from bytes, as they are when read from the API
+    // server, so that collections are allocated as a client would allocate
+    // them.
+    let json = (0..ROUTES)
+        .map(|i| serde_json::to_vec(&route(i)).expect("route must encode"))
+        .collect::<Vec<_>>();
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let routes = json
+        .iter()
+        .map(|r| serde_json::from_slice::<HttpRoute>(r).expect("route must decode"))
+        .collect::<Vec<_>>();
+    let used = ALLOCATED.load(Ordering::Relaxed) - before;
+
+    println!(
+        "{} routes: {} KiB ({} bytes per route)",
+        routes.len(),
+        used / 1024,
+        used / routes.len()
+    );
+}
+
+/// Returns a route with a mix of matches, filters, and backends, modeled on
+/// the upstream examples.
+fn route(i: usize) -> serde_json::Value {
+    serde_json::json!({
+        "apiVersion": "gateway.networking.k8s.io/v1beta1",
+        "kind": "HTTPRoute",
+        "metadata": {
+            "name": format!("route-{}", i),
+            "namespace": "default",
+        },
+        "spec": {
+            "parentRefs": [{ "name": "gateway" }],
+            "hostnames": [format!("app-{}.example.com", i)],
+            "rules": [
+                {
+                    "matches": [{
+                        "path": { "type": "PathPrefix", "value": "/api" },
+                        "headers": [{ "type": "Exact", "name": "version", "value": "v2" }],
+                    }],
+                    "filters": [{
+                        "type": "RequestHeaderModifier",
+                        "requestHeaderModifier": {
+                            "add": [{ "name": "x-route", "value": "api" }],
+                        },
+                    }],
+                    "backendRefs": [
+                        { "name": "api-v2", "port": 8080, "weight": 90 },
+                        { "name": "api-v3", "port": 8080, "weight": 10 },
+                    ],
+                },
+                {
+                    "matches": [{ "path": { "type": "Exact", "value": "/login" } }],
+                    "filters": [{
+                        "type": "RequestRedirect",
+                        "requestRedirect": { "scheme": "https", "statusCode": 301 },
+                    }],
+                },
+                {
+                    "backendRefs": [{ "name": "web", "port": 80 }],
+                },
+            ],
+        },
+    })
+}
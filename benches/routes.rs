@@ -0,0 +1,163 @@
+//! Benchmarks compiling and evaluating large sets of HTTPRoutes.
+//!
+//! Each route has three rules and attaches to one of 16 listeners:
+//!
+//! * `compile` compiles snapshots of 1,000 and 10,000 routes into a routing
+//!   table;
+//! * `recompile` updates the table of 10,000 routes after one of them moves
+//!   to another listener;
+//! * `route_request` selects the virtual host for a request by its hostname,
+//!   then finds the first of its routes that matches.
+//!
+//! Virtual hosts are selected with a few map lookups, but the routes of a
+//! virtual host are matched one at a time, in precedence order: the table
+//! does not build match trees or intern header names, so routing a request
+//! costs time proportional to the number of routes in its virtual host.
+//!
+//! ```sh
+//! cargo bench --bench routes
+//! ```
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use k8s_gateway_api::{
+    ir,
+    matcher::{HttpRequest, Matcher},
+    snapshot::{Event, ObjectKey, SnapshotStore},
+    *,
+};
+
+/// Returns a Gateway with `listeners` HTTP listeners on port 80, each for a
+/// different wildcard hostname.
+fn gateway(listeners: usize) -> Gateway {
+    let listeners = (0..listeners)
+        .map(|i| {
+            serde_json::json!({
+                "name": format!("http-{}", i),
+                "port": 80,
+                "protocol": "HTTP",
+                "hostname": format!("*.domain-{}.example.com", i),
+                "allowedRoutes": { "namespaces": { "from": "All" } },
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::from_value(serde_json::json!({
+        "apiVersion": "gateway.networking.k8s.io/v1beta1",
+        "kind": "Gateway",
+        "metadata": { "namespace": "gateway", "name": "gw" },
+        "spec": { "gatewayClassName": "bench", "listeners": listeners },
+    }))
+    .expect("gateway must decode")
+}
+
+/// Returns a route with three rules, each with a path, header, and query
+/// parameter match, for a hostname under one of the listeners' domains.
+fn route(i: usize, listeners: usize) -> HttpRoute {
+    let rules = (0..3)
+        .map(|j| {
+            serde_json::json!({
+                "matches": [{
+                    "path": { "type": "PathPrefix", "value": format!("/svc-{}/v{}", i, j) },
+                    "headers": [{ "type": "Exact", "name": "x-tenant", "value": format!("t{}", i % 7) }],
+                    "queryParams": [{ "type": "Exact", "name": "debug", "value": "1" }],
+                }],
+                "backendRefs": [{ "name": format!("svc-{}", i), "port": 8080 }],
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::from_value(serde_json::json!({
+        "apiVersion": "gateway.networking.k8s.io/v1beta1",
+        "kind": "HTTPRoute",
+        "metadata": { "namespace": format!("ns-{}", i % 50), "name": format!("route-{}", i) },
+        "spec": {
+            "parentRefs": [{ "namespace": "gateway", "name": "gw" }],
+            "hostnames": [format!("app-{}.domain-{}.example.com", i % 100, i % listeners)],
+            "rules": rules,
+        },
+    }))
+    .expect("route must decode")
+}
+
+const LISTENERS: usize = 16;
+
+fn store(routes: usize) -> SnapshotStore {
+    let mut store = SnapshotStore::default();
+    store.apply(Event::Applied(gateway(LISTENERS)));
+    store.apply(Event::Restarted(
+        (0..routes).map(|i| route(i, LISTENERS)).collect(),
+    ));
+    store
+}
+
+fn snapshot(routes: usize) -> snapshot::Snapshot {
+    store(routes)
+        .get(&ObjectKey::new("gateway", "gw"))
+        .expect("snapshot must exist")
+        .clone()
+}
+
+fn compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compile");
+    group.sample_size(10);
+    for routes in [1_000, 10_000] {
+        let snapshot = snapshot(routes);
+        group.bench_with_input(BenchmarkId::from_parameter(routes), &snapshot, |b, s| {
+            b.iter(|| ir::compile(s))
+        });
+    }
+    group.finish();
+}
+
+fn recompile(c: &mut Criterion) {
+    let key = ObjectKey::new("gateway", "gw");
+    let mut store = store(10_000);
+    let compiler = ir::Compiler::default();
+    let table = compiler.compile(store.get(&key).expect("snapshot must exist"));
+
+    // Moves one route to another listener's domain.
+    let mut updated = route(5_000, LISTENERS);
+    updated.spec.hostnames = Some(vec!["moved.domain-1.example.com".to_string()]);
+    let changed = ir::Changed::HttpRoute(ObjectKey::from_meta(&updated.metadata));
+    store.apply(Event::Applied(updated));
+    let snapshot = store.get(&key).expect("snapshot must exist");
+
+    c.bench_function("recompile/10000", |b| {
+        b.iter_batched(
+            || table.clone(),
+            |mut table| {
+                compiler.recompile(&mut table, snapshot, &changed);
+                table
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn route_request(c: &mut Criterion) {
+    let table = ir::compile(&snapshot(10_000));
+    // Requests the last rule of one of the last routes.
+    let i = 9_990;
+    let host = format!("app-{}.domain-{}.example.com", i % 100, i % 16);
+    let target = format!("/svc-{}/v2/items?debug=1", i);
+    let headers = [HttpHeader {
+        name: "X-Tenant".to_string(),
+        value: format!("t{}", i % 7),
+    }];
+    let req = HttpRequest::new("GET", &target).with_headers(&headers);
+    let matcher = Matcher::new();
+
+    c.bench_function("route_request/10000", |b| {
+        b.iter(|| {
+            let vhost = table
+                .virtual_host(80, &host)
+                .expect("virtual host must exist");
+            vhost
+                .routes
+                .iter()
+                .find(|r| matcher.http_match(&r.matcher, &req))
+                .expect("route must match")
+        })
+    });
+}
+
+criterion_group!(benches, compile, recompile, route_request);
+criterion_main!(benches);
@@ -0,0 +1,5 @@
+#![no_main]
+
+libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+    k8s_gateway_api::fuzz::fuzz_deserialize_httproute(data);
+});
@@ -0,0 +1,142 @@
+use k8s_gateway_api::filter::replace_prefix_match;
+
+/// The `ReplacePrefixMatch` examples from the Gateway API documentation, as
+/// (prefix, path, replacement, result).
+const UPSTREAM: &[(&str, &str, &str, &str)] = &[
+    ("/foo", "/foo/bar", "/xyz", "/xyz/bar"),
+    ("/foo", "/foo/bar", "/xyz/", "/xyz/bar"),
+    ("/foo/", "/foo/bar", "/xyz", "/xyz/bar"),
+    ("/foo/", "/foo/bar", "/xyz/", "/xyz/bar"),
+    ("/foo", "/foo", "/xyz", "/xyz"),
+    ("/foo", "/foo/", "/xyz", "/xyz/"),
+    ("/foo/", "/foo/", "/xyz", "/xyz/"),
+    ("/foo", "/foo", "", "/"),
+    ("/foo", "/foo/", "", "/"),
+    ("/foo", "/foo/bar", "/", "/bar"),
+    ("/foo/", "/foo/", "/", "/"),
+    ("/foo", "/foo", "/", "/"),
+    ("/foo/", "/foo", "/xyz/", "/xyz"),
+];
+
+/// The table in the documentation of `replace_prefix_match`, row by row, as
+/// (prefix, path, replacement, result).
+const DOCUMENTED: &[(&str, &str, &str, Option<&str>)] = &[
+    ("/foo", "/foo/bar", "/xyz", Some("/xyz/bar")),
+    ("/foo", "/foo/bar", "/xyz/", Some("/xyz/bar")),
+    ("/foo/", "/foo/bar", "/xyz", Some("/xyz/bar")),
+    ("/foo", "/foo", "/xyz", Some("/xyz")),
+    ("/foo", "/foo/", "/xyz", Some("/xyz/")),
+    ("/foo/", "/foo", "/xyz/", Some("/xyz")),
+    ("/foo", "/foo/bar", "/", Some("/bar")),
+    ("/foo", "/foo", "/", Some("/")),
+    ("/foo", "/foo/", "", Some("/")),
+    ("/", "/", "/xyz", Some("/xyz/")),
+    ("/", "/bar", "/xyz", Some("/xyz/bar")),
+    ("/foo", "/foobar", "/xyz", None),
+    ("/foo", "/bar/foo", "/xyz", None),
+];
+
+#[test]
+fn replace_prefix_match_documented_table() {
+    for (prefix, path, replacement, expected) in DOCUMENTED {
+        assert_eq!(
+            replace_prefix_match(path, prefix, replacement).as_deref(),
+            *expected,
+            "prefix={:?} path={:?} replacement={:?}",
+            prefix,
+            path,
+            replacement
+        );
+    }
+}
+
+#[test]
+fn replace_prefix_match_upstream_examples() {
+    for (prefix, path, replacement, expected) in UPSTREAM {
+        assert_eq!(
+            replace_prefix_match(path, prefix, replacement).as_deref(),
+            Some(*expected),
+            "prefix={:?} path={:?} replacement={:?}",
+            prefix,
+            path,
+            replacement
+        );
+    }
+}
+
+#[test]
+fn replace_prefix_match_every_combination() {
+    // Every combination of a prefix and replacement with and without a
+    // trailing slash, for paths equal to, ending in a slash after, and
+    // extending the prefix.
+    let prefixes = ["/foo", "/foo/", "/foo//"];
+    let replacements = [
+        ("", ""),
+        ("/", ""),
+        ("/xyz", "/xyz"),
+        ("/xyz/", "/xyz"),
+        ("/x/y", "/x/y"),
+    ];
+    let paths = [
+        ("/foo", ""),
+        ("/foo/", "/"),
+        ("/foo/bar", "/bar"),
+        ("/foo/bar/", "/bar/"),
+        ("/foo//bar", "//bar"),
+    ];
+    for prefix in prefixes {
+        for (replacement, base) in replacements {
+            for (path, rest) in paths {
+                let expected = match format!("{}{}", base, rest) {
+                    p if p.is_empty() => "/".to_string(),
+                    p => p,
+                };
+                assert_eq!(
+                    replace_prefix_match(path, prefix, replacement),
+                    Some(expected),
+                    "prefix={:?} path={:?} replacement={:?}",
+                    prefix,
+                    path,
+                    replacement
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn replace_prefix_match_root_prefix() {
+    assert_eq!(
+        replace_prefix_match("/", "/", "/xyz").as_deref(),
+        Some("/xyz/")
+    );
+    assert_eq!(
+        replace_prefix_match("/bar", "/", "/xyz").as_deref(),
+        Some("/xyz/bar")
+    );
+    assert_eq!(
+        replace_prefix_match("/bar", "/", "/").as_deref(),
+        Some("/bar")
+    );
+    assert_eq!(replace_prefix_match("/", "/", "").as_deref(), Some("/"));
+}
+
+#[test]
+fn replace_prefix_match_segments() {
+    for path in ["/foobar", "/fo", "/", "/bar/foo", "/Foo/bar"] {
+        assert_eq!(
+            replace_prefix_match(path, "/foo", "/xyz"),
+            None,
+            "path={:?}",
+            path
+        );
+    }
+    assert_eq!(
+        replace_prefix_match("/foo/bar/baz", "/foo/bar", "/xyz").as_deref(),
+        Some("/xyz/baz")
+    );
+    assert_eq!(
+        replace_prefix_match("/foo/barbaz", "/foo/bar", "/xyz"),
+        None
+    );
+}
@@ -0,0 +1,61 @@
+use k8s_gateway_api::{testing::golden, HttpRouteSpec};
+
+#[test]
+fn upstream_examples_round_trip() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/upstream");
+    let report = golden::check_dir(fixtures).expect("failed to read fixtures");
+    report.assert_ok();
+    assert_ne!(report.checked, 0, "no objects were checked");
+}
+
+#[test]
+fn every_kind_round_trips() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/golden");
+    let report = golden::check_dir(fixtures).expect("failed to read fixtures");
+    report.assert_ok();
+    assert_eq!(report.unchecked_kinds(), Vec::<&str>::new());
+}
+
+#[test]
+fn duplicate_keys() {
+    let paths = golden::duplicate_keys(br#"{"a":[{"b":1,"b":2}],"c":{"d/e":1,"d/e":2}}"#)
+        .expect("valid JSON");
+    assert_eq!(paths, ["/a/0/b", "/c/d~1e"]);
+
+    let report = golden::check_json(
+        "duplicate-kind.json",
+        br#"{"apiVersion":"gateway.networking.k8s.io/v1beta1","kind":"Gateway","kind":"GatewayClass"}"#,
+    );
+    assert!(
+        matches!(
+            &report.failures[..],
+            [golden::Failure { problem: golden::Problem::DuplicateKeys(paths), .. }]
+                if paths == &["/kind"]
+        ),
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn flattened_backend_refs_are_not_swallowed() {
+    let spec = |backend_ref| {
+        serde_json::from_value::<HttpRouteSpec>(serde_json::json!({
+            "rules": [{ "backendRefs": [backend_ref] }],
+        }))
+    };
+    let backend_ref = |spec: HttpRouteSpec| {
+        spec.rules.unwrap()[0].backend_refs.as_ref().unwrap()[0]
+            .backend_ref
+            .clone()
+    };
+
+    assert!(spec(serde_json::json!({ "nmae": "web", "port": 80 })).is_err());
+    assert!(spec(serde_json::json!({ "name": "web", "port": "http" })).is_err());
+
+    let only_filters = spec(serde_json::json!({ "filters": [] })).unwrap();
+    assert_eq!(backend_ref(only_filters), None);
+
+    let weighted = spec(serde_json::json!({ "name": "web", "port": 80, "weight": 2 })).unwrap();
+    assert_eq!(backend_ref(weighted).unwrap().weight, Some(2));
+}
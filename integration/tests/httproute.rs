@@ -1,6 +1,6 @@
 use k8s_gateway_api::{
-    BackendRef, HttpBackendRef, HttpHeaderMatch, HttpRoute, HttpRouteMatch, HttpRouteRule,
-    HttpRouteSpec,
+    BackendObjectReference, BackendRef, HttpBackendRef, HttpHeaderMatch, HttpRoute, HttpRouteMatch,
+    HttpRouteRule, HttpRouteSpec,
 };
 use kube::{api::PostParams, core::ObjectMeta};
 
@@ -23,20 +23,31 @@ async fn round_trip() {
             hostnames: Some(vec!["bar.example.com".to_string()]),
             rules: Some(vec![
                 HttpRouteRule {
+                    name: None,
                     backend_refs: Some(vec![
                         HttpBackendRef {
                             backend_ref: Some(BackendRef {
-                                name: "bar-v1".to_string(),
-                                port: 8080,
                                 weight: Some(90),
+                                inner: BackendObjectReference {
+                                    group: None,
+                                    kind: None,
+                                    name: "bar-v1".to_string(),
+                                    namespace: None,
+                                    port: Some(8080),
+                                },
                             }),
                             filters: None,
                         },
                         HttpBackendRef {
                             backend_ref: Some(BackendRef {
-                                name: "bar-v2".to_string(),
-                                port: 8080,
                                 weight: Some(10),
+                                inner: BackendObjectReference {
+                                    group: None,
+                                    kind: None,
+                                    name: "bar-v2".to_string(),
+                                    namespace: None,
+                                    port: Some(8080),
+                                },
                             }),
                             filters: None,
                         },
@@ -45,6 +56,7 @@ async fn round_trip() {
                     matches: None,
                 },
                 HttpRouteRule {
+                    name: None,
                     matches: Some(vec![HttpRouteMatch {
                         headers: Some(vec![HttpHeaderMatch::Exact {
                             name: "env".to_string(),
@@ -54,9 +66,14 @@ async fn round_trip() {
                     }]),
                     backend_refs: Some(vec![HttpBackendRef {
                         backend_ref: Some(BackendRef {
-                            name: "bar-v2".to_string(),
-                            port: 8080,
                             weight: None,
+                            inner: BackendObjectReference {
+                                group: None,
+                                kind: None,
+                                name: "bar-v2".to_string(),
+                                namespace: None,
+                                port: Some(8080),
+                            },
                         }),
                         filters: None,
                     }]),
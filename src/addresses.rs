@@ -0,0 +1,389 @@
+//! Helpers for Gateway address requests and assignments.
+//!
+//! A Gateway may request specific addresses in `spec.addresses`; the
+//! implementation reports the addresses actually bound to it in
+//! `status.addresses`. [`validate`] checks requested addresses against the
+//! address types an implementation supports and the constraints of
+//! [GEP-1651], [`diff`] compares requested
+//! addresses with assigned ones, and [`problem`] turns the result of both
+//! into the reason that the Gateway is not ready, if any.
+//!
+//! [GEP-1651]: https://gateway-api.sigs.k8s.io/geps/gep-1651/
+
+use crate::*;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::{fmt, net::IpAddr};
+
+/// The address type of textual IPv4 or IPv6 addresses. This is the default
+/// when an address does not specify a type.
+pub const IP_ADDRESS: &str = "IPAddress";
+
+/// The address type of DNS hostnames.
+pub const HOSTNAME: &str = "Hostname";
+
+/// The address type of opaque, implementation-specific identifiers of
+/// addresses (e.g. the name of a pre-allocated load balancer address).
+pub const NAMED_ADDRESS: &str = "NamedAddress";
+
+/// The maximum number of addresses a Gateway may request.
+pub const MAX_ADDRESSES: usize = 16;
+
+/// The `Accepted` condition reason used when a Gateway requests an address
+/// of a type that the implementation does not support.
+pub const UNSUPPORTED_ADDRESS: &str = "UnsupportedAddress";
+
+/// The `Ready` condition reason used when requested addresses have not been
+/// assigned to the Gateway.
+pub const ADDRESS_NOT_ASSIGNED: &str = "AddressNotAssigned";
+
+/// The `Ready` condition reason used when requested addresses cannot be used
+/// by the implementation, e.g. because their type is not supported.
+pub const ADDRESS_NOT_USABLE: &str = "AddressNotUsable";
+
+/// Indicates that a requested address cannot be used.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum AddressError {
+    /// The implementation does not support the address type.
+    UnsupportedType {
+        /// The index of the address in `spec.addresses`.
+        index: usize,
+        r#type: String,
+    },
+
+    /// The value is not valid for the address type.
+    InvalidValue {
+        /// The index of the address in `spec.addresses`.
+        index: usize,
+        r#type: String,
+        value: String,
+    },
+
+    /// The address was already requested.
+    Duplicate {
+        /// The index of the address in `spec.addresses`.
+        index: usize,
+
+        /// The index of the first request for the same address.
+        first: usize,
+        value: String,
+    },
+
+    /// The address's type differs from that of the first address, and the
+    /// implementation cannot bind addresses of different types to a Gateway.
+    MixedTypes {
+        /// The index of the address in `spec.addresses`.
+        index: usize,
+        r#type: String,
+        first_type: String,
+    },
+
+    /// More than [`MAX_ADDRESSES`] addresses were requested.
+    TooMany { count: usize },
+}
+
+/// The result of comparing requested addresses with assigned addresses.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AddressDiff<'a> {
+    /// Requested addresses that are assigned.
+    pub assigned: Vec<&'a GatewayAddress>,
+
+    /// Requested addresses that are not assigned.
+    pub unassigned: Vec<&'a GatewayAddress>,
+
+    /// Assigned addresses that were not requested. Implementations may assign
+    /// addresses from a pool when none are requested.
+    pub unrequested: Vec<&'a GatewayStatusAddress>,
+}
+
+/// The reason that a Gateway's addresses prevent it from being ready.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AddressProblem {
+    /// At least one requested address cannot be used.
+    NotUsable(Vec<AddressError>),
+
+    /// At least one requested address has not been assigned.
+    NotAssigned(Vec<GatewayAddress>),
+}
+
+/// Returns the type of an address, applying the default.
+pub fn address_type(r#type: Option<&str>) -> &str {
+    r#type.unwrap_or(IP_ADDRESS)
+}
+
+/// Validates requested addresses against the address types an
+/// implementation supports.
+///
+/// Values of the core types (`IPAddress` and `Hostname`) are checked for
+/// syntax; other types are only checked to be non-empty. Each address may
+/// only be requested once, and at most [`MAX_ADDRESSES`] addresses may be
+/// requested. IP addresses are compared by value, so `::1` and `0::1` are
+/// the same address.
+///
+/// Implementations that cannot bind addresses of different types to the same
+/// Gateway should also check [`validate_types_not_mixed`].
+pub fn validate(requested: &[GatewayAddress], supported: &[&str]) -> Vec<AddressError> {
+    let mut errors = Vec::new();
+    if requested.len() > MAX_ADDRESSES {
+        errors.push(AddressError::TooMany {
+            count: requested.len(),
+        });
+    }
+
+    for (index, addr) in requested.iter().enumerate() {
+        let ty = address_type(addr.r#type.as_deref());
+        if let Some(first) = requested[..index]
+            .iter()
+            .position(|a| same_address(a, addr))
+        {
+            errors.push(AddressError::Duplicate {
+                index,
+                first,
+                value: addr.value.clone(),
+            });
+            continue;
+        }
+
+        if !supported.contains(&ty) {
+            errors.push(AddressError::UnsupportedType {
+                index,
+                r#type: ty.to_string(),
+            });
+            continue;
+        }
+
+        let valid = match ty {
+            IP_ADDRESS => addr.value.parse::<IpAddr>().is_ok(),
+            HOSTNAME => is_dns_hostname(&addr.value),
+            _ => !addr.value.is_empty(),
+        };
+        if !valid {
+            errors.push(AddressError::InvalidValue {
+                index,
+                r#type: ty.to_string(),
+                value: addr.value.clone(),
+            });
+        }
+    }
+    errors
+}
+
+/// Returns errors for the requested addresses whose types differ from that of
+/// the first requested address.
+pub fn validate_types_not_mixed(requested: &[GatewayAddress]) -> Vec<AddressError> {
+    let first_type = match requested.first() {
+        Some(first) => address_type(first.r#type.as_deref()),
+        None => return Vec::new(),
+    };
+    requested
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(index, addr)| {
+            let ty = address_type(addr.r#type.as_deref());
+            if ty == first_type {
+                return None;
+            }
+            Some(AddressError::MixedTypes {
+                index,
+                r#type: ty.to_string(),
+                first_type: first_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Compares requested addresses with the addresses assigned to a Gateway.
+///
+/// Addresses are equal if their types (after defaulting) and values are
+/// equal.
+pub fn diff<'a>(
+    requested: &'a [GatewayAddress],
+    assigned: &'a [GatewayStatusAddress],
+) -> AddressDiff<'a> {
+    let is_assigned = |r: &GatewayAddress| {
+        assigned.iter().any(|a| {
+            address_type(a.r#type.as_deref()) == address_type(r.r#type.as_deref())
+                && a.value == r.value
+        })
+    };
+    let is_requested = |a: &GatewayStatusAddress| {
+        requested.iter().any(|r| {
+            address_type(a.r#type.as_deref()) == address_type(r.r#type.as_deref())
+                && a.value == r.value
+        })
+    };
+
+    let (assigned_reqs, unassigned) = requested.iter().partition(|r| is_assigned(r));
+    AddressDiff {
+        assigned: assigned_reqs,
+        unassigned,
+        unrequested: assigned.iter().filter(|a| !is_requested(a)).collect(),
+    }
+}
+
+/// Returns the reason that a Gateway's addresses prevent it from being
+/// ready, if any.
+///
+/// Unusable addresses take precedence over unassigned ones, since they can
+/// never be assigned.
+pub fn problem(errors: Vec<AddressError>, diff: &AddressDiff<'_>) -> Option<AddressProblem> {
+    if !errors.is_empty() {
+        return Some(AddressProblem::NotUsable(errors));
+    }
+    if !diff.unassigned.is_empty() {
+        let unassigned = diff.unassigned.iter().map(|a| (*a).clone()).collect();
+        return Some(AddressProblem::NotAssigned(unassigned));
+    }
+    None
+}
+
+/// Returns true if two requested addresses are the same address.
+fn same_address(a: &GatewayAddress, b: &GatewayAddress) -> bool {
+    let ty = address_type(a.r#type.as_deref());
+    if ty != address_type(b.r#type.as_deref()) {
+        return false;
+    }
+    if ty == IP_ADDRESS {
+        if let (Ok(a), Ok(b)) = (a.value.parse::<IpAddr>(), b.value.parse::<IpAddr>()) {
+            return a == b;
+        }
+    }
+    a.value == b.value
+}
+
+/// Returns true if `value` is a syntactically valid, non-wildcard DNS
+/// hostname (RFC 1123).
+fn is_dns_hostname(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+// === impl AddressDiff ===
+
+impl AddressDiff<'_> {
+    /// Returns true if all requested addresses are assigned.
+    pub fn is_satisfied(&self) -> bool {
+        self.unassigned.is_empty()
+    }
+}
+
+// === impl AddressError ===
+
+impl AddressError {
+    /// Returns the condition reason for the error.
+    ///
+    /// Unsupported address types are reported on the `Accepted` condition,
+    /// since the Gateway can never be programmed with them; other errors are
+    /// reported on the `Ready` condition.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::UnsupportedType { .. } => UNSUPPORTED_ADDRESS,
+            _ => ADDRESS_NOT_USABLE,
+        }
+    }
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedType { index, r#type } => write!(
+                f,
+                "spec.addresses[{}]: address type {:?} is not supported",
+                index, r#type
+            ),
+            Self::InvalidValue {
+                index,
+                r#type,
+                value,
+            } => write!(
+                f,
+                "spec.addresses[{}]: {:?} is not a valid {} value",
+                index, value, r#type
+            ),
+            Self::Duplicate {
+                index,
+                first,
+                value,
+            } => write!(
+                f,
+                "spec.addresses[{}]: {:?} is already requested by spec.addresses[{}]",
+                index, value, first
+            ),
+            Self::MixedTypes {
+                index,
+                r#type,
+                first_type,
+            } => write!(
+                f,
+                "spec.addresses[{}]: address type {:?} cannot be mixed with {:?}",
+                index, r#type, first_type
+            ),
+            Self::TooMany { count } => write!(
+                f,
+                "spec.addresses: {} addresses are requested, but at most {} are permitted",
+                count, MAX_ADDRESSES
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+// === impl AddressProblem ===
+
+impl AddressProblem {
+    /// Returns the `Ready` condition reason for the problem.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::NotUsable(_) => ADDRESS_NOT_USABLE,
+            Self::NotAssigned(_) => ADDRESS_NOT_ASSIGNED,
+        }
+    }
+
+    /// Returns a human-readable description of the problem, suitable for a
+    /// condition message.
+    pub fn message(&self) -> String {
+        match self {
+            Self::NotUsable(errors) => errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+            Self::NotAssigned(addrs) => {
+                let values = addrs
+                    .iter()
+                    .map(|a| a.value.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("requested addresses are not assigned: {}", values)
+            }
+        }
+    }
+
+    /// Returns a `Ready` condition with status `False` describing the
+    /// problem.
+    pub fn to_condition(
+        &self,
+        observed_generation: Option<i64>,
+        last_transition_time: metav1::Time,
+    ) -> metav1::Condition {
+        metav1::Condition {
+            type_: "Ready".to_string(),
+            status: "False".to_string(),
+            reason: self.reason().to_string(),
+            message: self.message(),
+            observed_generation,
+            last_transition_time,
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Counting of the routes attached to a Gateway's listeners.
+//!
+//! Each listener's status reports the number of routes of any kind that are
+//! attached to it. [`AttachmentCounter`] tracks which listeners each route
+//! attaches to, so that when a route changes only its own attachments are
+//! recomputed, rather than those of every route that references the Gateway:
+//!
+//! ```ignore
+//! let mut counter = AttachmentCounter::new(&gateway);
+//! for route in routes {
+//!     counter.update(&route);
+//! }
+//! // Later, for each watch event:
+//! if !counter.update(&changed).is_empty() {
+//!     patch_listener_statuses(&counter);
+//! }
+//! ```
+
+use crate::{
+    consts::GROUP,
+    ir,
+    route::{AnyRoute, RouteKind},
+    snapshot::ObjectKey,
+    *,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Tracks the routes of all kinds attached to each listener of a Gateway.
+#[derive(Clone, Debug)]
+pub struct AttachmentCounter {
+    gateway: ObjectKey,
+    listeners: Vec<Listener>,
+    namespace_labels: BTreeMap<String, BTreeMap<String, String>>,
+
+    /// The names of the listeners each route attaches to.
+    routes: BTreeMap<(RouteKind, ObjectKey), BTreeSet<String>>,
+
+    counts: BTreeMap<String, u16>,
+}
+
+/// Returns the route kinds that may attach to a listener with the given
+/// protocol, unless its `allowedRoutes` restricts them further.
+pub fn supported_kinds(protocol: &str) -> &'static [RouteKind] {
+    match protocol {
+        "HTTP" | "HTTPS" => &[
+            RouteKind::Http,
+            #[cfg(feature = "experimental")]
+            RouteKind::Grpc,
+        ],
+        #[cfg(feature = "experimental")]
+        "TLS" => &[RouteKind::Tls, RouteKind::Tcp],
+        #[cfg(feature = "experimental")]
+        "TCP" => &[RouteKind::Tcp],
+        #[cfg(feature = "experimental")]
+        "UDP" => &[RouteKind::Udp],
+        _ => &[],
+    }
+}
+
+// === impl AttachmentCounter ===
+
+impl AttachmentCounter {
+    /// Returns a counter for the listeners of a Gateway, to which no routes
+    /// are attached.
+    pub fn new(gateway: &Gateway) -> Self {
+        Self {
+            gateway: ObjectKey::from_meta(&gateway.metadata),
+            listeners: gateway.spec.listeners.clone(),
+            namespace_labels: BTreeMap::new(),
+            routes: BTreeMap::new(),
+            counts: gateway
+                .spec
+                .listeners
+                .iter()
+                .map(|l| (l.name.clone(), 0))
+                .collect(),
+        }
+    }
+
+    /// Sets the labels of a namespace, so that namespace selectors in
+    /// listeners' `allowedRoutes` may be evaluated against it. Routes in
+    /// namespaces without labels are never selected.
+    ///
+    /// Labels should be set before routes are counted, since the attachments
+    /// of routes that were already counted are not recomputed.
+    pub fn with_namespace_labels(
+        mut self,
+        namespace: impl Into<String>,
+        labels: BTreeMap<String, String>,
+    ) -> Self {
+        self.namespace_labels.insert(namespace.into(), labels);
+        self
+    }
+
+    /// Returns the number of routes attached to a listener, or 0 if the
+    /// Gateway has no such listener.
+    pub fn attached_routes(&self, listener: &str) -> u16 {
+        self.counts.get(listener).copied().unwrap_or(0)
+    }
+
+    /// Iterates over the listeners' names and their attached route counts,
+    /// in the order of the listeners' names.
+    pub fn counts(&self) -> impl Iterator<Item = (&str, u16)> + '_ {
+        self.counts.iter().map(|(name, n)| (name.as_str(), *n))
+    }
+
+    /// Iterates over the routes attached to a listener, ordered by kind and
+    /// key.
+    pub fn routes<'a>(
+        &'a self,
+        listener: &'a str,
+    ) -> impl Iterator<Item = (RouteKind, &'a ObjectKey)> + 'a {
+        self.routes
+            .iter()
+            .filter(move |(_, listeners)| listeners.contains(listener))
+            .map(|((kind, key), _)| (*kind, key))
+    }
+
+    /// Sets the `attachedRoutes` of each listener status.
+    pub fn apply(&self, statuses: &mut [ListenerStatus]) {
+        for status in statuses {
+            status.attached_routes = self.attached_routes(&status.name);
+        }
+    }
+
+    /// Recomputes the listeners a route attaches to, returning the names of
+    /// the listeners whose counts changed.
+    pub fn update(&mut self, route: &AnyRoute) -> Vec<String> {
+        let key = (route.kind(), ObjectKey::from_meta(route.metadata()));
+        let listeners = self
+            .listeners
+            .iter()
+            .filter(|l| self.attaches(route, &key.1, l))
+            .map(|l| l.name.clone())
+            .collect::<BTreeSet<_>>();
+        let previous = if listeners.is_empty() {
+            self.routes.remove(&key)
+        } else {
+            self.routes.insert(key, listeners.clone())
+        };
+        self.recount(&previous.unwrap_or_default(), &listeners)
+    }
+
+    /// Forgets a deleted route, returning the names of the listeners whose
+    /// counts changed.
+    pub fn remove(&mut self, kind: RouteKind, route: &ObjectKey) -> Vec<String> {
+        let previous = self.routes.remove(&(kind, route.clone()));
+        self.recount(&previous.unwrap_or_default(), &BTreeSet::new())
+    }
+
+    /// Applies the difference between the listeners a route attached to and
+    /// those it now attaches to.
+    fn recount(&mut self, before: &BTreeSet<String>, after: &BTreeSet<String>) -> Vec<String> {
+        let mut changed = Vec::new();
+        for name in before.symmetric_difference(after) {
+            if let Some(count) = self.counts.get_mut(name) {
+                *count = if after.contains(name) {
+                    count.saturating_add(1)
+                } else {
+                    count.saturating_sub(1)
+                };
+                changed.push(name.clone());
+            }
+        }
+        changed
+    }
+
+    /// Returns true if a route references a listener, the listener admits
+    /// the route's kind and namespace, and, for kinds that match by hostname,
+    /// the route's hostnames intersect the listener's.
+    fn attaches(&self, route: &AnyRoute, key: &ObjectKey, listener: &Listener) -> bool {
+        let references = route.parent_refs().iter().any(|p| {
+            p.group.as_deref().unwrap_or(GROUP) == GROUP
+                && p.kind.as_deref().unwrap_or("Gateway") == "Gateway"
+                && p.namespace.as_deref().unwrap_or(&key.namespace) == &*self.gateway.namespace
+                && p.name == *self.gateway.name
+                && p.section_name
+                    .as_deref()
+                    .map_or(true, |s| s == listener.name)
+                && p.port.map_or(true, |port| port == listener.port)
+        });
+        if !references {
+            return false;
+        }
+
+        let kind = route.kind();
+        let allowed = listener.allowed_routes.as_ref();
+        if !supported_kinds(&listener.protocol).contains(&kind)
+            || !kind.is_allowed_by(allowed.and_then(|a| a.kinds.as_deref()))
+        {
+            return false;
+        }
+
+        if !ir::allows_namespace(
+            listener,
+            &self.gateway.namespace,
+            &key.namespace,
+            &self.namespace_labels,
+        ) {
+            return false;
+        }
+
+        match route.hostnames() {
+            Some(hostnames) if !hostnames.is_empty() => {
+                !ir::intersect_hostnames(listener.hostname.as_deref(), hostnames).is_empty()
+            }
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Semantic events derived from successive snapshots of Gateways.
+//!
+//! Watch events report that an object changed, not what the change means
+//! for the traffic a Gateway serves: updating a route's labels and removing
+//! its parent reference are both just updates. An [`EventStream`] remembers
+//! the last snapshot of each Gateway and reports the consequences of each
+//! new one as [`AuditEvent`]s, such as routes attaching to or detaching from
+//! listeners and backends becoming unusable, so that alerting and audit
+//! pipelines can be built on them:
+//!
+//! ```ignore
+//! let mut events = EventStream::default();
+//! let affected = store.apply(event);
+//! for event in events.update(&store, &affected) {
+//!     tracing::info!(%event);
+//! }
+//! ```
+
+use crate::{
+    ir,
+    snapshot::{ObjectKey, Snapshot, SnapshotStore},
+    *,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// A change in how a Gateway handles traffic.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuditEvent {
+    /// An HTTPRoute attached to a listener.
+    RouteAttached {
+        gateway: ObjectKey,
+        listener: String,
+        route: ObjectKey,
+    },
+
+    /// An HTTPRoute detached from a listener, e.g. because the route or the
+    /// listener was removed or no longer references or allows the other.
+    RouteDetached {
+        gateway: ObjectKey,
+        listener: String,
+        route: ObjectKey,
+    },
+
+    /// A backend of an attached HTTPRoute became unusable, so requests that
+    /// would be forwarded to it receive a 500 response.
+    BackendBecameInvalid {
+        gateway: ObjectKey,
+        route: ObjectKey,
+        rule_index: usize,
+        backend: BackendObjectReference,
+        reason: InvalidBackend,
+    },
+
+    /// A backend of an attached HTTPRoute that was unusable became usable.
+    BackendBecameValid {
+        gateway: ObjectKey,
+        route: ObjectKey,
+        rule_index: usize,
+        backend: BackendObjectReference,
+    },
+}
+
+/// The reason a backend is unusable.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InvalidBackend {
+    /// The backend is in another namespace, and no ReferenceGrant permits the
+    /// route to reference it.
+    RefNotPermitted,
+
+    /// The backend's filters conflict with those of its rule.
+    ConflictingFilters,
+}
+
+/// Derives [`AuditEvent`]s from successive snapshots of Gateways.
+#[derive(Clone, Debug, Default)]
+pub struct EventStream {
+    compiler: ir::Compiler,
+    states: BTreeMap<ObjectKey, State>,
+}
+
+/// What is known about a Gateway's traffic from a snapshot.
+#[derive(Clone, Debug, Default)]
+struct State {
+    /// The routes attached to each listener, by listener name and route key.
+    attached: BTreeSet<(String, ObjectKey)>,
+
+    /// The unusable backends of attached routes, by route key, rule index,
+    /// and backend index.
+    invalid: BTreeMap<(ObjectKey, usize, usize), (BackendObjectReference, InvalidBackend)>,
+}
+
+/// Returns the events that describe the change from one snapshot of a
+/// Gateway to another. `None` indicates that the Gateway does not exist.
+pub fn diff(
+    compiler: &ir::Compiler,
+    gateway: &ObjectKey,
+    old: Option<&Snapshot>,
+    new: Option<&Snapshot>,
+) -> Vec<AuditEvent> {
+    let old = old.map(|s| State::new(compiler, s)).unwrap_or_default();
+    let new = new.map(|s| State::new(compiler, s)).unwrap_or_default();
+    old.diff(gateway, &new)
+}
+
+// === impl EventStream ===
+
+impl EventStream {
+    /// Returns a stream that attaches routes to listeners as `compiler`
+    /// does, e.g. evaluating namespace selectors with its namespace labels.
+    pub fn new(compiler: ir::Compiler) -> Self {
+        Self {
+            compiler,
+            states: BTreeMap::new(),
+        }
+    }
+
+    /// Records the current snapshot of a Gateway, or `None` if it no longer
+    /// exists, returning the events since its previous snapshot.
+    pub fn observe(&mut self, gateway: &ObjectKey, snapshot: Option<&Snapshot>) -> Vec<AuditEvent> {
+        let new = snapshot
+            .map(|s| State::new(&self.compiler, s))
+            .unwrap_or_default();
+        let old = self.states.remove(gateway).unwrap_or_default();
+        let events = old.diff(gateway, &new);
+        if snapshot.is_some() {
+            self.states.insert(gateway.clone(), new);
+        }
+        events
+    }
+
+    /// Observes the snapshots of the Gateways that were affected by a watch
+    /// event, as returned by [`SnapshotStore::apply`], returning their events
+    /// in order of the Gateways' keys.
+    pub fn update(
+        &mut self,
+        store: &SnapshotStore,
+        affected: &BTreeSet<ObjectKey>,
+    ) -> Vec<AuditEvent> {
+        affected
+            .iter()
+            .flat_map(|key| self.observe(key, store.get(key)))
+            .collect()
+    }
+}
+
+// === impl State ===
+
+impl State {
+    fn new(compiler: &ir::Compiler, snapshot: &Snapshot) -> Self {
+        let gw_key = snapshot.key();
+        let mut state = Self::default();
+        for route in snapshot.http_routes() {
+            let route_key = ObjectKey::from_meta(&route.metadata);
+            let listeners = snapshot
+                .gateway()
+                .spec
+                .listeners
+                .iter()
+                .filter(|l| compiler.attachment(gw_key, l, &route_key, route).is_ok())
+                .map(|l| (l.name.clone(), route_key.clone()))
+                .collect::<Vec<_>>();
+            if listeners.is_empty() {
+                continue;
+            }
+            state.attached.extend(listeners);
+
+            for (i, rule) in route.spec.rules.iter().flatten().enumerate() {
+                let rule_filters = compiler.prune_filters(rule.filters.as_deref());
+                for (j, backend) in rule.backend_refs.iter().flatten().enumerate() {
+                    // Backends with a weight of zero receive no requests.
+                    let backend_ref = match &backend.backend_ref {
+                        Some(b) if b.weight != Some(0) => &b.inner,
+                        _ => continue,
+                    };
+                    let backend_filters = compiler.prune_filters(backend.filters.as_deref());
+                    let ns = backend_ref
+                        .namespace
+                        .as_deref()
+                        .unwrap_or(&route_key.namespace);
+                    let reason =
+                        if filter::effective_filters(&rule_filters, &backend_filters).is_err() {
+                            InvalidBackend::ConflictingFilters
+                        } else if ns != &*route_key.namespace
+                            && !ir::is_permitted(snapshot, &route_key, backend_ref)
+                        {
+                            InvalidBackend::RefNotPermitted
+                        } else {
+                            continue;
+                        };
+                    state
+                        .invalid
+                        .insert((route_key.clone(), i, j), (backend_ref.clone(), reason));
+                }
+            }
+        }
+        state
+    }
+
+    fn diff(&self, gateway: &ObjectKey, new: &Self) -> Vec<AuditEvent> {
+        let mut events = Vec::new();
+        for (listener, route) in self.attached.difference(&new.attached) {
+            events.push(AuditEvent::RouteDetached {
+                gateway: gateway.clone(),
+                listener: listener.clone(),
+                route: route.clone(),
+            });
+        }
+        for (listener, route) in new.attached.difference(&self.attached) {
+            events.push(AuditEvent::RouteAttached {
+                gateway: gateway.clone(),
+                listener: listener.clone(),
+                route: route.clone(),
+            });
+        }
+
+        for ((route, rule_index, j), (backend, reason)) in &new.invalid {
+            if self.invalid.get(&(route.clone(), *rule_index, *j))
+                == Some(&(backend.clone(), *reason))
+            {
+                continue;
+            }
+            events.push(AuditEvent::BackendBecameInvalid {
+                gateway: gateway.clone(),
+                route: route.clone(),
+                rule_index: *rule_index,
+                backend: backend.clone(),
+                reason: *reason,
+            });
+        }
+        // Backends of routes that detached entirely are no longer used, so
+        // they do not become valid.
+        let attached = new
+            .attached
+            .iter()
+            .map(|(_, route)| route)
+            .collect::<BTreeSet<_>>();
+        for ((route, rule_index, j), (backend, _)) in &self.invalid {
+            if new.invalid.contains_key(&(route.clone(), *rule_index, *j))
+                || !attached.contains(route)
+            {
+                continue;
+            }
+            events.push(AuditEvent::BackendBecameValid {
+                gateway: gateway.clone(),
+                route: route.clone(),
+                rule_index: *rule_index,
+                backend: backend.clone(),
+            });
+        }
+        events
+    }
+}
+
+// === impl AuditEvent ===
+
+impl AuditEvent {
+    /// Returns the key of the Gateway the event concerns.
+    pub fn gateway(&self) -> &ObjectKey {
+        match self {
+            Self::RouteAttached { gateway, .. }
+            | Self::RouteDetached { gateway, .. }
+            | Self::BackendBecameInvalid { gateway, .. }
+            | Self::BackendBecameValid { gateway, .. } => gateway,
+        }
+    }
+
+    /// Returns the key of the HTTPRoute the event concerns.
+    pub fn route(&self) -> &ObjectKey {
+        match self {
+            Self::RouteAttached { route, .. }
+            | Self::RouteDetached { route, .. }
+            | Self::BackendBecameInvalid { route, .. }
+            | Self::BackendBecameValid { route, .. } => route,
+        }
+    }
+}
+
+impl fmt::Display for AuditEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RouteAttached {
+                gateway,
+                listener,
+                route,
+            } => write!(
+                f,
+                "HTTPRoute {} attached to listener {:?} of Gateway {}",
+                route, listener, gateway
+            ),
+            Self::RouteDetached {
+                gateway,
+                listener,
+                route,
+            } => write!(
+                f,
+                "HTTPRoute {} detached from listener {:?} of Gateway {}",
+                route, listener, gateway
+            ),
+            Self::BackendBecameInvalid {
+                gateway,
+                route,
+                rule_index,
+                backend,
+                reason,
+            } => write!(
+                f,
+                "backend {:?} of HTTPRoute {} rule {} on Gateway {} became invalid: {}",
+                backend.name, route, rule_index, gateway, reason
+            ),
+            Self::BackendBecameValid {
+                gateway,
+                route,
+                rule_index,
+                backend,
+            } => write!(
+                f,
+                "backend {:?} of HTTPRoute {} rule {} on Gateway {} became valid",
+                backend.name, route, rule_index, gateway
+            ),
+        }
+    }
+}
+
+// === impl InvalidBackend ===
+
+impl fmt::Display for InvalidBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RefNotPermitted => {
+                f.write_str("the reference is not permitted by a ReferenceGrant")
+            }
+            Self::ConflictingFilters => f.write_str("its filters conflict with the rule's"),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Resolution of backend reference ports.
+//!
+//! A `BackendObjectReference` must specify a port when it refers to a
+//! Service, but the CRDs cannot enforce this since the port is optional for
+//! other kinds. Implementations resolve an omitted port from the Service when
+//! it exposes a single port; otherwise the reference is invalid, so requests
+//! that would have been forwarded to it receive 500 responses and the route's
+//! "ResolvedRefs" condition is set to `False` with the
+//! [`ReferenceError::reason`].
+//!
+//! The protocol that a dataplane speaks to a Service port is described by
+//! the port's `appProtocol`. [`UpstreamProtocol::from_app_protocol`]
+//! interprets the values defined by [GEP-1911]; ports with other values may
+//! be rejected with [`ReferenceError::UnsupportedProtocol`]. Protocols other
+//! than HTTP/1.1 require the dataplane to enable an [`Upgrade`] for the
+//! requests it forwards to the port.
+//!
+//! [GEP-1911]: https://gateway-api.sigs.k8s.io/geps/gep-1911/
+
+use crate::*;
+use k8s_openapi::api::core::v1::Service;
+use std::{convert::TryFrom, fmt};
+
+/// The `appProtocol` of ports that speak HTTP/2 over cleartext, with prior
+/// knowledge rather than an upgrade.
+pub const H2C: &str = "kubernetes.io/h2c";
+
+/// The `appProtocol` of ports that speak WebSocket over cleartext.
+pub const WS: &str = "kubernetes.io/ws";
+
+/// The `appProtocol` of ports that speak WebSocket over TLS.
+pub const WSS: &str = "kubernetes.io/wss";
+
+/// The protocol with which a dataplane forwards requests to a backend.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum UpstreamProtocol {
+    /// HTTP/1.1, which is used when a port does not specify an
+    /// `appProtocol`.
+    Http1,
+
+    /// HTTP/2 over cleartext.
+    H2c,
+
+    /// WebSocket over cleartext, i.e. HTTP/1.1 with `Upgrade: websocket`.
+    WebSocket,
+
+    /// WebSocket over TLS.
+    SecureWebSocket,
+}
+
+/// Something that a dataplane must enable, beyond forwarding HTTP/1.1
+/// requests, to serve a backend's protocol.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Upgrade {
+    /// Requests are forwarded over HTTP/2 connections established with prior
+    /// knowledge, whatever the HTTP version of the client's request. The
+    /// `Upgrade: h2c` mechanism is not used.
+    H2c,
+
+    /// Requests with `Upgrade: websocket` are forwarded, and upgraded
+    /// connections are relayed in both directions.
+    WebSocket,
+}
+
+/// Returns the port of a backend reference, resolving an omitted port from
+/// `service`, the referenced Service, or `None` if it does not exist.
+///
+/// The reference is resolved in its own namespace, if it specifies one, or in
+/// `route_ns` otherwise. Specified ports of Service references must be
+/// exposed by the Service. References to other kinds are not checked against
+/// `service`: their specified port is returned as-is, and an omitted port
+/// resolves to `None`, since its meaning is implementation-specific.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            route_ns = %route_ns,
+            backend.kind = ?backend.kind,
+            backend.namespace = ?backend.namespace,
+            backend.name = %backend.name,
+            backend.port = ?backend.port,
+        ),
+        ret,
+        err(level = "debug", Display),
+    )
+)]
+pub fn resolve_port(
+    route_ns: &str,
+    backend: &BackendObjectReference,
+    service: Option<&Service>,
+) -> Result<Option<PortNumber>, ReferenceError> {
+    if !is_service(backend) {
+        return Ok(backend.port);
+    }
+
+    let namespace = backend.namespace.as_deref().unwrap_or(route_ns).to_string();
+    let name = backend.name.clone();
+    let service = match service {
+        Some(service) => service,
+        None => {
+            return Err(ReferenceError::NotFound {
+                kind: "Service".to_string(),
+                namespace,
+                name,
+            })
+        }
+    };
+    let ports = service
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.ports.as_deref())
+        .unwrap_or_default();
+    // Service ports are validated by the API server, so a single port always
+    // fits.
+    let single = match ports {
+        [p] => PortNumber::try_from(p.port).ok(),
+        _ => None,
+    };
+    match backend.port {
+        Some(port) if ports.iter().any(|p| p.port == i32::from(port)) => Ok(Some(port)),
+        Some(port) => Err(ReferenceError::PortNotFound {
+            namespace,
+            name,
+            port,
+        }),
+        None => single
+            .map(Some)
+            .ok_or_else(|| ReferenceError::PortRequired {
+                namespace,
+                name,
+                ports: ports.iter().map(|p| p.port).collect(),
+            }),
+    }
+}
+
+/// Returns the `appProtocol` of a Service port, if it specifies one.
+pub fn app_protocol(service: &Service, port: PortNumber) -> Option<&str> {
+    service
+        .spec
+        .as_ref()?
+        .ports
+        .iter()
+        .flatten()
+        .find(|p| p.port == i32::from(port))?
+        .app_protocol
+        .as_deref()
+}
+
+/// Returns true if the reference is to a core Service, which is the default
+/// when no group or kind is specified.
+pub fn is_service(backend: &BackendObjectReference) -> bool {
+    backend.group.as_deref().unwrap_or("").is_empty()
+        && backend.kind.as_deref().unwrap_or("Service") == "Service"
+}
+
+// === impl UpstreamProtocol ===
+
+impl UpstreamProtocol {
+    /// Returns the protocol described by a port's `appProtocol`, or `None`
+    /// if the value is not one of those defined by GEP-1911.
+    ///
+    /// An omitted `appProtocol` and the IANA service name `http` describe
+    /// HTTP/1.1. Implementations that support other, implementation-specific
+    /// values should interpret them before calling this.
+    pub fn from_app_protocol(app_protocol: Option<&str>) -> Option<Self> {
+        match app_protocol {
+            None | Some("http") => Some(Self::Http1),
+            Some(H2C) => Some(Self::H2c),
+            Some(WS) => Some(Self::WebSocket),
+            Some(WSS) => Some(Self::SecureWebSocket),
+            Some(_) => None,
+        }
+    }
+
+    /// Returns true if the dataplane must originate TLS to the backend.
+    pub fn is_tls(&self) -> bool {
+        matches!(self, Self::SecureWebSocket)
+    }
+
+    /// Returns the upgrade that the dataplane must enable to forward requests
+    /// with this protocol, if any.
+    pub fn upgrade(&self) -> Option<Upgrade> {
+        match self {
+            Self::Http1 => None,
+            Self::H2c => Some(Upgrade::H2c),
+            Self::WebSocket | Self::SecureWebSocket => Some(Upgrade::WebSocket),
+        }
+    }
+}
+
+impl fmt::Display for UpstreamProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Http1 => "HTTP/1.1",
+            Self::H2c => "HTTP/2 (cleartext)",
+            Self::WebSocket => "WebSocket",
+            Self::SecureWebSocket => "WebSocket (TLS)",
+        })
+    }
+}
+
+// === impl Upgrade ===
+
+impl fmt::Display for Upgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::H2c => "h2c",
+            Self::WebSocket => "WebSocket",
+        })
+    }
+}
@@ -0,0 +1,337 @@
+//! Canonical serialization of Gateway API objects.
+//!
+//! Struct fields are always serialized in declaration order and maps in this
+//! crate are `BTreeMap`s, so serializing the same value twice produces the
+//! same output. That is not enough to compare objects that came from
+//! elsewhere, though: an object read from the API server, decoded, and
+//! re-encoded may order keys differently from the manifest it was applied
+//! from, and unset optional fields are encoded as `null` rather than omitted.
+//!
+//! The canonical form addresses both: object keys are sorted
+//! lexicographically at every level and `null`-valued keys are removed. The
+//! order of array elements is significant in the Gateway API (e.g. rules,
+//! filters) and is preserved. Two values with the same canonical form are
+//! semantically equivalent, so the canonical bytes are suitable for diffing
+//! and content hashing:
+//!
+//! ```
+//! # use k8s_gateway_api::{canonical, HttpRouteMatch};
+//! let a = serde_json::json!({ "method": "GET", "path": null });
+//! let b = HttpRouteMatch {
+//!     method: Some("GET".to_string()),
+//!     ..HttpRouteMatch::default()
+//! };
+//! assert_eq!(
+//!     canonical::to_string(&a).unwrap(),
+//!     canonical::to_string(&b).unwrap(),
+//! );
+//! ```
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Serializes `value` to its canonical JSON form.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<Value> {
+    serde_json::to_value(value).map(canonicalize)
+}
+
+/// Serializes `value` to compact canonical JSON bytes.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<Vec<u8>> {
+    serde_json::to_vec(&to_value(value)?)
+}
+
+/// Serializes `value` to a compact canonical JSON string.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string(&to_value(value)?)
+}
+
+/// Serializes `value` to a canonical YAML document.
+#[cfg(feature = "yaml")]
+pub fn to_yaml<T: Serialize + ?Sized>(value: &T) -> Result<String, serde_yaml::Error> {
+    let value = to_value(value).map_err(<serde_yaml::Error as serde::ser::Error>::custom)?;
+    serde_yaml::to_string(&value)
+}
+
+/// Converts a JSON value to its canonical form.
+///
+/// Object keys are sorted and keys with `null` values are removed,
+/// recursively. Arrays keep their order.
+pub fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            // `Map` is only sorted when serde_json's `preserve_order` feature
+            // is disabled, which another crate in the build may not honor.
+            let mut entries = obj
+                .into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, canonicalize(v)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        value => value,
+    }
+}
+
+/// Computes a stable 64-bit hash of the canonical JSON form of `value`.
+///
+/// The hash is FNV-1a over the bytes returned by [`to_vec`]. Unlike
+/// `std::hash::Hash`, it does not depend on the Rust release or the platform,
+/// so it may be persisted (e.g. in an annotation) and compared later.
+pub fn hash<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<u64> {
+    to_vec(value).map(|bytes| fnv1a(&bytes))
+}
+
+/// Computes the 64-bit FNV-1a hash of `bytes`.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes
+        .iter()
+        .fold(OFFSET_BASIS, |h, b| (h ^ u64::from(*b)).wrapping_mul(PRIME))
+}
+
+macro_rules! impl_spec_hash {
+    ($($(#[$attr:meta])* $ty:ty $(=> $normalize:ident)?),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            impl $ty {
+                /// Returns a stable hash of this object's spec, formatted as 16
+                /// hexadecimal digits.
+                ///
+                /// Objects with semantically equivalent specs have the same
+                /// hash, regardless of key order or unset fields, so the hash
+                /// may be recorded in an annotation to detect no-op updates.
+                /// Specs that have a normal form, such as
+                /// [`GatewaySpec::normalized`](crate::GatewaySpec::normalized),
+                /// are hashed in it. Metadata and status do not contribute to
+                /// the hash.
+                pub fn spec_hash(&self) -> String {
+                    let spec = &self.spec;
+                    $(let spec = &spec.$normalize();)?
+                    // Specs only contain maps with string keys, so they
+                    // always serialize.
+                    let hash = hash(spec).expect("spec must serialize");
+                    format!("{:016x}", hash)
+                }
+            }
+        )+
+    };
+}
+
+impl_spec_hash!(
+    crate::GatewayClass,
+    crate::Gateway => normalized,
+    crate::HttpRoute,
+    #[cfg(feature = "experimental")]
+    crate::BackendLbPolicy,
+    #[cfg(feature = "experimental")]
+    crate::GrpcRoute,
+    #[cfg(feature = "experimental")]
+    crate::ReferenceGrant,
+    #[cfg(feature = "experimental")]
+    crate::TcpRoute,
+    #[cfg(feature = "experimental")]
+    crate::TlsRoute,
+    #[cfg(feature = "experimental")]
+    crate::UdpRoute,
+    #[cfg(feature = "experimental")]
+    crate::XBackendTrafficPolicy,
+);
+
+// === impl HttpRouteMatch ===
+
+impl crate::HttpRouteMatch {
+    /// Returns a key that identifies the requests that satisfy the match.
+    ///
+    /// The key is the canonical JSON form of the match after it is
+    /// normalized: an omitted path becomes a `PathPrefix` match on `/`,
+    /// trailing slashes are removed from path prefixes, header names are
+    /// lowercased, header and query parameter conditions that are ignored
+    /// because an earlier condition has the same name are removed, and the
+    /// remaining conditions are sorted. Matches with the same key are
+    /// satisfied by the same requests, so the key may be used to deduplicate
+    /// matches or to group them by their conditions.
+    pub fn canonical_key(&self) -> String {
+        // Matches only contain maps with string keys, so they always
+        // serialize.
+        to_string(&self.normalized()).expect("match must serialize")
+    }
+
+    fn normalized(&self) -> Self {
+        use crate::HttpPathMatch;
+
+        let path = match &self.path {
+            None => HttpPathMatch::PathPrefix {
+                value: "/".to_string(),
+            },
+            Some(HttpPathMatch::PathPrefix { value }) => {
+                let value = value.trim_end_matches('/');
+                HttpPathMatch::PathPrefix {
+                    value: if value.is_empty() { "/" } else { value }.to_string(),
+                }
+            }
+            Some(path) => path.clone(),
+        };
+
+        let mut names = Vec::new();
+        let headers = self.headers.as_ref().map(|headers| {
+            let headers = headers
+                .iter()
+                .filter(|h| is_first(h.name().map(str::to_ascii_lowercase), &mut names))
+                .map(|h| {
+                    let mut h = h.clone();
+                    if let crate::HttpHeaderMatch::Exact { name, .. }
+                    | crate::HttpHeaderMatch::RegularExpression { name, .. } = &mut h
+                    {
+                        name.make_ascii_lowercase();
+                    }
+                    h
+                })
+                .collect();
+            sorted(headers)
+        });
+
+        let mut names = Vec::new();
+        let query_params = self.query_params.as_ref().map(|params| {
+            let params = params
+                .iter()
+                .filter(|q| is_first(q.name().map(str::to_string), &mut names))
+                .cloned()
+                .collect();
+            sorted(params)
+        });
+
+        Self {
+            path: Some(path),
+            headers,
+            query_params,
+            method: self.method.clone(),
+        }
+    }
+}
+
+// === impl GatewaySpec ===
+
+impl crate::GatewaySpec {
+    /// Returns the spec in a normal form, for comparison only.
+    ///
+    /// Listeners are sorted by name, and addresses by type and value, with
+    /// exact duplicates removed. Address types are defaulted, IP addresses
+    /// are formatted canonically (so `0::1` becomes `::1`), and an empty
+    /// address list is omitted. The order of listeners and addresses has no
+    /// meaning, so specs with the same normal form are equivalent; but
+    /// controllers should write specs in the order users gave them, rather
+    /// than their normal forms, to avoid fighting with GitOps tools over the
+    /// order of the lists.
+    pub fn normalized(&self) -> Self {
+        use crate::addresses::{address_type, IP_ADDRESS};
+        use std::net::IpAddr;
+
+        let mut listeners = sorted(self.listeners.clone());
+        listeners.sort_by(|a, b| a.name.cmp(&b.name));
+        listeners.dedup();
+
+        let addresses = self
+            .addresses
+            .as_ref()
+            .filter(|addrs| !addrs.is_empty())
+            .map(|addrs| {
+                let mut addrs = addrs
+                    .iter()
+                    .map(|a| {
+                        let ty = address_type(a.r#type.as_deref());
+                        let value = match a.value.parse::<IpAddr>() {
+                            Ok(ip) if ty == IP_ADDRESS => ip.to_string(),
+                            _ => a.value.clone(),
+                        };
+                        crate::GatewayAddress {
+                            r#type: Some(ty.to_string()),
+                            value,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                addrs.sort_by(|a, b| (&a.r#type, &a.value).cmp(&(&b.r#type, &b.value)));
+                addrs.dedup();
+                addrs
+            });
+
+        Self {
+            gateway_class_name: self.gateway_class_name.clone(),
+            listeners,
+            addresses,
+        }
+    }
+
+    /// Returns true if two specs have the same [normal form](Self::normalized),
+    /// i.e. they differ at most in the order of their listeners and addresses
+    /// and in duplicate addresses.
+    pub fn is_equivalent(&self, other: &Self) -> bool {
+        let a = to_value(&self.normalized()).expect("spec must serialize");
+        let b = to_value(&other.normalized()).expect("spec must serialize");
+        a == b
+    }
+}
+
+/// Returns true if a condition is the first with its name, recording the name.
+/// Conditions of unknown types have no name and are always kept.
+fn is_first(name: Option<String>, seen: &mut Vec<String>) -> bool {
+    match name {
+        Some(name) if seen.contains(&name) => false,
+        Some(name) => {
+            seen.push(name);
+            true
+        }
+        None => true,
+    }
+}
+
+/// Sorts values by their canonical form.
+fn sorted<T: Serialize>(mut values: Vec<T>) -> Vec<T> {
+    values.sort_by_cached_key(|v| to_string(v).expect("value must serialize"));
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    fn gateway(listeners: &[&str], addresses: &[&str]) -> crate::Gateway {
+        let listeners = listeners
+            .iter()
+            .map(|name| json!({ "name": name, "port": 80, "protocol": "HTTP" }))
+            .collect::<Vec<_>>();
+        let addresses = addresses
+            .iter()
+            .map(|value| json!({ "value": value }))
+            .collect::<Vec<_>>();
+        serde_json::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "web", "namespace": "infra" },
+            "spec": {
+                "gatewayClassName": "acme",
+                "listeners": listeners,
+                "addresses": addresses,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn hashes_equivalent_gateway_specs_equally() {
+        let hash = gateway(&["a", "b"], &["10.0.0.1", "0::1"]).spec_hash();
+        assert_eq!(gateway(&["b", "a"], &["::1", "10.0.0.1"]).spec_hash(), hash);
+        assert_eq!(
+            gateway(&["a", "b"], &["10.0.0.1", "::1", "10.0.0.1"]).spec_hash(),
+            hash
+        );
+        assert_ne!(gateway(&["a", "c"], &["10.0.0.1", "::1"]).spec_hash(), hash);
+    }
+}
@@ -0,0 +1,120 @@
+//! Helpers for working with Gateway API resources with a Kubernetes client.
+//!
+//! Resources are written with [server-side apply][ssa] so that controllers
+//! only need to describe the fields they own.
+//!
+//! [ssa]: https://kubernetes.io/docs/reference/using-api/server-side-apply/
+
+mod crds;
+mod endpoints;
+mod fetch;
+mod mirror;
+mod tls;
+mod watch;
+mod writer;
+
+pub use self::{
+    crds::{check_supported_version, SupportedVersion},
+    endpoints::{resolve_backend, Endpoint, ResolveError},
+    fetch::ClusterSnapshot,
+    mirror::{resolve_mirror, Mirror},
+    tls::{fetch_certificate, CertificateKeyPair, FetchCertificateError},
+    watch::Watcher,
+    writer::StatusWriter,
+};
+
+use kube::{
+    api::{Api, Patch, PatchParams},
+    Resource,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+/// Metadata fields that are managed by the API server and must not be
+/// included in an applied configuration.
+const SERVER_MANAGED_METADATA: &[&str] = &[
+    "creationTimestamp",
+    "generation",
+    "managedFields",
+    "resourceVersion",
+    "selfLink",
+    "uid",
+];
+
+/// Applies `obj` with the given field manager, forcing ownership of any
+/// conflicting fields.
+///
+/// Null fields, the status, and server-managed metadata are stripped from the
+/// applied configuration so that an object that was previously read from the
+/// API server may be applied as-is.
+pub async fn apply<K>(api: &Api<K>, obj: &K, field_manager: &str) -> kube::Result<K>
+where
+    K: Resource + Clone + Debug + DeserializeOwned + Serialize,
+{
+    let name = obj
+        .meta()
+        .name
+        .as_deref()
+        .ok_or_else(|| invalid("object must have a name"))?;
+
+    let mut patch = serde_json::to_value(obj).map_err(kube::Error::SerdeError)?;
+    if let Some(patch) = patch.as_object_mut() {
+        patch.remove("status");
+        if let Some(serde_json::Value::Object(meta)) = patch.get_mut("metadata") {
+            for field in SERVER_MANAGED_METADATA {
+                meta.remove(*field);
+            }
+        }
+    }
+    strip_nulls(&mut patch);
+
+    api.patch(name, &params(field_manager), &Patch::Apply(patch))
+        .await
+}
+
+/// Applies `status` to the status subresource of the named object with the
+/// given field manager, forcing ownership of any conflicting fields.
+pub async fn apply_status<K, S>(
+    api: &Api<K>,
+    name: &str,
+    status: &S,
+    field_manager: &str,
+) -> kube::Result<K>
+where
+    K: Resource + Clone + Debug + DeserializeOwned,
+    K::DynamicType: Default,
+    S: Serialize,
+{
+    let dt = K::DynamicType::default();
+    let mut status = serde_json::to_value(status).map_err(kube::Error::SerdeError)?;
+    strip_nulls(&mut status);
+    let patch = serde_json::json!({
+        "apiVersion": K::api_version(&dt),
+        "kind": K::kind(&dt),
+        "status": status,
+    });
+
+    api.patch_status(name, &params(field_manager), &Patch::Apply(patch))
+        .await
+}
+
+fn params(field_manager: &str) -> PatchParams {
+    PatchParams::apply(field_manager).force()
+}
+
+fn invalid(msg: &str) -> kube::Error {
+    kube::Error::BuildRequest(kube::core::request::Error::Validation(msg.to_string()))
+}
+
+/// Removes null-valued fields so that an applied configuration only includes
+/// the fields that the caller has actually set.
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    consts::GROUP,
+    well_known::{BundleVersion, MetadataExt},
+    GATEWAY_CLASS_CONDITION_SUPPORTED_VERSION, GATEWAY_CLASS_REASON_SUPPORTED_VERSION,
+    GATEWAY_CLASS_REASON_UNSUPPORTED_VERSION,
+};
+use k8s_openapi::{
+    apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+    apimachinery::pkg::apis::meta::v1 as metav1,
+};
+use kube::api::{Api, ListParams};
+use std::{collections::BTreeMap, ops::RangeBounds};
+
+/// The bundle versions of the Gateway API CRDs installed in a cluster,
+/// checked against the range of versions supported by an implementation.
+///
+/// Implementations report the result with the GatewayClass
+/// "SupportedVersion" condition, and should not accept GatewayClasses while
+/// the installed CRDs are unsupported.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SupportedVersion {
+    /// The bundle version annotation of each Gateway API CRD, by CRD name, or
+    /// `None` if the CRD is not annotated.
+    pub installed: BTreeMap<String, Option<String>>,
+
+    /// The names of the CRDs whose bundle version is missing, invalid, or
+    /// not supported.
+    pub unsupported: Vec<String>,
+}
+
+/// Reads the bundle versions of the Gateway API CRDs installed in the
+/// cluster and checks that they are in the `supported` range.
+///
+/// ```ignore
+/// let check = check_supported_version(client, BundleVersion::new(1, 1, 0)..).await?;
+/// status.set_condition(check.to_condition(class.metadata.generation, now));
+/// ```
+pub async fn check_supported_version(
+    client: kube::Client,
+    supported: impl RangeBounds<BundleVersion>,
+) -> kube::Result<SupportedVersion> {
+    let crds = Api::<CustomResourceDefinition>::all(client)
+        .list(&ListParams::default())
+        .await?;
+    Ok(SupportedVersion::check(&crds.items, supported))
+}
+
+// === impl SupportedVersion ===
+
+impl SupportedVersion {
+    /// Checks the bundle versions of the Gateway API CRDs in `crds`, ignoring
+    /// CRDs of other groups.
+    pub fn check(
+        crds: &[CustomResourceDefinition],
+        supported: impl RangeBounds<BundleVersion>,
+    ) -> Self {
+        let mut installed = BTreeMap::new();
+        let mut unsupported = Vec::new();
+        for crd in crds.iter().filter(|crd| crd.spec.group == GROUP) {
+            let name = crd.metadata.name.clone().unwrap_or_default();
+            let version = crd.metadata.bundle_version();
+            let is_supported = version
+                .and_then(|v| v.parse::<BundleVersion>().ok())
+                .map_or(false, |v| supported.contains(&v));
+            if !is_supported {
+                unsupported.push(name.clone());
+            }
+            installed.insert(name, version.map(ToString::to_string));
+        }
+        Self {
+            installed,
+            unsupported,
+        }
+    }
+
+    /// Returns true if all of the installed CRDs are supported.
+    ///
+    /// If no Gateway API CRDs are installed, there is nothing to support.
+    pub fn is_supported(&self) -> bool {
+        self.unsupported.is_empty()
+    }
+
+    /// Returns a description of the unsupported CRDs, suitable for a
+    /// condition message, or an empty string if all are supported.
+    pub fn message(&self) -> String {
+        self.unsupported
+            .iter()
+            .map(|name| match self.installed.get(name).cloned().flatten() {
+                Some(version) => format!("CRD {} has unsupported bundle version {}", name, version),
+                None => format!("CRD {} has no bundle version", name),
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Returns a GatewayClass "SupportedVersion" condition describing the
+    /// result.
+    pub fn to_condition(
+        &self,
+        observed_generation: Option<i64>,
+        last_transition_time: metav1::Time,
+    ) -> metav1::Condition {
+        let (status, reason) = if self.is_supported() {
+            ("True", GATEWAY_CLASS_REASON_SUPPORTED_VERSION)
+        } else {
+            ("False", GATEWAY_CLASS_REASON_UNSUPPORTED_VERSION)
+        };
+        metav1::Condition {
+            type_: GATEWAY_CLASS_CONDITION_SUPPORTED_VERSION.to_string(),
+            status: status.to_string(),
+            reason: reason.to_string(),
+            message: self.message(),
+            observed_generation,
+            last_transition_time,
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::{backend, BackendObjectReference, ReferenceError};
+use k8s_openapi::api::{
+    core::v1::{Service, ServicePort},
+    discovery::v1::{EndpointPort, EndpointSlice},
+};
+use kube::api::{Api, ListParams};
+use std::fmt;
+
+/// The label that associates an EndpointSlice with its Service.
+const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+
+const NOT_FOUND: u16 = 404;
+
+/// A ready endpoint of a backend.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Endpoint {
+    /// The endpoint's address. Depending on the EndpointSlice's address type,
+    /// this is an IPv4 address, an IPv6 address, or an FQDN.
+    pub address: String,
+
+    /// The port on the endpoint, i.e. the Service port's resolved
+    /// `targetPort`.
+    pub port: u16,
+
+    /// The application protocol of the port, taken from the EndpointSlice
+    /// port or, when unset there, from the Service port.
+    pub app_protocol: Option<String>,
+
+    /// The zone in which the endpoint exists, if known.
+    pub zone: Option<String>,
+}
+
+/// The reason a backend reference could not be resolved.
+///
+/// Controllers are expected to report failures that have a
+/// [`ResolveError::reason`] by setting the route's "ResolvedRefs" condition to
+/// `False` with that reason. Other failures, e.g. when the Kubernetes API is
+/// unavailable, say nothing about the reference and should be retried.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ResolveError {
+    /// The reference is invalid, or its referent does not exist.
+    Reference(ReferenceError),
+
+    /// The Kubernetes API returned an error.
+    Kube(kube::Error),
+}
+
+/// Resolves a backend reference into the ready endpoints of the referenced
+/// Service.
+///
+/// The reference is resolved in its own namespace, if it specifies one, or
+/// in `route_ns` otherwise. Callers are responsible for checking that
+/// cross-namespace references are permitted by a ReferenceGrant before
+/// resolving them.
+///
+/// An omitted port is resolved with [`backend::resolve_port`]. The Service
+/// port is matched by number and its endpoints are discovered from the
+/// Service's EndpointSlices, so the returned ports reflect the Service's
+/// `targetPort` mapping, including named target ports. Endpoints
+/// that are explicitly not ready are omitted. A Service without any ready
+/// endpoints resolves to an empty list rather than an error.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            route_ns = %route_ns,
+            backend.kind = ?backend.kind,
+            backend.namespace = ?backend.namespace,
+            backend.name = %backend.name,
+            backend.port = ?backend.port,
+        ),
+        err(level = "debug", Display),
+    )
+)]
+pub async fn resolve_backend(
+    client: kube::Client,
+    route_ns: &str,
+    backend: &BackendObjectReference,
+) -> Result<Vec<Endpoint>, ResolveError> {
+    if !backend::is_service(backend) {
+        return Err(ResolveError::Reference(ReferenceError::InvalidKind {
+            group: backend.group.as_deref().unwrap_or("").to_string(),
+            kind: backend.kind.as_deref().unwrap_or("Service").to_string(),
+        }));
+    }
+    let ns = backend.namespace.as_deref().unwrap_or(route_ns);
+
+    let service = Api::<Service>::namespaced(client.clone(), ns)
+        .get_opt(&backend.name)
+        .await
+        .map_err(ResolveError::Kube)?;
+    let port = backend::resolve_port(route_ns, backend, service.as_ref())
+        .map_err(ResolveError::Reference)?
+        .expect("Service references must resolve to a port");
+    let service_port = service
+        .iter()
+        .filter_map(|svc| svc.spec.as_ref()?.ports.as_ref())
+        .flatten()
+        .find(|p| p.port == i32::from(port))
+        .expect("resolved port must be exposed by the Service");
+
+    let selector = format!("{}={}", SERVICE_NAME_LABEL, backend.name);
+    let slices = Api::<EndpointSlice>::namespaced(client, ns)
+        .list(&ListParams::default().labels(&selector))
+        .await
+        .map_err(ResolveError::Kube)?;
+
+    let mut endpoints = Vec::new();
+    for slice in &slices.items {
+        collect(slice, service_port, &mut endpoints);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        port,
+        slices = slices.items.len(),
+        endpoints = endpoints.len(),
+        "Resolved backend endpoints"
+    );
+    Ok(endpoints)
+}
+
+fn collect(slice: &EndpointSlice, service_port: &ServicePort, endpoints: &mut Vec<Endpoint>) {
+    let slice_port = match slice
+        .ports
+        .iter()
+        .flatten()
+        .find(|p| same_port_name(p, service_port))
+    {
+        Some(p) => p,
+        None => {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                slice = ?slice.metadata.name,
+                service_port = ?service_port.name,
+                "EndpointSlice does not expose the Service port"
+            );
+            return;
+        }
+    };
+    let port = match slice_port.port.and_then(|p| u16::try_from(p).ok()) {
+        Some(p) => p,
+        None => return,
+    };
+    let app_protocol = slice_port
+        .app_protocol
+        .clone()
+        .or_else(|| service_port.app_protocol.clone());
+
+    for ep in &slice.endpoints {
+        // A nil ready condition must be interpreted as ready.
+        let ready = ep.conditions.as_ref().and_then(|c| c.ready).unwrap_or(true);
+        if !ready {
+            continue;
+        }
+        for address in &ep.addresses {
+            endpoints.push(Endpoint {
+                address: address.clone(),
+                port,
+                app_protocol: app_protocol.clone(),
+                zone: ep.zone.clone(),
+            });
+        }
+    }
+}
+
+/// EndpointSlice ports carry the name of the Service port they were derived
+/// from. Unnamed Service ports (only permitted when a Service has a single
+/// port) produce unnamed EndpointSlice ports.
+fn same_port_name(slice_port: &EndpointPort, service_port: &ServicePort) -> bool {
+    slice_port.name.as_deref().unwrap_or("") == service_port.name.as_deref().unwrap_or("")
+}
+
+// === impl Endpoint ===
+
+impl Endpoint {
+    /// Returns the protocol with which requests are forwarded to the
+    /// endpoint, or `None` if its `appProtocol` is not one of those defined
+    /// by GEP-1911.
+    pub fn upstream_protocol(&self) -> Option<backend::UpstreamProtocol> {
+        backend::UpstreamProtocol::from_app_protocol(self.app_protocol.as_deref())
+    }
+}
+
+// === impl ResolveError ===
+
+impl ResolveError {
+    /// Returns the reason to set on the "ResolvedRefs" condition, or `None`
+    /// if the backend could not be resolved because of an API error other
+    /// than a `404 Not Found`, in which case the caller should retry.
+    pub fn reason(&self) -> Option<&'static str> {
+        match self {
+            Self::Reference(e) => Some(e.reason()),
+            Self::Kube(kube::Error::Api(e)) if e.code == NOT_FOUND => Some("BackendNotFound"),
+            Self::Kube(_) => None,
+        }
+    }
+}
+
+impl From<ReferenceError> for ResolveError {
+    fn from(e: ReferenceError) -> Self {
+        Self::Reference(e)
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reference(e) => e.fmt(f),
+            Self::Kube(e) => write!(f, "failed to resolve backend: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Reference(e) => Some(e),
+            Self::Kube(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(code: u16, reason: &str) -> ResolveError {
+        ResolveError::Kube(kube::Error::Api(kube::core::ErrorResponse {
+            status: "Failure".to_string(),
+            message: String::new(),
+            reason: reason.to_string(),
+            code,
+        }))
+    }
+
+    #[test]
+    fn reasons() {
+        let invalid = ResolveError::Reference(ReferenceError::InvalidKind {
+            group: String::new(),
+            kind: "Pod".to_string(),
+        });
+        assert_eq!(invalid.reason(), Some("InvalidKind"));
+        assert_eq!(api_error(404, "NotFound").reason(), Some("BackendNotFound"));
+
+        // Other errors are transient, so they are not reported as reasons.
+        assert_eq!(api_error(403, "Forbidden").reason(), None);
+        assert_eq!(api_error(500, "InternalError").reason(), None);
+        assert_eq!(api_error(503, "ServiceUnavailable").reason(), None);
+        let decode = serde_json::from_str::<()>("{").unwrap_err();
+        assert_eq!(
+            ResolveError::Kube(kube::Error::SerdeError(decode)).reason(),
+            None
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use crate::{
+    backend, consts,
+    manifest::GatewayApiObject,
+    scope::Scope,
+    snapshot::{Event, ObjectKey, SnapshotStore},
+    tls, *,
+};
+use futures::future::{self, BoxFuture, FutureExt};
+use k8s_openapi::api::core::v1::{Namespace, Secret, Service};
+use kube::{
+    api::{Api, ListParams},
+    Resource,
+};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+};
+
+/// The number of objects requested per page when listing.
+const PAGE_SIZE: u32 = 500;
+
+/// A point-in-time view of the Gateway API objects in a cluster, and of the
+/// Services, Secrets, and Namespaces that they reference.
+///
+/// Tools that need a consistent view of a cluster, but not to follow it as
+/// it changes, can fetch a snapshot instead of running watches:
+///
+/// ```ignore
+/// let snapshot = ClusterSnapshot::fetch(client, &Scope::default()).await?;
+/// let report = describe::describe_gateway(&snapshot.compiler(), &snapshot.objects, &key);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ClusterSnapshot {
+    /// The Gateway API objects in the scope, ordered by kind. GatewayClasses
+    /// are cluster-scoped and are always included.
+    pub objects: Vec<GatewayApiObject>,
+
+    /// The Services referenced by routes' backends that exist.
+    pub services: Vec<Service>,
+
+    /// The Secrets referenced by Gateways' certificates that exist.
+    pub secrets: Vec<Secret>,
+
+    /// The Namespaces that contain the objects, or that objects reference,
+    /// that exist.
+    pub namespaces: Vec<Namespace>,
+
+    /// The `resourceVersion` at which each kind of Gateway API object was
+    /// listed, by kind. Watches that start from these versions observe every
+    /// change made after the snapshot was taken.
+    pub resource_versions: BTreeMap<String, String>,
+}
+
+type Listed = (String, String, Vec<GatewayApiObject>);
+
+// === impl ClusterSnapshot ===
+
+impl ClusterSnapshot {
+    /// Fetches a snapshot of the objects in `scope`.
+    ///
+    /// Each kind of Gateway API object is listed concurrently, a page at a
+    /// time. Every page of a list is served from the same `resourceVersion`,
+    /// so each kind is consistent, although different kinds may be listed at
+    /// slightly different moments. The referenced Services, Secrets, and
+    /// Namespaces are then read concurrently; references to objects that do
+    /// not exist are ignored.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(level = "debug", Display))
+    )]
+    pub async fn fetch(client: kube::Client, scope: &Scope) -> kube::Result<Self> {
+        #[allow(unused_mut)]
+        let mut lists = vec![
+            list::<GatewayClass>(&client, scope),
+            list::<Gateway>(&client, scope),
+            list::<HttpRoute>(&client, scope),
+        ];
+        #[cfg(feature = "experimental")]
+        lists.extend([
+            list::<BackendLbPolicy>(&client, scope),
+            list::<GrpcRoute>(&client, scope),
+            list::<ReferenceGrant>(&client, scope),
+            list::<TcpRoute>(&client, scope),
+            list::<TlsRoute>(&client, scope),
+            list::<UdpRoute>(&client, scope),
+        ]);
+
+        let mut snapshot = Self::default();
+        for (kind, version, objects) in future::try_join_all(lists).await? {
+            snapshot.resource_versions.insert(kind, version);
+            snapshot.objects.extend(objects);
+        }
+
+        let mut services = BTreeSet::new();
+        let mut secrets = BTreeSet::new();
+        let mut namespaces = BTreeSet::new();
+        for obj in &snapshot.objects {
+            let ns = match obj.namespace() {
+                Some(ns) => ns,
+                None => continue,
+            };
+            namespaces.insert(ns.to_string());
+            if let GatewayApiObject::Gateway(gw) = obj {
+                let certs = gw
+                    .spec
+                    .listeners
+                    .iter()
+                    .filter_map(|l| l.tls.as_ref()?.certificate_refs.as_ref())
+                    .flatten()
+                    .filter(|c| tls::is_secret(c));
+                for cert in certs {
+                    let ns = cert.namespace.as_deref().unwrap_or(ns);
+                    secrets.insert(ObjectKey::new(ns, &*cert.name));
+                }
+            }
+            for backend in backend_refs(obj).filter(|b| backend::is_service(b)) {
+                let ns = backend.namespace.as_deref().unwrap_or(ns);
+                services.insert(ObjectKey::new(ns, &*backend.name));
+            }
+        }
+        namespaces.extend(
+            services
+                .iter()
+                .chain(&secrets)
+                .map(|k| k.namespace.to_string()),
+        );
+
+        let services = services.into_iter().map(|k| {
+            let api = Api::<Service>::namespaced(client.clone(), &k.namespace);
+            get_opt(api, k.name.to_string())
+        });
+        let secrets = secrets.into_iter().map(|k| {
+            let api = Api::<Secret>::namespaced(client.clone(), &k.namespace);
+            get_opt(api, k.name.to_string())
+        });
+        let namespaces = namespaces
+            .into_iter()
+            .map(|ns| get_opt(Api::<Namespace>::all(client.clone()), ns));
+        let (services, secrets, namespaces) = futures::try_join!(
+            future::try_join_all(services),
+            future::try_join_all(secrets),
+            future::try_join_all(namespaces),
+        )?;
+        snapshot.services = services.into_iter().flatten().collect();
+        snapshot.secrets = secrets.into_iter().flatten().collect();
+        snapshot.namespaces = namespaces.into_iter().flatten().collect();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            objects = snapshot.objects.len(),
+            services = snapshot.services.len(),
+            secrets = snapshot.secrets.len(),
+            namespaces = snapshot.namespaces.len(),
+            "Fetched snapshot"
+        );
+        Ok(snapshot)
+    }
+
+    /// Returns a compiler that evaluates namespace selectors against the
+    /// labels of the snapshot's Namespaces and compiles the clusters of its
+    /// Services with their ports' `appProtocol`s.
+    pub fn compiler(&self) -> ir::Compiler {
+        let mut compiler = ir::Compiler::default();
+        for ns in &self.namespaces {
+            if let Some(name) = &ns.metadata.name {
+                let labels = ns.metadata.labels.clone().unwrap_or_default();
+                compiler = compiler.with_namespace_labels(name.clone(), labels);
+            }
+        }
+        for service in &self.services {
+            compiler = compiler.with_service(service);
+        }
+        compiler
+    }
+
+    /// Returns a store that holds the snapshot's objects, restricted to
+    /// `scope`.
+    pub fn to_store(&self, scope: Scope) -> SnapshotStore {
+        let mut store = SnapshotStore::with_scope(scope);
+        let mut gateways = Vec::new();
+        let mut http_routes = Vec::new();
+        #[cfg(feature = "experimental")]
+        let mut grants = Vec::new();
+        for obj in &self.objects {
+            match obj {
+                GatewayApiObject::Gateway(gw) => gateways.push(gw.clone()),
+                GatewayApiObject::HttpRoute(route) => http_routes.push(route.clone()),
+                #[cfg(feature = "experimental")]
+                GatewayApiObject::ReferenceGrant(grant) => grants.push(grant.clone()),
+                _ => {}
+            }
+        }
+        store.apply(Event::Restarted(gateways));
+        store.apply(Event::Restarted(http_routes));
+        #[cfg(feature = "experimental")]
+        store.apply(Event::Restarted(grants));
+        store
+    }
+}
+
+/// Lists all objects of a kind in `scope`, a page at a time.
+fn list<K>(client: &kube::Client, scope: &Scope) -> BoxFuture<'static, kube::Result<Listed>>
+where
+    K: Resource<DynamicType = ()> + Clone + Debug + DeserializeOwned + Send + 'static,
+    K: Into<GatewayApiObject>,
+{
+    let kind = K::kind(&()).to_string();
+    let api = Api::<K>::all(client.clone());
+    let scope = scope.clone();
+    async move {
+        let (version, objects) = list_all(&api, &scope).await?;
+        Ok((kind, version, objects.into_iter().map(Into::into).collect()))
+    }
+    .boxed()
+}
+
+/// Lists all objects of a kind in `scope`, a page at a time, returning the
+/// `resourceVersion` of the list and the objects.
+pub(super) async fn list_all<K>(api: &Api<K>, scope: &Scope) -> kube::Result<(String, Vec<K>)>
+where
+    K: Resource<DynamicType = ()> + Clone + Debug + DeserializeOwned,
+{
+    let namespaced = is_namespaced::<K>();
+    let mut params = scoped_params::<K>(scope).limit(PAGE_SIZE);
+    let mut objects = Vec::new();
+    loop {
+        let page = api.list(&params).await?;
+        objects.extend(
+            page.items
+                .into_iter()
+                .filter(|o| !namespaced || scope.contains(o.meta())),
+        );
+        match page.metadata.continue_ {
+            Some(token) if !token.is_empty() => params = params.continue_token(&token),
+            _ => {
+                let version = page.metadata.resource_version.unwrap_or_default();
+                return Ok((version, objects));
+            }
+        }
+    }
+}
+
+/// Returns parameters that select the objects of a kind in `scope` by label.
+/// Cluster-scoped kinds are not filtered.
+pub(super) fn scoped_params<K: Resource<DynamicType = ()>>(scope: &Scope) -> ListParams {
+    let params = ListParams::default();
+    match scope.label_selector().filter(|_| is_namespaced::<K>()) {
+        Some(selector) => params.labels(&selector),
+        None => params,
+    }
+}
+
+pub(super) fn is_namespaced<K: Resource<DynamicType = ()>>() -> bool {
+    consts::find(&K::kind(&())).map_or(true, |k| k.namespaced)
+}
+
+async fn get_opt<K>(api: Api<K>, name: String) -> kube::Result<Option<K>>
+where
+    K: Clone + Debug + DeserializeOwned,
+{
+    api.get_opt(&name).await
+}
+
+/// Iterates over the backends that a route references, including the
+/// backends of request mirrors.
+fn backend_refs(obj: &GatewayApiObject) -> Box<dyn Iterator<Item = &BackendObjectReference> + '_> {
+    match obj {
+        GatewayApiObject::HttpRoute(route) => {
+            let rules = route.spec.rules.iter().flatten();
+            let filters = rules.clone().flat_map(|r| {
+                let backend_filters = r
+                    .backend_refs
+                    .iter()
+                    .flatten()
+                    .flat_map(|b| b.filters.iter().flatten());
+                r.filters.iter().flatten().chain(backend_filters)
+            });
+            let mirrors = filters.filter_map(|f| match f {
+                HttpRouteFilter::RequestMirror { request_mirror } => {
+                    Some(&request_mirror.backend_ref)
+                }
+                _ => None,
+            });
+            Box::new(
+                rules
+                    .flat_map(|r| r.backend_refs.iter().flatten())
+                    .filter_map(|b| Some(&b.backend_ref.as_ref()?.inner))
+                    .chain(mirrors),
+            )
+        }
+        #[cfg(feature = "experimental")]
+        GatewayApiObject::GrpcRoute(route) => Box::new(
+            route
+                .spec
+                .rules
+                .iter()
+                .flatten()
+                .flat_map(|r| r.backend_refs.iter().flatten())
+                .filter_map(|b| Some(&b.backend_ref.as_ref()?.inner)),
+        ),
+        #[cfg(feature = "experimental")]
+        GatewayApiObject::TcpRoute(route) => Box::new(
+            route
+                .spec
+                .rules
+                .iter()
+                .flat_map(|r| &r.backend_refs)
+                .map(|b| &b.inner),
+        ),
+        #[cfg(feature = "experimental")]
+        GatewayApiObject::TlsRoute(route) => Box::new(
+            route
+                .spec
+                .rules
+                .iter()
+                .flat_map(|r| &r.backend_refs)
+                .map(|b| &b.inner),
+        ),
+        #[cfg(feature = "experimental")]
+        GatewayApiObject::UdpRoute(route) => Box::new(
+            route
+                .spec
+                .rules
+                .iter()
+                .flat_map(|r| &r.backend_refs)
+                .map(|b| &b.inner),
+        ),
+        _ => Box::new(std::iter::empty()),
+    }
+}
@@ -0,0 +1,175 @@
+use super::{resolve_backend, Endpoint, ResolveError};
+use crate::{filter, HttpRequestMirrorFilter};
+use http::{uri::PathAndQuery, Request, Uri};
+use std::{
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tower::{Service, ServiceExt};
+
+/// Mirrors requests to the endpoints of a `RequestMirror` filter's backend.
+///
+/// Requests are sampled deterministically so that exactly the configured
+/// fraction of them is mirrored, spread evenly over time, and mirrored
+/// requests are spread over the endpoints in turn.
+#[derive(Debug)]
+pub struct Mirror<S> {
+    client: S,
+    endpoints: Vec<Endpoint>,
+    fraction: (u32, u32),
+    requests: AtomicU64,
+    mirrored: AtomicU64,
+}
+
+/// Resolves the backend of a `RequestMirror` filter into a [`Mirror`] that
+/// sends mirrored requests with `client`.
+///
+/// The backend is resolved with [`resolve_backend`], so callers remain
+/// responsible for checking that cross-namespace references are permitted.
+pub async fn resolve_mirror<S>(
+    kube: kube::Client,
+    route_ns: &str,
+    filter: &HttpRequestMirrorFilter,
+    client: S,
+) -> Result<Mirror<S>, ResolveError> {
+    let endpoints = resolve_backend(kube, route_ns, &filter.backend_ref).await?;
+    Ok(Mirror::new(filter, endpoints, client))
+}
+
+// === impl Mirror ===
+
+impl<S> Mirror<S> {
+    /// Returns a mirror that sends requests to `endpoints` with `client`, at
+    /// the rate configured by `filter`.
+    pub fn new(filter: &HttpRequestMirrorFilter, endpoints: Vec<Endpoint>, client: S) -> Self {
+        Self {
+            client,
+            endpoints,
+            fraction: filter::mirror_fraction(filter),
+            requests: AtomicU64::new(0),
+            mirrored: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the endpoints to which requests are mirrored.
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+
+    /// Returns a future that sends a copy of `req` to an endpoint of the
+    /// mirror backend, or `None` if the request is not sampled or the
+    /// backend has no ready endpoints.
+    ///
+    /// The copy has the original method, path, query, headers, and body, but
+    /// not its extensions. The future completes when the mirrored request
+    /// does, ignoring its response and any error, and is typically spawned so
+    /// that the original request is not delayed.
+    pub fn mirror<B>(&self, req: &Request<B>) -> Option<impl Future<Output = ()>>
+    where
+        B: Clone,
+        S: Service<Request<B>> + Clone,
+    {
+        let n = self.requests.fetch_add(1, Ordering::Relaxed);
+        if self.endpoints.is_empty() || !self.is_sampled(n) {
+            return None;
+        }
+
+        // Endpoints are chosen by a separate counter so that mirrored requests
+        // are spread evenly regardless of the sampled fraction.
+        let i = self.mirrored.fetch_add(1, Ordering::Relaxed);
+        let endpoint = &self.endpoints[(i % self.endpoints.len() as u64) as usize];
+        let host = if endpoint.address.contains(':') {
+            format!("[{}]", endpoint.address)
+        } else {
+            endpoint.address.clone()
+        };
+        let uri = Uri::builder()
+            .scheme("http")
+            .authority(format!("{}:{}", host, endpoint.port).as_str())
+            .path_and_query(
+                req.uri()
+                    .path_and_query()
+                    .map(PathAndQuery::as_str)
+                    .unwrap_or("/"),
+            )
+            .build()
+            .ok()?;
+
+        let mut mirrored = Request::new(req.body().clone());
+        *mirrored.method_mut() = req.method().clone();
+        *mirrored.uri_mut() = uri;
+        *mirrored.version_mut() = req.version();
+        *mirrored.headers_mut() = req.headers().clone();
+
+        let client = self.client.clone();
+        Some(async move {
+            let _ = client.oneshot(mirrored).await;
+        })
+    }
+
+    /// Returns true if the `n`th request is mirrored: i.e. if the number of
+    /// requests that should have been mirrored grows with it.
+    fn is_sampled(&self, n: u64) -> bool {
+        let (numerator, denominator) = self.fraction;
+        let (n, numerator, denominator) = (
+            u128::from(n),
+            u128::from(numerator),
+            u128::from(denominator),
+        );
+        (n + 1) * numerator / denominator > n * numerator / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+    };
+
+    fn endpoint(address: &str) -> Endpoint {
+        Endpoint {
+            address: address.to_string(),
+            port: 8080,
+            app_protocol: None,
+            zone: None,
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn spreads_sampled_requests_over_endpoints() {
+        let filter = serde_json::from_value::<HttpRequestMirrorFilter>(serde_json::json!({
+            "backendRef": { "name": "mirror", "port": 8080 },
+            "percent": 50,
+        }))
+        .unwrap();
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let client = {
+            let sent = sent.clone();
+            tower::service_fn(move |req: Request<()>| {
+                sent.lock()
+                    .unwrap()
+                    .push(req.uri().host().unwrap().to_string());
+                async { Ok::<_, Infallible>(()) }
+            })
+        };
+        let mirror = Mirror::new(
+            &filter,
+            vec![endpoint("10.0.0.1"), endpoint("10.0.0.2")],
+            client,
+        );
+
+        let req = Request::get("/").body(()).unwrap();
+        for _ in 0..8 {
+            if let Some(mirrored) = mirror.mirror(&req) {
+                mirrored.await;
+            }
+        }
+        assert_eq!(
+            *sent.lock().unwrap(),
+            ["10.0.0.1", "10.0.0.2", "10.0.0.1", "10.0.0.2"]
+        );
+    }
+}
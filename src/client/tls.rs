@@ -0,0 +1,118 @@
+use crate::{tls, SecretObjectReference};
+use k8s_openapi::api::core::v1::Secret;
+use kube::api::Api;
+use std::fmt;
+
+/// The PEM-encoded certificate chain and private key of a TLS Secret.
+#[derive(Clone, Eq, PartialEq)]
+pub struct CertificateKeyPair {
+    /// The PEM-encoded certificate chain, from the Secret's `tls.crt` key.
+    pub certificate: Vec<u8>,
+
+    /// The PEM-encoded private key, from the Secret's `tls.key` key.
+    pub private_key: Vec<u8>,
+}
+
+/// The reason a certificate reference could not be fetched.
+///
+/// Controllers are expected to report these failures by setting the
+/// listener's "ResolvedRefs" condition to `False` with the "InvalidCertificateRef"
+/// reason.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FetchCertificateError {
+    /// The reference is not to a core Secret.
+    InvalidKind,
+
+    /// The referenced Secret does not exist.
+    NotFound,
+
+    /// The referenced Secret is not of type `kubernetes.io/tls`.
+    InvalidType(Option<String>),
+
+    /// The referenced Secret does not have a value for the given key.
+    MissingData(&'static str),
+
+    /// The Kubernetes API returned an error.
+    Kube(kube::Error),
+}
+
+/// Fetches the certificate and private key referenced by a listener.
+///
+/// The reference is resolved in its own namespace, if it specifies one, or in
+/// `gateway_ns` otherwise. Callers are responsible for checking that
+/// cross-namespace references are permitted (see
+/// [`tls::validate_certificate_refs`]) before fetching them.
+pub async fn fetch_certificate(
+    client: kube::Client,
+    gateway_ns: &str,
+    cert: &SecretObjectReference,
+) -> Result<CertificateKeyPair, FetchCertificateError> {
+    if !tls::is_secret(cert) {
+        return Err(FetchCertificateError::InvalidKind);
+    }
+    let ns = cert.namespace.as_deref().unwrap_or(gateway_ns);
+
+    let secret = Api::<Secret>::namespaced(client, ns)
+        .get_opt(&cert.name)
+        .await
+        .map_err(FetchCertificateError::Kube)?
+        .ok_or(FetchCertificateError::NotFound)?;
+    if secret.type_.as_deref() != Some(tls::TLS_SECRET_TYPE) {
+        return Err(FetchCertificateError::InvalidType(secret.type_));
+    }
+
+    let mut data = secret.data.unwrap_or_default();
+    let mut take = |key: &'static str| match data.remove(key) {
+        Some(v) if !v.0.is_empty() => Ok(v.0),
+        _ => Err(FetchCertificateError::MissingData(key)),
+    };
+    Ok(CertificateKeyPair {
+        certificate: take("tls.crt")?,
+        private_key: take("tls.key")?,
+    })
+}
+
+// === impl CertificateKeyPair ===
+
+/// Omits the private key.
+impl fmt::Debug for CertificateKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertificateKeyPair")
+            .field("certificate", &String::from_utf8_lossy(&self.certificate))
+            .finish_non_exhaustive()
+    }
+}
+
+// === impl FetchCertificateError ===
+
+impl fmt::Display for FetchCertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKind => write!(f, "certificate reference must be to a Secret"),
+            Self::NotFound => write!(f, "certificate Secret not found"),
+            Self::InvalidType(Some(t)) => write!(
+                f,
+                "certificate Secret has type {}, expected {}",
+                t,
+                tls::TLS_SECRET_TYPE
+            ),
+            Self::InvalidType(None) => write!(
+                f,
+                "certificate Secret has no type, expected {}",
+                tls::TLS_SECRET_TYPE
+            ),
+            Self::MissingData(key) => write!(f, "certificate Secret has no {}", key),
+            Self::Kube(e) => write!(f, "failed to fetch certificate: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FetchCertificateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Kube(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use super::fetch::{is_namespaced, list_all, scoped_params};
+use crate::{
+    manifest::GatewayApiObject,
+    scope::Scope,
+    snapshot::{Event, ObjectKey},
+    unversioned, *,
+};
+use futures::{
+    future,
+    stream::{self, BoxStream, StreamExt},
+};
+use kube::{
+    api::{Api, WatchEvent},
+    Resource,
+};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    time::Duration,
+};
+
+/// The HTTP status with which the API server reports that it no longer
+/// retains the `resourceVersion` that a watch would resume from.
+const GONE: u16 = 410;
+
+/// Watches Gateway API objects, recovering from the failures of individual
+/// watches.
+///
+/// The API server ends watches every few minutes, rejects watches that
+/// resume from a `resourceVersion` it no longer retains (410 Gone), and may
+/// be unavailable for a while. The streams returned by a watcher never end:
+///
+/// - Ended watches resume from the last `resourceVersion` observed,
+///   including the versions reported by bookmark events, which are not
+///   yielded.
+/// - When a version is gone, the objects are listed again and yielded as an
+///   [`Event::Restarted`].
+/// - Failed requests are retried with exponential backoff, which is reset
+///   whenever an event is received.
+///
+/// Objects outside of the watcher's [`Scope`] are not yielded.
+///
+/// ```ignore
+/// let mut events = Watcher::new(client, scope).watch_all();
+/// while let Some(event) = events.next().await {
+///     // ...
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Watcher {
+    client: kube::Client,
+    scope: Scope,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+/// The watch of a single kind.
+struct KindWatch<K> {
+    api: Api<K>,
+    scope: Scope,
+    backoff: Backoff,
+    state: State<K>,
+}
+
+enum State<K> {
+    /// The objects must be listed before they can be watched.
+    List,
+
+    /// The objects are watched from a `resourceVersion`, by a watch that is
+    /// started when needed.
+    Watch {
+        version: String,
+        events: Option<BoxStream<'static, kube::Result<WatchEvent<K>>>>,
+    },
+}
+
+/// Exponential backoff between failed requests.
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+/// Merges the events of each kind into events that describe all kinds.
+struct Merge {
+    /// The kinds that have not yet been listed.
+    unlisted: BTreeSet<&'static str>,
+
+    /// The current objects of every kind.
+    objects: BTreeMap<(&'static str, ObjectKey), GatewayApiObject>,
+}
+
+// === impl Watcher ===
+
+impl Watcher {
+    /// The default delay before a failed request is first retried.
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+    /// The default limit of the delay between retries, which doubles with
+    /// each consecutive failure.
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+    /// Returns a watcher of the objects in `scope`.
+    pub fn new(client: kube::Client, scope: Scope) -> Self {
+        Self {
+            client,
+            scope,
+            initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Sets the delay before a failed request is first retried, and the
+    /// limit of the delay between retries.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Watches the objects of kind `K`.
+    ///
+    /// The stream first yields an [`Event::Restarted`] with all of the
+    /// objects, and then a change to one object per event.
+    pub fn watch<K>(&self) -> BoxStream<'static, Event<K>>
+    where
+        K: Resource<DynamicType = ()> + Clone + Debug + DeserializeOwned + Send + 'static,
+    {
+        let watch = KindWatch {
+            api: Api::all(self.client.clone()),
+            scope: self.scope.clone(),
+            backoff: Backoff::new(self.initial_backoff, self.max_backoff),
+            state: State::List,
+        };
+        stream::unfold(watch, |mut watch| async move {
+            let event = watch.next().await;
+            Some((event, watch))
+        })
+        .boxed()
+    }
+
+    /// Watches the objects of every kind, as listed by
+    /// [`ClusterSnapshot::fetch`](super::ClusterSnapshot::fetch).
+    ///
+    /// The stream first yields an [`Event::Restarted`] with the objects of
+    /// all kinds, once every kind has been listed. Whenever a kind is listed
+    /// again, it yields another with the current objects of all kinds, so
+    /// that every restart describes all of the objects that exist. The
+    /// stream keeps a copy of every object to do so.
+    pub fn watch_all(&self) -> BoxStream<'static, Event<GatewayApiObject>> {
+        #[allow(unused_mut)]
+        let mut kinds = vec![
+            self.watch_any::<GatewayClass>(),
+            self.watch_any::<Gateway>(),
+            self.watch_any::<HttpRoute>(),
+        ];
+        #[cfg(feature = "experimental")]
+        kinds.extend([
+            self.watch_any::<BackendLbPolicy>(),
+            self.watch_any::<GrpcRoute>(),
+            self.watch_any::<ReferenceGrant>(),
+            self.watch_any::<TcpRoute>(),
+            self.watch_any::<TlsRoute>(),
+            self.watch_any::<UdpRoute>(),
+            self.watch_any::<XBackendTrafficPolicy>(),
+        ]);
+
+        let mut merge = Merge {
+            unlisted: BTreeSet::new(),
+            objects: BTreeMap::new(),
+        };
+        let kinds = kinds
+            .into_iter()
+            .map(|(kind, events)| {
+                merge.unlisted.insert(kind);
+                events.map(move |event| (kind, event))
+            })
+            .collect::<Vec<_>>();
+        stream::select_all(kinds)
+            .filter_map(move |(kind, event)| future::ready(merge.apply(kind, event)))
+            .boxed()
+    }
+
+    fn watch_any<K>(&self) -> (&'static str, BoxStream<'static, Event<GatewayApiObject>>)
+    where
+        K: Resource<DynamicType = ()> + Clone + Debug + DeserializeOwned + Send + 'static,
+        K: Into<GatewayApiObject>,
+    {
+        let kind = unversioned::kind_name(&K::kind(&()));
+        let events = self.watch::<K>().map(|event| match event {
+            Event::Applied(obj) => Event::Applied(obj.into()),
+            Event::Deleted(obj) => Event::Deleted(obj.into()),
+            Event::Restarted(objs) => Event::Restarted(objs.into_iter().map(Into::into).collect()),
+        });
+        (kind, events.boxed())
+    }
+}
+
+// === impl KindWatch ===
+
+impl<K> KindWatch<K>
+where
+    K: Resource<DynamicType = ()> + Clone + Debug + DeserializeOwned + Send + 'static,
+{
+    async fn next(&mut self) -> Event<K> {
+        loop {
+            match &mut self.state {
+                State::List => match list_all(&self.api, &self.scope).await {
+                    Ok((version, objects)) => {
+                        self.backoff.reset();
+                        self.state = State::Watch {
+                            version,
+                            events: None,
+                        };
+                        return Event::Restarted(objects);
+                    }
+                    Err(error) => self.retry(error).await,
+                },
+
+                State::Watch {
+                    version,
+                    events: events @ None,
+                } => {
+                    let params = scoped_params::<K>(&self.scope);
+                    match self.api.watch(&params, version).await {
+                        Ok(stream) => *events = Some(stream.boxed()),
+                        Err(kube::Error::Api(e)) if e.code == GONE => self.relist(),
+                        Err(error) => self.retry(error).await,
+                    }
+                }
+
+                State::Watch {
+                    version,
+                    events: events @ Some(_),
+                } => {
+                    let event = match events.as_mut().expect("watch must be started").next().await {
+                        Some(Ok(event)) => event,
+                        Some(Err(error)) => {
+                            *events = None;
+                            self.retry(error).await;
+                            continue;
+                        }
+                        // The API server ended the watch, which resumes from
+                        // the last version observed.
+                        None => {
+                            *events = None;
+                            continue;
+                        }
+                    };
+
+                    let event = match event {
+                        WatchEvent::Added(obj) | WatchEvent::Modified(obj) => {
+                            Self::observe(version, &obj);
+                            Event::Applied(obj)
+                        }
+                        WatchEvent::Deleted(obj) => {
+                            Self::observe(version, &obj);
+                            Event::Deleted(obj)
+                        }
+                        WatchEvent::Bookmark(bookmark) => {
+                            *version = bookmark.metadata.resource_version;
+                            continue;
+                        }
+                        WatchEvent::Error(e) if e.code == GONE => {
+                            self.relist();
+                            continue;
+                        }
+                        WatchEvent::Error(e) => {
+                            *events = None;
+                            self.retry(kube::Error::Api(e)).await;
+                            continue;
+                        }
+                    };
+                    self.backoff.reset();
+                    if self.in_scope(&event) {
+                        return event;
+                    }
+                }
+            }
+        }
+    }
+
+    fn observe(version: &mut String, obj: &K) {
+        if let Some(v) = &obj.meta().resource_version {
+            *version = v.clone();
+        }
+    }
+
+    fn in_scope(&self, event: &Event<K>) -> bool {
+        match event {
+            Event::Applied(obj) | Event::Deleted(obj) => {
+                !is_namespaced::<K>() || self.scope.contains(obj.meta())
+            }
+            Event::Restarted(_) => true,
+        }
+    }
+
+    fn relist(&mut self) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(kind = %K::kind(&()), "Watch version is gone; listing again");
+        self.state = State::List;
+    }
+
+    async fn retry(&mut self, _error: kube::Error) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            kind = %K::kind(&()),
+            error = %_error,
+            backoff = ?self.backoff.next,
+            "Watch failed"
+        );
+        self.backoff.wait().await;
+    }
+}
+
+// === impl Backoff ===
+
+impl Backoff {
+    fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            next: initial,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.next = self.initial;
+    }
+
+    async fn wait(&mut self) {
+        tokio::time::sleep(self.next).await;
+        self.next = (self.next * 2).min(self.max);
+    }
+}
+
+// === impl Merge ===
+
+impl Merge {
+    fn apply(
+        &mut self,
+        kind: &'static str,
+        event: Event<GatewayApiObject>,
+    ) -> Option<Event<GatewayApiObject>> {
+        let event = match event {
+            Event::Applied(obj) => {
+                let key = (kind, ObjectKey::from_meta(obj.metadata()));
+                self.objects.insert(key, obj.clone());
+                Event::Applied(obj)
+            }
+            Event::Deleted(obj) => {
+                let key = (kind, ObjectKey::from_meta(obj.metadata()));
+                self.objects.remove(&key);
+                Event::Deleted(obj)
+            }
+            Event::Restarted(objs) => {
+                self.objects.retain(|(k, _), _| *k != kind);
+                for obj in objs {
+                    let key = (kind, ObjectKey::from_meta(obj.metadata()));
+                    self.objects.insert(key, obj);
+                }
+                self.unlisted.remove(kind);
+                if !self.unlisted.is_empty() {
+                    return None;
+                }
+                Event::Restarted(self.objects.values().cloned().collect())
+            }
+        };
+        // Changes are folded into the first restart until every kind has been
+        // listed.
+        if !self.unlisted.is_empty() {
+            return None;
+        }
+        Some(event)
+    }
+}
@@ -0,0 +1,343 @@
+use kube::{
+    api::{Api, PostParams},
+    Resource,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+/// The HTTP status with which the API server rejects writes that are
+/// conditioned on an outdated `resourceVersion`.
+const CONFLICT: u16 = 409;
+
+/// Batches status updates and writes them to the API server.
+///
+/// Controllers typically recompute an object's status on every event that
+/// affects it, which on large clusters produces many redundant writes.
+/// Statuses are instead queued with [`StatusWriter::queue`], replacing any
+/// status queued earlier for the same object, and written by
+/// [`StatusWriter::flush`]:
+///
+/// - Statuses that equal the last status written for an object, or the
+///   object's current status, are not written.
+/// - Each write is conditioned on the object's `resourceVersion`. When the
+///   object was modified concurrently, it is re-fetched and the write is
+///   retried with backoff, up to a limit.
+/// - Writes are spaced by a minimum interval so that a burst of updates does
+///   not overwhelm the API server.
+///
+/// Each queued status replaces the object's status in its entirety: fields
+/// that are no longer set are removed, and the status must include any fields
+/// or list entries set by other controllers that should be preserved.
+pub struct StatusWriter<K> {
+    api: Api<K>,
+    field_manager: String,
+    min_interval: Duration,
+    max_conflicts: usize,
+    backoff: Duration,
+
+    /// Statuses that have yet to be written, by object name.
+    pending: BTreeMap<String, serde_json::Value>,
+
+    /// The statuses that were last written, by object name.
+    written: BTreeMap<String, serde_json::Value>,
+
+    last_write: Option<Instant>,
+}
+
+/// The result of writing a status to an object.
+enum Outcome {
+    Written,
+    Unchanged,
+    NotFound,
+}
+
+// === impl StatusWriter ===
+
+impl<K> StatusWriter<K>
+where
+    K: Resource + Clone + Debug + DeserializeOwned + Serialize,
+    K::DynamicType: Default,
+{
+    /// The default minimum interval between writes.
+    pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_millis(20);
+
+    /// The default number of times a write is retried after a conflict.
+    pub const DEFAULT_MAX_CONFLICTS: usize = 5;
+
+    /// The default delay before the first retry after a conflict, which
+    /// doubles with each subsequent retry.
+    pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(50);
+
+    /// Returns a writer that writes statuses with the given field manager.
+    pub fn new(api: Api<K>, field_manager: impl Into<String>) -> Self {
+        Self {
+            api,
+            field_manager: field_manager.into(),
+            min_interval: Self::DEFAULT_MIN_INTERVAL,
+            max_conflicts: Self::DEFAULT_MAX_CONFLICTS,
+            backoff: Self::DEFAULT_BACKOFF,
+            pending: BTreeMap::new(),
+            written: BTreeMap::new(),
+            last_write: None,
+        }
+    }
+
+    /// Sets the minimum interval between writes.
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Sets the number of times a write is retried after a conflict, and the
+    /// delay before the first retry.
+    pub fn with_conflict_retries(mut self, max: usize, backoff: Duration) -> Self {
+        self.max_conflicts = max;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Queues a status to be written to the named object, replacing any
+    /// status already queued for it.
+    pub fn queue<S: Serialize>(&mut self, name: impl Into<String>, status: &S) -> kube::Result<()> {
+        let mut status = serde_json::to_value(status).map_err(kube::Error::SerdeError)?;
+        super::strip_nulls(&mut status);
+        self.pending.insert(name.into(), status);
+        Ok(())
+    }
+
+    /// Returns the number of objects with queued statuses.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Forgets a deleted object, discarding any status queued for it.
+    pub fn forget(&mut self, name: &str) {
+        self.pending.remove(name);
+        self.written.remove(name);
+    }
+
+    /// Writes all queued statuses, returning the number of objects whose
+    /// statuses were written.
+    ///
+    /// Statuses queued for objects that no longer exist are discarded. If a
+    /// write fails, it and the statuses that were not yet written remain
+    /// queued, so that they are written by a later flush.
+    pub async fn flush(&mut self) -> kube::Result<usize> {
+        let mut count = 0;
+        while let Some(name) = self.pending.keys().next().cloned() {
+            let status = self.pending[&name].clone();
+            if self.written.get(&name) == Some(&status) {
+                self.pending.remove(&name);
+                continue;
+            }
+
+            let outcome = self.write(&name, &status).await?;
+            self.pending.remove(&name);
+            match outcome {
+                Outcome::Written => count += 1,
+                Outcome::Unchanged => {}
+                Outcome::NotFound => continue,
+            }
+            self.written.insert(name, status);
+        }
+        Ok(count)
+    }
+
+    /// Writes a status to an object, unless the object already has it.
+    async fn write(&mut self, name: &str, status: &serde_json::Value) -> kube::Result<Outcome> {
+        let mut backoff = self.backoff;
+        let mut conflicts = 0;
+        loop {
+            let obj = match self.api.get_opt(name).await? {
+                Some(obj) => obj,
+                None => return Ok(Outcome::NotFound),
+            };
+            let mut obj = serde_json::to_value(&obj).map_err(kube::Error::SerdeError)?;
+            let mut current = obj
+                .get_mut("status")
+                .map(serde_json::Value::take)
+                .unwrap_or_default();
+            super::strip_nulls(&mut current);
+            if current == *status {
+                return Ok(Outcome::Unchanged);
+            }
+
+            // The object is replaced with the resourceVersion at which it was
+            // fetched, so the write fails with a conflict if the object was
+            // modified since.
+            obj["status"] = status.clone();
+            let data = serde_json::to_vec(&obj).map_err(kube::Error::SerdeError)?;
+            self.throttle().await;
+            let params = PostParams {
+                field_manager: Some(self.field_manager.clone()),
+                ..PostParams::default()
+            };
+            match self.api.replace_status(name, &params, data).await {
+                Ok(_) => return Ok(Outcome::Written),
+                Err(kube::Error::Api(e))
+                    if e.code == CONFLICT && conflicts < self.max_conflicts =>
+                {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(%name, conflicts, ?backoff, "Status write conflicted");
+                    conflicts += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Waits until the minimum interval has elapsed since the last write.
+    async fn throttle(&mut self) {
+        if let Some(last) = self.last_write {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_interval {
+                tokio::time::sleep(self.min_interval - elapsed).await;
+            }
+        }
+        self.last_write = Some(Instant::now());
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::{testing::FakeApiServer, Gateway, GatewayStatus};
+    use serde_json::json;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn gateway(status: serde_json::Value) -> Gateway {
+        serde_json::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "gw", "namespace": "default" },
+            "spec": { "gatewayClassName": "acme", "listeners": [] },
+            "status": status,
+        }))
+        .unwrap()
+    }
+
+    fn status(addresses: &[&str], conditions: Option<serde_json::Value>) -> GatewayStatus {
+        serde_json::from_value(json!({
+            "addresses": addresses
+                .iter()
+                .map(|a| json!({ "type": "IPAddress", "value": a }))
+                .collect::<Vec<_>>(),
+            "conditions": conditions,
+        }))
+        .unwrap()
+    }
+
+    fn stored(server: &FakeApiServer) -> Gateway {
+        server.get::<Gateway>(Some("default"), "gw").unwrap()
+    }
+
+    fn writer(client: kube::Client) -> StatusWriter<Gateway> {
+        StatusWriter::new(Api::namespaced(client, "default"), "test")
+            .with_min_interval(Duration::ZERO)
+            .with_conflict_retries(2, Duration::ZERO)
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn removes_fields_that_are_no_longer_set() {
+        let server = FakeApiServer::new();
+        let conditions = json!([{
+            "type": "Programmed",
+            "status": "True",
+            "reason": "Programmed",
+            "message": "",
+            "lastTransitionTime": "2023-01-01T00:00:00Z",
+        }]);
+        server.insert(&gateway(
+            serde_json::to_value(status(&["10.0.0.1"], Some(conditions))).unwrap(),
+        ));
+
+        let mut writer = writer(server.client());
+        writer.queue("gw", &status(&["10.0.0.2"], None)).unwrap();
+        assert_eq!(writer.flush().await.unwrap(), 1);
+        assert_eq!(stored(&server).status, Some(status(&["10.0.0.2"], None)));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn skips_unchanged_statuses() {
+        let server = FakeApiServer::new();
+        server.insert(&gateway(
+            serde_json::to_value(status(&["10.0.0.1"], None)).unwrap(),
+        ));
+        let rv = stored(&server).metadata.resource_version;
+
+        let mut writer = writer(server.client());
+        writer.queue("gw", &status(&["10.0.0.1"], None)).unwrap();
+        assert_eq!(writer.flush().await.unwrap(), 0);
+        assert_eq!(writer.pending(), 0);
+        assert_eq!(stored(&server).metadata.resource_version, rv);
+
+        // Statuses that were already written are not re-fetched.
+        writer.queue("gw", &status(&["10.0.0.2"], None)).unwrap();
+        assert_eq!(writer.flush().await.unwrap(), 1);
+        writer.queue("gw", &status(&["10.0.0.2"], None)).unwrap();
+        assert_eq!(writer.flush().await.unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn discards_statuses_of_missing_objects() {
+        let server = FakeApiServer::new();
+        let mut writer = writer(server.client());
+        writer.queue("gw", &status(&["10.0.0.1"], None)).unwrap();
+        assert_eq!(writer.flush().await.unwrap(), 0);
+        assert_eq!(writer.pending(), 0);
+    }
+
+    /// Returns a client that modifies the stored Gateway before each of the
+    /// first `n` status writes, so that they conflict.
+    fn conflicting_client(server: &FakeApiServer, n: usize) -> kube::Client {
+        let conflicts = Arc::new(AtomicUsize::new(n));
+        let server = server.clone();
+        let svc = tower::service_fn(move |req: hyper::Request<hyper::Body>| {
+            let mut server = server.clone();
+            if req.method() == hyper::Method::PUT
+                && conflicts
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok()
+            {
+                server.insert(&stored(&server));
+            }
+            tower::Service::call(&mut server, req)
+        });
+        kube::Client::new(svc, "default")
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn retries_conflicts() {
+        let server = FakeApiServer::new();
+        server.insert(&gateway(json!(null)));
+
+        let mut writer = writer(conflicting_client(&server, 2));
+        writer.queue("gw", &status(&["10.0.0.1"], None)).unwrap();
+        assert_eq!(writer.flush().await.unwrap(), 1);
+        assert_eq!(stored(&server).status, Some(status(&["10.0.0.1"], None)));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fails_after_too_many_conflicts() {
+        let server = FakeApiServer::new();
+        server.insert(&gateway(json!(null)));
+
+        let mut writer = writer(conflicting_client(&server, 3));
+        writer.queue("gw", &status(&["10.0.0.1"], None)).unwrap();
+        match writer.flush().await {
+            Err(kube::Error::Api(e)) => assert_eq!(e.code, CONFLICT),
+            res => panic!("expected a conflict, got {:?}", res),
+        }
+        assert_eq!(writer.pending(), 1);
+        assert_eq!(stored(&server).status, None);
+    }
+}
@@ -0,0 +1,339 @@
+//! Conformance levels of Gateway API features.
+//!
+//! Every field and enumerated value in the Gateway API is documented with a
+//! support level ("Support: Core", "Support: Extended", or
+//! "Support: Implementation-specific"). This module exposes those levels so
+//! that implementations can generate the list of features they support and
+//! reject routes that depend on extended features that they do not
+//! implement:
+//!
+//! ```ignore
+//! let unsupported = conformance::http_route_features(&route)
+//!     .into_iter()
+//!     .filter(|f| !SUPPORTED.contains(f))
+//!     .collect::<Vec<_>>();
+//! ```
+
+use crate::*;
+use std::{collections::BTreeSet, fmt};
+
+/// The level of support that implementations are expected to provide for a
+/// feature.
+///
+/// Levels are ordered from the most to the least portable, so the level of a
+/// composite value is the maximum of the levels of its parts.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ConformanceLevel {
+    /// All implementations must support the feature.
+    Core,
+
+    /// Implementations are encouraged to support the feature, and must
+    /// implement it as specified if they do.
+    Extended,
+
+    /// The feature's semantics are defined by each implementation.
+    ImplementationSpecific,
+}
+
+/// An extended feature that implementations may claim to support.
+///
+/// Names match the supported features reported by the upstream conformance
+/// test suite.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SupportedFeature {
+    /// `ReferenceGrant` resources are honored.
+    ReferenceGrant,
+
+    /// HTTPRoute matches on the request's method.
+    HttpRouteMethodMatching,
+
+    /// HTTPRoute matches on the request's query parameters.
+    HttpRouteQueryParamMatching,
+
+    /// HTTPRoute `RequestMirror` filters.
+    HttpRouteRequestMirror,
+
+    /// HTTPRoute `URLRewrite` filters that rewrite the hostname.
+    HttpRouteHostRewrite,
+
+    /// HTTPRoute `URLRewrite` filters that rewrite the path.
+    HttpRoutePathRewrite,
+
+    /// HTTPRoute `RequestRedirect` filters that set the port.
+    HttpRoutePortRedirect,
+
+    /// HTTPRoute `RequestRedirect` filters that set the scheme.
+    HttpRouteSchemeRedirect,
+
+    /// HTTPRoute `RequestRedirect` filters that modify the path.
+    HttpRoutePathRedirect,
+}
+
+/// Returns the extended features that an implementation must support to
+/// fully implement the route.
+pub fn http_route_features(route: &HttpRoute) -> BTreeSet<SupportedFeature> {
+    let mut features = BTreeSet::new();
+    for rule in route.spec.rules.iter().flatten() {
+        for m in rule.matches.iter().flatten() {
+            if m.method.is_some() {
+                features.insert(SupportedFeature::HttpRouteMethodMatching);
+            }
+            if m.query_params.as_ref().map_or(false, |q| !q.is_empty()) {
+                features.insert(SupportedFeature::HttpRouteQueryParamMatching);
+            }
+        }
+
+        let backend_filters = rule
+            .backend_refs
+            .iter()
+            .flatten()
+            .flat_map(|b| b.filters.iter().flatten());
+        for filter in rule.filters.iter().flatten().chain(backend_filters) {
+            filter_features(filter, &mut features);
+        }
+    }
+    features
+}
+
+/// Returns the least portable conformance level of the features used by the
+/// route.
+pub fn http_route_level(route: &HttpRoute) -> ConformanceLevel {
+    let mut level = ConformanceLevel::Core;
+    for rule in route.spec.rules.iter().flatten() {
+        for m in rule.matches.iter().flatten() {
+            level = level.max(m.conformance());
+        }
+        for backend in rule.backend_refs.iter().flatten() {
+            if let Some(backend_ref) = &backend.backend_ref {
+                level = level.max(backend_level(&backend_ref.inner));
+            }
+            for filter in backend.filters.iter().flatten() {
+                level = level.max(filter.conformance());
+            }
+        }
+        for filter in rule.filters.iter().flatten() {
+            level = level.max(filter.conformance());
+        }
+    }
+    level
+}
+
+fn filter_features(filter: &HttpRouteFilter, features: &mut BTreeSet<SupportedFeature>) {
+    match filter {
+        HttpRouteFilter::RequestHeaderModifier { .. } | HttpRouteFilter::ExtensionRef { .. } => {}
+        #[cfg(feature = "unknown-filters")]
+        HttpRouteFilter::Unknown { .. } => {}
+        HttpRouteFilter::RequestMirror { .. } => {
+            features.insert(SupportedFeature::HttpRouteRequestMirror);
+        }
+        HttpRouteFilter::RequestRedirect { request_redirect } => {
+            if request_redirect.scheme.is_some() {
+                features.insert(SupportedFeature::HttpRouteSchemeRedirect);
+            }
+            if request_redirect.port.is_some() {
+                features.insert(SupportedFeature::HttpRoutePortRedirect);
+            }
+            if request_redirect.path.is_some() {
+                features.insert(SupportedFeature::HttpRoutePathRedirect);
+            }
+        }
+        HttpRouteFilter::URLRewrite { url_rewrite } => {
+            if url_rewrite.hostname.is_some() {
+                features.insert(SupportedFeature::HttpRouteHostRewrite);
+            }
+            if url_rewrite.path.is_some() {
+                features.insert(SupportedFeature::HttpRoutePathRewrite);
+            }
+        }
+    }
+}
+
+/// Backends other than Kubernetes Services are implementation-specific.
+fn backend_level(backend: &BackendObjectReference) -> ConformanceLevel {
+    let is_service = backend.group.as_deref().unwrap_or("").is_empty()
+        && backend.kind.as_deref().unwrap_or("Service") == "Service";
+    if is_service {
+        ConformanceLevel::Core
+    } else {
+        ConformanceLevel::ImplementationSpecific
+    }
+}
+
+// === impl ConformanceLevel ===
+
+impl ConformanceLevel {
+    /// Returns the level as it is written in the API documentation, e.g.
+    /// `Extended`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Core => "Core",
+            Self::Extended => "Extended",
+            Self::ImplementationSpecific => "Implementation-specific",
+        }
+    }
+}
+
+impl fmt::Display for ConformanceLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+// === impl SupportedFeature ===
+
+impl SupportedFeature {
+    /// All supported features, in order.
+    pub const ALL: &'static [Self] = &[
+        Self::ReferenceGrant,
+        Self::HttpRouteMethodMatching,
+        Self::HttpRouteQueryParamMatching,
+        Self::HttpRouteRequestMirror,
+        Self::HttpRouteHostRewrite,
+        Self::HttpRoutePathRewrite,
+        Self::HttpRoutePortRedirect,
+        Self::HttpRouteSchemeRedirect,
+        Self::HttpRoutePathRedirect,
+    ];
+
+    /// Returns the upstream name of the feature, e.g.
+    /// `HTTPRouteMethodMatching`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReferenceGrant => "ReferenceGrant",
+            Self::HttpRouteMethodMatching => "HTTPRouteMethodMatching",
+            Self::HttpRouteQueryParamMatching => "HTTPRouteQueryParamMatching",
+            Self::HttpRouteRequestMirror => "HTTPRouteRequestMirror",
+            Self::HttpRouteHostRewrite => "HTTPRouteHostRewrite",
+            Self::HttpRoutePathRewrite => "HTTPRoutePathRewrite",
+            Self::HttpRoutePortRedirect => "HTTPRoutePortRedirect",
+            Self::HttpRouteSchemeRedirect => "HTTPRouteSchemeRedirect",
+            Self::HttpRoutePathRedirect => "HTTPRoutePathRedirect",
+        }
+    }
+
+    /// Returns the conformance level of the feature.
+    pub fn level(&self) -> ConformanceLevel {
+        match self {
+            Self::ReferenceGrant => ConformanceLevel::Core,
+            _ => ConformanceLevel::Extended,
+        }
+    }
+}
+
+impl fmt::Display for SupportedFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl std::str::FromStr for SupportedFeature {
+    type Err = UnknownFeature;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|f| f.as_str() == s)
+            .copied()
+            .ok_or_else(|| UnknownFeature(s.to_string()))
+    }
+}
+
+/// Indicates that a string does not name a [`SupportedFeature`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnknownFeature(pub String);
+
+impl fmt::Display for UnknownFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown feature {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFeature {}
+
+// === impl HttpRouteMatch ===
+
+impl HttpRouteMatch {
+    /// Returns the least portable conformance level of the match's
+    /// conditions.
+    pub fn conformance(&self) -> ConformanceLevel {
+        let mut level = self
+            .path
+            .as_ref()
+            .map_or(ConformanceLevel::Core, HttpPathMatch::conformance);
+        for header in self.headers.iter().flatten() {
+            level = level.max(header.conformance());
+        }
+        for param in self.query_params.iter().flatten() {
+            level = level.max(param.conformance());
+        }
+        if self.method.is_some() {
+            level = level.max(ConformanceLevel::Extended);
+        }
+        level
+    }
+}
+
+// === impl HttpPathMatch ===
+
+impl HttpPathMatch {
+    /// `Exact` and `PathPrefix` matches are core; regular expressions and
+    /// unknown types are implementation-specific.
+    pub fn conformance(&self) -> ConformanceLevel {
+        match self {
+            Self::Exact { .. } | Self::PathPrefix { .. } => ConformanceLevel::Core,
+            Self::RegularExpression { .. } | Self::Unknown(_) => {
+                ConformanceLevel::ImplementationSpecific
+            }
+        }
+    }
+}
+
+// === impl HttpHeaderMatch ===
+
+impl HttpHeaderMatch {
+    /// `Exact` matches are core; regular expressions and unknown types are
+    /// implementation-specific.
+    pub fn conformance(&self) -> ConformanceLevel {
+        match self {
+            Self::Exact { .. } => ConformanceLevel::Core,
+            Self::RegularExpression { .. } | Self::Unknown(_) => {
+                ConformanceLevel::ImplementationSpecific
+            }
+        }
+    }
+}
+
+// === impl HttpQueryParamMatch ===
+
+impl HttpQueryParamMatch {
+    /// `Exact` matches are extended; regular expressions and unknown types are
+    /// implementation-specific.
+    pub fn conformance(&self) -> ConformanceLevel {
+        match self {
+            Self::Exact { .. } => ConformanceLevel::Extended,
+            Self::RegularExpression { .. } | Self::Unknown(_) => {
+                ConformanceLevel::ImplementationSpecific
+            }
+        }
+    }
+}
+
+// === impl HttpRouteFilter ===
+
+impl HttpRouteFilter {
+    /// Returns the conformance level of the filter's type.
+    ///
+    /// Optional fields of core filters may themselves be extended; see
+    /// [`http_route_features`].
+    pub fn conformance(&self) -> ConformanceLevel {
+        match self {
+            Self::RequestHeaderModifier { .. } | Self::RequestRedirect { .. } => {
+                ConformanceLevel::Core
+            }
+            Self::RequestMirror { .. } | Self::URLRewrite { .. } => ConformanceLevel::Extended,
+            Self::ExtensionRef { .. } => ConformanceLevel::ImplementationSpecific,
+            #[cfg(feature = "unknown-filters")]
+            Self::Unknown { .. } => ConformanceLevel::ImplementationSpecific,
+        }
+    }
+}
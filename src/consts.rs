@@ -0,0 +1,172 @@
+//! Names of the Gateway API's groups, versions, kinds, and resources.
+//!
+//! Dynamic clients, RBAC generators, and informers need these names without
+//! a typed object at hand. [`KINDS`] describes every kind defined by the
+//! Gateway API CRDs, including those that this crate only models with the
+//! `experimental` feature, and [`ApiResource`]s can be built for each of the
+//! versions in which a kind is served:
+//!
+//! ```
+//! # use k8s_gateway_api::consts;
+//! let route = consts::find("HTTPRoute").unwrap();
+//! assert_eq!(route.plural, consts::plural::HTTP_ROUTE);
+//! let resource = route.api_resource(consts::V1BETA1).unwrap();
+//! assert_eq!(resource.api_version, "gateway.networking.k8s.io/v1beta1");
+//! ```
+
+use kube::core::{ApiResource, GroupVersionKind};
+
+/// The API group of the Gateway API.
+pub const GROUP: &str = "gateway.networking.k8s.io";
+
+/// The API group of experimental resources that have not been accepted into
+/// [`GROUP`].
+pub const EXPERIMENTAL_GROUP: &str = "gateway.networking.x-k8s.io";
+
+pub const V1ALPHA1: &str = "v1alpha1";
+pub const V1ALPHA2: &str = "v1alpha2";
+pub const V1BETA1: &str = "v1beta1";
+
+/// Kind names.
+pub mod kind {
+    pub const GATEWAY_CLASS: &str = "GatewayClass";
+    pub const GATEWAY: &str = "Gateway";
+    pub const HTTP_ROUTE: &str = "HTTPRoute";
+    pub const BACKEND_LB_POLICY: &str = "BackendLBPolicy";
+    pub const GRPC_ROUTE: &str = "GRPCRoute";
+    pub const REFERENCE_GRANT: &str = "ReferenceGrant";
+    pub const TCP_ROUTE: &str = "TCPRoute";
+    pub const TLS_ROUTE: &str = "TLSRoute";
+    pub const UDP_ROUTE: &str = "UDPRoute";
+    pub const X_BACKEND_TRAFFIC_POLICY: &str = "XBackendTrafficPolicy";
+}
+
+/// Plural resource names, as used in API paths and RBAC rules.
+pub mod plural {
+    pub const GATEWAY_CLASS: &str = "gatewayclasses";
+    pub const GATEWAY: &str = "gateways";
+    pub const HTTP_ROUTE: &str = "httproutes";
+    pub const BACKEND_LB_POLICY: &str = "backendlbpolicies";
+    pub const GRPC_ROUTE: &str = "grpcroutes";
+    pub const REFERENCE_GRANT: &str = "referencegrants";
+    pub const TCP_ROUTE: &str = "tcproutes";
+    pub const TLS_ROUTE: &str = "tlsroutes";
+    pub const UDP_ROUTE: &str = "udproutes";
+    pub const X_BACKEND_TRAFFIC_POLICY: &str = "xbackendtrafficpolicies";
+}
+
+/// Describes a kind defined by the Gateway API CRDs.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct KindInfo {
+    pub group: &'static str,
+    pub kind: &'static str,
+    pub plural: &'static str,
+    pub namespaced: bool,
+
+    /// The versions in which the kind is served, from oldest to newest.
+    pub versions: &'static [&'static str],
+}
+
+/// All kinds defined by the Gateway API CRDs.
+pub const KINDS: &[KindInfo] = &[
+    KindInfo::new(
+        GROUP,
+        kind::GATEWAY_CLASS,
+        plural::GATEWAY_CLASS,
+        false,
+        BETA,
+    ),
+    KindInfo::new(GROUP, kind::GATEWAY, plural::GATEWAY, true, BETA),
+    KindInfo::new(GROUP, kind::HTTP_ROUTE, plural::HTTP_ROUTE, true, BETA),
+    KindInfo::new(
+        GROUP,
+        kind::BACKEND_LB_POLICY,
+        plural::BACKEND_LB_POLICY,
+        true,
+        ALPHA,
+    ),
+    KindInfo::new(GROUP, kind::GRPC_ROUTE, plural::GRPC_ROUTE, true, ALPHA),
+    KindInfo::new(
+        GROUP,
+        kind::REFERENCE_GRANT,
+        plural::REFERENCE_GRANT,
+        true,
+        ALPHA,
+    ),
+    KindInfo::new(GROUP, kind::TCP_ROUTE, plural::TCP_ROUTE, true, ALPHA),
+    KindInfo::new(GROUP, kind::TLS_ROUTE, plural::TLS_ROUTE, true, ALPHA),
+    KindInfo::new(GROUP, kind::UDP_ROUTE, plural::UDP_ROUTE, true, ALPHA),
+    KindInfo::new(
+        EXPERIMENTAL_GROUP,
+        kind::X_BACKEND_TRAFFIC_POLICY,
+        plural::X_BACKEND_TRAFFIC_POLICY,
+        true,
+        &[V1ALPHA1],
+    ),
+];
+
+const ALPHA: &[&str] = &[V1ALPHA2];
+const BETA: &[&str] = &[V1ALPHA2, V1BETA1];
+
+/// Returns the kind with the given name, if it is defined by the Gateway API.
+pub fn find(kind: &str) -> Option<&'static KindInfo> {
+    KINDS.iter().find(|k| k.kind == kind)
+}
+
+/// Returns an `ApiResource` for every version of every kind.
+pub fn api_resources() -> impl Iterator<Item = ApiResource> {
+    KINDS.iter().flat_map(|k| {
+        k.versions
+            .iter()
+            .map(move |v| k.api_resource(v).expect("versions must be served"))
+    })
+}
+
+// === impl KindInfo ===
+
+impl KindInfo {
+    const fn new(
+        group: &'static str,
+        kind: &'static str,
+        plural: &'static str,
+        namespaced: bool,
+        versions: &'static [&'static str],
+    ) -> Self {
+        Self {
+            group,
+            kind,
+            plural,
+            namespaced,
+            versions,
+        }
+    }
+
+    /// Returns whether this crate models the kind with its enabled features.
+    ///
+    /// Kinds that are not served in `v1beta1` are only modeled with the
+    /// `experimental` feature.
+    pub(crate) fn is_modeled(&self) -> bool {
+        cfg!(feature = "experimental") || self.versions.contains(&V1BETA1)
+    }
+
+    /// Returns the newest version in which the kind is served.
+    pub fn latest_version(&self) -> &'static str {
+        self.versions.last().copied().unwrap_or_default()
+    }
+
+    /// Returns the `apiVersion` of the kind in the given version, e.g.
+    /// `gateway.networking.k8s.io/v1beta1`.
+    pub fn api_version(&self, version: &str) -> String {
+        format!("{}/{}", self.group, version)
+    }
+
+    /// Returns an `ApiResource` for the kind in the given version, or `None`
+    /// if the kind is not served in that version.
+    pub fn api_resource(&self, version: &str) -> Option<ApiResource> {
+        if !self.versions.contains(&version) {
+            return None;
+        }
+        let gvk = GroupVersionKind::gvk(self.group, version, self.kind);
+        Some(ApiResource::from_gvk_with_plural(&gvk, self.plural))
+    }
+}
@@ -0,0 +1,61 @@
+//! Generation of Gateway API CRDs.
+//!
+//! The CRDs generated by kube's [`CustomResourceExt`] only describe their
+//! schemas. The official CRDs are also annotated with the bundle version and
+//! release channel they were generated from, and with the API review that
+//! approved them, which the API server requires of CRDs in `*.k8s.io` groups.
+//! Tooling identifies Gateway API CRDs by these annotations (e.g.
+//! `SupportedVersion` checks read the bundle version), so [`generate`] stamps
+//! them on CRDs generated by this crate:
+//!
+//! ```ignore
+//! let crd = crd::generate::<Gateway>(Channel::Standard);
+//! assert_eq!(crd.metadata.bundle_version(), Some(crd::BUNDLE_VERSION));
+//! ```
+
+use crate::{
+    consts::GROUP,
+    well_known::{Channel, MetadataExt},
+};
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::CustomResourceExt;
+
+/// The version of the Gateway API bundle that this crate's types model.
+pub const BUNDLE_VERSION: &str = "v0.5.0-rc1";
+
+/// Annotation that references the API review that approved a CRD in a
+/// protected `*.k8s.io` group.
+pub const API_APPROVED_ANNOTATION: &str = "api-approved.kubernetes.io";
+
+/// The API review that approved the CRDs in the Gateway API group, at
+/// [`BUNDLE_VERSION`].
+pub const API_APPROVAL: &str = "https://github.com/kubernetes-sigs/gateway-api/pull/1086";
+
+/// The value of the [`API_APPROVED_ANNOTATION`] on CRDs outside of the
+/// Gateway API group, which have not been reviewed and are only released in
+/// the experimental channel.
+pub const UNAPPROVED: &str = "unapproved, experimental-only";
+
+/// Generates the CRD of kind `K`, annotated as a CRD of the given release
+/// channel at [`BUNDLE_VERSION`].
+pub fn generate<K: CustomResourceExt>(channel: Channel) -> CustomResourceDefinition {
+    let mut crd = K::crd();
+    stamp(&mut crd, channel);
+    crd
+}
+
+/// Annotates a CRD with [`BUNDLE_VERSION`], a release channel, and its API
+/// approval, replacing any existing values.
+pub fn stamp(crd: &mut CustomResourceDefinition, channel: Channel) {
+    let approval = if crd.spec.group == GROUP {
+        API_APPROVAL
+    } else {
+        UNAPPROVED
+    };
+    let meta = &mut crd.metadata;
+    meta.set_bundle_version(BUNDLE_VERSION);
+    meta.set_channel(channel);
+    meta.annotations
+        .get_or_insert_with(Default::default)
+        .insert(API_APPROVED_ANNOTATION.to_string(), approval.to_string());
+}
@@ -0,0 +1,344 @@
+//! Reports describing Gateways and routes, as by `gwctl describe`.
+//!
+//! The functions in this module gather what is known about an object from
+//! all of the Gateway API objects in a cluster: its conditions, the routes
+//! attached to it or the parents it attaches to, and (with the
+//! `experimental` feature) the policies that apply to it. CLIs only need to
+//! load objects and render the reports:
+//!
+//! ```ignore
+//! let objects = manifest::parse_yaml(&bytes)?;
+//! let compiler = ir::Compiler::default();
+//! let report = describe::describe_gateway(&compiler, &objects, &ObjectKey::new("infra", "web"))
+//!     .ok_or("gateway not found")?;
+//! for listener in &report.listeners {
+//!     println!("{}: {} routes", listener.name, listener.attached_routes.len());
+//! }
+//! ```
+//!
+//! Routes attach to listeners as they do when compiled by the given
+//! [`ir::Compiler`], including namespace selectors evaluated against the
+//! namespace labels it holds.
+
+use crate::{
+    attachment::AttachmentCounter,
+    explain::{self, ParentExplanation},
+    ir::Compiler,
+    manifest::GatewayApiObject,
+    route::{AnyRoute, RouteKind},
+    snapshot::ObjectKey,
+    *,
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::convert::TryFrom;
+
+#[cfg(feature = "experimental")]
+use crate::policy::{self, Policy, PolicyTarget};
+
+#[cfg(feature = "experimental")]
+use crate::consts::GROUP;
+
+/// Describes a Gateway.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GatewayReport {
+    pub key: ObjectKey,
+    pub class_name: String,
+
+    /// The addresses bound to the Gateway, as reported in its status.
+    pub addresses: Vec<GatewayStatusAddress>,
+
+    /// The Gateway's conditions, as reported in its status.
+    pub conditions: Vec<metav1::Condition>,
+
+    pub listeners: Vec<ListenerReport>,
+
+    /// The policies that apply to the Gateway's listeners, the Gateway, or
+    /// its GatewayClass, ordered from the most to the least specific object
+    /// they apply through.
+    #[cfg(feature = "experimental")]
+    pub policies: Vec<PolicyReport>,
+}
+
+/// Describes a listener of a Gateway.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListenerReport {
+    pub name: String,
+    pub port: PortNumber,
+    pub protocol: String,
+    pub hostname: Option<String>,
+
+    /// The routes of all kinds that attach to the listener, ordered by kind
+    /// and key.
+    pub attached_routes: Vec<(RouteKind, ObjectKey)>,
+
+    /// The listener's conditions, as reported in the Gateway's status.
+    pub conditions: Vec<metav1::Condition>,
+}
+
+/// Describes an HTTPRoute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRouteReport {
+    pub key: ObjectKey,
+    pub hostnames: Vec<String>,
+
+    /// Explains whether each parent Gateway accepts the route.
+    pub parents: Vec<ParentExplanation>,
+
+    /// The route's status for each parent, as reported by controllers.
+    pub statuses: Vec<RouteParentStatus>,
+
+    /// The backends that the route's rules reference, without duplicates, in
+    /// the order in which they are first referenced.
+    pub backends: Vec<BackendObjectReference>,
+
+    /// The policies that apply to the route's backends, the route, or its
+    /// parent Gateways and their GatewayClasses, ordered from the most to the
+    /// least specific object they apply through.
+    #[cfg(feature = "experimental")]
+    pub policies: Vec<PolicyReport>,
+}
+
+/// A policy that applies to an object.
+#[cfg(feature = "experimental")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolicyReport {
+    /// The policy's kind, e.g. `BackendLBPolicy`.
+    pub kind: &'static str,
+    pub key: ObjectKey,
+
+    /// The object the policy applies through: the object itself, or an
+    /// object above it in the hierarchy.
+    pub target: PolicyTarget,
+}
+
+/// Describes a Gateway, given all of the Gateway API objects in a cluster,
+/// or returns `None` if the Gateway does not exist.
+pub fn describe_gateway(
+    compiler: &Compiler,
+    objects: &[GatewayApiObject],
+    key: &ObjectKey,
+) -> Option<GatewayReport> {
+    let gateway = objects.iter().find_map(|o| match o {
+        GatewayApiObject::Gateway(gw) if ObjectKey::from_meta(&gw.metadata) == *key => Some(gw),
+        _ => None,
+    })?;
+
+    let mut counter = AttachmentCounter::new(gateway);
+    for (namespace, labels) in compiler.namespace_labels() {
+        counter = counter.with_namespace_labels(namespace.clone(), labels.clone());
+    }
+    for route in objects
+        .iter()
+        .filter_map(|o| AnyRoute::try_from(o.clone()).ok())
+    {
+        counter.update(&route);
+    }
+
+    let status = gateway.status.as_ref();
+    let listener_statuses = status
+        .and_then(|s| s.listeners.as_deref())
+        .unwrap_or_default();
+    let listeners = gateway
+        .spec
+        .listeners
+        .iter()
+        .map(|l| ListenerReport {
+            name: l.name.clone(),
+            port: l.port,
+            protocol: l.protocol.clone(),
+            hostname: l.hostname.clone(),
+            attached_routes: counter
+                .routes(&l.name)
+                .map(|(kind, key)| (kind, key.clone()))
+                .collect(),
+            conditions: listener_statuses
+                .iter()
+                .find(|s| *s.name == l.name)
+                .map(|s| s.conditions.clone())
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    Some(GatewayReport {
+        key: key.clone(),
+        class_name: gateway.spec.gateway_class_name.clone(),
+        addresses: status.and_then(|s| s.addresses.clone()).unwrap_or_default(),
+        conditions: status
+            .and_then(|s| s.conditions.clone())
+            .unwrap_or_default(),
+        listeners,
+        #[cfg(feature = "experimental")]
+        policies: gateway_policies(objects, gateway),
+    })
+}
+
+/// Describes an HTTPRoute, given all of the Gateway API objects in a
+/// cluster, or returns `None` if the route does not exist.
+pub fn describe_httproute(
+    compiler: &Compiler,
+    objects: &[GatewayApiObject],
+    key: &ObjectKey,
+) -> Option<HttpRouteReport> {
+    let route = objects.iter().find_map(|o| match o {
+        GatewayApiObject::HttpRoute(r) if ObjectKey::from_meta(&r.metadata) == *key => Some(r),
+        _ => None,
+    })?;
+
+    let mut backends = Vec::<BackendObjectReference>::new();
+    for rule in route.spec.rules.iter().flatten() {
+        let mirrors = rule
+            .filters
+            .iter()
+            .flatten()
+            .chain(
+                rule.backend_refs
+                    .iter()
+                    .flatten()
+                    .flat_map(|b| b.filters.iter().flatten()),
+            )
+            .filter_map(|f| match f {
+                HttpRouteFilter::RequestMirror { request_mirror } => {
+                    Some(&request_mirror.backend_ref)
+                }
+                _ => None,
+            });
+        let refs = rule
+            .backend_refs
+            .iter()
+            .flatten()
+            .filter_map(|b| Some(&b.backend_ref.as_ref()?.inner))
+            .chain(mirrors);
+        for backend in refs {
+            if !backends.contains(backend) {
+                backends.push(backend.clone());
+            }
+        }
+    }
+
+    Some(HttpRouteReport {
+        key: key.clone(),
+        hostnames: route.spec.hostnames.clone().unwrap_or_default(),
+        parents: explain::explain_route(compiler, objects, route),
+        statuses: route
+            .status
+            .as_ref()
+            .map(|s| s.inner.parents.clone())
+            .unwrap_or_default(),
+        #[cfg(feature = "experimental")]
+        policies: route_policies(objects, key, route, &backends),
+        backends,
+    })
+}
+
+/// Returns the policies that apply to `target`, from highest to lowest
+/// precedence.
+///
+/// If `target` names a section, policies that target the whole object are
+/// included along with those that target the section.
+#[cfg(feature = "experimental")]
+pub fn list_policies_for(objects: &[GatewayApiObject], target: &PolicyTarget) -> Vec<PolicyReport> {
+    let lb_policies = objects.iter().filter_map(|o| match o {
+        GatewayApiObject::BackendLbPolicy(p) => Some(p),
+        _ => None,
+    });
+    policy::targeting(lb_policies, target)
+        .into_iter()
+        .map(|p| PolicyReport {
+            kind: "BackendLBPolicy",
+            key: ObjectKey::from_meta(p.metadata()),
+            target: target.clone(),
+        })
+        .collect()
+}
+
+/// Returns the policies that apply to each of `targets`, omitting policies
+/// that apply through an earlier target.
+#[cfg(feature = "experimental")]
+fn policies_for_all(objects: &[GatewayApiObject], targets: &[PolicyTarget]) -> Vec<PolicyReport> {
+    let mut reports = Vec::new();
+    for target in targets {
+        for report in list_policies_for(objects, target) {
+            if !reports
+                .iter()
+                .any(|r: &PolicyReport| r.kind == report.kind && r.key == report.key)
+            {
+                reports.push(report);
+            }
+        }
+    }
+    reports
+}
+
+#[cfg(feature = "experimental")]
+fn gateway_policies(objects: &[GatewayApiObject], gateway: &Gateway) -> Vec<PolicyReport> {
+    let mut targets = gateway
+        .spec
+        .listeners
+        .iter()
+        .map(|l| gateway_target(&gateway.metadata, Some(&l.name)))
+        .collect::<Vec<_>>();
+    targets.push(gateway_target(&gateway.metadata, None));
+    targets.push(class_target(&gateway.spec.gateway_class_name));
+    policies_for_all(objects, &targets)
+}
+
+#[cfg(feature = "experimental")]
+fn route_policies(
+    objects: &[GatewayApiObject],
+    key: &ObjectKey,
+    route: &HttpRoute,
+    backends: &[BackendObjectReference],
+) -> Vec<PolicyReport> {
+    let mut targets = backends
+        .iter()
+        .map(|b| PolicyTarget {
+            group: b.group.as_deref().unwrap_or("").to_string(),
+            kind: b.kind.as_deref().unwrap_or("Service").to_string(),
+            namespace: Some(b.namespace.as_deref().unwrap_or(&key.namespace).to_string()),
+            name: b.name.clone(),
+            section_name: None,
+        })
+        .collect::<Vec<_>>();
+    targets.push(PolicyTarget {
+        group: GROUP.to_string(),
+        kind: "HTTPRoute".to_string(),
+        namespace: Some(key.namespace.to_string()),
+        name: key.name.to_string(),
+        section_name: None,
+    });
+    for gw_key in crate::snapshot::parent_gateways(&key.namespace, &route.spec.inner) {
+        let gateway = objects.iter().find_map(|o| match o {
+            GatewayApiObject::Gateway(gw) if ObjectKey::from_meta(&gw.metadata) == gw_key => {
+                Some(gw)
+            }
+            _ => None,
+        });
+        if let Some(gateway) = gateway {
+            targets.push(gateway_target(&gateway.metadata, None));
+            targets.push(class_target(&gateway.spec.gateway_class_name));
+        }
+    }
+    policies_for_all(objects, &targets)
+}
+
+#[cfg(feature = "experimental")]
+fn gateway_target(meta: &metav1::ObjectMeta, section_name: Option<&str>) -> PolicyTarget {
+    PolicyTarget {
+        group: GROUP.to_string(),
+        kind: "Gateway".to_string(),
+        namespace: meta.namespace.clone(),
+        name: meta.name.clone().unwrap_or_default(),
+        section_name: section_name.map(ToString::to_string),
+    }
+}
+
+#[cfg(feature = "experimental")]
+fn class_target(name: &str) -> PolicyTarget {
+    PolicyTarget {
+        group: GROUP.to_string(),
+        kind: "GatewayClass".to_string(),
+        namespace: None,
+        name: name.to_string(),
+        section_name: None,
+    }
+}
@@ -0,0 +1,187 @@
+//! Conversions between this crate's types and kube's untyped
+//! [`DynamicObject`].
+//!
+//! Generic controllers that watch several kinds, and garbage collectors that
+//! only need object metadata, typically handle `DynamicObject`s. Every kind
+//! converts into a `DynamicObject` with [`From`], and back with [`TryFrom`],
+//! which accepts any version in which the kind is served (see
+//! [`unversioned`](crate::unversioned)).
+//!
+//! kube 0.76 predates `PartialObjectMeta`; [`partial_object_meta`] projects an
+//! object onto the equivalent `DynamicObject` that carries only its type and
+//! metadata, as returned by the API server's metadata-only endpoints.
+
+use crate::{
+    consts::GROUP,
+    manifest::{self, GatewayApiObject},
+    unversioned::{self, Error},
+};
+use kube::{
+    core::{DynamicObject, TypeMeta},
+    Resource,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::convert::TryFrom;
+
+/// Returns the type and metadata of an object, without its spec or status.
+pub fn partial_object_meta<K: Resource<DynamicType = ()>>(obj: &K) -> DynamicObject {
+    DynamicObject {
+        types: Some(type_meta::<K>()),
+        metadata: obj.meta().clone(),
+        data: serde_json::Value::Null,
+    }
+}
+
+fn type_meta<K: Resource<DynamicType = ()>>() -> TypeMeta {
+    TypeMeta {
+        api_version: K::api_version(&()).into_owned(),
+        kind: K::kind(&()).into_owned(),
+    }
+}
+
+fn to_dynamic<K>(mut obj: K) -> DynamicObject
+where
+    K: Resource<DynamicType = ()> + Serialize,
+{
+    // The metadata is moved rather than re-encoded.
+    let metadata = std::mem::take(obj.meta_mut());
+    let mut data = serde_json::to_value(&obj).expect("object must serialize");
+    if let Some(fields) = data.as_object_mut() {
+        for field in ["apiVersion", "kind", "metadata"] {
+            fields.remove(field);
+        }
+    }
+    DynamicObject {
+        types: Some(type_meta::<K>()),
+        metadata,
+        data,
+    }
+}
+
+fn from_dynamic<K>(obj: DynamicObject) -> Result<K, Error>
+where
+    K: Resource<DynamicType = ()> + DeserializeOwned + Serialize,
+{
+    let value = serde_json::to_value(&obj).expect("object must serialize");
+    if K::group(&()) == GROUP {
+        return unversioned::decode(value).map(unversioned::Unversioned::into_inner);
+    }
+
+    // Kinds outside of the standard group are only served in one version.
+    let types = obj.types.unwrap_or_default();
+    if types.kind != K::kind(&()) || types.api_version != K::api_version(&()) {
+        return Err(Error::Decode(manifest::Error::UnknownKind {
+            api_version: types.api_version,
+            kind: types.kind,
+        }));
+    }
+    serde_json::from_value(value).map_err(|source| {
+        Error::Decode(manifest::Error::Decode {
+            kind: unversioned::kind_name(&types.kind),
+            source,
+        })
+    })
+}
+
+macro_rules! impl_dynamic {
+    ($($(#[$attr:meta])* $ty:ty),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            impl From<$ty> for DynamicObject {
+                fn from(obj: $ty) -> Self {
+                    to_dynamic(obj)
+                }
+            }
+
+            $(#[$attr])*
+            impl TryFrom<DynamicObject> for $ty {
+                type Error = Error;
+
+                fn try_from(obj: DynamicObject) -> Result<Self, Self::Error> {
+                    from_dynamic(obj)
+                }
+            }
+        )+
+    };
+}
+
+impl_dynamic!(
+    crate::GatewayClass,
+    crate::Gateway,
+    crate::HttpRoute,
+    #[cfg(feature = "experimental")]
+    crate::BackendLbPolicy,
+    #[cfg(feature = "experimental")]
+    crate::GrpcRoute,
+    #[cfg(feature = "experimental")]
+    crate::ReferenceGrant,
+    #[cfg(feature = "experimental")]
+    crate::TcpRoute,
+    #[cfg(feature = "experimental")]
+    crate::TlsRoute,
+    #[cfg(feature = "experimental")]
+    crate::UdpRoute,
+    #[cfg(feature = "experimental")]
+    crate::XBackendTrafficPolicy,
+);
+
+// === impl GatewayApiObject ===
+
+impl From<GatewayApiObject> for DynamicObject {
+    fn from(obj: GatewayApiObject) -> Self {
+        match obj {
+            GatewayApiObject::GatewayClass(o) => o.into(),
+            GatewayApiObject::Gateway(o) => o.into(),
+            GatewayApiObject::HttpRoute(o) => o.into(),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::BackendLbPolicy(o) => o.into(),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::GrpcRoute(o) => o.into(),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::ReferenceGrant(o) => o.into(),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::TcpRoute(o) => o.into(),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::TlsRoute(o) => o.into(),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::UdpRoute(o) => o.into(),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::XBackendTrafficPolicy(o) => o.into(),
+        }
+    }
+}
+
+impl TryFrom<DynamicObject> for GatewayApiObject {
+    type Error = Error;
+
+    fn try_from(obj: DynamicObject) -> Result<Self, Self::Error> {
+        let value = serde_json::to_value(&obj).expect("object must serialize");
+        unversioned::decode_any(value).map(unversioned::Unversioned::into_inner)
+    }
+}
+
+impl GatewayApiObject {
+    /// Returns the type and metadata of this object, without its spec or
+    /// status.
+    pub fn partial_object_meta(&self) -> DynamicObject {
+        match self {
+            Self::GatewayClass(o) => partial_object_meta(o),
+            Self::Gateway(o) => partial_object_meta(o),
+            Self::HttpRoute(o) => partial_object_meta(o),
+            #[cfg(feature = "experimental")]
+            Self::BackendLbPolicy(o) => partial_object_meta(o),
+            #[cfg(feature = "experimental")]
+            Self::GrpcRoute(o) => partial_object_meta(o),
+            #[cfg(feature = "experimental")]
+            Self::ReferenceGrant(o) => partial_object_meta(o),
+            #[cfg(feature = "experimental")]
+            Self::TcpRoute(o) => partial_object_meta(o),
+            #[cfg(feature = "experimental")]
+            Self::TlsRoute(o) => partial_object_meta(o),
+            #[cfg(feature = "experimental")]
+            Self::UdpRoute(o) => partial_object_meta(o),
+            #[cfg(feature = "experimental")]
+            Self::XBackendTrafficPolicy(o) => partial_object_meta(o),
+        }
+    }
+}
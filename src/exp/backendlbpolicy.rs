@@ -0,0 +1,33 @@
+use crate::*;
+
+/// BackendLBPolicy provides a way to define load balancing rules for a
+/// backend.
+///
+/// Support: Extended
+//
+// gateway:experimental
+#[derive(
+    Clone, Debug, kube::CustomResource, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[kube(
+    group = "gateway.networking.k8s.io",
+    version = "v1alpha2",
+    kind = "BackendLBPolicy",
+    struct = "BackendLbPolicy",
+    status = "PolicyStatus",
+    namespaced
+)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendLbPolicySpec {
+    /// TargetRef identifies an API object to apply policy to. Currently,
+    /// Backends (i.e. Service, ServiceImport, or any implementation-specific
+    /// backendRef) are the only valid API target references.
+    #[schemars(length(min = 1, max = 16))]
+    pub target_refs: Vec<LocalPolicyTargetReference>,
+
+    /// SessionPersistence defines and configures session persistence for the
+    /// backend.
+    ///
+    /// Support: Extended
+    pub session_persistence: Option<SessionPersistence>,
+}
@@ -0,0 +1,211 @@
+use crate::*;
+
+/// XBackendTrafficPolicy defines the configuration for how traffic to a
+/// target backend should be handled.
+///
+/// Support: Extended
+//
+// gateway:experimental
+#[derive(
+    Clone, Debug, kube::CustomResource, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[kube(
+    group = "gateway.networking.x-k8s.io",
+    version = "v1alpha1",
+    kind = "XBackendTrafficPolicy",
+    struct = "XBackendTrafficPolicy",
+    status = "PolicyStatus",
+    namespaced
+)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendTrafficPolicySpec {
+    /// TargetRefs identifies API object(s) to apply this policy to.
+    /// Currently, Backends (A grouping of like endpoints such as Service,
+    /// ServiceImport, or any implementation-specific backendRef) are the only
+    /// valid API target references.
+    ///
+    /// Currently, a TargetRef can not be scoped to a specific port on a
+    /// Service.
+    #[schemars(length(min = 1, max = 16))]
+    pub target_refs: Vec<LocalPolicyTargetReference>,
+
+    /// RetryConstraint defines the configuration for when to allow or prevent
+    /// further retries to a target backend, by dynamically calculating a 'retry
+    /// budget'. This budget is calculated based on the percentage of incoming
+    /// traffic composed of retries over a given time interval. Once the budget
+    /// is exceeded, additional retries will be rejected.
+    ///
+    /// For example, if the retry budget interval is 10 seconds, there have been
+    /// 1000 active requests in the past 10 seconds, and the allowed percentage
+    /// of requests that can be retried is 20% (the default), then 200 of those
+    /// requests may be composed of retries. Active requests will only be
+    /// considered for the duration of the interval when calculating the retry
+    /// budget. Retrying the same original request multiple times within the
+    /// retry budget interval will lead to each retry being counted towards
+    /// calculating the budget.
+    ///
+    /// Configuring a RetryConstraint in BackendTrafficPolicy is compatible with
+    /// HTTPRoute Retry settings for each HTTPRouteRule that targets the same
+    /// backend. While the HTTPRouteRule Retry stanza can specify whether a
+    /// request will be retried, and the number of retry attempts each client
+    /// may perform, RetryConstraint helps prevent cascading failures such as
+    /// retry storms during periods of consistent failures.
+    ///
+    /// After the retry budget has been exceeded, additional retries to the
+    /// backend MUST return a 503 response to the client.
+    ///
+    /// Support: Extended
+    pub retry_constraint: Option<RetryConstraint>,
+
+    /// SessionPersistence defines and configures session persistence for the
+    /// backend.
+    ///
+    /// Support: Extended
+    pub session_persistence: Option<SessionPersistence>,
+}
+
+/// RetryConstraint defines the configuration for when to retry a request.
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryConstraint {
+    /// Budget holds the details of the retry budget configuration.
+    pub budget: Option<BudgetDetails>,
+
+    /// MinRetryRate defines the minimum rate of retries that will be allowable
+    /// over a specified duration of time.
+    ///
+    /// The effective overall minimum rate of retries targeting the backend
+    /// service may be much higher, as there can be any number of clients which
+    /// are applying this setting locally.
+    ///
+    /// This ensures that requests can still be retried during periods of low
+    /// traffic, where the budget for retries may be calculated as a very low
+    /// value.
+    ///
+    /// Support: Extended
+    pub min_retry_rate: Option<RequestRate>,
+}
+
+/// BudgetDetails specifies the details of the budget configuration, like the
+/// percentage of requests in the budget, and the interval between checks.
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetDetails {
+    /// Percent defines the maximum percentage of active requests that may be
+    /// made up of retries.
+    ///
+    /// Defaults to 20% when unset.
+    ///
+    /// Support: Extended
+    #[schemars(range(min = 0, max = 100))]
+    pub percent: Option<u8>,
+
+    /// Interval defines the duration in which requests will be considered for
+    /// calculating the budget for retries.
+    ///
+    /// Defaults to 10s when unset.
+    ///
+    /// Support: Extended
+    pub interval: Option<Duration>,
+}
+
+/// RequestRate expresses a rate of requests over a given period of time.
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestRate {
+    /// Count specifies the number of requests per time interval.
+    ///
+    /// Support: Extended
+    #[schemars(range(min = 1, max = 1_000_000))]
+    pub count: Option<u32>,
+
+    /// Interval specifies the divisor of the rate of requests, the amount of
+    /// time during which the given count of requests occur.
+    ///
+    /// Support: Extended
+    pub interval: Option<Duration>,
+}
+
+/// SessionPersistence defines the desired state of SessionPersistence.
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPersistence {
+    /// SessionName defines the name of the persistent session token which may
+    /// be reflected in the cookie or the header. Users should avoid reusing
+    /// session names to prevent unintended consequences, such as rejection or
+    /// unpredictable behavior.
+    ///
+    /// Support: Implementation-specific
+    #[schemars(length(max = 128))]
+    pub session_name: Option<String>,
+
+    /// AbsoluteTimeout defines the absolute timeout of the persistent session.
+    /// Once the AbsoluteTimeout duration has elapsed, the session becomes
+    /// invalid.
+    ///
+    /// Support: Extended
+    pub absolute_timeout: Option<Duration>,
+
+    /// IdleTimeout defines the idle timeout of the persistent session. Once the
+    /// session has been idle for more than the specified IdleTimeout duration,
+    /// the session becomes invalid.
+    ///
+    /// Support: Extended
+    pub idle_timeout: Option<Duration>,
+
+    /// Type defines the type of session persistence such as through the use a
+    /// header or cookie. Defaults to cookie based session persistence.
+    ///
+    /// Support: Core for "Cookie" type
+    ///
+    /// Support: Extended for "Header" type
+    pub r#type: Option<SessionPersistenceType>,
+
+    /// CookieConfig provides configuration settings that are specific to
+    /// cookie-based session persistence.
+    ///
+    /// Support: Core
+    pub cookie_config: Option<CookieConfig>,
+}
+
+/// SessionPersistenceType is the type of session persistence, either "Cookie"
+/// or "Header".
+pub type SessionPersistenceType = String;
+
+/// CookieConfig defines the configuration for cookie-based session
+/// persistence.
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct CookieConfig {
+    /// LifetimeType specifies whether the cookie has a permanent or
+    /// session-based lifetime. A permanent cookie persists until its specified
+    /// expiry time, defined by the Expires or Max-Age cookie attributes, while
+    /// a session cookie is deleted when the current session ends.
+    ///
+    /// When set to "Permanent", AbsoluteTimeout indicates the cookie's lifetime
+    /// via the Expires or Max-Age cookie attributes and is required.
+    ///
+    /// When set to "Session", AbsoluteTimeout indicates the absolute lifetime
+    /// of the cookie tracked by the gateway and is optional.
+    ///
+    /// Defaults to "Session".
+    ///
+    /// Support: Core for "Session" type
+    ///
+    /// Support: Extended for "Permanent" type
+    pub lifetime_type: Option<CookieLifetimeType>,
+}
+
+/// CookieLifetimeType is the lifetime of a session cookie, either "Session" or
+/// "Permanent".
+pub type CookieLifetimeType = String;
@@ -0,0 +1,558 @@
+use crate::*;
+
+/// GRPCRoute provides a way to route gRPC requests. This includes the
+/// capability to match requests by hostname, gRPC service, gRPC method, or
+/// HTTP/2 header. Filters can be used to specify additional processing steps.
+/// Backends specify where matching requests will be routed.
+///
+/// GRPCRoute falls under extended support within the Gateway API. Within the
+/// following specification, the word "MUST" indicates that an implementation
+/// supporting GRPCRoute must conform to the indicated requirement, but an
+/// implementation not supporting this route type need not follow the
+/// requirement unless explicitly indicated.
+///
+/// Implementations supporting `GRPCRoute` with the `HTTPS` `ProtocolType` MUST
+/// accept HTTP/2 connections without an initial upgrade from HTTP/1.1, i.e. via
+/// ALPN. If the implementation does not support this, then it MUST set the
+/// "Accepted" condition to "False" for the affected listener with a reason of
+/// "UnsupportedProtocol". Implementations MAY also accept HTTP/2 connections
+/// with an upgrade from HTTP/1.
+///
+/// Implementations supporting `GRPCRoute` with the `HTTP` `ProtocolType` MUST
+/// support HTTP/2 over cleartext TCP (h2c,
+/// <https://www.rfc-editor.org/rfc/rfc7540#section-3.1>) without an initial
+/// upgrade from HTTP/1.1, i.e. with prior knowledge
+/// (<https://www.rfc-editor.org/rfc/rfc7540#section-3.4>). If the
+/// implementation does not support this, then it MUST set the "Accepted"
+/// condition to "False" for the affected listener with a reason of
+/// "UnsupportedProtocol". Implementations MAY also accept HTTP/2 connections
+/// with an upgrade from HTTP/1, i.e. without prior knowledge.
+#[derive(
+    Clone, Debug, kube::CustomResource, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[kube(
+    group = "gateway.networking.k8s.io",
+    version = "v1alpha2",
+    kind = "GRPCRoute",
+    struct = "GrpcRoute",
+    status = "GrpcRouteStatus",
+    namespaced,
+    printcolumn = r#"{"name":"Hostnames", "type":"string", "jsonPath":".spec.hostnames"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
+)]
+pub struct GrpcRouteSpec {
+    /// Common route information.
+    #[serde(flatten)]
+    pub inner: CommonRouteSpec,
+
+    /// Hostnames defines a set of hostnames to match against the GRPC
+    /// Host header to select a GRPCRoute to process the request. This matches
+    /// the RFC 1123 definition of a hostname with 2 notable exceptions:
+    ///
+    /// 1. IPs are not allowed.
+    /// 2. A hostname may be prefixed with a wildcard label (`*.`). The wildcard
+    ///    label MUST appear by itself as the first label.
+    ///
+    /// If a hostname is specified by both the Listener and GRPCRoute, there
+    /// MUST be at least one intersecting hostname for the GRPCRoute to be
+    /// attached to the Listener. For example:
+    ///
+    /// * A Listener with `test.example.com` as the hostname matches GRPCRoutes
+    ///   that have either not specified any hostnames, or have specified at
+    ///   least one of `test.example.com` or `*.example.com`.
+    /// * A Listener with `*.example.com` as the hostname matches GRPCRoutes
+    ///   that have either not specified any hostnames or have specified at least
+    ///   one hostname that matches the Listener hostname. For example,
+    ///   `test.example.com` and `*.example.com` would both match. On the other
+    ///   hand, `example.com` and `test.example.net` would not match.
+    ///
+    /// Hostnames that are prefixed with a wildcard label (`*.`) are interpreted
+    /// as a suffix match. That means that a match for `*.example.com` would match
+    /// both `test.example.com`, and `foo.test.example.com`, but not `example.com`.
+    ///
+    /// If both the Listener and GRPCRoute have specified hostnames, any
+    /// GRPCRoute hostnames that do not match the Listener hostname MUST be
+    /// ignored. For example, if a Listener specified `*.example.com`, and the
+    /// GRPCRoute specified `test.example.com` and `test.example.net`,
+    /// `test.example.net` MUST NOT be considered for a match.
+    ///
+    /// If both the Listener and GRPCRoute have specified hostnames, and none
+    /// match with the criteria above, then the GRPCRoute MUST NOT be accepted by
+    /// the implementation. The implementation MUST raise an 'Accepted' Condition
+    /// with a status of `False` in the corresponding RouteParentStatus.
+    ///
+    /// Support: Core
+    #[schemars(length(max = "crate::validation::MAX_HOSTNAMES"))]
+    pub hostnames: Option<Vec<Hostname>>,
+
+    /// Rules are a list of GRPC matchers, filters and actions.
+    #[schemars(length(max = "crate::validation::MAX_RULES"))]
+    pub rules: Option<Vec<GrpcRouteRule>>,
+}
+
+/// GRPCRouteStatus defines the observed state of GRPCRoute.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+pub struct GrpcRouteStatus {
+    /// Common route status information.
+    #[serde(flatten)]
+    pub inner: RouteStatus,
+}
+
+/// GRPCRouteRule defines the semantics for matching a gRPC request based on
+/// conditions (matches), processing it (filters), and forwarding the request to
+/// an API object (backendRefs).
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcRouteRule {
+    /// Name is the name of the route rule. This name MUST be unique within a
+    /// Route if it is set.
+    ///
+    /// Support: Extended
+    ///
+    // gateway:experimental
+    pub name: Option<SectionName>,
+
+    /// Matches define conditions used for matching the rule against incoming
+    /// gRPC requests. Each match is independent, i.e. this rule will be matched
+    /// if **any** one of the matches is satisfied.
+    ///
+    /// For example, take the following matches configuration:
+    ///
+    /// ```yaml
+    /// matches:
+    /// - method:
+    ///     service: foo.bar
+    ///   headers:
+    ///     values:
+    ///       version: 2
+    /// - method:
+    ///     service: foo.bar.v2
+    /// ```
+    ///
+    /// For a request to match against this rule, it MUST satisfy
+    /// EITHER of the two conditions:
+    ///
+    /// - service of foo.bar AND contains the header `version: 2`
+    /// - service of foo.bar.v2
+    ///
+    /// See the documentation for GRPCRouteMatch on how to specify multiple
+    /// match conditions to be ANDed together.
+    ///
+    /// If no matches are specified, the implementation MUST match every gRPC
+    /// request.
+    ///
+    /// Proxy or Load Balancer routing configuration generated from GRPCRoutes
+    /// MUST prioritize rules based on the following criteria, continuing on
+    /// ties. Merging MUST not be done between GRPCRoutes and HTTPRoutes.
+    /// Precedence MUST be given to the rule with the largest number of:
+    ///
+    /// * Characters in a matching non-wildcard hostname.
+    /// * Characters in a matching hostname.
+    /// * Characters in a matching service.
+    /// * Characters in a matching method.
+    /// * Header matches.
+    ///
+    /// If ties still exist across multiple Routes, matching precedence MUST be
+    /// determined in order of the following criteria, continuing on ties:
+    ///
+    /// * The oldest Route based on creation timestamp.
+    /// * The Route appearing first in alphabetical order by
+    ///   "{namespace}/{name}".
+    ///
+    /// If ties still exist within the Route that has been given precedence,
+    /// matching precedence MUST be granted to the first matching rule meeting
+    /// the above criteria.
+    #[schemars(length(max = "crate::validation::MAX_GRPC_MATCHES"))]
+    pub matches: Option<Vec<GrpcRouteMatch>>,
+
+    /// Filters define the filters that are applied to requests that match
+    /// this rule.
+    ///
+    /// The effects of ordering of multiple behaviors are currently unspecified.
+    /// This can change in the future based on feedback during the alpha stage.
+    ///
+    /// Conformance-levels at this level are defined based on the type of filter:
+    ///
+    /// - ALL core filters MUST be supported by all implementations that support
+    ///   GRPCRoute.
+    /// - Implementers are encouraged to support extended filters.
+    /// - Implementation-specific custom filters have no API guarantees across
+    ///   implementations.
+    ///
+    /// Specifying the same filter multiple times is not supported unless
+    /// explicitly indicated in the filter.
+    ///
+    /// If an implementation can not support a combination of filters, it must
+    /// clearly document that limitation. In cases where incompatible or
+    /// unsupported filters are specified and cause the `Accepted` condition to
+    /// be set to status `False`, implementations may use the
+    /// `IncompatibleFilters` reason to specify this configuration error.
+    ///
+    /// Support: Core
+    #[schemars(length(max = "crate::validation::MAX_FILTERS"))]
+    pub filters: Option<Vec<GrpcRouteFilter>>,
+
+    /// BackendRefs defines the backend(s) where matching requests should be
+    /// sent.
+    ///
+    /// Failure behavior here depends on how many BackendRefs are specified and
+    /// how many are invalid.
+    ///
+    /// If *all* entries in BackendRefs are invalid, and there are also no
+    /// filters specified in this route rule, *all* traffic which matches this
+    /// rule MUST receive an `UNAVAILABLE` status.
+    ///
+    /// See the GRPCBackendRef definition for the rules about what makes a single
+    /// GRPCBackendRef invalid.
+    ///
+    /// When a GRPCBackendRef is invalid, `UNAVAILABLE` statuses MUST be returned
+    /// for requests that would have otherwise been routed to an invalid backend.
+    /// If multiple backends are specified, and some are invalid, the proportion
+    /// of requests that would otherwise have been routed to an invalid backend
+    /// MUST receive an `UNAVAILABLE` status.
+    ///
+    /// Support: Core for Kubernetes Service
+    ///
+    /// Support: Implementation-specific for any other resource
+    ///
+    /// Support for weight: Core
+    #[schemars(length(max = "crate::validation::MAX_BACKEND_REFS"))]
+    pub backend_refs: Option<Vec<GrpcBackendRef>>,
+}
+
+/// GRPCRouteMatch defines the predicate used to match requests to a given
+/// action. Multiple match types are ANDed together, i.e. the match will
+/// evaluate to true only if all conditions are satisfied.
+///
+/// For example, the match below will match a gRPC request only if its service
+/// is `foo` AND it contains the `version: v1` header:
+///
+/// ```yaml
+/// matches:
+///   - method:
+///     type: Exact
+///     service: "foo"
+///     headers:
+///   - name: "version"
+///     value "v1"
+/// ```
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcRouteMatch {
+    /// Method specifies a gRPC request service/method matcher. If this field is
+    /// not specified, all services and methods will match.
+    pub method: Option<GrpcMethodMatch>,
+
+    /// Headers specifies gRPC request header matchers. Multiple match values
+    /// are ANDed together, meaning, a request MUST match all the specified
+    /// headers to select the route.
+    #[schemars(length(max = "crate::validation::MAX_MATCH_CONDITIONS"))]
+    pub headers: Option<Vec<GrpcHeaderMatch>>,
+}
+
+/// GRPCMethodMatch describes how to select a gRPC route by matching the gRPC
+/// request service and/or method.
+///
+/// At least one of Service and Method MUST be a non-empty string.
+///
+/// The `type` specifies how to match against the service and/or method:
+///
+/// * "Exact" - Matches the service and/or method exactly.
+/// * "RegularExpression" - Matches the service and/or method against a
+///   regular expression. Support: Implementation-specific
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(remote = "Self", tag = "type", rename_all = "PascalCase")]
+#[non_exhaustive]
+pub enum GrpcMethodMatch {
+    #[serde(rename_all = "camelCase")]
+    Exact {
+        /// Value of the service to match against. If left empty or omitted,
+        /// will match any service.
+        service: Option<String>,
+
+        /// Value of the method to match against. If left empty or omitted,
+        /// will match all services.
+        method: Option<String>,
+    },
+
+    #[serde(rename_all = "camelCase")]
+    RegularExpression {
+        /// A regular expression to match against the service. If left empty
+        /// or omitted, will match any service.
+        service: Option<String>,
+
+        /// A regular expression to match against the method. If left empty or
+        /// omitted, will match all services.
+        method: Option<String>,
+    },
+
+    /// A match of a type that is not known to this crate, as it was
+    /// deserialized. Unknown matches do not match any method.
+    #[serde(skip)]
+    #[schemars(skip)]
+    Unknown(serde_json::Value),
+}
+
+/// GRPCHeaderName is the name of a gRPC header.
+pub type GrpcHeaderName = String;
+
+/// GRPCHeaderMatch describes how to select a gRPC route by matching gRPC
+/// request headers.
+///
+/// Name matching is case insensitive. If multiple entries specify equivalent
+/// header names, only the first entry with an equivalent name MUST be
+/// considered for a match. Subsequent entries with an equivalent header name
+/// MUST be ignored.
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(remote = "Self", tag = "type", rename_all = "PascalCase")]
+#[non_exhaustive]
+pub enum GrpcHeaderMatch {
+    #[serde(rename_all = "camelCase")]
+    Exact { name: GrpcHeaderName, value: String },
+
+    #[serde(rename_all = "camelCase")]
+    RegularExpression { name: GrpcHeaderName, value: String },
+
+    /// A match of a type that is not known to this crate, as it was
+    /// deserialized. Unknown matches are not satisfied by any headers.
+    #[serde(skip)]
+    #[schemars(skip)]
+    Unknown(serde_json::Value),
+}
+
+/// GRPCRouteFilter defines processing steps that must be completed during the
+/// request or response lifecycle. GRPCRouteFilters are meant as an extension
+/// point to express processing that may be done in Gateway implementations.
+/// Some examples include request or response modification, implementing
+/// authentication strategies, rate-limiting, and traffic shaping. API
+/// guarantee/conformance is defined based on the type of the filter.
+//
+// Filter configurations are boxed for the same reasons as `HttpRouteFilter`.
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(tag = "type", rename_all = "PascalCase")]
+#[non_exhaustive]
+pub enum GrpcRouteFilter {
+    /// RequestHeaderModifier defines a schema for a filter that modifies request
+    /// headers.
+    ///
+    /// Support: Core
+    #[serde(rename_all = "camelCase")]
+    RequestHeaderModifier {
+        request_header_modifier: Box<HttpRequestHeaderFilter>,
+    },
+
+    /// RequestMirror defines a schema for a filter that mirrors requests.
+    /// Requests are sent to the specified destination, but responses from
+    /// that destination are ignored.
+    ///
+    /// Support: Extended
+    #[serde(rename_all = "camelCase")]
+    RequestMirror {
+        request_mirror: Box<HttpRequestMirrorFilter>,
+    },
+
+    /// ExtensionRef is an optional, implementation-specific extension to the
+    /// "filter" behavior.  For example, resource "myroutefilter" in group
+    /// "networking.example.net"). ExtensionRef MUST NOT be used for core and
+    /// extended filters.
+    ///
+    /// Support: Implementation-specific
+    #[serde(rename_all = "camelCase")]
+    ExtensionRef {
+        extension_ref: Box<LocalObjectReference>,
+    },
+}
+
+/// GRPCBackendRef defines how a GRPCRoute forwards a gRPC request.
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcBackendRef {
+    /// BackendRef is a reference to a backend to forward matched requests to.
+    ///
+    /// If the referent cannot be found, this GRPCBackendRef is invalid and must
+    /// be dropped from the Gateway. The controller must ensure the
+    /// "ResolvedRefs" condition on the Route is set to `status: False` and not
+    /// configure this backend in the underlying implementation.
+    ///
+    /// Support: Core for Kubernetes Service
+    ///
+    /// Support: Implementation-specific for any other resource
+    #[serde(
+        flatten,
+        deserialize_with = "crate::shared::deserialize_flattened_backend_ref"
+    )]
+    pub backend_ref: Option<BackendRef>,
+
+    /// Filters defined at this level MUST be executed if and only if the
+    /// request is being forwarded to the backend defined here.
+    ///
+    /// Support: Implementation-specific (For broader support of filters, use the
+    /// Filters field in GRPCRouteRule.)
+    #[schemars(length(max = "crate::validation::MAX_FILTERS"))]
+    pub filters: Option<Vec<GrpcRouteFilter>>,
+}
+
+// === impl GrpcMethodMatch ===
+
+impl_tagged_serde!(
+    GrpcMethodMatch,
+    ["Exact", "RegularExpression"],
+    Some("Exact")
+);
+
+impl GrpcMethodMatch {
+    /// Returns the match's type, e.g. `Exact`.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::Exact { .. } => "Exact",
+            Self::RegularExpression { .. } => "RegularExpression",
+            Self::Unknown(raw) => unknown_type(raw),
+        }
+    }
+
+    /// Returns the service to match, if any.
+    pub fn service(&self) -> Option<&str> {
+        match self {
+            Self::Exact { service, .. } | Self::RegularExpression { service, .. } => {
+                service.as_deref()
+            }
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Returns the method to match, if any.
+    pub fn method(&self) -> Option<&str> {
+        match self {
+            Self::Exact { method, .. } | Self::RegularExpression { method, .. } => {
+                method.as_deref()
+            }
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+// === impl GrpcHeaderMatch ===
+
+impl_tagged_serde!(
+    GrpcHeaderMatch,
+    ["Exact", "RegularExpression"],
+    Some("Exact")
+);
+
+impl GrpcHeaderMatch {
+    /// Returns the match's type, e.g. `Exact`.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::Exact { .. } => "Exact",
+            Self::RegularExpression { .. } => "RegularExpression",
+            Self::Unknown(raw) => unknown_type(raw),
+        }
+    }
+
+    /// Returns the name of the header to match, or `None` if the match's type
+    /// is unknown.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Exact { name, .. } | Self::RegularExpression { name, .. } => Some(name),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Returns the value or regular expression to match, or `None` if the
+    /// match's type is unknown.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Self::Exact { value, .. } | Self::RegularExpression { value, .. } => Some(value),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+// === impl GrpcRouteFilter ===
+
+impl GrpcRouteFilter {
+    /// Returns the filter's type, e.g. `RequestHeaderModifier`.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::RequestHeaderModifier { .. } => "RequestHeaderModifier",
+            Self::RequestMirror { .. } => "RequestMirror",
+            Self::ExtensionRef { .. } => "ExtensionRef",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn match_type_defaults() {
+        let method = json!({ "service": "foo.Bar", "method": "Get" });
+        assert_eq!(
+            serde_json::from_value::<GrpcMethodMatch>(method).unwrap(),
+            GrpcMethodMatch::Exact {
+                service: Some("foo.Bar".to_string()),
+                method: Some("Get".to_string())
+            }
+        );
+        let header = json!({ "name": "x-foo", "value": "bar" });
+        assert_eq!(
+            serde_json::from_value::<GrpcHeaderMatch>(header).unwrap(),
+            GrpcHeaderMatch::Exact {
+                name: "x-foo".to_string(),
+                value: "bar".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn known_types_with_invalid_fields_are_rejected() {
+        for method in [
+            json!({ "type": "Exact", "service": 5 }),
+            json!({ "type": 7, "service": "foo.Bar" }),
+        ] {
+            assert!(
+                serde_json::from_value::<GrpcMethodMatch>(method.clone()).is_err(),
+                "{}",
+                method
+            );
+        }
+        for header in [
+            json!({ "type": "Exact", "name": "x-foo", "value": 5 }),
+            json!({ "type": "Exact" }),
+            json!({ "value": "x" }),
+        ] {
+            assert!(
+                serde_json::from_value::<GrpcHeaderMatch>(header.clone()).is_err(),
+                "{}",
+                header
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_types_round_trip() {
+        let method = json!({ "type": "Prefix", "service": "foo" });
+        let decoded = serde_json::from_value::<GrpcMethodMatch>(method.clone()).unwrap();
+        assert!(matches!(decoded, GrpcMethodMatch::Unknown(_)));
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), method);
+
+        let header = json!({ "type": "Prefix", "name": "x-foo", "value": "b" });
+        let decoded = serde_json::from_value::<GrpcHeaderMatch>(header.clone()).unwrap();
+        assert!(matches!(decoded, GrpcHeaderMatch::Unknown(_)));
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), header);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::*;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
 
 /// PolicyTargetReference identifies an API object to apply policy to. This
 /// should be used as part of Policy resources that can target Gateway API
@@ -24,3 +25,94 @@ pub struct PolicyTargetReference {
     /// same namespace as the policy.
     pub namespace: Option<Namespace>,
 }
+
+/// LocalPolicyTargetReference identifies an API object to apply a direct or
+/// inherited policy to. This should be used as part of Policy resources that
+/// can target Gateway API resources. The target must be in the same namespace
+/// as the policy.
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+pub struct LocalPolicyTargetReference {
+    /// Group is the group of the target resource.
+    pub group: Group,
+
+    /// Kind is kind of the target resource.
+    pub kind: Kind,
+
+    /// Name is the name of the target resource.
+    pub name: ObjectName,
+}
+
+/// LocalPolicyTargetReferenceWithSectionName identifies an API object to apply
+/// a direct policy to. This should be used as part of Policy resources that
+/// can target single resources. The target must be in the same namespace as
+/// the policy.
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalPolicyTargetReferenceWithSectionName {
+    #[serde(flatten)]
+    pub inner: LocalPolicyTargetReference,
+
+    /// SectionName is the name of a section within the target resource. When
+    /// unspecified, this targetRef targets the entire resource. In the
+    /// following resources, SectionName is interpreted as the following:
+    ///
+    /// * Gateway: Listener name
+    /// * HTTPRoute: HTTPRouteRule name
+    /// * Service: Port name
+    ///
+    /// If a SectionName is specified, but does not exist on the targeted
+    /// object, the Policy must fail to attach, and the policy implementation
+    /// should record a `ResolvedRefs` or similar Condition in the Policy's
+    /// status.
+    pub section_name: Option<SectionName>,
+}
+
+/// PolicyStatus defines the common attributes that all Policies should include
+/// within their status.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+pub struct PolicyStatus {
+    /// Ancestors is a list of ancestor resources (usually Gateways) that are
+    /// associated with the policy, and the status of the policy with respect to
+    /// each ancestor. When this policy attaches to a parent, the controller that
+    /// manages the parent and the ancestors MUST add an entry to this list when
+    /// the controller first sees the policy and SHOULD update the entry as
+    /// appropriate when the relevant ancestor is modified.
+    ///
+    /// A maximum of 16 ancestors will be represented in this list. An empty
+    /// list means the Policy is not relevant for any ancestors.
+    #[schemars(length(max = 16))]
+    pub ancestors: Vec<PolicyAncestorStatus>,
+}
+
+/// PolicyAncestorStatus describes the status of a route with respect to an
+/// associated Ancestor.
+///
+/// Ancestors refer to objects that are either the Target of a policy or above
+/// it in terms of object hierarchy. For example, if a policy targets a
+/// Service, the Policy's Ancestors are, in order, the Service, the HTTPRoute,
+/// the Gateway, and the GatewayClass.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyAncestorStatus {
+    /// AncestorRef corresponds with a ParentRef in the spec that this
+    /// PolicyAncestorStatus struct describes the status of.
+    pub ancestor_ref: ParentReference,
+
+    /// ControllerName is a domain/path string that indicates the name of the
+    /// controller that wrote this status. This corresponds with the
+    /// controllerName field on GatewayClass.
+    pub controller_name: GatewayController,
+
+    /// Conditions describes the status of the Policy with respect to the given
+    /// Ancestor.
+    pub conditions: Vec<metav1::Condition>,
+}
+
+/// Duration is a string value representing a duration in time. The format is
+/// as specified in GEP-2257, a strict subset of the syntax parsed by Golang
+/// time.ParseDuration, e.g. "1h", "30s", or "500ms".
+pub type Duration = String;
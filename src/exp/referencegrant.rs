@@ -10,8 +10,18 @@ use crate::*;
 ///
 /// All cross-namespace references in Gateway API (with the exception of
 /// cross-namespace Gateway-route attachment) require a ReferenceGrant.
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
-pub struct ReferenceGrant {
+#[derive(
+    Clone, Debug, kube::CustomResource, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[kube(
+    group = "gateway.networking.k8s.io",
+    version = "v1alpha2",
+    kind = "ReferenceGrant",
+    struct = "ReferenceGrant",
+    namespaced,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
+)]
+pub struct ReferenceGrantSpec {
     /// From describes the trusted namespaces and kinds that can reference the
     /// resources described in "To". Each entry in this list must be considered
     /// to be an additional place that references can be valid from, or to put
@@ -26,7 +36,7 @@ pub struct ReferenceGrant {
     /// way, entries must be combined using OR.
     ///
     /// Support: Core
-    pub to: Vec<ReferenceGrantFrom>,
+    pub to: Vec<ReferenceGrantTo>,
 }
 
 /// ReferenceGrantFrom describes trusted namespaces and kinds.
@@ -77,3 +87,69 @@ pub struct ReferenceGrantTo {
     /// namespace.
     pub name: Option<ObjectName>,
 }
+
+/// Returns true if any of the given grants permits the reference.
+pub fn is_reference_permitted(
+    grants: &[ReferenceGrant],
+    reference: &CrossNamespaceReference<'_>,
+) -> bool {
+    grants.iter().any(|grant| grant.permits(reference))
+}
+
+/// Checks that any of the given grants permits the reference.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            from.group = %reference.from_group,
+            from.kind = %reference.from_kind,
+            from.namespace = %reference.from_namespace,
+            to.group = %reference.to_group,
+            to.kind = %reference.to_kind,
+            to.namespace = %reference.to_namespace,
+            to.name = %reference.to_name,
+            grants = grants.len(),
+        ),
+        err(level = "debug", Display),
+    )
+)]
+pub fn check_reference(
+    grants: &[ReferenceGrant],
+    reference: &CrossNamespaceReference<'_>,
+) -> Result<(), ReferenceError> {
+    if is_reference_permitted(grants, reference) {
+        return Ok(());
+    }
+    Err(ReferenceError::RefNotPermitted {
+        kind: reference.to_kind.to_string(),
+        namespace: reference.to_namespace.to_string(),
+        name: reference.to_name.to_string(),
+    })
+}
+
+// === impl ReferenceGrant ===
+
+impl ReferenceGrant {
+    /// Returns true if this grant permits the reference, i.e. the grant is in
+    /// the referent's namespace and both the referrer and the referent are
+    /// described by the grant.
+    pub fn permits(&self, reference: &CrossNamespaceReference<'_>) -> bool {
+        if self.metadata.namespace.as_deref() != Some(reference.to_namespace) {
+            return false;
+        }
+
+        let from = self.spec.from.iter().any(|from| {
+            from.group == reference.from_group
+                && from.kind == reference.from_kind
+                && from.namespace == reference.from_namespace
+        });
+        let to = self.spec.to.iter().any(|to| {
+            to.group == reference.to_group
+                && to.kind == reference.to_kind
+                && to.name.as_deref().map_or(true, |n| n == reference.to_name)
+        });
+        from && to
+    }
+}
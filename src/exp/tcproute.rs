@@ -12,7 +12,8 @@ use crate::*;
     kind = "TCPRoute",
     struct = "TcpRoute",
     status = "TcpRouteStatus",
-    namespaced
+    namespaced,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
 )]
 pub struct TcpRouteSpec {
     /// Common route information.
@@ -20,6 +21,7 @@ pub struct TcpRouteSpec {
     pub inner: CommonRouteSpec,
 
     /// Rules are a list of TCP matchers and actions.
+    #[schemars(length(max = "crate::validation::MAX_RULES"))]
     pub rules: Vec<TcpRouteRule>,
 }
 
@@ -48,5 +50,6 @@ pub struct TcpRouteRule {
     /// Support: Custom for any other resource
     ///
     /// Support for weight: Extended
+    #[schemars(length(max = "crate::validation::MAX_BACKEND_REFS"))]
     pub backend_refs: Vec<BackendRef>,
 }
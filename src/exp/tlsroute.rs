@@ -15,7 +15,8 @@ use crate::*;
     kind = "TLSRoute",
     struct = "TlsRoute",
     status = "TlsRouteStatus",
-    namespaced
+    namespaced,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
 )]
 pub struct TlsRouteSpec {
     /// Common route information.
@@ -55,9 +56,11 @@ pub struct TlsRouteSpec {
     /// `False` in the corresponding RouteParentStatus.
     ///
     /// Support: Core
+    #[schemars(length(max = "crate::validation::MAX_HOSTNAMES"))]
     pub hostnames: Option<Vec<Hostname>>,
 
     /// Rules are a list of TLS matchers and actions.
+    #[schemars(length(max = "crate::validation::MAX_RULES"))]
     pub rules: Vec<TlsRouteRule>,
 }
 
@@ -89,5 +92,6 @@ pub struct TlsRouteRule {
     /// Support: Custom for any other resource
     ///
     /// Support for weight: Extended
+    #[schemars(length(max = "crate::validation::MAX_BACKEND_REFS"))]
     pub backend_refs: Vec<BackendRef>,
 }
@@ -9,12 +9,14 @@ use crate::*;
     kind = "UDPRoute",
     struct = "UdpRoute",
     status = "UdpRouteStatus",
-    namespaced
+    namespaced,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
 )]
 pub struct UdpRouteSpec {
     #[serde(flatten)]
     pub inner: CommonRouteSpec,
 
+    #[schemars(length(max = "crate::validation::MAX_RULES"))]
     pub rules: Vec<UdpRouteRule>,
 }
 
@@ -29,5 +31,6 @@ pub struct UdpRouteStatus {
 )]
 #[serde(rename_all = "camelCase")]
 pub struct UdpRouteRule {
+    #[schemars(length(max = "crate::validation::MAX_BACKEND_REFS"))]
     pub backend_refs: Vec<BackendRef>,
 }
@@ -0,0 +1,601 @@
+//! Explanations of HTTPRoute status conditions.
+//!
+//! [`explain_route`] evaluates the checks that determine an HTTPRoute's
+//! `Accepted` and `ResolvedRefs` conditions against the Gateway API objects
+//! in a cluster, and reports which of them failed, so that tools can describe
+//! why a route is (or is not) accepted by each of its parents:
+//!
+//! ```ignore
+//! let objects = manifest::parse_yaml(&bytes)?;
+//! let compiler = ir::Compiler::default().with_namespace_labels("apps", labels);
+//! for parent in explain::explain_route(&compiler, &objects, &route) {
+//!     println!("{}: {}", parent.parent_ref.name, parent.accepted);
+//!     for unresolved in &parent.resolved_refs {
+//!         println!("  {}", unresolved);
+//!     }
+//! }
+//! ```
+//!
+//! Attachment is evaluated by the [`ir::Compiler`] that is passed in, so
+//! listeners that select namespaces by label admit routes from the namespaces
+//! whose labels it was given. Services are not Gateway API objects, so
+//! backends are not checked for existence: only their kinds and whether
+//! cross-namespace references are permitted by a ReferenceGrant are checked.
+
+use crate::{
+    consts::GROUP,
+    ir::{self, Compiler, Detached},
+    manifest::GatewayApiObject,
+    snapshot::ObjectKey,
+    validation::FieldPath,
+    *,
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::fmt;
+
+/// Explains the conditions of a route for one of its parents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParentExplanation {
+    /// The parent reference, as specified by the route.
+    pub parent_ref: ParentReference,
+
+    /// Explains the `Accepted` condition.
+    pub accepted: Acceptance,
+
+    /// The route's references that cannot be resolved. The `ResolvedRefs`
+    /// condition is `True` only if this is empty.
+    pub resolved_refs: Vec<UnresolvedRef>,
+}
+
+/// Whether a parent accepts a route, and why.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Acceptance {
+    /// The route attaches to the listeners with these names.
+    Accepted { listeners: Vec<String> },
+
+    /// The referenced Gateway does not exist.
+    GatewayNotFound { namespace: String, name: String },
+
+    /// No listener of the Gateway has the referenced section name and port.
+    NoMatchingParent {
+        section_name: Option<String>,
+        port: Option<PortNumber>,
+    },
+
+    /// Every listener selected by the reference rejects the route.
+    NotAllowedByListeners { rejections: Vec<ListenerRejection> },
+
+    /// The listeners with these names allow the route, but none of their
+    /// hostnames intersect the route's hostnames.
+    NoMatchingListenerHostname { listeners: Vec<String> },
+}
+
+/// A listener that does not allow a route to attach to it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListenerRejection {
+    pub listener: String,
+    pub reason: RejectionReason,
+}
+
+/// The reason a listener does not allow a route to attach to it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RejectionReason {
+    /// The listener's protocol or `allowedRoutes` does not admit HTTPRoutes.
+    KindNotAllowed,
+
+    /// The listener's `allowedRoutes` does not admit the route's namespace.
+    NamespaceNotAllowed,
+}
+
+/// A reference of a route that cannot be resolved.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnresolvedRef {
+    /// The reference's field in the route.
+    pub path: FieldPath,
+
+    /// Why the reference cannot be resolved.
+    pub error: ReferenceError,
+
+    /// The ReferenceGrant that would permit the reference, if it is not
+    /// permitted.
+    pub missing_grant: Option<MissingGrant>,
+}
+
+/// Describes a ReferenceGrant that would permit a cross-namespace reference.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MissingGrant {
+    /// The namespace of the referent, in which the grant must be created.
+    pub namespace: String,
+
+    pub from_group: String,
+    pub from_kind: String,
+    pub from_namespace: String,
+    pub to_group: String,
+    pub to_kind: String,
+    pub to_name: String,
+}
+
+/// Explains the `Accepted` and `ResolvedRefs` conditions of an HTTPRoute
+/// for each of its parent Gateways, given all of the Gateway API objects in a
+/// cluster and the compiler that configures the Gateways.
+///
+/// Parents are explained in the order in which the route references them.
+/// References to parents other than Gateways are omitted.
+pub fn explain_route(
+    compiler: &Compiler,
+    objects: &[GatewayApiObject],
+    route: &HttpRoute,
+) -> Vec<ParentExplanation> {
+    let route_key = ObjectKey::from_meta(&route.metadata);
+    let resolved_refs = unresolved_refs(objects, &route_key.namespace, route);
+
+    route
+        .spec
+        .inner
+        .parent_refs
+        .iter()
+        .flatten()
+        .filter(|p| {
+            p.group.as_deref().unwrap_or(GROUP) == GROUP
+                && p.kind.as_deref().unwrap_or("Gateway") == "Gateway"
+        })
+        .map(|parent_ref| ParentExplanation {
+            parent_ref: parent_ref.clone(),
+            accepted: acceptance(objects, compiler, &route_key, route, parent_ref),
+            resolved_refs: resolved_refs.clone(),
+        })
+        .collect()
+}
+
+fn acceptance(
+    objects: &[GatewayApiObject],
+    compiler: &Compiler,
+    route_key: &ObjectKey,
+    route: &HttpRoute,
+    parent_ref: &ParentReference,
+) -> Acceptance {
+    let gw_key = ObjectKey::new(
+        parent_ref
+            .namespace
+            .as_deref()
+            .unwrap_or(&route_key.namespace),
+        &*parent_ref.name,
+    );
+    let gateway = objects.iter().find_map(|o| match o {
+        GatewayApiObject::Gateway(gw) if ObjectKey::from_meta(&gw.metadata) == gw_key => Some(gw),
+        _ => None,
+    });
+    let gateway = match gateway {
+        Some(gateway) => gateway,
+        None => {
+            return Acceptance::GatewayNotFound {
+                namespace: gw_key.namespace.to_string(),
+                name: gw_key.name.to_string(),
+            }
+        }
+    };
+
+    let listeners = gateway
+        .spec
+        .listeners
+        .iter()
+        .filter(|l| {
+            parent_ref
+                .section_name
+                .as_deref()
+                .map_or(true, |s| s == l.name)
+                && parent_ref.port.map_or(true, |p| p == l.port)
+        })
+        .collect::<Vec<_>>();
+    if listeners.is_empty() {
+        return Acceptance::NoMatchingParent {
+            section_name: parent_ref.section_name.as_ref().map(ToString::to_string),
+            port: parent_ref.port,
+        };
+    }
+
+    let mut allowed = Vec::new();
+    let mut rejections = Vec::new();
+    for listener in listeners {
+        let reason = if listener.protocol != "HTTP" && listener.protocol != "HTTPS" {
+            Some(RejectionReason::KindNotAllowed)
+        } else {
+            match compiler.attachment(&gw_key, listener, route_key, route) {
+                Err(Detached::KindNotAllowed) => Some(RejectionReason::KindNotAllowed),
+                Err(Detached::NamespaceNotAllowed) => Some(RejectionReason::NamespaceNotAllowed),
+                // The listener was selected by this reference, so it is
+                // always referenced.
+                Ok(()) | Err(Detached::NotReferenced) => None,
+            }
+        };
+        match reason {
+            Some(reason) => rejections.push(ListenerRejection {
+                listener: listener.name.clone(),
+                reason,
+            }),
+            None => allowed.push(listener),
+        }
+    }
+    if allowed.is_empty() {
+        return Acceptance::NotAllowedByListeners { rejections };
+    }
+
+    let hostnames = route.spec.hostnames.as_deref().unwrap_or_default();
+    let attached = allowed
+        .iter()
+        .filter(|l| !ir::intersect_hostnames(l.hostname.as_deref(), hostnames).is_empty())
+        .map(|l| l.name.clone())
+        .collect::<Vec<_>>();
+    if attached.is_empty() {
+        return Acceptance::NoMatchingListenerHostname {
+            listeners: allowed.iter().map(|l| l.name.clone()).collect(),
+        };
+    }
+    Acceptance::Accepted {
+        listeners: attached,
+    }
+}
+
+/// Returns the backend references of a route, including those of its
+/// `RequestMirror` filters, that cannot be resolved.
+fn unresolved_refs(
+    objects: &[GatewayApiObject],
+    route_ns: &str,
+    route: &HttpRoute,
+) -> Vec<UnresolvedRef> {
+    let grants = Grants::new(objects);
+    let mut unresolved = Vec::new();
+    let mut check = |path: FieldPath, backend: &BackendObjectReference| {
+        if let Some(r) = grants.check(route_ns, path, backend) {
+            unresolved.push(r);
+        }
+    };
+
+    let rules = FieldPath::root().field("spec").field("rules");
+    for (i, rule) in route.spec.rules.iter().flatten().enumerate() {
+        let rule_path = rules.index(i);
+        mirror_refs(
+            rule.filters.as_deref().unwrap_or_default(),
+            &rule_path.field("filters"),
+            &mut check,
+        );
+        for (j, backend) in rule.backend_refs.iter().flatten().enumerate() {
+            let backend_path = rule_path.field("backendRefs").index(j);
+            if let Some(backend_ref) = &backend.backend_ref {
+                check(backend_path.clone(), &backend_ref.inner);
+            }
+            mirror_refs(
+                backend.filters.as_deref().unwrap_or_default(),
+                &backend_path.field("filters"),
+                &mut check,
+            );
+        }
+    }
+    unresolved
+}
+
+fn mirror_refs(
+    filters: &[HttpRouteFilter],
+    path: &FieldPath,
+    check: &mut impl FnMut(FieldPath, &BackendObjectReference),
+) {
+    for (i, filter) in filters.iter().enumerate() {
+        if let HttpRouteFilter::RequestMirror { request_mirror } = filter {
+            check(
+                path.index(i).field("requestMirror").field("backendRef"),
+                &request_mirror.backend_ref,
+            );
+        }
+    }
+}
+
+/// The ReferenceGrants in a cluster.
+struct Grants<'a> {
+    #[cfg(feature = "experimental")]
+    grants: Vec<&'a ReferenceGrant>,
+
+    #[cfg(not(feature = "experimental"))]
+    grants: std::marker::PhantomData<&'a ()>,
+}
+
+// === impl Grants ===
+
+impl<'a> Grants<'a> {
+    #[cfg(feature = "experimental")]
+    fn new(objects: &'a [GatewayApiObject]) -> Self {
+        let grants = objects
+            .iter()
+            .filter_map(|o| match o {
+                GatewayApiObject::ReferenceGrant(grant) => Some(grant),
+                _ => None,
+            })
+            .collect();
+        Self { grants }
+    }
+
+    /// Cross-namespace references require a ReferenceGrant, which is only
+    /// modeled when the `experimental` feature is enabled.
+    #[cfg(not(feature = "experimental"))]
+    fn new(_: &'a [GatewayApiObject]) -> Self {
+        Self {
+            grants: std::marker::PhantomData,
+        }
+    }
+
+    #[cfg(feature = "experimental")]
+    fn permits(&self, reference: &CrossNamespaceReference<'_>) -> bool {
+        self.grants.iter().any(|g| g.permits(reference))
+    }
+
+    #[cfg(not(feature = "experimental"))]
+    fn permits(&self, _: &CrossNamespaceReference<'_>) -> bool {
+        false
+    }
+
+    /// Returns the reason a backend reference cannot be resolved, if any.
+    fn check(
+        &self,
+        route_ns: &str,
+        path: FieldPath,
+        backend: &BackendObjectReference,
+    ) -> Option<UnresolvedRef> {
+        let group = backend.group.as_deref().unwrap_or("");
+        let kind = backend.kind.as_deref().unwrap_or("Service");
+        if !backend::is_service(backend) {
+            return Some(UnresolvedRef {
+                path,
+                error: ReferenceError::InvalidKind {
+                    group: group.to_string(),
+                    kind: kind.to_string(),
+                },
+                missing_grant: None,
+            });
+        }
+
+        let namespace = backend.namespace.as_deref().unwrap_or(route_ns);
+        let reference = CrossNamespaceReference {
+            from_group: GROUP,
+            from_kind: "HTTPRoute",
+            from_namespace: route_ns,
+            to_group: group,
+            to_kind: kind,
+            to_namespace: namespace,
+            to_name: &backend.name,
+        };
+        if namespace == route_ns || self.permits(&reference) {
+            return None;
+        }
+        Some(UnresolvedRef {
+            path,
+            error: ReferenceError::RefNotPermitted {
+                kind: kind.to_string(),
+                namespace: namespace.to_string(),
+                name: backend.name.clone(),
+            },
+            missing_grant: Some(MissingGrant::from(&reference)),
+        })
+    }
+}
+
+// === impl ParentExplanation ===
+
+impl ParentExplanation {
+    /// Returns the `Accepted` and `ResolvedRefs` conditions explained for the
+    /// parent. When several references cannot be resolved, the
+    /// `ResolvedRefs` condition describes the first.
+    pub fn to_conditions(
+        &self,
+        observed_generation: Option<i64>,
+        last_transition_time: metav1::Time,
+    ) -> Vec<metav1::Condition> {
+        let accepted = metav1::Condition {
+            type_: "Accepted".to_string(),
+            status: if self.accepted.is_accepted() {
+                "True"
+            } else {
+                "False"
+            }
+            .to_string(),
+            reason: self.accepted.reason().to_string(),
+            message: self.accepted.to_string(),
+            observed_generation,
+            last_transition_time: last_transition_time.clone(),
+        };
+        let resolved_refs = match self.resolved_refs.first() {
+            Some(unresolved) => unresolved
+                .error
+                .to_condition(observed_generation, last_transition_time),
+            None => metav1::Condition {
+                type_: "ResolvedRefs".to_string(),
+                status: "True".to_string(),
+                reason: "ResolvedRefs".to_string(),
+                message: String::new(),
+                observed_generation,
+                last_transition_time,
+            },
+        };
+        vec![accepted, resolved_refs]
+    }
+}
+
+// === impl Acceptance ===
+
+impl Acceptance {
+    /// Returns true if the parent accepts the route.
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Self::Accepted { .. })
+    }
+
+    /// Returns the reason to set on the `Accepted` condition.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::Accepted { .. } => "Accepted",
+            Self::GatewayNotFound { .. } | Self::NoMatchingParent { .. } => "NoMatchingParent",
+            Self::NotAllowedByListeners { .. } => "NotAllowedByListeners",
+            Self::NoMatchingListenerHostname { .. } => "NoMatchingListenerHostname",
+        }
+    }
+}
+
+impl fmt::Display for Acceptance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Accepted { listeners } => {
+                write!(f, "accepted by listeners {}", listeners.join(", "))
+            }
+            Self::GatewayNotFound { namespace, name } => {
+                write!(f, "Gateway {}/{} not found", namespace, name)
+            }
+            Self::NoMatchingParent { section_name, port } => {
+                write!(f, "no listener matches")?;
+                if let Some(section_name) = section_name {
+                    write!(f, " section name {}", section_name)?;
+                }
+                if let Some(port) = port {
+                    if section_name.is_some() {
+                        write!(f, " and")?;
+                    }
+                    write!(f, " port {}", port)?;
+                }
+                Ok(())
+            }
+            Self::NotAllowedByListeners { rejections } => {
+                write!(f, "not allowed by listeners ")?;
+                for (i, rejection) in rejections.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} ({})", rejection.listener, rejection.reason)?;
+                }
+                Ok(())
+            }
+            Self::NoMatchingListenerHostname { listeners } => {
+                write!(f, "no hostname matches listeners {}", listeners.join(", "))
+            }
+        }
+    }
+}
+
+// === impl RejectionReason ===
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KindNotAllowed => write!(f, "kind not allowed"),
+            Self::NamespaceNotAllowed => write!(f, "namespace not allowed"),
+        }
+    }
+}
+
+// === impl UnresolvedRef ===
+
+impl fmt::Display for UnresolvedRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.error)?;
+        if let Some(grant) = &self.missing_grant {
+            write!(f, " (requires {})", grant)?;
+        }
+        Ok(())
+    }
+}
+
+// === impl MissingGrant ===
+
+impl From<&CrossNamespaceReference<'_>> for MissingGrant {
+    fn from(reference: &CrossNamespaceReference<'_>) -> Self {
+        Self {
+            namespace: reference.to_namespace.to_string(),
+            from_group: reference.from_group.to_string(),
+            from_kind: reference.from_kind.to_string(),
+            from_namespace: reference.from_namespace.to_string(),
+            to_group: reference.to_group.to_string(),
+            to_kind: reference.to_kind.to_string(),
+            to_name: reference.to_name.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for MissingGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn qualified(kind: &str, group: &str) -> String {
+            if group.is_empty() {
+                return kind.to_string();
+            }
+            format!("{}.{}", kind, group)
+        }
+
+        write!(
+            f,
+            "a ReferenceGrant in namespace {} from {} in namespace {} to {} {}",
+            self.namespace,
+            qualified(&self.from_kind, &self.from_group),
+            self.from_namespace,
+            qualified(&self.to_kind, &self.to_group),
+            self.to_name,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn objects() -> Vec<GatewayApiObject> {
+        let gateway = json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "web", "namespace": "infra" },
+            "spec": {
+                "gatewayClassName": "acme",
+                "listeners": [{
+                    "name": "http",
+                    "port": 80,
+                    "protocol": "HTTP",
+                    "allowedRoutes": {
+                        "namespaces": {
+                            "from": "Selector",
+                            "selector": { "matchLabels": { "gateway": "web" } },
+                        },
+                    },
+                }],
+            },
+        });
+        vec![GatewayApiObject::from_value(gateway).unwrap()]
+    }
+
+    fn route() -> HttpRoute {
+        serde_json::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": "app", "namespace": "apps" },
+            "spec": { "parentRefs": [{ "name": "web", "namespace": "infra" }] },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn evaluates_namespace_selectors_with_the_compiler() {
+        let explained = explain_route(&Compiler::default(), &objects(), &route());
+        assert_eq!(
+            explained[0].accepted,
+            Acceptance::NotAllowedByListeners {
+                rejections: vec![ListenerRejection {
+                    listener: "http".to_string(),
+                    reason: RejectionReason::NamespaceNotAllowed,
+                }],
+            }
+        );
+
+        let labels = BTreeMap::from([("gateway".to_string(), "web".to_string())]);
+        let compiler = Compiler::default().with_namespace_labels("apps", labels);
+        let explained = explain_route(&compiler, &objects(), &route());
+        assert_eq!(
+            explained[0].accepted,
+            Acceptance::Accepted {
+                listeners: vec!["http".to_string()],
+            }
+        );
+    }
+}
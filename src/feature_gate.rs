@@ -0,0 +1,548 @@
+//! Runtime gates for experimental-channel fields.
+//!
+//! The Gateway API is published in two release channels. The experimental
+//! channel's CRDs include fields that the standard channel's omit, and the
+//! API server prunes fields that a CRD omits. The `experimental` Cargo
+//! feature adds the experimental resources to this crate, but fields of
+//! standard resources are always present, so a controller that must serve
+//! either channel decides at runtime which fields it honors.
+//!
+//! [`FeatureGates`] records that decision. It is consulted by
+//! [`Validate::validate_with_feature_gates`], which rejects objects that set
+//! gated fields, and by the [`ir::Compiler`], which ignores them, as if the
+//! API server had pruned them:
+//!
+//! ```
+//! # use k8s_gateway_api::feature_gate::{Feature, FeatureGates};
+//! let gates = "HTTPRouteRequestMirrorPercentage=true".parse::<FeatureGates>().unwrap();
+//! assert!(gates.is_enabled(Feature::HttpRouteRequestMirrorPercentage));
+//! assert!(!gates.is_enabled(Feature::HttpRouteNamedRules));
+//! ```
+
+use crate::{
+    manifest::GatewayApiObject,
+    validation::{FieldPath, Validate, ValidationError},
+    *,
+};
+use std::{collections::BTreeSet, fmt, str::FromStr};
+
+/// A feature of the experimental channel that may be enabled at runtime.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Feature {
+    /// The `name` field of HTTPRoute rules.
+    HttpRouteNamedRules,
+
+    /// The `percent` and `fraction` fields of `RequestMirror` filters, which
+    /// mirror a portion of requests, on HTTPRoutes and GRPCRoutes.
+    HttpRouteRequestMirrorPercentage,
+
+    /// The `name` field of GRPCRoute rules.
+    GrpcRouteNamedRules,
+
+    /// The `sessionPersistence` field of BackendLBPolicies and
+    /// XBackendTrafficPolicies.
+    SessionPersistence,
+}
+
+/// The set of enabled [`Feature`]s.
+///
+/// The default set is empty, i.e. only standard-channel fields are honored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FeatureGates(BTreeSet<Feature>);
+
+/// Indicates that a feature gate specification could not be parsed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ParseFeatureGatesError {
+    /// The specification names a feature that does not exist.
+    UnknownFeature(String),
+
+    /// A feature is not set to `true` or `false`.
+    InvalidValue { feature: String, value: String },
+}
+
+// === impl Feature ===
+
+impl Feature {
+    /// All features, in order.
+    pub const ALL: &'static [Feature] = &[
+        Feature::HttpRouteNamedRules,
+        Feature::HttpRouteRequestMirrorPercentage,
+        Feature::GrpcRouteNamedRules,
+        Feature::SessionPersistence,
+    ];
+
+    /// Returns the name of the feature, as used in feature gate
+    /// specifications.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HttpRouteNamedRules => "HTTPRouteNamedRules",
+            Self::HttpRouteRequestMirrorPercentage => "HTTPRouteRequestMirrorPercentage",
+            Self::GrpcRouteNamedRules => "GRPCRouteNamedRules",
+            Self::SessionPersistence => "SessionPersistence",
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = ParseFeatureGatesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|f| f.as_str() == s)
+            .ok_or_else(|| ParseFeatureGatesError::UnknownFeature(s.to_string()))
+    }
+}
+
+// === impl FeatureGates ===
+
+impl FeatureGates {
+    /// Returns a set with all features enabled, as when the experimental
+    /// channel's CRDs are installed.
+    pub fn experimental() -> Self {
+        Self(Feature::ALL.iter().copied().collect())
+    }
+
+    /// Enables a feature.
+    pub fn enable(mut self, feature: Feature) -> Self {
+        self.0.insert(feature);
+        self
+    }
+
+    /// Disables a feature.
+    pub fn disable(mut self, feature: Feature) -> Self {
+        self.0.remove(&feature);
+        self
+    }
+
+    /// Returns true if a feature is enabled.
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.0.contains(&feature)
+    }
+
+    /// Iterates over the enabled features, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Feature> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Returns an error for each field of an object that requires a disabled
+    /// feature.
+    pub fn validate(&self, obj: &GatewayApiObject) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        obj.validate_features_at(self, &FieldPath::root(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub(crate) fn validate_http_route(
+        &self,
+        route: &HttpRoute,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let rules_path = path.field("spec").field("rules");
+        for (i, rule) in route.spec.rules.iter().flatten().enumerate() {
+            let rule_path = rules_path.index(i);
+            if rule.name.is_some() {
+                self.require(
+                    Feature::HttpRouteNamedRules,
+                    rule_path.field("name"),
+                    errors,
+                );
+            }
+
+            let filters = rule
+                .filters
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(|(j, f)| (rule_path.field("filters").index(j), f));
+            let backend_filters =
+                rule.backend_refs
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .flat_map(|(j, b)| {
+                        let path = rule_path.field("backendRefs").index(j).field("filters");
+                        b.filters
+                            .iter()
+                            .flatten()
+                            .enumerate()
+                            .map(move |(k, f)| (path.index(k), f))
+                    });
+            for (path, filter) in filters.chain(backend_filters) {
+                if let HttpRouteFilter::RequestMirror { request_mirror } = filter {
+                    self.validate_mirror(request_mirror, &path.field("requestMirror"), errors);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "experimental")]
+    pub(crate) fn validate_grpc_route(
+        &self,
+        route: &GrpcRoute,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let rules_path = path.field("spec").field("rules");
+        for (i, rule) in route.spec.rules.iter().flatten().enumerate() {
+            let rule_path = rules_path.index(i);
+            if rule.name.is_some() {
+                self.require(
+                    Feature::GrpcRouteNamedRules,
+                    rule_path.field("name"),
+                    errors,
+                );
+            }
+
+            let filters = rule
+                .filters
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(|(j, f)| (rule_path.field("filters").index(j), f));
+            let backend_filters =
+                rule.backend_refs
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .flat_map(|(j, b)| {
+                        let path = rule_path.field("backendRefs").index(j).field("filters");
+                        b.filters
+                            .iter()
+                            .flatten()
+                            .enumerate()
+                            .map(move |(k, f)| (path.index(k), f))
+                    });
+            for (path, filter) in filters.chain(backend_filters) {
+                if let GrpcRouteFilter::RequestMirror { request_mirror } = filter {
+                    self.validate_mirror(request_mirror, &path.field("requestMirror"), errors);
+                }
+            }
+        }
+    }
+
+    fn validate_mirror(
+        &self,
+        mirror: &HttpRequestMirrorFilter,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if mirror.percent.is_some() {
+            self.require(
+                Feature::HttpRouteRequestMirrorPercentage,
+                path.field("percent"),
+                errors,
+            );
+        }
+        if mirror.fraction.is_some() {
+            self.require(
+                Feature::HttpRouteRequestMirrorPercentage,
+                path.field("fraction"),
+                errors,
+            );
+        }
+    }
+
+    pub(crate) fn require(
+        &self,
+        feature: Feature,
+        path: FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !self.is_enabled(feature) {
+            errors.push(ValidationError::forbidden(
+                path,
+                format!("requires the {} feature gate", feature),
+            ));
+        }
+    }
+
+    /// Removes the fields of a filter that require disabled features.
+    pub(crate) fn prune_filter(&self, filter: &mut HttpRouteFilter) {
+        if let HttpRouteFilter::RequestMirror { request_mirror } = filter {
+            if !self.is_enabled(Feature::HttpRouteRequestMirrorPercentage) {
+                request_mirror.percent = None;
+                request_mirror.fraction = None;
+            }
+        }
+    }
+}
+
+/// Parses a comma-separated list of `Feature=true|false` pairs, as accepted
+/// by the `--feature-gates` flag of Kubernetes components. Features that are
+/// not listed are disabled.
+impl FromStr for FeatureGates {
+    type Err = ParseFeatureGatesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut gates = Self::default();
+        for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, value) = match pair.split_once('=') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => (pair, ""),
+            };
+            let feature = name.parse::<Feature>()?;
+            gates = match value {
+                "true" => gates.enable(feature),
+                "false" => gates.disable(feature),
+                _ => {
+                    return Err(ParseFeatureGatesError::InvalidValue {
+                        feature: name.to_string(),
+                        value: value.to_string(),
+                    })
+                }
+            };
+        }
+        Ok(gates)
+    }
+}
+
+impl fmt::Display for FeatureGates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, feature) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}=true", feature)?;
+        }
+        Ok(())
+    }
+}
+
+// === impl ParseFeatureGatesError ===
+
+impl fmt::Display for ParseFeatureGatesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFeature(name) => write!(f, "unknown feature gate {:?}", name),
+            Self::InvalidValue { feature, value } => write!(
+                f,
+                "feature gate {} must be set to true or false, not {:?}",
+                feature, value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseFeatureGatesError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{Event, SnapshotStore};
+
+    fn object(value: serde_json::Value) -> GatewayApiObject {
+        GatewayApiObject::from_value(value).expect("object must be valid")
+    }
+
+    fn pointers(errors: Vec<ValidationError>) -> Vec<String> {
+        errors.iter().map(|e| e.path.to_json_pointer()).collect()
+    }
+
+    /// An HTTPRoute with a named rule that mirrors a portion of requests to
+    /// one of its backends.
+    fn http_route() -> HttpRoute {
+        let mut route = scaffold::http_route("web", "example.com", "web", 8080);
+        route.metadata.namespace = Some("default".to_string());
+        let rule = &mut route.spec.rules.as_mut().unwrap()[0];
+        rule.name = Some(SectionName::try_from("main").unwrap());
+        let backend = &mut rule.backend_refs.as_mut().unwrap()[0];
+        backend.filters = Some(vec![HttpRouteFilter::RequestMirror {
+            request_mirror: Box::new(HttpRequestMirrorFilter {
+                backend_ref: backend.backend_ref.clone().unwrap().inner,
+                percent: Some(10),
+                fraction: None,
+            }),
+        }]);
+        route
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let gates = "GRPCRouteNamedRules=true, SessionPersistence=true,HTTPRouteNamedRules=false"
+            .parse::<FeatureGates>()
+            .unwrap();
+        assert!(gates.is_enabled(Feature::GrpcRouteNamedRules));
+        assert!(gates.is_enabled(Feature::SessionPersistence));
+        assert!(!gates.is_enabled(Feature::HttpRouteNamedRules));
+        assert_eq!(
+            gates.to_string(),
+            "GRPCRouteNamedRules=true,SessionPersistence=true"
+        );
+        assert_eq!(gates.to_string().parse::<FeatureGates>().unwrap(), gates);
+
+        assert_eq!(
+            "Unknown=true".parse::<FeatureGates>(),
+            Err(ParseFeatureGatesError::UnknownFeature(
+                "Unknown".to_string()
+            ))
+        );
+        assert_eq!(
+            "SessionPersistence".parse::<FeatureGates>(),
+            Err(ParseFeatureGatesError::InvalidValue {
+                feature: "SessionPersistence".to_string(),
+                value: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn http_route_fields() {
+        let route = http_route();
+        let errors = route
+            .validate_with_feature_gates(&FeatureGates::default())
+            .unwrap_err();
+        assert_eq!(
+            pointers(errors),
+            [
+                "/spec/rules/0/name",
+                "/spec/rules/0/backendRefs/0/filters/0/requestMirror/percent",
+            ]
+        );
+
+        let gates = FeatureGates::default().enable(Feature::HttpRouteNamedRules);
+        let errors = route.validate_with_feature_gates(&gates).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "requires the HTTPRouteRequestMirrorPercentage feature gate"
+        );
+
+        assert!(route
+            .validate_with_feature_gates(&FeatureGates::experimental())
+            .is_ok());
+        assert!(route.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_dispatches_on_kind() {
+        let gates = FeatureGates::default();
+        let route = object(serde_json::to_value(http_route()).unwrap());
+        assert_eq!(gates.validate(&route).unwrap_err().len(), 2);
+        assert_eq!(
+            route.validate_with_feature_gates(&gates).unwrap_err().len(),
+            2
+        );
+
+        let gateway = object(serde_json::to_value(scaffold::gateway("gw", "gc", None)).unwrap());
+        assert!(gates.validate(&gateway).is_ok());
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn grpc_route_fields() {
+        let route = object(serde_json::json!({
+            "apiVersion": "gateway.networking.k8s.io/v1alpha2",
+            "kind": "GRPCRoute",
+            "metadata": { "name": "grpc", "namespace": "default" },
+            "spec": {
+                "rules": [{
+                    "name": "main",
+                    "filters": [{
+                        "type": "RequestMirror",
+                        "requestMirror": {
+                            "backendRef": { "name": "mirror", "port": 9090 },
+                            "fraction": { "numerator": 1 },
+                        },
+                    }],
+                }],
+            },
+        }));
+        assert_eq!(
+            pointers(
+                route
+                    .validate_with_feature_gates(&FeatureGates::default())
+                    .unwrap_err()
+            ),
+            [
+                "/spec/rules/0/name",
+                "/spec/rules/0/filters/0/requestMirror/fraction",
+            ]
+        );
+
+        // Named HTTPRoute rules do not enable named GRPCRoute rules.
+        let gates = FeatureGates::default()
+            .enable(Feature::HttpRouteNamedRules)
+            .enable(Feature::HttpRouteRequestMirrorPercentage);
+        assert_eq!(
+            pointers(route.validate_with_feature_gates(&gates).unwrap_err()),
+            ["/spec/rules/0/name"]
+        );
+        let gates = gates.enable(Feature::GrpcRouteNamedRules);
+        assert!(route.validate_with_feature_gates(&gates).is_ok());
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn session_persistence() {
+        let policy = object(serde_json::json!({
+            "apiVersion": "gateway.networking.k8s.io/v1alpha2",
+            "kind": "BackendLBPolicy",
+            "metadata": { "name": "lb", "namespace": "default" },
+            "spec": {
+                "targetRefs": [{ "group": "", "kind": "Service", "name": "web" }],
+                "sessionPersistence": { "sessionName": "web" },
+            },
+        }));
+        assert_eq!(
+            pointers(
+                policy
+                    .validate_with_feature_gates(&FeatureGates::default())
+                    .unwrap_err()
+            ),
+            ["/spec/sessionPersistence"]
+        );
+        let gates = FeatureGates::default().enable(Feature::SessionPersistence);
+        assert!(policy.validate_with_feature_gates(&gates).is_ok());
+    }
+
+    #[test]
+    fn compiler_prunes_gated_fields() {
+        let mut gateway = scaffold::gateway("gw", "gc", None);
+        gateway.metadata.namespace = Some("default".to_string());
+        let mut route = http_route();
+        scaffold::attach(&mut route, &gateway);
+        let key = crate::snapshot::ObjectKey::from_meta(&gateway.metadata);
+        let mut store = SnapshotStore::default();
+        store.apply(Event::Restarted(vec![gateway]));
+        store.apply(Event::Restarted(vec![route]));
+        let snapshot = store.get(&key).unwrap();
+
+        let compiled = |compiler: ir::Compiler| {
+            let table = compiler.compile(snapshot);
+            let route = table.virtual_hosts.values().next().unwrap().routes[0].clone();
+            let percent = match &route.backends[0].filters[..] {
+                [HttpRouteFilter::RequestMirror { request_mirror }] => request_mirror.percent,
+                filters => panic!("unexpected filters: {:?}", filters),
+            };
+            (route.rule_name.map(|n| n.to_string()), percent)
+        };
+
+        // All fields are honored without feature gates.
+        assert_eq!(
+            compiled(ir::Compiler::default()),
+            (Some("main".to_string()), Some(10))
+        );
+        assert_eq!(
+            compiled(ir::Compiler::default().with_feature_gates(FeatureGates::default())),
+            (None, None)
+        );
+        assert_eq!(
+            compiled(ir::Compiler::default().with_feature_gates(FeatureGates::experimental())),
+            (Some("main".to_string()), Some(10))
+        );
+    }
+}
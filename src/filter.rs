@@ -0,0 +1,487 @@
+//! Application of route filters to requests.
+//!
+//! These helpers implement the effects of filters on request headers so that
+//! dataplanes need not reinterpret the filter semantics. HTTPRoute and
+//! GRPCRoute share the `RequestHeaderModifier` configuration, so the same
+//! helpers apply to both. Headers may be held in any [`HeaderMap`]: a plain
+//! list of [`HttpHeader`]s, or, with the `http` feature, an
+//! `http::HeaderMap`.
+//!
+//! [`effective_filters`] flattens the filters of a rule and of one of its
+//! backends into the single chain that applies to requests forwarded to that
+//! backend. [`FilterOrder`] determines the order in which each list of that
+//! chain is applied. [`replace_prefix_match`] implements the `ReplacePrefixMatch`
+//! path modifier of `URLRewrite` and `RequestRedirect` filters.
+
+use crate::*;
+use std::fmt;
+
+/// Indicates that a filter chain combines filters that may not be combined.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum FilterError {
+    /// A filter type that may be specified at most once in a list of filters
+    /// is repeated.
+    Duplicate {
+        /// The index of the repeated filter in the chain.
+        index: usize,
+
+        /// The type of the filter, e.g. `RequestHeaderModifier`.
+        filter: &'static str,
+    },
+
+    /// Two filters that are mutually exclusive are both in the chain, e.g.
+    /// `RequestRedirect` and `URLRewrite`.
+    Incompatible {
+        /// The index of the later filter in the chain.
+        index: usize,
+
+        /// The type of the later filter.
+        filter: &'static str,
+
+        /// The type of the earlier filter it conflicts with.
+        conflicts_with: &'static str,
+    },
+}
+
+/// The order in which the filters of a chain are applied.
+///
+/// In either order, a rule's filters are applied before its backend's, and
+/// all filters are applied before the request is forwarded. Orders only
+/// rearrange the filters within each list.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum FilterOrder {
+    /// Filters are applied in the order in which they are specified. This is
+    /// the default.
+    Specified,
+
+    /// Filters are applied in phases: filters that modify the request
+    /// (`RequestHeaderModifier`, `URLRewrite`, and `ExtensionRef`) first, so
+    /// that mirrored requests include their modifications, then
+    /// `RequestMirror`, and finally `RequestRedirect`, which responds to the
+    /// request instead of forwarding it. Filters within a phase are applied
+    /// in the order in which they are specified.
+    Phased,
+}
+
+/// Request headers that filters may modify.
+///
+/// Header names are compared case-insensitively. A header may have several
+/// values, e.g. after values are appended to it.
+pub trait HeaderMap {
+    /// Returns the first value of a header, if it is set.
+    fn get(&self, name: &str) -> Option<&str>;
+
+    /// Replaces all values of a header with a single value, adding the header
+    /// if it is absent.
+    fn set(&mut self, name: &str, value: &str);
+
+    /// Adds a value to a header, keeping its existing values.
+    fn append(&mut self, name: &str, value: &str);
+
+    /// Removes all values of a header.
+    fn remove(&mut self, name: &str);
+}
+
+/// Returns the filters that apply to requests forwarded to a backend: the
+/// rule's filters followed by the backend's filters.
+///
+/// Each list may specify the `RequestHeaderModifier`, `RequestRedirect`, and
+/// `URLRewrite` filters at most once, and a `RequestRedirect` may not be
+/// combined with a `URLRewrite` anywhere in the chain. Filters of other
+/// types, e.g. `RequestMirror` and `ExtensionRef`, may be repeated.
+pub fn effective_filters(
+    rule: &[HttpRouteFilter],
+    backend: &[HttpRouteFilter],
+) -> Result<Vec<HttpRouteFilter>, FilterError> {
+    check_chain(rule, backend)?;
+    Ok(rule.iter().chain(backend).cloned().collect())
+}
+
+/// Checks a single list of filters, e.g. a rule's, against the rules
+/// described by [`effective_filters`].
+pub fn check_filters(filters: &[HttpRouteFilter]) -> Result<(), FilterError> {
+    check_chain(filters, &[])
+}
+
+/// Checks the chain formed by two lists of filters. Duplicates are only
+/// checked within each list, since a backend's filters may legitimately
+/// repeat a type configured on its rule.
+fn check_chain(first: &[HttpRouteFilter], second: &[HttpRouteFilter]) -> Result<(), FilterError> {
+    let mut exclusive = None;
+    for (offset, filters) in [(0, first), (first.len(), second)] {
+        let mut seen = Vec::new();
+        for (i, filter) in filters.iter().enumerate() {
+            let index = offset + i;
+            let ty = match unique_filter_type(filter) {
+                Some(ty) => ty,
+                None => continue,
+            };
+            if seen.contains(&ty) {
+                return Err(FilterError::Duplicate { index, filter: ty });
+            }
+            seen.push(ty);
+
+            if let HttpRouteFilter::RequestRedirect { .. } | HttpRouteFilter::URLRewrite { .. } =
+                filter
+            {
+                match exclusive {
+                    Some(other) if other != ty => {
+                        return Err(FilterError::Incompatible {
+                            index,
+                            filter: ty,
+                            conflicts_with: other,
+                        });
+                    }
+                    _ => exclusive = Some(ty),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the phase in which a filter is applied by [`FilterOrder::Phased`].
+fn phase(filter: &HttpRouteFilter) -> u8 {
+    match filter {
+        HttpRouteFilter::RequestHeaderModifier { .. }
+        | HttpRouteFilter::URLRewrite { .. }
+        | HttpRouteFilter::ExtensionRef { .. } => 0,
+        HttpRouteFilter::RequestMirror { .. } => 1,
+        HttpRouteFilter::RequestRedirect { .. } => 2,
+        #[cfg(feature = "unknown-filters")]
+        HttpRouteFilter::Unknown { .. } => 0,
+    }
+}
+
+/// Returns the type of a filter that may be specified at most once in a list
+/// of filters, or `None` if the filter may be repeated.
+fn unique_filter_type(filter: &HttpRouteFilter) -> Option<&'static str> {
+    match filter {
+        HttpRouteFilter::RequestHeaderModifier { .. } => Some("RequestHeaderModifier"),
+        HttpRouteFilter::RequestRedirect { .. } => Some("RequestRedirect"),
+        HttpRouteFilter::URLRewrite { .. } => Some("URLRewrite"),
+        _ => None,
+    }
+}
+
+/// Returns the fraction of requests that a `RequestMirror` filter mirrors, as
+/// a numerator and a non-zero denominator.
+///
+/// All requests are mirrored if neither `percent` nor `fraction` is set.
+/// Out-of-range values, which are rejected by validation, are clamped.
+pub fn mirror_fraction(filter: &HttpRequestMirrorFilter) -> (u32, u32) {
+    let (numerator, denominator) = match (filter.percent, &filter.fraction) {
+        (Some(percent), _) => (percent, 100),
+        (None, Some(fraction)) => (fraction.numerator, fraction.denominator.unwrap_or(100)),
+        (None, None) => return (1, 1),
+    };
+    let denominator = denominator.max(1);
+    let numerator = numerator.clamp(0, denominator);
+    (numerator as u32, denominator as u32)
+}
+
+/// Returns a request's path after a `ReplacePrefixMatch` path modifier
+/// replaces the `PathPrefix` that it matched, or `None` if the path does not
+/// match the prefix.
+///
+/// Prefixes match whole path segments, and a trailing slash on a prefix is
+/// ignored, so both `/foo` and `/foo/` match `/foo`, `/foo/`, and
+/// `/foo/bar`, but not `/foobar`. The rest of the path, including any
+/// trailing slash, follows the replacement, without a doubled slash when the
+/// replacement ends with one. A path that would be empty is `/`:
+///
+/// | Prefix | Path       | Replacement | Result     |
+/// |--------|------------|-------------|------------|
+/// | `/foo` | `/foo/bar` | `/xyz`      | `/xyz/bar` |
+/// | `/foo` | `/foo/bar` | `/xyz/`     | `/xyz/bar` |
+/// | `/foo/`| `/foo/bar` | `/xyz`      | `/xyz/bar` |
+/// | `/foo` | `/foo`     | `/xyz`      | `/xyz`     |
+/// | `/foo` | `/foo/`    | `/xyz`      | `/xyz/`    |
+/// | `/foo/`| `/foo`     | `/xyz/`     | `/xyz`     |
+/// | `/foo` | `/foo/bar` | `/`         | `/bar`     |
+/// | `/foo` | `/foo`     | `/`         | `/`        |
+/// | `/foo` | `/foo/`    | empty       | `/`        |
+/// | `/`    | `/`        | `/xyz`      | `/xyz/`    |
+/// | `/`    | `/bar`     | `/xyz`      | `/xyz/bar` |
+/// | `/foo` | `/foobar`  | `/xyz`      | no match   |
+/// | `/foo` | `/bar/foo` | `/xyz`      | no match   |
+///
+/// ```
+/// # use k8s_gateway_api::filter::replace_prefix_match;
+/// assert_eq!(replace_prefix_match("/foo/bar", "/foo", "/xyz").as_deref(), Some("/xyz/bar"));
+/// assert_eq!(replace_prefix_match("/foobar", "/foo", "/xyz"), None);
+/// ```
+pub fn replace_prefix_match(path: &str, prefix: &str, replacement: &str) -> Option<String> {
+    let rest = path.strip_prefix(prefix.trim_end_matches('/'))?;
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return None;
+    }
+    let path = format!("{}{}", replacement.trim_end_matches('/'), rest);
+    if path.is_empty() {
+        return Some("/".to_string());
+    }
+    Some(path)
+}
+
+/// Applies a `RequestHeaderModifier` filter to request headers.
+///
+/// The filter's `set` headers are applied first, replacing all existing
+/// values of each header (or adding it, if it was absent). Then `add` headers
+/// are appended, and finally `remove` headers are removed. Header names are
+/// compared case-insensitively.
+pub fn modify_request_headers<H: HeaderMap + ?Sized>(
+    filter: &HttpRequestHeaderFilter,
+    headers: &mut H,
+) {
+    for header in filter.set.iter().flatten() {
+        headers.set(&header.name, &header.value);
+    }
+
+    for header in filter.add.iter().flatten() {
+        headers.append(&header.name, &header.value);
+    }
+
+    for name in filter.remove.iter().flatten() {
+        headers.remove(name);
+    }
+}
+
+/// Applies the `RequestHeaderModifier` filters in `filters`, in order, to
+/// request headers. Other filters are ignored.
+pub fn modify_http_request_headers<'f, H: HeaderMap + ?Sized>(
+    filters: impl IntoIterator<Item = &'f HttpRouteFilter>,
+    headers: &mut H,
+) {
+    for filter in filters {
+        if let HttpRouteFilter::RequestHeaderModifier {
+            request_header_modifier,
+        } = filter
+        {
+            modify_request_headers(request_header_modifier, headers);
+        }
+    }
+}
+
+/// Applies the `RequestHeaderModifier` filters in `filters`, in order, to
+/// request headers. Other filters are ignored.
+#[cfg(feature = "experimental")]
+pub fn modify_grpc_request_headers<'f, H: HeaderMap + ?Sized>(
+    filters: impl IntoIterator<Item = &'f GrpcRouteFilter>,
+    headers: &mut H,
+) {
+    for filter in filters {
+        if let GrpcRouteFilter::RequestHeaderModifier {
+            request_header_modifier,
+        } = filter
+        {
+            modify_request_headers(request_header_modifier, headers);
+        }
+    }
+}
+
+// === impl HeaderMap ===
+
+impl HeaderMap for Vec<HttpHeader> {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| h.value.as_str())
+    }
+
+    /// Replaces the value of the header's first occurrence, preserving its
+    /// position, and removes its other occurrences.
+    fn set(&mut self, name: &str, value: &str) {
+        match self.iter().position(|h| h.name.eq_ignore_ascii_case(name)) {
+            Some(i) => {
+                self[i].value = value.to_string();
+                let mut j = 0;
+                self.retain(|h| {
+                    let keep = j <= i || !h.name.eq_ignore_ascii_case(name);
+                    j += 1;
+                    keep
+                });
+            }
+            None => HeaderMap::append(self, name, value),
+        }
+    }
+
+    fn append(&mut self, name: &str, value: &str) {
+        self.push(HttpHeader {
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.retain(|h| !h.name.eq_ignore_ascii_case(name));
+    }
+}
+
+/// Headers whose names or values are not valid HTTP are not modified, and
+/// values that are not visible ASCII are not returned by `get`.
+#[cfg(feature = "http")]
+impl HeaderMap for http::HeaderMap {
+    fn get(&self, name: &str) -> Option<&str> {
+        http::HeaderMap::get(self, name).and_then(|v| v.to_str().ok())
+    }
+
+    fn set(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) = (
+            http::header::HeaderName::from_bytes(name.as_bytes()),
+            http::header::HeaderValue::from_str(value),
+        ) {
+            self.insert(name, value);
+        }
+    }
+
+    fn append(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) = (
+            http::header::HeaderName::from_bytes(name.as_bytes()),
+            http::header::HeaderValue::from_str(value),
+        ) {
+            http::HeaderMap::append(self, name, value);
+        }
+    }
+
+    fn remove(&mut self, name: &str) {
+        http::HeaderMap::remove(self, name);
+    }
+}
+
+// === impl FilterOrder ===
+
+impl FilterOrder {
+    /// Reorders a single list of filters, e.g. a rule's, into the order in
+    /// which they are applied.
+    pub fn apply(&self, filters: &mut [HttpRouteFilter]) {
+        match self {
+            Self::Specified => {}
+            // The sort is stable, so filters within a phase keep their order.
+            Self::Phased => filters.sort_by_key(phase),
+        }
+    }
+
+    /// Returns the chain of filters that apply to requests forwarded to a
+    /// backend, as described by [`effective_filters`], in the order in which
+    /// they are applied: the rule's filters, reordered, followed by the
+    /// backend's filters, reordered.
+    pub fn effective_filters(
+        &self,
+        rule: &[HttpRouteFilter],
+        backend: &[HttpRouteFilter],
+    ) -> Result<Vec<HttpRouteFilter>, FilterError> {
+        check_chain(rule, backend)?;
+        let mut rule = rule.to_vec();
+        self.apply(&mut rule);
+        let mut backend = backend.to_vec();
+        self.apply(&mut backend);
+        rule.extend(backend);
+        Ok(rule)
+    }
+}
+
+impl Default for FilterOrder {
+    fn default() -> Self {
+        Self::Specified
+    }
+}
+
+// === impl FilterError ===
+
+impl FilterError {
+    /// Returns the index of the offending filter in the chain.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Duplicate { index, .. } | Self::Incompatible { index, .. } => *index,
+        }
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate { filter, .. } => {
+                write!(f, "{} filter may only be specified once", filter)
+            }
+            Self::Incompatible {
+                filter,
+                conflicts_with,
+                ..
+            } => write!(
+                f,
+                "{} filter cannot be combined with {}",
+                filter, conflicts_with
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filters(value: serde_json::Value) -> Vec<HttpRouteFilter> {
+        serde_json::from_value(value).expect("filters must be valid")
+    }
+
+    fn types(filters: &[HttpRouteFilter]) -> Vec<&str> {
+        filters.iter().map(HttpRouteFilter::type_name).collect()
+    }
+
+    #[test]
+    fn phased_order_keeps_rule_filters_first() {
+        let mirror = json!({
+            "type": "RequestMirror",
+            "requestMirror": { "backendRef": { "name": "mirror", "port": 80 } },
+        });
+        let modifier = json!({
+            "type": "RequestHeaderModifier",
+            "requestHeaderModifier": { "set": [{ "name": "x-backend", "value": "a" }] },
+        });
+        let rule = filters(json!([mirror, modifier]));
+        let backend = filters(json!([modifier, mirror]));
+
+        let chain = FilterOrder::Specified
+            .effective_filters(&rule, &backend)
+            .unwrap();
+        assert_eq!(
+            types(&chain),
+            [
+                "RequestMirror",
+                "RequestHeaderModifier",
+                "RequestHeaderModifier",
+                "RequestMirror"
+            ]
+        );
+        assert_eq!(chain, effective_filters(&rule, &backend).unwrap());
+
+        // The backend's header modifier is not hoisted above the rule's
+        // mirror.
+        let chain = FilterOrder::Phased
+            .effective_filters(&rule, &backend)
+            .unwrap();
+        assert_eq!(
+            types(&chain),
+            [
+                "RequestHeaderModifier",
+                "RequestMirror",
+                "RequestHeaderModifier",
+                "RequestMirror"
+            ]
+        );
+    }
+
+    #[test]
+    fn phased_order_checks_the_chain() {
+        let redirect = json!({ "type": "RequestRedirect", "requestRedirect": {} });
+        let rewrite = json!({ "type": "URLRewrite", "urlRewrite": {} });
+        let err = FilterOrder::Phased
+            .effective_filters(&filters(json!([redirect])), &filters(json!([rewrite])))
+            .unwrap_err();
+        assert_eq!(err.index(), 1);
+    }
+}
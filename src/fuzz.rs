@@ -0,0 +1,117 @@
+//! Entry points for fuzzing the deserialization of Gateway API objects.
+//!
+//! The types in this crate rely on serde features whose interactions are
+//! easy to get wrong: internally tagged enums with fallbacks for unknown
+//! types, and structs flattened into one another. Each entry point decodes
+//! arbitrary bytes as YAML (or JSON), and, if they describe an object, checks
+//! that validating it does not panic and that it survives a round trip
+//! through its serialization unchanged. Entry points panic when a check
+//! fails, as fuzzers expect.
+//!
+//! The entry points are public so that crates that embed these types, or
+//! wrap them, can run the same checks from their own fuzz targets, e.g. with
+//! `cargo fuzz`:
+//!
+//! ```ignore
+//! libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+//!     k8s_gateway_api::fuzz::fuzz_deserialize_httproute(data);
+//! });
+//! ```
+//!
+//! [`CORPUS`] holds inputs that exercise known edge cases, to seed fuzzers
+//! and to run as regression tests.
+
+use crate::{canonical, manifest, validation::Validate, *};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Inputs that exercise edge cases of deserialization, by name.
+///
+/// Not every input describes a valid object: some are expected to be
+/// rejected, and are included to check that they are rejected without
+/// panicking.
+pub const CORPUS: &[(&str, &str)] = &[
+    ("empty-spec", include_str!("fuzz/corpus/empty-spec.yaml")),
+    (
+        "filter-tag-collisions",
+        include_str!("fuzz/corpus/filter-tag-collisions.yaml"),
+    ),
+    (
+        "flattened-backend-ref",
+        include_str!("fuzz/corpus/flattened-backend-ref.yaml"),
+    ),
+    (
+        "flattened-common-spec",
+        include_str!("fuzz/corpus/flattened-common-spec.yaml"),
+    ),
+    (
+        "json-document",
+        include_str!("fuzz/corpus/json-document.yaml"),
+    ),
+    (
+        "known-type-missing-value",
+        include_str!("fuzz/corpus/known-type-missing-value.yaml"),
+    ),
+    (
+        "type-not-a-string",
+        include_str!("fuzz/corpus/type-not-a-string.yaml"),
+    ),
+    (
+        "unknown-path-match",
+        include_str!("fuzz/corpus/unknown-path-match.yaml"),
+    ),
+];
+
+/// Checks that an HTTPRoute decoded from `bytes` round-trips.
+pub fn fuzz_deserialize_httproute(bytes: &[u8]) {
+    round_trip::<HttpRoute>(bytes);
+}
+
+/// Checks that a Gateway decoded from `bytes` round-trips.
+pub fn fuzz_deserialize_gateway(bytes: &[u8]) {
+    round_trip::<Gateway>(bytes);
+}
+
+/// Checks that a GRPCRoute decoded from `bytes` round-trips.
+#[cfg(feature = "experimental")]
+pub fn fuzz_deserialize_grpcroute(bytes: &[u8]) {
+    round_trip::<GrpcRoute>(bytes);
+}
+
+/// Checks that every object of a multi-document manifest decoded from
+/// `bytes` round-trips.
+pub fn fuzz_parse_manifest(bytes: &[u8]) {
+    let objects = match manifest::parse_yaml(bytes) {
+        Ok(objects) => objects,
+        Err(_) => return,
+    };
+    for obj in objects {
+        check(obj, |value| {
+            manifest::GatewayApiObject::from_value(value).ok()
+        });
+    }
+}
+
+fn round_trip<T>(bytes: &[u8])
+where
+    T: DeserializeOwned + Serialize + Validate,
+{
+    if let Ok(obj) = serde_yaml::from_slice::<T>(bytes) {
+        check(obj, |value| serde_json::from_value::<T>(value).ok());
+    }
+}
+
+/// Checks that validating an object does not panic, and that decoding its
+/// serialization produces an object with the same serialization.
+fn check<T, F>(obj: T, decode: F)
+where
+    T: Serialize + Validate,
+    F: FnOnce(serde_json::Value) -> Option<T>,
+{
+    let _ = obj.validate();
+    canonical::hash(&obj).expect("objects must serialize canonically");
+
+    let value = serde_json::to_value(&obj).expect("objects must serialize");
+    let decoded = decode(value.clone()).expect("serialized objects must deserialize");
+    let reencoded = serde_json::to_value(&decoded).expect("objects must serialize");
+    assert_eq!(value, reencoded, "object changed in a round trip");
+}
@@ -12,7 +12,11 @@ use std::collections::BTreeMap;
     version = "v1beta1",
     kind = "Gateway",
     status = "GatewayStatus",
-    namespaced
+    namespaced,
+    printcolumn = r#"{"name":"Class", "type":"string", "jsonPath":".spec.gatewayClassName"}"#,
+    printcolumn = r#"{"name":"Address", "type":"string", "jsonPath":".status.addresses[*].value"}"#,
+    printcolumn = r#"{"name":"Programmed", "type":"string", "jsonPath":".status.conditions[?(@.type==\"Programmed\")].status"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
 )]
 #[serde(rename_all = "camelCase")]
 pub struct GatewaySpec {
@@ -58,6 +62,7 @@ pub struct GatewaySpec {
     /// condition in the Listener status.
     ///
     /// Support: Core
+    #[schemars(length(min = 1, max = "crate::validation::MAX_LISTENERS"))]
     pub listeners: Vec<Listener>,
 
     /// Addresses requested for this Gateway. This is optional and behavior can
@@ -83,6 +88,7 @@ pub struct GatewaySpec {
     /// GatewayStatus.Addresses.
     ///
     /// Support: Extended
+    #[schemars(length(max = "crate::addresses::MAX_ADDRESSES"))]
     pub addresses: Option<Vec<GatewayAddress>>,
 }
 
@@ -242,6 +248,7 @@ pub struct GatewayTlsConfig {
     ///
     /// Support: Implementation-specific (More than one reference or other
     /// resource types)
+    #[schemars(length(max = "crate::tls::MAX_CERTIFICATE_REFS"))]
     pub certificate_refs: Option<Vec<SecretObjectReference>>,
 
     /// Options are a list of key/value pairs to enable extended TLS
@@ -334,6 +341,24 @@ pub struct GatewayAddress {
     /// and support by the controller.
     ///
     /// Examples: `1.2.3.4`, `128::1`, `my-ip-address`.
+    #[schemars(length(max = 253))]
+    pub value: String,
+}
+
+/// GatewayStatusAddress describes a network address that is bound to a
+/// Gateway.
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+pub struct GatewayStatusAddress {
+    /// Type of the address.
+    pub r#type: Option<AddressType>,
+
+    /// Value of the address. The validity of the values will depend on the type
+    /// and support by the controller.
+    ///
+    /// Examples: `1.2.3.4`, `128::1`, `my-ip-address`.
+    #[schemars(length(min = 1, max = 253))]
     pub value: String,
 }
 
@@ -343,7 +368,7 @@ pub struct GatewayStatus {
     /// Addresses lists the IP addresses that have actually been bound to the
     /// Gateway. These addresses may differ from the addresses in the Spec, e.g.
     /// if the Gateway automatically assigns an address from a reserved pool.
-    pub addresses: Option<Vec<GatewayAddress>>,
+    pub addresses: Option<Vec<GatewayStatusAddress>>,
 
     /// Conditions describe the current conditions of the Gateway.
     ///
@@ -373,6 +398,7 @@ pub type GatewayConditionReason = String;
 
 /// ListenerStatus is the status associated with a Listener.
 #[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct ListenerStatus {
     /// Name is the name of the Listener that this status corresponds to.
     pub name: SectionName,
@@ -403,3 +429,12 @@ pub type ListenerConditionType = String;
 /// ListenerConditionReason defines the set of reasons that explain why a
 /// particular Listener condition type has been raised.
 pub type ListenerConditionReason = String;
+
+// === impl GatewayStatusAddress ===
+
+/// Reports a requested address as bound.
+impl From<GatewayAddress> for GatewayStatusAddress {
+    fn from(GatewayAddress { r#type, value }: GatewayAddress) -> Self {
+        Self { r#type, value }
+    }
+}
@@ -25,7 +25,11 @@ use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
     group = "gateway.networking.k8s.io",
     version = "v1beta1",
     kind = "GatewayClass",
-    status = "GatewayClassStatus"
+    status = "GatewayClassStatus",
+    printcolumn = r#"{"name":"Controller", "type":"string", "jsonPath":".spec.controllerName"}"#,
+    printcolumn = r#"{"name":"Accepted", "type":"string", "jsonPath":".status.conditions[?(@.type==\"Accepted\")].status"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#,
+    printcolumn = r#"{"name":"Description", "type":"string", "jsonPath":".spec.description", "priority":1}"#
 )]
 #[serde(rename_all = "camelCase")]
 pub struct GatewayClassSpec {
@@ -50,7 +54,7 @@ pub struct GatewayClassSpec {
     /// status condition will be true.
     ///
     /// Support: Custom
-    pub paramters_ref: Option<ParametersReference>,
+    pub parameters_ref: Option<ParametersReference>,
 
     /// Description helps describe a GatewayClass with more details.
     pub description: Option<String>,
@@ -87,8 +91,51 @@ pub type GatewayClassConditionType = String;
 /// particular GatewayClass condition type has been raised.
 pub type GatewayClassConditionReason = String;
 
+/// This condition indicates whether the GatewayClass has been accepted by the
+/// controller requested in the `spec.controller` field.
+///
+/// This condition defaults to Unknown, and MUST be set by a controller when it
+/// sees a GatewayClass using its controller string. The status of this
+/// condition MUST be set to True if the controller will support provisioning
+/// Gateways using this class. Otherwise, this status MUST be set to False. If
+/// the status is set to False, the controller SHOULD set a Message and Reason
+/// as an explanation.
+pub const GATEWAY_CLASS_CONDITION_ACCEPTED: &str = "Accepted";
+
+/// This reason is used with the "Accepted" condition when the condition is
+/// true.
+pub const GATEWAY_CLASS_REASON_ACCEPTED: &str = "Accepted";
+
+/// This reason is used with the "Accepted" condition when the GatewayClass
+/// was not accepted because the parametersRef field was invalid, with more
+/// detail in the message.
+pub const GATEWAY_CLASS_REASON_INVALID_PARAMETERS: &str = "InvalidParameters";
+
+/// This reason is used with the "Accepted" condition when the requested
+/// controller has not yet made a decision about whether to admit the
+/// GatewayClass. It is the default Reason on a new GatewayClass.
+pub const GATEWAY_CLASS_REASON_WAITING: &str = "Waiting";
+
+/// This condition indicates whether the GatewayClass supports the version(s)
+/// of Gateway API CRDs present in the cluster.
+///
+/// Implementations SHOULD NOT accept a GatewayClass when the installed CRDs
+/// are not supported.
+pub const GATEWAY_CLASS_CONDITION_SUPPORTED_VERSION: &str = "SupportedVersion";
+
+/// This reason is used with the "SupportedVersion" condition when the
+/// condition is true.
+pub const GATEWAY_CLASS_REASON_SUPPORTED_VERSION: &str = "SupportedVersion";
+
+/// This reason is used with the "SupportedVersion" or "Accepted" condition
+/// when the condition is false because the installed CRD versions are not
+/// supported.
+pub const GATEWAY_CLASS_REASON_UNSUPPORTED_VERSION: &str = "UnsupportedVersion";
+
 /// GatewayClassStatus is the current status for the GatewayClass.
-#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[derive(
+    Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
 pub struct GatewayClassStatus {
     /// Conditions is the current status from the controller for this
     /// GatewayClass.
@@ -97,3 +144,67 @@ pub struct GatewayClassStatus {
     /// GatewayClassConditionType for the type of each Condition.
     pub conditions: Option<Vec<metav1::Condition>>,
 }
+
+// === impl GatewayClassStatus ===
+
+impl GatewayClassStatus {
+    /// Returns the condition with the given type, if it is set.
+    pub fn condition(&self, type_: &str) -> Option<&metav1::Condition> {
+        self.conditions.iter().flatten().find(|c| c.type_ == type_)
+    }
+
+    /// Sets a condition, replacing any condition with the same type.
+    ///
+    /// As with `meta.SetStatusCondition`, the existing transition time is
+    /// kept if the status of the condition did not change.
+    pub fn set_condition(&mut self, mut condition: metav1::Condition) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            condition = %condition.type_,
+            status = %condition.status,
+            reason = %condition.reason,
+            observed_generation = ?condition.observed_generation,
+            "Setting GatewayClass condition"
+        );
+        let conditions = self.conditions.get_or_insert_with(Vec::new);
+        match conditions.iter_mut().find(|c| c.type_ == condition.type_) {
+            Some(existing) => {
+                if existing.status == condition.status {
+                    condition.last_transition_time = existing.last_transition_time.clone();
+                }
+                *existing = condition;
+            }
+            None => conditions.push(condition),
+        }
+    }
+
+    /// Marks the GatewayClass as accepted by its controller.
+    pub fn accept(&mut self, observed_generation: Option<i64>, now: metav1::Time) {
+        self.set_condition(metav1::Condition {
+            type_: GATEWAY_CLASS_CONDITION_ACCEPTED.to_string(),
+            status: "True".to_string(),
+            reason: GATEWAY_CLASS_REASON_ACCEPTED.to_string(),
+            message: String::new(),
+            observed_generation,
+            last_transition_time: now,
+        });
+    }
+
+    /// Marks the GatewayClass as not accepted because its `parametersRef` is
+    /// invalid, e.g. because the referent does not exist.
+    pub fn reject_invalid_parameters(
+        &mut self,
+        message: impl Into<String>,
+        observed_generation: Option<i64>,
+        now: metav1::Time,
+    ) {
+        self.set_condition(metav1::Condition {
+            type_: GATEWAY_CLASS_CONDITION_ACCEPTED.to_string(),
+            status: "False".to_string(),
+            reason: GATEWAY_CLASS_REASON_INVALID_PARAMETERS.to_string(),
+            message: message.into(),
+            observed_generation,
+            last_transition_time: now,
+        });
+    }
+}
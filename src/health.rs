@@ -0,0 +1,159 @@
+//! Readiness of the backends that routes forward to.
+//!
+//! Requests forwarded to a backend without ready endpoints cannot be served,
+//! and receive 503 responses, unlike requests for invalid backends, which
+//! receive 500 responses. A [`BackendHealthSource`] reports whether a
+//! compiled [`Cluster`] is ready, so that the same handling can be driven by
+//! EndpointSlices, active health checks, or any other source of health:
+//!
+//! ```ignore
+//! let mut health = EndpointSlices::default();
+//! health.apply(Event::Applied(slice));
+//! let compiler = Compiler::default().with_health(health.clone());
+//! let plan = SplitPlan::from_route_with_health(&table, route, &health);
+//! ```
+//!
+//! The [`Compiler`](ir::Compiler) records the readiness of each cluster when
+//! it compiles a table, and [`RouteTable::update_health`] refreshes it without
+//! recompiling. [`SplitPlan::from_route_with_health`] consults a source
+//! directly when a backend is selected for a request.
+//!
+//! [`Cluster`]: ir::Cluster
+//! [`RouteTable::update_health`]: ir::RouteTable::update_health
+//! [`SplitPlan::from_route_with_health`]: crate::split::SplitPlan::from_route_with_health
+
+use crate::{
+    ir,
+    snapshot::{Event, ObjectKey},
+};
+use k8s_openapi::api::discovery::v1::EndpointSlice;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The label that associates an EndpointSlice with its Service.
+pub const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+
+/// Reports whether backends can serve requests.
+pub trait BackendHealthSource {
+    /// Returns true if requests forwarded to a cluster can be served by at
+    /// least one of its endpoints.
+    fn is_ready(&self, cluster: &ir::Cluster) -> bool;
+}
+
+/// A [`BackendHealthSource`] for implementations that do not track backend
+/// health; every backend is ready.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AssumeReady;
+
+/// A [`BackendHealthSource`] that tracks the EndpointSlices of Services.
+///
+/// A Service backend is ready if any endpoint of its EndpointSlices is ready,
+/// regardless of port, as slices identify ports by name and clusters by
+/// number. Endpoints whose readiness is unknown are considered ready, as
+/// Kubernetes prescribes. Backends of other kinds are always ready.
+#[derive(Clone, Debug, Default)]
+pub struct EndpointSlices {
+    /// Whether each EndpointSlice has a ready endpoint, by Service and then
+    /// slice name.
+    services: BTreeMap<ObjectKey, BTreeMap<String, bool>>,
+}
+
+// === impl BackendHealthSource ===
+
+impl<T: BackendHealthSource + ?Sized> BackendHealthSource for &T {
+    fn is_ready(&self, cluster: &ir::Cluster) -> bool {
+        (**self).is_ready(cluster)
+    }
+}
+
+impl<T: BackendHealthSource + ?Sized> BackendHealthSource for std::sync::Arc<T> {
+    fn is_ready(&self, cluster: &ir::Cluster) -> bool {
+        (**self).is_ready(cluster)
+    }
+}
+
+// === impl AssumeReady ===
+
+impl BackendHealthSource for AssumeReady {
+    fn is_ready(&self, _: &ir::Cluster) -> bool {
+        true
+    }
+}
+
+// === impl EndpointSlices ===
+
+impl EndpointSlices {
+    /// Applies a watch event for EndpointSlices.
+    ///
+    /// Slices without a [`SERVICE_NAME_LABEL`] are not associated with a
+    /// Service, and are ignored.
+    pub fn apply(&mut self, event: Event<EndpointSlice>) {
+        match event {
+            Event::Applied(slice) => self.insert(&slice),
+            Event::Deleted(slice) => self.remove(&slice),
+            Event::Restarted(slices) => {
+                self.services.clear();
+                for slice in &slices {
+                    self.insert(slice);
+                }
+            }
+        }
+    }
+
+    /// Returns the Services that have at least one ready endpoint.
+    pub fn ready_services(&self) -> BTreeSet<&ObjectKey> {
+        self.services
+            .iter()
+            .filter(|(_, slices)| slices.values().any(|ready| *ready))
+            .map(|(service, _)| service)
+            .collect()
+    }
+
+    fn insert(&mut self, slice: &EndpointSlice) {
+        let (service, name) = match slice_key(slice) {
+            Some(key) => key,
+            None => return,
+        };
+        let ready = slice.endpoints.iter().any(|e| {
+            let ready = e.conditions.as_ref().and_then(|c| c.ready);
+            ready.unwrap_or(true)
+        });
+        self.services
+            .entry(service)
+            .or_default()
+            .insert(name.to_string(), ready);
+    }
+
+    fn remove(&mut self, slice: &EndpointSlice) {
+        let (service, name) = match slice_key(slice) {
+            Some(key) => key,
+            None => return,
+        };
+        if let Some(slices) = self.services.get_mut(&service) {
+            slices.remove(name);
+            if slices.is_empty() {
+                self.services.remove(&service);
+            }
+        }
+    }
+}
+
+impl BackendHealthSource for EndpointSlices {
+    fn is_ready(&self, cluster: &ir::Cluster) -> bool {
+        if !cluster.group.is_empty() || cluster.kind != "Service" {
+            return true;
+        }
+        let service = ObjectKey::new(&*cluster.namespace, &*cluster.backend);
+        self.services
+            .get(&service)
+            .map_or(false, |slices| slices.values().any(|ready| *ready))
+    }
+}
+
+/// Returns the key of a slice's Service and the slice's name.
+fn slice_key(slice: &EndpointSlice) -> Option<(ObjectKey, &str)> {
+    let meta = &slice.metadata;
+    let service = meta.labels.as_ref()?.get(SERVICE_NAME_LABEL)?;
+    let namespace = meta.namespace.as_deref().unwrap_or_default();
+    let name = meta.name.as_deref()?;
+    Some((ObjectKey::new(namespace, &**service), name))
+}
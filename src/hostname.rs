@@ -0,0 +1,79 @@
+//! Matching of hostnames against wildcard patterns.
+//!
+//! A hostname pattern is either a precise hostname, e.g. `foo.example.com`,
+//! or a wildcard: a single leading `*` label followed by a precise suffix,
+//! e.g. `*.example.com`. A wildcard matches hostnames that end with its suffix
+//! and have at least one additional label, so `*.example.com` matches
+//! `foo.example.com` and `foo.bar.example.com` but not `example.com`.
+//! Hostnames are compared case-insensitively.
+//!
+//! ```
+//! # use k8s_gateway_api::hostname;
+//! let capture = hostname::matches("*.example.com", "foo.bar.example.com").unwrap();
+//! assert_eq!(capture.as_str(), Some("foo.bar"));
+//! assert_eq!(capture.substitute("*.example.org"), "foo.bar.example.org");
+//! assert!(hostname::matches("*.example.com", "example.com").is_none());
+//! ```
+
+/// The portion of a hostname matched by the wildcard label of a pattern.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct WildcardCapture<'h>(Option<&'h str>);
+
+/// Returns true if `pattern` is a wildcard hostname.
+pub fn is_wildcard(pattern: &str) -> bool {
+    pattern.starts_with("*.")
+}
+
+/// Matches `host` against a hostname pattern, returning the labels matched
+/// by the pattern's wildcard, if any.
+///
+/// `host` may itself be a wildcard, in which case it matches `pattern` if
+/// every hostname it matches is also matched by `pattern`; the captured
+/// portion then begins with `*`.
+pub fn matches<'h>(pattern: &str, host: &'h str) -> Option<WildcardCapture<'h>> {
+    if pattern.eq_ignore_ascii_case(host) {
+        return Some(WildcardCapture(None));
+    }
+
+    // The suffix includes the leading dot, so that `*.example.com` does not
+    // match `fooexample.com`.
+    let suffix = pattern.strip_prefix('*').filter(|s| s.starts_with('.'))?;
+    if host.len() <= suffix.len() {
+        return None;
+    }
+    let (labels, host_suffix) = host.split_at(host.len() - suffix.len());
+    if !host_suffix.eq_ignore_ascii_case(suffix) || labels.ends_with('.') {
+        return None;
+    }
+    Some(WildcardCapture(Some(labels)))
+}
+
+// === impl WildcardCapture ===
+
+impl<'h> WildcardCapture<'h> {
+    /// Returns the labels matched by the wildcard, without the trailing dot,
+    /// or `None` if the pattern matched the hostname exactly.
+    pub fn as_str(&self) -> Option<&'h str> {
+        self.0
+    }
+
+    /// Returns true if the pattern matched the hostname exactly, rather than
+    /// through its wildcard.
+    pub fn is_exact(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Replaces the wildcard label of `hostname` with the captured labels,
+    /// e.g. to derive a redirect hostname from the requested one.
+    ///
+    /// `hostname` is returned unchanged if it is not a wildcard or if nothing
+    /// was captured.
+    pub fn substitute(&self, hostname: &str) -> String {
+        match (self.0, hostname.strip_prefix('*')) {
+            (Some(labels), Some(suffix)) if is_wildcard(hostname) => {
+                format!("{}{}", labels, suffix)
+            }
+            _ => hostname.to_string(),
+        }
+    }
+}
@@ -19,7 +19,9 @@ use crate::*;
     kind = "HTTPRoute",
     struct = "HttpRoute",
     status = "HttpRouteStatus",
-    namespaced
+    namespaced,
+    printcolumn = r#"{"name":"Hostnames", "type":"string", "jsonPath":".spec.hostnames"}"#,
+    printcolumn = r#"{"name":"Age", "type":"date", "jsonPath":".metadata.creationTimestamp"}"#
 )]
 pub struct HttpRouteSpec {
     /// Common route information.
@@ -59,9 +61,11 @@ pub struct HttpRouteSpec {
     /// `False` in the corresponding RouteParentStatus.
     ///
     /// Support: Core
+    #[schemars(length(max = "crate::validation::MAX_HOSTNAMES"))]
     pub hostnames: Option<Vec<Hostname>>,
 
     /// Rules are a list of HTTP matchers, filters and actions.
+    #[schemars(length(max = "crate::validation::MAX_RULES"))]
     pub rules: Option<Vec<HttpRouteRule>>,
 }
 
@@ -73,6 +77,14 @@ pub struct HttpRouteSpec {
 )]
 #[serde(rename_all = "camelCase")]
 pub struct HttpRouteRule {
+    /// Name is the name of the route rule. This name MUST be unique within a
+    /// Route if it is set.
+    ///
+    /// Support: Extended
+    ///
+    // gateway:experimental
+    pub name: Option<SectionName>,
+
     /// Matches define conditions used for matching the rule against incoming
     /// HTTP requests. Each match is independent, i.e. this rule will be matched
     /// if **any** one of the matches is satisfied.
@@ -127,6 +139,7 @@ pub struct HttpRouteRule {
     ///
     /// When no rules matching a request have been successfully attached to the
     /// parent a request is coming from, a HTTP 404 status code MUST be returned.
+    #[schemars(length(max = "crate::validation::MAX_MATCHES"))]
     pub matches: Option<Vec<HttpRouteMatch>>,
 
     /// Filters define the filters that are applied to requests that match this
@@ -147,6 +160,7 @@ pub struct HttpRouteRule {
     /// conformance.
     ///
     /// Support: Core
+    #[schemars(length(max = "crate::validation::MAX_FILTERS"))]
     pub filters: Option<Vec<HttpRouteFilter>>,
 
     /// BackendRefs defines the backend(s) where matching requests should be
@@ -175,6 +189,7 @@ pub struct HttpRouteRule {
     /// Support: Custom for any other resource
     ///
     /// Support for weight: Core
+    #[schemars(length(max = "crate::validation::MAX_BACKEND_REFS"))]
     pub backend_refs: Option<Vec<HttpBackendRef>>,
 }
 
@@ -205,11 +220,13 @@ pub struct HttpRouteMatch {
     /// Headers specifies HTTP request header matchers. Multiple match values
     /// are ANDed together, meaning, a request must match all the specified
     /// headers to select the route.
+    #[schemars(length(max = "crate::validation::MAX_MATCH_CONDITIONS"))]
     pub headers: Option<Vec<HttpHeaderMatch>>,
 
     /// QueryParams specifies HTTP query parameter matchers. Multiple match
     /// values are ANDed together, meaning, a request must match all the
     /// specified query parameters to select the route.
+    #[schemars(length(max = "crate::validation::MAX_MATCH_CONDITIONS"))]
     pub query_params: Option<Vec<HttpQueryParamMatch>>,
 
     /// Method specifies HTTP method matcher.
@@ -237,11 +254,25 @@ pub struct HttpRouteMatch {
 #[derive(
     Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
 )]
-#[serde(tag = "type", rename_all = "PascalCase")]
+#[serde(remote = "Self", tag = "type", rename_all = "PascalCase")]
+#[non_exhaustive]
 pub enum HttpPathMatch {
-    Exact { value: String },
-    PathPrefix { value: String },
-    RegularExpression { value: String },
+    Exact {
+        value: String,
+    },
+    PathPrefix {
+        value: String,
+    },
+    RegularExpression {
+        value: String,
+    },
+
+    /// A match of a type that is not known to this crate, e.g. one added in a
+    /// later version of the API, as it was deserialized. Unknown matches do
+    /// not match any path.
+    #[serde(skip)]
+    #[schemars(skip)]
+    Unknown(serde_json::Value),
 }
 
 /// HTTPHeaderName is the name of an HTTP header.
@@ -278,7 +309,8 @@ pub type HttpHeaderName = String;
 #[derive(
     Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
 )]
-#[serde(tag = "type", rename_all = "PascalCase")]
+#[serde(remote = "Self", tag = "type", rename_all = "PascalCase")]
+#[non_exhaustive]
 pub enum HttpHeaderMatch {
     #[serde(rename_all = "camelCase")]
     Exact { name: HttpHeaderName, value: String },
@@ -293,20 +325,41 @@ pub enum HttpHeaderMatch {
         /// the supported dialect.
         value: String,
     },
+
+    /// A match of a type that is not known to this crate, as it was
+    /// deserialized. Unknown matches are not satisfied by any headers.
+    #[serde(skip)]
+    #[schemars(skip)]
+    Unknown(serde_json::Value),
 }
 
 /// HTTPQueryParamMatch describes how to select a HTTP route by matching HTTP
 /// query parameters.
+///
+/// Name is the name of the HTTP query param to be matched. This must be an
+/// exact string match. (See
+/// <https://tools.ietf.org/html/rfc7230#section-2.7.3>).
+///
+/// If multiple entries specify equivalent query param names, only the first
+/// entry with an equivalent name MUST be considered for a match. Subsequent
+/// entries with an equivalent query param name MUST be ignored.
 #[derive(
     Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
 )]
-#[serde(tag = "type", rename_all = "PascalCase")]
+#[serde(remote = "Self", tag = "type", rename_all = "PascalCase")]
+#[non_exhaustive]
 pub enum HttpQueryParamMatch {
     #[serde(rename_all = "camelCase")]
     Exact { name: String, value: String },
 
     #[serde(rename_all = "camelCase")]
     RegularExpression { name: String, value: String },
+
+    /// A match of a type that is not known to this crate, as it was
+    /// deserialized. Unknown matches are not satisfied by any parameters.
+    #[serde(skip)]
+    #[schemars(skip)]
+    Unknown(serde_json::Value),
 }
 
 /// HTTPMethod describes how to select a HTTP route by matching the HTTP
@@ -347,10 +400,16 @@ pub type HttpMethod = String;
 /// If a reference to a custom filter type cannot be resolved, the filter
 /// MUST NOT be skipped. Instead, requests that would have been processed by
 /// that filter MUST receive a HTTP error response.
+//
+// Filter configurations are boxed so that a filter is only as large as a
+// pointer and a tag. Most rules have few filters, while controllers may hold
+// tens of thousands of rules; see `benches/memory.rs`.
 #[derive(
     Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
 )]
 #[serde(tag = "type", rename_all = "PascalCase")]
+#[cfg_attr(feature = "unknown-filters", serde(remote = "Self"))]
+#[non_exhaustive]
 pub enum HttpRouteFilter {
     /// RequestHeaderModifier defines a schema for a filter that modifies request
     /// headers.
@@ -358,7 +417,7 @@ pub enum HttpRouteFilter {
     /// Support: Core
     #[serde(rename_all = "camelCase")]
     RequestHeaderModifier {
-        request_header_modifier: HttpRequestHeaderFilter,
+        request_header_modifier: Box<HttpRequestHeaderFilter>,
     },
 
     /// RequestMirror defines a schema for a filter that mirrors requests.
@@ -368,7 +427,7 @@ pub enum HttpRouteFilter {
     /// Support: Extended
     #[serde(rename_all = "camelCase")]
     RequestMirror {
-        request_mirror: HttpRequestMirrorFilter,
+        request_mirror: Box<HttpRequestMirrorFilter>,
     },
 
     /// RequestRedirect defines a schema for a filter that responds to the
@@ -377,14 +436,16 @@ pub enum HttpRouteFilter {
     /// Support: Core
     #[serde(rename_all = "camelCase")]
     RequestRedirect {
-        request_redirect: HttpRequestRedirectFilter,
+        request_redirect: Box<HttpRequestRedirectFilter>,
     },
 
     /// URLRewrite defines a schema for a filter that modifies a request during forwarding.
     ///
     /// Support: Extended
     #[serde(rename_all = "camelCase")]
-    URLRewrite { url_rewrite: HttpUrlRewriteFilter },
+    URLRewrite {
+        url_rewrite: Box<HttpUrlRewriteFilter>,
+    },
 
     /// ExtensionRef is an optional, implementation-specific extension to the
     /// "filter" behavior.  For example, resource "myroutefilter" in group
@@ -393,7 +454,29 @@ pub enum HttpRouteFilter {
     ///
     /// Support: Implementation-specific
     #[serde(rename_all = "camelCase")]
-    ExtensionRef { extension_ref: LocalObjectReference },
+    ExtensionRef {
+        extension_ref: Box<LocalObjectReference>,
+    },
+
+    /// A filter of a type that is not known to this crate, e.g. a type added
+    /// in a later version of the API.
+    ///
+    /// By default, deserializing a filter of an unknown type fails, and so
+    /// does deserializing the route that contains it. With the
+    /// `unknown-filters` feature, such filters are deserialized into this
+    /// variant so that controllers can report them as unsupported rather than
+    /// ignoring the whole route. They must not be skipped: requests that they
+    /// apply to must receive an error response.
+    #[cfg(feature = "unknown-filters")]
+    #[serde(skip)]
+    #[schemars(skip)]
+    Unknown {
+        /// The filter's `type`.
+        type_name: String,
+
+        /// The filter's other fields, as they were deserialized.
+        raw: serde_json::Map<String, serde_json::Value>,
+    },
 }
 
 /// HTTPRequestHeaderFilter defines configuration for the RequestHeaderModifier
@@ -483,18 +566,25 @@ pub struct HttpHeader {
 #[derive(
     Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
 )]
-#[serde(tag = "type", rename_all = "PascalCase")]
+#[serde(remote = "Self", tag = "type", rename_all = "PascalCase")]
+#[non_exhaustive]
 pub enum HttpPathModifier {
     /// ReplaceFullPath specifies the value with which to replace the full path
     /// of a request during a rewrite or redirect.
     #[serde(rename_all = "camelCase")]
-    ReplaceFullPath(String),
+    ReplaceFullPath { replace_full_path: String },
 
     /// ReplacePrefixMatch specifies the value with which to replace the prefix
     /// match of a request during a rewrite or redirect. For example, a request
     /// to "/foo/bar" with a prefix match of "/foo" would be modified to "/bar".
     #[serde(rename_all = "camelCase")]
-    ReplacePrefixMatch(String),
+    ReplacePrefixMatch { replace_prefix_match: String },
+
+    /// A modifier of a type that is not known to this crate, as it was
+    /// deserialized. Unknown modifiers leave the path unchanged.
+    #[serde(skip)]
+    #[schemars(skip)]
+    Unknown(serde_json::Value),
 }
 
 /// HTTPRequestRedirect defines a filter that redirects a request. This filter
@@ -509,7 +599,7 @@ pub struct HttpRequestRedirectFilter {
     /// When empty, the scheme of the request is used.
     ///
     /// Support: Extended
-    pub scheme: Option<String>,
+    pub scheme: Option<Scheme>,
 
     /// Hostname is the hostname to be used in the value of the `Location`
     /// header in the response.
@@ -539,6 +629,23 @@ pub struct HttpRequestRedirectFilter {
     pub status_code: Option<u16>,
 }
 
+/// Scheme is the scheme of a redirect's `Location` header.
+///
+/// The `http` and `https` schemes are supported by all implementations.
+/// Implementations may support other schemes as an extension; these must be
+/// valid URI schemes and, as with all schemes, lowercase. Values are validated
+/// when they are parsed or deserialized, so `Extension` should only be
+/// constructed by parsing.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Scheme {
+    Http,
+    Https,
+
+    /// An implementation-specific scheme.
+    Extension(String),
+}
+
 /// HTTPURLRewriteFilter defines a filter that modifies a request during
 /// forwarding. At most one of these filters may be used on a Route rule. This
 /// may not be used on the same Route rule as a HTTPRequestRedirect filter.
@@ -586,6 +693,41 @@ pub struct HttpRequestMirrorFilter {
     /// Support: Extended for Kubernetes Service
     /// Support: Custom for any other resource
     pub backend_ref: BackendObjectReference,
+
+    /// Percent represents the percentage of requests that should be mirrored
+    /// to BackendRef. Its minimum value is 0 (indicating 0% of requests) and
+    /// its maximum value is 100 (indicating 100% of requests).
+    ///
+    /// Only one of Fraction or Percent may be specified. If neither field is
+    /// specified, 100% of requests will be mirrored.
+    ///
+    /// Support: Extended
+    ///
+    // gateway:experimental
+    pub percent: Option<i32>,
+
+    /// Fraction represents the fraction of requests that should be mirrored
+    /// to BackendRef.
+    ///
+    /// Only one of Fraction or Percent may be specified. If neither field is
+    /// specified, 100% of requests will be mirrored.
+    ///
+    /// Support: Extended
+    ///
+    // gateway:experimental
+    pub fraction: Option<Fraction>,
+}
+
+/// Fraction is a ratio, e.g. of requests. The numerator must not be greater
+/// than the denominator.
+#[derive(
+    Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+pub struct Fraction {
+    pub numerator: i32,
+
+    /// Defaults to 100.
+    pub denominator: Option<i32>,
 }
 
 /// HTTPBackendRef defines how a HTTPRoute should forward an HTTP request.
@@ -611,7 +753,10 @@ pub struct HttpBackendRef {
     /// should be used to provide more detail about the problem.
     ///
     /// Support: Custom
-    #[serde(flatten)]
+    #[serde(
+        flatten,
+        deserialize_with = "crate::shared::deserialize_flattened_backend_ref"
+    )]
     pub backend_ref: Option<BackendRef>,
 
     /// Filters defined at this level should be executed if and only if the
@@ -619,6 +764,7 @@ pub struct HttpBackendRef {
     ///
     /// Support: Custom (For broader support of filters, use the Filters field
     /// in HTTPRouteRule.)
+    #[schemars(length(max = "crate::validation::MAX_FILTERS"))]
     pub filters: Option<Vec<HttpRouteFilter>>,
 }
 
@@ -629,3 +775,439 @@ pub struct HttpRouteStatus {
     #[serde(flatten)]
     pub inner: RouteStatus,
 }
+
+// === impl HttpPathMatch ===
+
+impl_tagged_serde!(
+    HttpPathMatch,
+    ["Exact", "PathPrefix", "RegularExpression"],
+    Some("PathPrefix")
+);
+
+impl HttpPathMatch {
+    /// Returns the match's type, e.g. `PathPrefix`.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::Exact { .. } => "Exact",
+            Self::PathPrefix { .. } => "PathPrefix",
+            Self::RegularExpression { .. } => "RegularExpression",
+            Self::Unknown(raw) => unknown_type(raw),
+        }
+    }
+
+    /// Returns the path or regular expression to match, or `None` if the
+    /// match's type is unknown.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Self::Exact { value }
+            | Self::PathPrefix { value }
+            | Self::RegularExpression { value } => Some(value),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+// === impl HttpHeaderMatch ===
+
+impl_tagged_serde!(
+    HttpHeaderMatch,
+    ["Exact", "RegularExpression"],
+    Some("Exact")
+);
+
+impl HttpHeaderMatch {
+    /// Returns the match's type, e.g. `Exact`.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::Exact { .. } => "Exact",
+            Self::RegularExpression { .. } => "RegularExpression",
+            Self::Unknown(raw) => unknown_type(raw),
+        }
+    }
+
+    /// Returns the name of the header to match, or `None` if the match's type
+    /// is unknown.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Exact { name, .. } | Self::RegularExpression { name, .. } => Some(name),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Returns the value or regular expression to match, or `None` if the
+    /// match's type is unknown.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Self::Exact { value, .. } | Self::RegularExpression { value, .. } => Some(value),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+// === impl HttpQueryParamMatch ===
+
+impl_tagged_serde!(
+    HttpQueryParamMatch,
+    ["Exact", "RegularExpression"],
+    Some("Exact")
+);
+
+impl HttpQueryParamMatch {
+    /// Returns the match's type, e.g. `Exact`.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::Exact { .. } => "Exact",
+            Self::RegularExpression { .. } => "RegularExpression",
+            Self::Unknown(raw) => unknown_type(raw),
+        }
+    }
+
+    /// Returns the name of the query parameter to match, or `None` if the
+    /// match's type is unknown.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Exact { name, .. } | Self::RegularExpression { name, .. } => Some(name),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Returns the value or regular expression to match, or `None` if the
+    /// match's type is unknown.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Self::Exact { value, .. } | Self::RegularExpression { value, .. } => Some(value),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+// === impl HttpRouteFilter ===
+
+impl HttpRouteFilter {
+    /// Returns the filter's type, e.g. `RequestHeaderModifier`.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::RequestHeaderModifier { .. } => "RequestHeaderModifier",
+            Self::RequestMirror { .. } => "RequestMirror",
+            Self::RequestRedirect { .. } => "RequestRedirect",
+            Self::URLRewrite { .. } => "URLRewrite",
+            Self::ExtensionRef { .. } => "ExtensionRef",
+            #[cfg(feature = "unknown-filters")]
+            Self::Unknown { type_name, .. } => type_name,
+        }
+    }
+}
+
+#[cfg(feature = "unknown-filters")]
+impl<'de> serde::Deserialize<'de> for HttpRouteFilter {
+    fn deserialize<D: serde::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        crate::shared::deserialize_tagged(
+            de,
+            &[
+                "RequestHeaderModifier",
+                "RequestMirror",
+                "RequestRedirect",
+                "URLRewrite",
+                "ExtensionRef",
+            ],
+            None,
+            Self::deserialize,
+            |raw| {
+                let mut raw = match raw {
+                    serde_json::Value::Object(raw) => raw,
+                    _ => unreachable!("tagged objects are maps"),
+                };
+                let type_name = match raw.remove("type") {
+                    Some(serde_json::Value::String(t)) => t,
+                    _ => unreachable!("unknown types are strings"),
+                };
+                Self::Unknown { type_name, raw }
+            },
+        )
+    }
+}
+
+#[cfg(feature = "unknown-filters")]
+impl serde::Serialize for HttpRouteFilter {
+    fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Unknown { type_name, raw } => {
+                let mut fields = raw.clone();
+                fields.insert("type".to_string(), type_name.clone().into());
+                serde::Serialize::serialize(&fields, ser)
+            }
+            known => Self::serialize(known, ser),
+        }
+    }
+}
+
+// === impl HttpPathModifier ===
+
+impl_tagged_serde!(
+    HttpPathModifier,
+    ["ReplaceFullPath", "ReplacePrefixMatch"],
+    None
+);
+
+impl HttpPathModifier {
+    /// Returns the modifier's type, e.g. `ReplacePrefixMatch`.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::ReplaceFullPath { .. } => "ReplaceFullPath",
+            Self::ReplacePrefixMatch { .. } => "ReplacePrefixMatch",
+            Self::Unknown(raw) => unknown_type(raw),
+        }
+    }
+
+    /// Returns the replacement path or prefix, or `None` if the modifier's
+    /// type is unknown.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Self::ReplaceFullPath { replace_full_path } => Some(replace_full_path),
+            Self::ReplacePrefixMatch {
+                replace_prefix_match,
+            } => Some(replace_prefix_match),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+// === impl Scheme ===
+
+impl Scheme {
+    /// Returns the scheme as a string slice, e.g. `https`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+            Self::Extension(scheme) => scheme,
+        }
+    }
+
+    /// Returns the port that is implied when a URI with this scheme omits
+    /// one, if it is known.
+    pub fn default_port(&self) -> Option<PortNumber> {
+        match self {
+            Self::Http => Some(80),
+            Self::Https => Some(443),
+            Self::Extension(_) => None,
+        }
+    }
+}
+
+impl TryFrom<String> for Scheme {
+    type Error = InvalidValue;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "http" => return Ok(Self::Http),
+            "https" => return Ok(Self::Https),
+            _ => {}
+        }
+        // RFC 3986 schemes are case-insensitive, but Gateway API requires
+        // them to be lowercase.
+        let bytes = value.as_bytes();
+        let valid = bytes.first().map_or(false, u8::is_ascii_lowercase)
+            && bytes
+                .iter()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"+-.".contains(b));
+        if !valid {
+            let reason = "must be a lowercase URI scheme, e.g. http or https".to_string();
+            return Err(InvalidValue::new("Scheme", value, reason));
+        }
+        Ok(Self::Extension(value))
+    }
+}
+
+impl TryFrom<&str> for Scheme {
+    type Error = InvalidValue;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::try_from(value.to_string())
+    }
+}
+
+impl std::str::FromStr for Scheme {
+    type Err = InvalidValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl From<Scheme> for String {
+    fn from(scheme: Scheme) -> Self {
+        match scheme {
+            Scheme::Extension(scheme) => scheme,
+            scheme => scheme.as_str().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Scheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl schemars::JsonSchema for Scheme {
+    fn schema_name() -> String {
+        "Scheme".to_string()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{InstanceType, SchemaObject, StringValidation};
+
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(r"^[a-z][a-z0-9+.-]*$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn match_type_defaults() {
+        let path = serde_json::from_value::<HttpPathMatch>(json!({ "value": "/foo" }));
+        assert_eq!(
+            path.unwrap(),
+            HttpPathMatch::PathPrefix {
+                value: "/foo".to_string()
+            }
+        );
+
+        let header = json!({ "name": "x-foo", "value": "bar" });
+        assert_eq!(
+            serde_json::from_value::<HttpHeaderMatch>(header.clone()).unwrap(),
+            HttpHeaderMatch::Exact {
+                name: "x-foo".to_string(),
+                value: "bar".to_string()
+            }
+        );
+        assert_eq!(
+            serde_json::from_value::<HttpQueryParamMatch>(header).unwrap(),
+            HttpQueryParamMatch::Exact {
+                name: "x-foo".to_string(),
+                value: "bar".to_string()
+            }
+        );
+
+        // Path modifiers have no default type.
+        let modifier = json!({ "replaceFullPath": "/foo" });
+        assert!(serde_json::from_value::<HttpPathModifier>(modifier).is_err());
+    }
+
+    #[test]
+    fn known_types_with_invalid_fields_are_rejected() {
+        for path in [
+            json!({ "type": "PathPrefix", "value": 5 }),
+            json!({ "type": "Exact" }),
+            json!({ "type": 7, "value": "/" }),
+        ] {
+            assert!(
+                serde_json::from_value::<HttpPathMatch>(path.clone()).is_err(),
+                "{}",
+                path
+            );
+        }
+        for m in [
+            json!({ "type": "Exact", "name": "x-foo", "value": 5 }),
+            json!({ "type": "Exact" }),
+            json!({ "type": "RegularExpression", "value": "x" }),
+            json!({ "value": "x" }),
+            json!({ "type": ["Exact"], "name": "x-foo", "value": "x" }),
+        ] {
+            assert!(
+                serde_json::from_value::<HttpHeaderMatch>(m.clone()).is_err(),
+                "{}",
+                m
+            );
+            assert!(
+                serde_json::from_value::<HttpQueryParamMatch>(m.clone()).is_err(),
+                "{}",
+                m
+            );
+        }
+        for modifier in [
+            json!({ "type": "ReplaceFullPath" }),
+            json!({ "type": "ReplacePrefixMatch", "replacePrefixMatch": 5 }),
+        ] {
+            assert!(
+                serde_json::from_value::<HttpPathModifier>(modifier.clone()).is_err(),
+                "{}",
+                modifier
+            );
+        }
+    }
+
+    fn round_trip<T>(value: serde_json::Value) -> T
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize,
+    {
+        let decoded = serde_json::from_value::<T>(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), value);
+        decoded
+    }
+
+    #[test]
+    fn unknown_types_round_trip() {
+        let path = round_trip::<HttpPathMatch>(json!({ "type": "Glob", "value": "/api/*" }));
+        assert_eq!(path.type_name(), "Glob");
+        assert_eq!(path.value(), None);
+
+        let header = round_trip::<HttpHeaderMatch>(json!({ "type": "Prefix", "name": "x-foo" }));
+        assert_eq!(header.type_name(), "Prefix");
+        assert_eq!(header.name(), None);
+
+        let param = round_trip::<HttpQueryParamMatch>(json!({ "type": "Present", "name": "q" }));
+        assert_eq!(param.type_name(), "Present");
+
+        let modifier =
+            round_trip::<HttpPathModifier>(json!({ "type": "ReplaceRegex", "pattern": "^/a" }));
+        assert_eq!(modifier.type_name(), "ReplaceRegex");
+        assert_eq!(modifier.value(), None);
+
+        // Known types round-trip through the derived implementations.
+        let path = round_trip::<HttpPathMatch>(json!({ "type": "Exact", "value": "/" }));
+        assert_eq!(path.type_name(), "Exact");
+    }
+
+    #[cfg(feature = "unknown-filters")]
+    #[test]
+    fn unknown_filters() {
+        let filter = round_trip::<HttpRouteFilter>(json!({
+            "type": "CORS",
+            "cors": { "allowOrigins": ["*"] },
+        }));
+        assert_eq!(filter.type_name(), "CORS");
+
+        let filter = round_trip::<HttpRouteFilter>(json!({
+            "type": "ExtensionRef",
+            "extensionRef": { "group": "example.com", "kind": "Filter", "name": "f" },
+        }));
+        assert_eq!(filter.type_name(), "ExtensionRef");
+
+        // Filters of known types must have the fields of their type.
+        for filter in [
+            json!({ "type": "RequestHeaderModifier" }),
+            json!({ "type": "RequestMirror", "requestRedirect": { "scheme": "https" } }),
+            json!({ "type": 5 }),
+            json!({ "requestMirror": { "backendRef": { "name": "web", "port": 80 } } }),
+        ] {
+            assert!(
+                serde_json::from_value::<HttpRouteFilter>(filter.clone()).is_err(),
+                "{}",
+                filter
+            );
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Analysis of the impact of deleting an object on a Gateway.
+//!
+//! [`of_deletion`] compares how a Gateway routes traffic with and without an
+//! object, so that admission webhooks can warn about deletions that would
+//! break routing and CLIs can preview them:
+//!
+//! ```ignore
+//! let impact = impact::of_deletion(&compiler, &impact::Object::Service(key), &snapshot);
+//! for backend in &impact.unreachable_backends {
+//!     eprintln!("warning: {} becomes unreachable", backend);
+//! }
+//! ```
+//!
+//! The Gateway's routing is compiled by the caller's [`Compiler`], which
+//! should be configured as it is for the dataplane, e.g. with the labels of
+//! namespaces that listeners may select.
+
+use crate::{
+    ir::Compiler,
+    snapshot::{self, ObjectKey, Snapshot},
+    *,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// An object that may be deleted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Object {
+    Gateway(ObjectKey),
+
+    #[cfg(feature = "experimental")]
+    ReferenceGrant(ObjectKey),
+
+    Service(ObjectKey),
+}
+
+/// The effect of deleting an object on a Gateway.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Impact {
+    /// Routes that attach to the Gateway and would no longer reference any
+    /// Gateway.
+    pub orphaned_routes: Vec<ObjectKey>,
+
+    /// Listeners to which routes attach, but to which no route would attach.
+    pub emptied_listeners: Vec<String>,
+
+    /// Backends to which the Gateway forwards requests, but to which it would
+    /// not.
+    pub unreachable_backends: Vec<UnreachableBackend>,
+}
+
+/// A backend of a route rule that would become unreachable.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct UnreachableBackend {
+    /// The route that references the backend.
+    pub route: ObjectKey,
+
+    /// The index of the rule within the route.
+    pub rule_index: usize,
+
+    /// The name of the backend's cluster, as in the [`ir`].
+    pub cluster: String,
+}
+
+/// Returns the effect of deleting `object` on the Gateway described by
+/// `snapshot`, as compiled by `compiler`.
+///
+/// Deleting a Gateway other than the snapshot's, or an object that the
+/// Gateway does not depend on, has no effect.
+pub fn of_deletion(compiler: &Compiler, object: &Object, snapshot: &Snapshot) -> Impact {
+    let before = State::new(compiler, snapshot);
+    let after = match object {
+        Object::Gateway(key) if key == snapshot.key() => {
+            let mut impact = Impact {
+                orphaned_routes: before.attached_routes().into_iter().collect(),
+                emptied_listeners: before.listeners_with_routes(),
+                unreachable_backends: before.backends.iter().cloned().collect(),
+            };
+            // Routes that reference other Gateways keep a parent.
+            impact.orphaned_routes.retain(|key| {
+                snapshot.http_route(key).map_or(false, |route| {
+                    snapshot::parent_gateways(&key.namespace, &route.spec.inner)
+                        .iter()
+                        .all(|gw| gw == snapshot.key())
+                })
+            });
+            return impact;
+        }
+        Object::Gateway(_) => return Impact::default(),
+
+        #[cfg(feature = "experimental")]
+        Object::ReferenceGrant(key) => State::new(compiler, &snapshot.without_reference_grant(key)),
+
+        Object::Service(key) => {
+            let mut after = before.clone();
+            after
+                .backends
+                .retain(|b| !before.is_service(&b.cluster, key));
+            after
+        }
+    };
+
+    let before_listeners = before.listeners_with_routes();
+    let after_listeners = after.listeners_with_routes();
+    let after_routes = after.attached_routes();
+    Impact {
+        orphaned_routes: before
+            .attached_routes()
+            .into_iter()
+            .filter(|r| !after_routes.contains(r))
+            .collect(),
+        emptied_listeners: before_listeners
+            .into_iter()
+            .filter(|l| !after_listeners.contains(l))
+            .collect(),
+        unreachable_backends: before
+            .backends
+            .difference(&after.backends)
+            .cloned()
+            .collect(),
+    }
+}
+
+/// The routes attached to each listener of a Gateway and the backends to
+/// which it forwards requests.
+#[derive(Clone, Debug)]
+struct State {
+    listeners: Vec<(String, BTreeSet<ObjectKey>)>,
+    backends: BTreeSet<UnreachableBackend>,
+    clusters: BTreeMap<String, ir::Cluster>,
+}
+
+// === impl State ===
+
+impl State {
+    fn new(compiler: &Compiler, snapshot: &Snapshot) -> Self {
+        let gateway = snapshot.gateway();
+        let listeners = gateway
+            .spec
+            .listeners
+            .iter()
+            .map(|listener| {
+                let routes = snapshot
+                    .http_routes()
+                    .iter()
+                    .map(|route| (ObjectKey::from_meta(&route.metadata), route))
+                    .filter(|(key, route)| {
+                        (listener.protocol == "HTTP" || listener.protocol == "HTTPS")
+                            && compiler
+                                .attachment(snapshot.key(), listener, key, route)
+                                .is_ok()
+                    })
+                    .map(|(key, _)| key)
+                    .collect();
+                (listener.name.clone(), routes)
+            })
+            .collect();
+
+        let table = compiler.compile(snapshot);
+        let backends = table
+            .virtual_hosts
+            .values()
+            .flat_map(|vhost| &vhost.routes)
+            .flat_map(|route| {
+                route.backends.iter().filter_map(move |b| {
+                    Some(UnreachableBackend {
+                        route: route.source.clone(),
+                        rule_index: route.rule_index,
+                        cluster: b.cluster.clone()?,
+                    })
+                })
+            })
+            .collect();
+
+        Self {
+            listeners,
+            backends,
+            clusters: table.clusters,
+        }
+    }
+
+    /// Returns true if a cluster is the Service with the given key. Cluster
+    /// names are derived from backend references, so clusters are looked up
+    /// rather than parsed.
+    fn is_service(&self, cluster: &str, key: &ObjectKey) -> bool {
+        self.clusters.get(cluster).map_or(false, |c| {
+            c.group.is_empty()
+                && c.kind == "Service"
+                && c.namespace == *key.namespace
+                && c.backend == *key.name
+        })
+    }
+
+    fn attached_routes(&self) -> BTreeSet<ObjectKey> {
+        self.listeners
+            .iter()
+            .flat_map(|(_, routes)| routes.iter().cloned())
+            .collect()
+    }
+
+    fn listeners_with_routes(&self) -> Vec<String> {
+        self.listeners
+            .iter()
+            .filter(|(_, routes)| !routes.is_empty())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+// === impl Impact ===
+
+impl Impact {
+    /// Returns true if the deletion has no effect on the Gateway.
+    pub fn is_empty(&self) -> bool {
+        self.orphaned_routes.is_empty()
+            && self.emptied_listeners.is_empty()
+            && self.unreachable_backends.is_empty()
+    }
+}
+
+// === impl UnreachableBackend ===
+
+impl fmt::Display for UnreachableBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "backend {} of rule {} of HTTPRoute {}",
+            self.cluster, self.rule_index, self.route
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{Event, SnapshotStore};
+    use serde_json::json;
+
+    #[test]
+    fn attaches_routes_with_the_compiler() {
+        let gateway = serde_json::from_value::<Gateway>(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "web", "namespace": "infra" },
+            "spec": {
+                "gatewayClassName": "acme",
+                "listeners": [{
+                    "name": "http",
+                    "port": 80,
+                    "protocol": "HTTP",
+                    "allowedRoutes": {
+                        "namespaces": {
+                            "from": "Selector",
+                            "selector": { "matchLabels": { "gateway": "web" } },
+                        },
+                    },
+                }],
+            },
+        }))
+        .unwrap();
+        let route = serde_json::from_value::<HttpRoute>(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": "app", "namespace": "apps" },
+            "spec": {
+                "parentRefs": [{ "name": "web", "namespace": "infra" }],
+                "rules": [{ "backendRefs": [{ "name": "app", "port": 8080 }] }],
+            },
+        }))
+        .unwrap();
+        let mut store = SnapshotStore::default();
+        store.apply(Event::Restarted(vec![gateway]));
+        store.apply(Event::Restarted(vec![route]));
+        let gw_key = ObjectKey::new("infra", "web");
+        let snapshot = store.get(&gw_key).unwrap();
+        let deletion = Object::Gateway(gw_key.clone());
+
+        assert!(of_deletion(&Compiler::default(), &deletion, snapshot).is_empty());
+
+        let labels = BTreeMap::from([("gateway".to_string(), "web".to_string())]);
+        let compiler = Compiler::default().with_namespace_labels("apps", labels);
+        let impact = of_deletion(&compiler, &deletion, snapshot);
+        assert_eq!(impact.orphaned_routes, [ObjectKey::new("apps", "app")]);
+        assert_eq!(impact.emptied_listeners, ["http"]);
+        assert_eq!(impact.unreachable_backends.len(), 1);
+    }
+}
@@ -0,0 +1,87 @@
+//! Sharing of the strings that identify resources.
+//!
+//! Controllers that process watch streams of tens of thousands of objects
+//! allocate the same namespaces and names over and over: every event carries
+//! fresh copies, from which fresh [`ObjectKey`]s are built. An [`Interner`]
+//! keeps a single copy of each string, so that the keys of a resource share
+//! it across events, and the keys of all resources in a namespace share the
+//! namespace:
+//!
+//! ```ignore
+//! let interner = Interner::default();
+//! let mut store = SnapshotStore::default().with_interner(interner.clone());
+//! for event in events {
+//!     store.apply(event);
+//! }
+//! // Periodically, or whenever a watch restarts:
+//! interner.purge();
+//! ```
+//!
+//! Interned strings are retained until they are purged, so interning is
+//! opt-in: short-lived stores gain little from it.
+
+use crate::snapshot::ObjectKey;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+/// A pool of shared strings.
+///
+/// Clones share the same pool, so one interner may be used by every store of
+/// a controller, across threads.
+#[derive(Clone, Debug, Default)]
+pub struct Interner(Arc<Mutex<HashSet<Arc<str>>>>);
+
+// === impl Interner ===
+
+impl Interner {
+    /// Returns the pool's copy of a string, adding it if necessary.
+    pub fn intern(&self, s: &str) -> Arc<str> {
+        let mut strings = self.lock();
+        if let Some(s) = strings.get(s) {
+            return s.clone();
+        }
+        let s = Arc::<str>::from(s);
+        strings.insert(s.clone());
+        s
+    }
+
+    /// Returns the key of the named object in a namespace, with interned
+    /// strings.
+    pub fn key(&self, namespace: &str, name: &str) -> ObjectKey {
+        ObjectKey::new(self.intern(namespace), self.intern(name))
+    }
+
+    /// Returns the key of an object from its metadata, with interned strings.
+    pub fn key_from_meta(&self, meta: &metav1::ObjectMeta) -> ObjectKey {
+        self.key(
+            meta.namespace.as_deref().unwrap_or_default(),
+            meta.name.as_deref().unwrap_or_default(),
+        )
+    }
+
+    /// Returns the number of strings in the pool.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if the pool holds no strings.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Drops the strings that are no longer used outside of the pool,
+    /// returning the number of strings dropped.
+    pub fn purge(&self) -> usize {
+        let mut strings = self.lock();
+        let before = strings.len();
+        strings.retain(|s| Arc::strong_count(s) > 1);
+        before - strings.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<Arc<str>>> {
+        self.0.lock().expect("interner must not be poisoned")
+    }
+}
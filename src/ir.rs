@@ -0,0 +1,884 @@
+//! A compiled, proxy-agnostic routing table.
+//!
+//! [`compile`] flattens the [`Snapshot`] of a Gateway into a [`RouteTable`]:
+//! a set of virtual hosts, each holding the HTTP routes that apply to it in
+//! precedence order, and the set of clusters (backends) that those routes
+//! forward to. Route attachment (parent references, allowed routes, and
+//! hostname intersection) and cross-namespace reference checks are resolved
+//! during compilation, so dataplanes consuming the table need not know about
+//! the Gateway API.
+//!
+//! [`diff`] compares two tables so that xDS-style incremental updates can be
+//! sent instead of full configuration pushes, and [`Compiler::recompile`]
+//! updates a table after a single object changes without compiling the
+//! routes that it does not affect.
+//! [`RouteTable::for_listener`] extracts the part of a table that a single
+//! listener serves.
+//!
+//! A [`RouteTableRenderer`] turns a table into the configuration of a proxy;
+//! [`Nginx`] is a reference implementation. Clusters carry the `appProtocol`
+//! of the Service ports they forward to, when the [`Compiler`] knows the
+//! Services, so that renderers can enable the [`Upgrade`]s each route needs;
+//! [`check_upgrades`] reports the combinations that a dataplane cannot serve.
+//! Clusters also record whether their backends are ready, as reported by the
+//! compiler's [`BackendHealthSource`].
+//!
+//! [`Upgrade`]: crate::backend::Upgrade
+
+use crate::{
+    consts::GROUP,
+    health::{AssumeReady, BackendHealthSource},
+    snapshot::{ObjectKey, Snapshot},
+    *,
+};
+use k8s_openapi::{api::core::v1::Service, apimachinery::pkg::apis::meta::v1 as metav1};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashSet},
+};
+
+mod delta;
+mod incremental;
+mod render;
+mod upgrade;
+
+pub use self::{
+    delta::{diff, Changes, Delta, RouteKey},
+    incremental::Changed,
+    render::{Nginx, RouteTableRenderer},
+    upgrade::{check_upgrades, UpgradeError},
+};
+
+/// The compiled routing configuration of a Gateway.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RouteTable {
+    /// Virtual hosts, by name.
+    pub virtual_hosts: BTreeMap<String, VirtualHost>,
+
+    /// Clusters referenced by the virtual hosts' routes, by name.
+    pub clusters: BTreeMap<String, Cluster>,
+
+    /// The order in which the filters of routes and backends are listed,
+    /// which is the order in which dataplanes must apply them.
+    pub filter_order: filter::FilterOrder,
+}
+
+/// The routes that apply to requests for a hostname on a port.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VirtualHost {
+    /// Identifies the virtual host, e.g. `80/foo.example.com` or `80/*`.
+    pub name: String,
+
+    /// The port on which the Gateway receives requests.
+    pub port: PortNumber,
+
+    /// The hostname (which may be a wildcard) that requests must match, or
+    /// `None` if requests for any hostname match.
+    pub hostname: Option<Hostname>,
+
+    /// Routes in precedence order. The first route that matches a request
+    /// handles it.
+    ///
+    /// Routes are not indexed by their matches, so dataplanes that evaluate
+    /// them in order scan every route that precedes the one that matches.
+    pub routes: Vec<Route>,
+}
+
+/// A single match of an HTTPRoute rule, with the actions of the rule.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Route {
+    /// Identifies the route within its virtual host, e.g.
+    /// `default/web/rule/0/match/1`.
+    pub name: String,
+
+    /// The HTTPRoute that defines the route.
+    pub source: ObjectKey,
+
+    /// The index of the rule within the HTTPRoute.
+    pub rule_index: usize,
+
+    /// The name of the rule, if it has one and the compiler honors the
+    /// `HTTPRouteNamedRules` feature.
+    pub rule_name: Option<SectionName>,
+
+    /// The conditions a request must satisfy.
+    pub matcher: HttpRouteMatch,
+
+    /// Filters applied to all requests handled by the route, in the order in
+    /// which they are applied.
+    pub filters: Vec<HttpRouteFilter>,
+
+    /// Backends to which requests are forwarded, in proportion to their
+    /// weights.
+    pub backends: Vec<Backend>,
+}
+
+/// A weighted backend of a route.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Backend {
+    /// The name of the cluster, or `None` if the reference is invalid (e.g.
+    /// it is not permitted by a ReferenceGrant, or its filters conflict with
+    /// the rule's), in which case requests that would have been forwarded to
+    /// it must receive a 500 response.
+    pub cluster: Option<String>,
+
+    /// The proportion of requests forwarded to this backend.
+    pub weight: u32,
+
+    /// Filters applied to requests forwarded to this backend, i.e. the
+    /// rule's filters and the backend's own filters, in the order in which
+    /// they are applied. Empty if the backend is invalid.
+    pub filters: Vec<HttpRouteFilter>,
+}
+
+/// A backend object that routes forward requests to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cluster {
+    /// Identifies the cluster, e.g. `default/web:8080`.
+    pub name: String,
+
+    /// The API group of the backend; empty for core Services.
+    pub group: String,
+
+    /// The kind of the backend, e.g. `Service`.
+    pub kind: String,
+
+    /// The namespace of the backend.
+    pub namespace: String,
+
+    /// The name of the backend.
+    pub backend: String,
+
+    /// The port of the backend, if one was specified.
+    pub port: Option<PortNumber>,
+
+    /// The `appProtocol` of the Service port, if the backend is a Service
+    /// known to the compiler and its port specifies one.
+    pub app_protocol: Option<String>,
+
+    /// Whether the backend has endpoints that can serve requests, as reported
+    /// by the compiler's [`BackendHealthSource`]. Requests forwarded to a
+    /// cluster that is not ready must receive a 503 response.
+    pub ready: bool,
+}
+
+/// Compiles the routing table of a Gateway.
+///
+/// Namespace selectors in listeners' `allowedRoutes` cannot be evaluated from
+/// a snapshot, as it does not include Namespaces; use a [`Compiler`] with
+/// namespace labels to admit routes from selected namespaces.
+pub fn compile(snapshot: &Snapshot) -> RouteTable {
+    Compiler::default().compile(snapshot)
+}
+
+/// The reason a route does not attach to a listener.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Detached {
+    /// None of the route's parent references select the listener.
+    NotReferenced,
+
+    /// The listener's `allowedRoutes` does not include HTTPRoutes.
+    KindNotAllowed,
+
+    /// The listener's `allowedRoutes` does not include the route's namespace.
+    NamespaceNotAllowed,
+}
+
+/// Compiles routing tables.
+///
+/// By default, every cluster is compiled as ready; a [`BackendHealthSource`]
+/// set with [`Compiler::with_health`] reports which are not.
+#[derive(Clone, Debug)]
+pub struct Compiler<H = AssumeReady> {
+    namespace_labels: BTreeMap<String, BTreeMap<String, String>>,
+    app_protocols: BTreeMap<ObjectKey, BTreeMap<PortNumber, Option<String>>>,
+    filter_order: filter::FilterOrder,
+    feature_gates: Option<feature_gate::FeatureGates>,
+    health: H,
+}
+
+// === impl RouteTable ===
+
+impl RouteTable {
+    /// Returns the upgrades that the dataplane must enable on a route for the
+    /// requests it forwards to its clusters.
+    pub fn upgrades(&self, route: &Route) -> BTreeSet<backend::Upgrade> {
+        route
+            .backends
+            .iter()
+            .filter_map(|b| self.clusters.get(b.cluster.as_deref()?)?.upgrade())
+            .collect()
+    }
+
+    /// Returns the virtual host that handles requests for `host` on `port`:
+    /// the one with the most specific hostname that matches it.
+    ///
+    /// Dataplanes must select virtual hosts this way so that listeners with
+    /// overlapping hostnames remain isolated; see [`listener`].
+    pub fn virtual_host(&self, port: PortNumber, host: &str) -> Option<&VirtualHost> {
+        // Virtual hosts are named by their port and hostname, so the most
+        // specific one is found by looking up the exact hostname, then
+        // wildcards from the longest suffix to the shortest, then `*`.
+        let host = host.to_ascii_lowercase();
+        let wildcards = host
+            .match_indices('.')
+            .map(|(i, _)| format!("*{}", &host[i..]));
+        let candidates = std::iter::once(host.clone())
+            .chain(wildcards)
+            .chain(std::iter::once("*".to_string()));
+        for hostname in candidates {
+            let vhost = self.virtual_hosts.get(&format!("{}/{}", port, hostname));
+            if let Some(vhost) = vhost {
+                if vhost.port == port
+                    && listener::hostname_matches(vhost.hostname.as_deref(), &host)
+                {
+                    return Some(vhost);
+                }
+            }
+        }
+
+        // Hostnames that are not lowercase are rejected by the CRDs, but may
+        // still be compiled from objects that were not validated.
+        let mut selected: Option<&VirtualHost> = None;
+        for vhost in self.virtual_hosts.values() {
+            let hostname = vhost.hostname.as_deref();
+            if vhost.port != port || !listener::hostname_matches(hostname, &host) {
+                continue;
+            }
+            let more_specific = selected.map_or(true, |s| {
+                listener::specificity(hostname) > listener::specificity(s.hostname.as_deref())
+            });
+            if more_specific {
+                selected = Some(vhost);
+            }
+        }
+        selected
+    }
+
+    /// Updates the readiness of the table's clusters from `health`, e.g. when
+    /// the endpoints of a backend change, returning the names of the clusters
+    /// whose readiness changed.
+    pub fn update_health(&mut self, health: &impl BackendHealthSource) -> Vec<String> {
+        let mut changed = Vec::new();
+        for cluster in self.clusters.values_mut() {
+            let ready = health.is_ready(cluster);
+            if cluster.ready != ready {
+                cluster.ready = ready;
+                changed.push(cluster.name.clone());
+            }
+        }
+        changed
+    }
+
+    /// Returns the part of the table that serves a single listener of the
+    /// Gateway the table was compiled from, or `None` if the Gateway has no
+    /// listener with that name.
+    ///
+    /// The table holds the virtual hosts on the listener's port whose
+    /// hostnames the listener handles, i.e. that match its hostname and are
+    /// not isolated to a more specific listener, with their routes in
+    /// precedence order, and only the clusters that those routes reference.
+    /// Dataplanes that serve each listener separately (e.g. with a proxy
+    /// listener per Gateway listener) can be configured with only the routes
+    /// that each one serves.
+    pub fn for_listener(&self, gateway: &Gateway, name: &str) -> Option<RouteTable> {
+        let listeners = &gateway.spec.listeners;
+        let listener = listeners.iter().find(|l| l.name == name)?;
+        let mut table = RouteTable {
+            filter_order: self.filter_order,
+            ..RouteTable::default()
+        };
+        if listener.protocol != "HTTP" && listener.protocol != "HTTPS" {
+            return Some(table);
+        }
+
+        for (name, vhost) in &self.virtual_hosts {
+            let handles = vhost.port == listener.port
+                && match vhost.hostname.as_deref() {
+                    // Only listeners without hostnames accept requests for
+                    // any hostname.
+                    None => listener.hostname.is_none(),
+                    Some(h) => {
+                        listener::hostname_matches(listener.hostname.as_deref(), h)
+                            && !listener::is_isolated(listeners, listener, h)
+                    }
+                };
+            if handles {
+                table.virtual_hosts.insert(name.clone(), vhost.clone());
+            }
+        }
+
+        let clusters = table
+            .virtual_hosts
+            .values()
+            .flat_map(|vh| &vh.routes)
+            .flat_map(|r| &r.backends)
+            .filter_map(|b| b.cluster.as_deref())
+            .collect::<BTreeSet<_>>();
+        table.clusters = self
+            .clusters
+            .iter()
+            .filter(|(name, _)| clusters.contains(name.as_str()))
+            .map(|(name, cluster)| (name.clone(), cluster.clone()))
+            .collect();
+        Some(table)
+    }
+}
+
+// === impl Cluster ===
+
+impl Cluster {
+    /// Returns the protocol with which requests are forwarded to the cluster,
+    /// or `None` if its `appProtocol` is not one of those defined by
+    /// GEP-1911.
+    pub fn upstream_protocol(&self) -> Option<backend::UpstreamProtocol> {
+        backend::UpstreamProtocol::from_app_protocol(self.app_protocol.as_deref())
+    }
+
+    /// Returns the upgrade that the dataplane must enable to forward requests
+    /// to the cluster, if any.
+    pub fn upgrade(&self) -> Option<backend::Upgrade> {
+        self.upstream_protocol()?.upgrade()
+    }
+}
+
+// === impl Compiler ===
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self {
+            namespace_labels: BTreeMap::new(),
+            app_protocols: BTreeMap::new(),
+            filter_order: filter::FilterOrder::default(),
+            feature_gates: None,
+            health: AssumeReady,
+        }
+    }
+}
+
+impl<H: BackendHealthSource> Compiler<H> {
+    /// Sets the labels of a namespace, so that namespace selectors in
+    /// listeners' `allowedRoutes` may be evaluated against it.
+    pub fn with_namespace_labels(
+        mut self,
+        namespace: impl Into<String>,
+        labels: BTreeMap<String, String>,
+    ) -> Self {
+        self.namespace_labels.insert(namespace.into(), labels);
+        self
+    }
+
+    /// Returns the labels of the namespaces set with
+    /// [`Compiler::with_namespace_labels`].
+    pub(crate) fn namespace_labels(&self) -> &BTreeMap<String, BTreeMap<String, String>> {
+        &self.namespace_labels
+    }
+
+    /// Adds a Service that routes may forward to, so that the clusters of its
+    /// ports are compiled with their `appProtocol`s.
+    pub fn with_service(mut self, service: &Service) -> Self {
+        let ports = service
+            .spec
+            .iter()
+            .flat_map(|s| s.ports.iter().flatten())
+            .filter_map(|p| {
+                let port = PortNumber::try_from(p.port).ok()?;
+                Some((port, p.app_protocol.clone()))
+            })
+            .collect();
+        self.app_protocols
+            .insert(ObjectKey::from_meta(&service.metadata), ports);
+        self
+    }
+
+    /// Sets the order in which filters are listed in compiled tables. By
+    /// default, filters are listed in the order in which they are specified.
+    pub fn with_filter_order(mut self, order: filter::FilterOrder) -> Self {
+        self.filter_order = order;
+        self
+    }
+
+    /// Ignores fields that require features that are not enabled by `gates`.
+    /// By default, all fields are honored.
+    pub fn with_feature_gates(mut self, gates: feature_gate::FeatureGates) -> Self {
+        self.feature_gates = Some(gates);
+        self
+    }
+
+    /// Sets the source of the readiness of the clusters in compiled tables.
+    pub fn with_health<G: BackendHealthSource>(self, health: G) -> Compiler<G> {
+        Compiler {
+            namespace_labels: self.namespace_labels,
+            app_protocols: self.app_protocols,
+            filter_order: self.filter_order,
+            feature_gates: self.feature_gates,
+            health,
+        }
+    }
+
+    /// Compiles the routing table of a Gateway.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                gateway.namespace = %snapshot.key().namespace,
+                gateway.name = %snapshot.key().name,
+            ),
+        )
+    )]
+    pub fn compile(&self, snapshot: &Snapshot) -> RouteTable {
+        let gateway = snapshot.gateway();
+        let gw_key = snapshot.key();
+        let mut table = RouteTable {
+            filter_order: self.filter_order,
+            ..RouteTable::default()
+        };
+        // Older routes take precedence, then routes are ordered by name. Keys
+        // are computed once, rather than for every comparison and listener.
+        let mut routes = snapshot
+            .http_routes()
+            .iter()
+            .map(|r| {
+                let created = r.metadata.creation_timestamp.as_ref();
+                (created, ObjectKey::from_meta(&r.metadata), &**r)
+            })
+            .collect::<Vec<_>>();
+        routes.sort_by(|(a_created, a_key, _), (b_created, b_key, _)| {
+            (a_created, a_key).cmp(&(b_created, b_key))
+        });
+
+        for (_, route_key, route) in &routes {
+            let vhosts = self.attach(&mut table, gateway, gw_key, route_key, route);
+            // Each route's rules are compiled once, regardless of the number
+            // of virtual hosts it attaches to.
+            let (last, rest) = match vhosts.split_last() {
+                Some(split) => split,
+                None => continue,
+            };
+            let compiled = compile_route(self, snapshot, route_key, route, &mut table.clusters);
+            for name in rest {
+                if let Some(vhost) = table.virtual_hosts.get_mut(name) {
+                    vhost.routes.extend(compiled.iter().cloned());
+                }
+            }
+            if let Some(vhost) = table.virtual_hosts.get_mut(last) {
+                vhost.routes.extend(compiled);
+            }
+        }
+
+        for vhost in table.virtual_hosts.values_mut() {
+            // The sort is stable, so ties are broken by route age and name,
+            // then by rule and match order.
+            vhost.routes.sort_by_key(|r| precedence(&r.matcher));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            virtual_hosts = table.virtual_hosts.len(),
+            clusters = table.clusters.len(),
+            "Compiled route table"
+        );
+        table
+    }
+
+    /// Returns the names of the virtual hosts that a route attaches to, adding
+    /// those that are not yet in the table.
+    ///
+    /// Listeners may share a port and hostname, in which case a route that
+    /// attaches to several of them is only attached to the virtual host once.
+    fn attach(
+        &self,
+        table: &mut RouteTable,
+        gateway: &Gateway,
+        gw_key: &ObjectKey,
+        route_key: &ObjectKey,
+        route: &HttpRoute,
+    ) -> Vec<String> {
+        // Routes without rules handle no requests, so they do not add virtual
+        // hosts.
+        let mut vhosts = Vec::new();
+        if route.spec.rules.as_deref().unwrap_or_default().is_empty() {
+            return vhosts;
+        }
+
+        for listener in &gateway.spec.listeners {
+            if listener.protocol != "HTTP" && listener.protocol != "HTTPS" {
+                continue;
+            }
+            if !self.is_attached(gw_key, listener, route_key, route) {
+                continue;
+            }
+
+            for hostname in intersect_hostnames(
+                listener.hostname.as_deref(),
+                route.spec.hostnames.as_deref().unwrap_or_default(),
+            ) {
+                // Requests for hostnames that belong to a more specific
+                // listener are never handled by this one.
+                if let Some(h) = hostname {
+                    if listener::is_isolated(&gateway.spec.listeners, listener, h) {
+                        continue;
+                    }
+                }
+                let name = format!("{}/{}", listener.port, hostname.unwrap_or("*"));
+                if vhosts.contains(&name) {
+                    continue;
+                }
+                if !table.virtual_hosts.contains_key(&name) {
+                    let vhost = VirtualHost {
+                        name: name.clone(),
+                        port: listener.port,
+                        hostname: hostname.map(Into::into),
+                        routes: Vec::new(),
+                    };
+                    table.virtual_hosts.insert(name.clone(), vhost);
+                }
+                vhosts.push(name);
+            }
+        }
+        vhosts
+    }
+
+    /// Returns true if the route references the listener and the listener
+    /// allows the route.
+    fn is_attached(
+        &self,
+        gw_key: &ObjectKey,
+        listener: &Listener,
+        route_key: &ObjectKey,
+        route: &HttpRoute,
+    ) -> bool {
+        let attachment = self.attachment(gw_key, listener, route_key, route);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            gateway.namespace = %gw_key.namespace,
+            gateway.name = %gw_key.name,
+            listener = %listener.name,
+            route.namespace = %route_key.namespace,
+            route.name = %route_key.name,
+            attached = attachment.is_ok(),
+            reason = ?attachment.err(),
+            "Checked route attachment"
+        );
+        attachment.is_ok()
+    }
+
+    /// Returns true if fields that require `feature` are honored: if no
+    /// feature gates are set, or they enable it.
+    pub(crate) fn honors(&self, feature: feature_gate::Feature) -> bool {
+        self.feature_gates
+            .as_ref()
+            .map_or(true, |gates| gates.is_enabled(feature))
+    }
+
+    /// Returns a copy of filters without the fields that require disabled
+    /// features.
+    pub(crate) fn prune_filters(
+        &self,
+        filters: Option<&[HttpRouteFilter]>,
+    ) -> Vec<HttpRouteFilter> {
+        let mut filters = filters.unwrap_or_default().to_vec();
+        if let Some(gates) = &self.feature_gates {
+            filters.iter_mut().for_each(|f| gates.prune_filter(f));
+        }
+        filters
+    }
+
+    /// Checks whether a route attaches to a listener, returning the reason it
+    /// does not, if any.
+    pub(crate) fn attachment(
+        &self,
+        gw_key: &ObjectKey,
+        listener: &Listener,
+        route_key: &ObjectKey,
+        route: &HttpRoute,
+    ) -> Result<(), Detached> {
+        let references = route.spec.inner.parent_refs.iter().flatten().any(|p| {
+            p.group.as_deref().unwrap_or(GROUP) == GROUP
+                && p.kind.as_deref().unwrap_or("Gateway") == "Gateway"
+                && p.namespace.as_deref().unwrap_or(&route_key.namespace) == &*gw_key.namespace
+                && p.name == *gw_key.name
+                && p.section_name
+                    .as_deref()
+                    .map_or(true, |s| s == listener.name)
+                && p.port.map_or(true, |port| port == listener.port)
+        });
+        if !references {
+            return Err(Detached::NotReferenced);
+        }
+
+        let allowed = listener.allowed_routes.as_ref();
+        if let Some(kinds) = allowed.and_then(|a| a.kinds.as_deref()) {
+            let allows_kind = kinds.is_empty()
+                || kinds
+                    .iter()
+                    .any(|k| k.group.as_deref().unwrap_or(GROUP) == GROUP && k.kind == "HTTPRoute");
+            if !allows_kind {
+                return Err(Detached::KindNotAllowed);
+            }
+        }
+
+        if !allows_namespace(
+            listener,
+            &gw_key.namespace,
+            &route_key.namespace,
+            &self.namespace_labels,
+        ) {
+            return Err(Detached::NamespaceNotAllowed);
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if a listener of a Gateway in `gw_ns` admits routes from
+/// `route_ns`.
+///
+/// Namespace selectors are evaluated against `namespace_labels`, so a selector
+/// does not admit routes from a namespace whose labels are unknown.
+pub(crate) fn allows_namespace(
+    listener: &Listener,
+    gw_ns: &str,
+    route_ns: &str,
+    namespace_labels: &BTreeMap<String, BTreeMap<String, String>>,
+) -> bool {
+    let namespaces = listener
+        .allowed_routes
+        .as_ref()
+        .and_then(|a| a.namespaces.as_ref());
+    match namespaces.and_then(|n| n.from.as_deref()) {
+        Some("All") => true,
+        Some("Selector") => {
+            let selector = namespaces.and_then(|n| n.selector.as_ref());
+            match (selector, namespace_labels.get(route_ns)) {
+                (Some(selector), Some(labels)) => selector_matches(selector, labels),
+                _ => false,
+            }
+        }
+        _ => route_ns == gw_ns,
+    }
+}
+
+/// Compiles a route's rules, adding the clusters they reference.
+fn compile_route(
+    compiler: &Compiler<impl BackendHealthSource>,
+    snapshot: &Snapshot,
+    route_key: &ObjectKey,
+    route: &HttpRoute,
+    clusters: &mut BTreeMap<String, Cluster>,
+) -> Vec<Route> {
+    let filter_order = compiler.filter_order;
+    let prune = |filters| compiler.prune_filters(filters);
+
+    let mut routes = Vec::new();
+    for (i, rule) in route.spec.rules.iter().flatten().enumerate() {
+        let rule_filters = &*prune(rule.filters.as_deref());
+        let mut ordered_rule_filters = rule_filters.to_vec();
+        filter_order.apply(&mut ordered_rule_filters);
+        let backends = rule
+            .backend_refs
+            .iter()
+            .flatten()
+            .filter_map(|b| {
+                let backend_ref = b.backend_ref.as_ref()?;
+                let weight = u32::from(backend_ref.weight.unwrap_or(1));
+                if weight == 0 {
+                    return None;
+                }
+                let backend_filters = prune(b.filters.as_deref());
+                let filters = match filter_order.effective_filters(rule_filters, &backend_filters) {
+                    Ok(filters) => filters,
+                    Err(_) => {
+                        return Some(Backend {
+                            cluster: None,
+                            weight,
+                            filters: Vec::new(),
+                        })
+                    }
+                };
+                let cluster =
+                    compile_cluster(compiler, snapshot, route_key, &backend_ref.inner, clusters);
+                Some(Backend {
+                    cluster,
+                    weight,
+                    filters,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let rule_name = rule
+            .name
+            .clone()
+            .filter(|_| compiler.honors(feature_gate::Feature::HttpRouteNamedRules));
+        let default_match = [HttpRouteMatch::default()];
+        let matches = match rule.matches.as_deref() {
+            Some(matches) if !matches.is_empty() => matches,
+            _ => &default_match,
+        };
+        // A match that repeats an earlier match of the rule never handles a
+        // request, so it is omitted.
+        let mut keys = HashSet::new();
+        for (j, matcher) in matches.iter().enumerate() {
+            if !keys.insert(matcher.canonical_key()) {
+                continue;
+            }
+            routes.push(Route {
+                name: format!("{}/rule/{}/match/{}", route_key, i, j),
+                source: route_key.clone(),
+                rule_index: i,
+                rule_name: rule_name.clone(),
+                matcher: matcher.clone(),
+                filters: ordered_rule_filters.clone(),
+                backends: backends.clone(),
+            });
+        }
+    }
+    routes
+}
+
+/// Adds the cluster for a backend reference, returning its name, or `None`
+/// if the reference is not permitted.
+fn compile_cluster(
+    compiler: &Compiler<impl BackendHealthSource>,
+    snapshot: &Snapshot,
+    route_key: &ObjectKey,
+    backend: &BackendObjectReference,
+    clusters: &mut BTreeMap<String, Cluster>,
+) -> Option<String> {
+    let group = backend.group.as_deref().unwrap_or("");
+    let kind = backend.kind.as_deref().unwrap_or("Service");
+    let namespace = backend.namespace.as_deref().unwrap_or(&route_key.namespace);
+    if namespace != &*route_key.namespace && !is_permitted(snapshot, route_key, backend) {
+        return None;
+    }
+
+    let mut name = if group.is_empty() && kind == "Service" {
+        format!("{}/{}", namespace, backend.name)
+    } else {
+        format!("{}/{}/{}/{}", group, kind, namespace, backend.name)
+    };
+    if let Some(port) = backend.port {
+        name = format!("{}:{}", name, port);
+    }
+
+    if !clusters.contains_key(&name) {
+        // An omitted port refers to a Service's only port.
+        let app_protocol = match compiler
+            .app_protocols
+            .get(&ObjectKey::new(namespace, &*backend.name))
+        {
+            Some(ports) if backend::is_service(backend) => match backend.port {
+                Some(port) => ports.get(&port).cloned().flatten(),
+                None if ports.len() == 1 => ports.values().next().cloned().flatten(),
+                None => None,
+            },
+            _ => None,
+        };
+        let mut cluster = Cluster {
+            name: name.clone(),
+            group: group.to_string(),
+            kind: kind.to_string(),
+            namespace: namespace.to_string(),
+            backend: backend.name.clone(),
+            port: backend.port,
+            app_protocol,
+            ready: true,
+        };
+        cluster.ready = compiler.health.is_ready(&cluster);
+        clusters.insert(name.clone(), cluster);
+    }
+    Some(name)
+}
+
+#[cfg(feature = "experimental")]
+pub(crate) fn is_permitted(
+    snapshot: &Snapshot,
+    route_key: &ObjectKey,
+    backend: &BackendObjectReference,
+) -> bool {
+    let reference = CrossNamespaceReference {
+        from_group: GROUP,
+        from_kind: "HTTPRoute",
+        from_namespace: &route_key.namespace,
+        to_group: backend.group.as_deref().unwrap_or(""),
+        to_kind: backend.kind.as_deref().unwrap_or("Service"),
+        to_namespace: backend.namespace.as_deref().unwrap_or(&route_key.namespace),
+        to_name: &backend.name,
+    };
+    snapshot
+        .reference_grants()
+        .iter()
+        .any(|grant| grant.permits(&reference))
+}
+
+/// Cross-namespace references require a ReferenceGrant, which is only
+/// modeled when the `experimental` feature is enabled.
+#[cfg(not(feature = "experimental"))]
+pub(crate) fn is_permitted(_: &Snapshot, _: &ObjectKey, _: &BackendObjectReference) -> bool {
+    false
+}
+
+/// Orders matches from the most to the least specific, as defined by the
+/// HTTPRoute rule precedence rules.
+fn precedence(m: &HttpRouteMatch) -> impl Ord {
+    let path = match &m.path {
+        Some(HttpPathMatch::Exact { value }) => (2, value.len()),
+        Some(HttpPathMatch::PathPrefix { value }) => (1, value.len()),
+        None => (1, 1),
+        Some(HttpPathMatch::RegularExpression { value }) => (0, value.len()),
+        Some(HttpPathMatch::Unknown(_)) => (0, 0),
+    };
+    Reverse((
+        path,
+        m.method.is_some(),
+        m.headers.as_ref().map_or(0, Vec::len),
+        m.query_params.as_ref().map_or(0, Vec::len),
+    ))
+}
+
+/// Returns the hostnames of the virtual hosts that a route attaches to on a
+/// listener. `None` indicates that any hostname matches.
+pub(crate) fn intersect_hostnames<'a>(
+    listener: Option<&'a str>,
+    route: &'a [Hostname],
+) -> Vec<Option<&'a str>> {
+    match listener {
+        None if route.is_empty() => vec![None],
+        None => route.iter().map(|h| Some(h.as_str())).collect(),
+        Some(l) if route.is_empty() => vec![Some(l)],
+        Some(l) => route
+            .iter()
+            .filter_map(|r| {
+                if hostname::matches(l, r).is_some() {
+                    Some(Some(r.as_str()))
+                } else if hostname::matches(r, l).is_some() {
+                    Some(Some(l))
+                } else {
+                    None
+                }
+            })
+            .collect(),
+    }
+}
+
+pub(crate) fn selector_matches(
+    selector: &metav1::LabelSelector,
+    labels: &BTreeMap<String, String>,
+) -> bool {
+    let labels_match = selector
+        .match_labels
+        .iter()
+        .flatten()
+        .all(|(k, v)| labels.get(k) == Some(v));
+    let exprs_match = selector.match_expressions.iter().flatten().all(|e| {
+        let values = e.values.as_deref().unwrap_or_default();
+        match e.operator.as_str() {
+            "In" => labels.get(&e.key).map_or(false, |v| values.contains(v)),
+            "NotIn" => labels.get(&e.key).map_or(true, |v| !values.contains(v)),
+            "Exists" => labels.contains_key(&e.key),
+            "DoesNotExist" => !labels.contains_key(&e.key),
+            _ => false,
+        }
+    });
+    labels_match && exprs_match
+}
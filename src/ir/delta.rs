@@ -0,0 +1,114 @@
+use super::{Route, RouteTable};
+use std::collections::BTreeMap;
+
+/// The differences between two routing tables.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Delta {
+    /// Changes to virtual hosts, by name. A virtual host is modified if any of
+    /// its routes, or their order, changed.
+    pub virtual_hosts: Changes<String>,
+
+    /// Changes to individual routes.
+    pub routes: Changes<RouteKey>,
+
+    /// Changes to clusters, by name.
+    pub clusters: Changes<String>,
+}
+
+/// The keys of items that were added, removed, or modified.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Changes<K> {
+    /// Items that are only in the new table.
+    pub added: Vec<K>,
+
+    /// Items that are only in the old table.
+    pub removed: Vec<K>,
+
+    /// Items that are in both tables, but differ.
+    pub modified: Vec<K>,
+}
+
+/// Identifies a route within a routing table.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RouteKey {
+    /// The name of the virtual host.
+    pub virtual_host: String,
+
+    /// The name of the route within the virtual host.
+    pub route: String,
+}
+
+/// Computes the changes needed to turn `old` into `new`.
+///
+/// Keys are reported in order.
+pub fn diff(old: &RouteTable, new: &RouteTable) -> Delta {
+    Delta {
+        virtual_hosts: Changes::between(&old.virtual_hosts, &new.virtual_hosts),
+        routes: Changes::between(&routes(old), &routes(new)),
+        clusters: Changes::between(&old.clusters, &new.clusters),
+    }
+}
+
+/// Indexes the routes of all virtual hosts in a table.
+fn routes(table: &RouteTable) -> BTreeMap<RouteKey, &Route> {
+    table
+        .virtual_hosts
+        .values()
+        .flat_map(|vh| {
+            vh.routes.iter().map(move |r| {
+                let key = RouteKey {
+                    virtual_host: vh.name.clone(),
+                    route: r.name.clone(),
+                };
+                (key, r)
+            })
+        })
+        .collect()
+}
+
+// === impl Delta ===
+
+impl Delta {
+    /// Returns true if the tables are identical.
+    pub fn is_empty(&self) -> bool {
+        self.virtual_hosts.is_empty() && self.routes.is_empty() && self.clusters.is_empty()
+    }
+}
+
+// === impl Changes ===
+
+impl<K: Clone + Ord> Changes<K> {
+    fn between<V: PartialEq>(old: &BTreeMap<K, V>, new: &BTreeMap<K, V>) -> Self {
+        let mut changes = Self::default();
+        for (k, v) in old {
+            match new.get(k) {
+                None => changes.removed.push(k.clone()),
+                Some(n) if n != v => changes.modified.push(k.clone()),
+                Some(_) => {}
+            }
+        }
+        for k in new.keys() {
+            if !old.contains_key(k) {
+                changes.added.push(k.clone());
+            }
+        }
+        changes
+    }
+}
+
+impl<K> Changes<K> {
+    /// Returns true if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+impl<K> Default for Changes<K> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            modified: Vec::new(),
+        }
+    }
+}
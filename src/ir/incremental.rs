@@ -0,0 +1,387 @@
+use super::{compile_route, precedence, Compiler, RouteTable};
+use crate::{
+    health::BackendHealthSource,
+    snapshot::{ObjectKey, Snapshot},
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::collections::BTreeSet;
+
+/// An object whose change may affect a compiled routing table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Changed {
+    /// The Gateway was updated, which may affect all of its routes.
+    Gateway,
+
+    /// An HTTPRoute was created, updated, or deleted.
+    HttpRoute(ObjectKey),
+
+    /// A ReferenceGrant was created, updated, or deleted. Only routes with
+    /// backends in the grant's namespace are affected.
+    #[cfg(feature = "experimental")]
+    ReferenceGrant(ObjectKey),
+}
+
+// === impl Compiler ===
+
+impl<H: BackendHealthSource> Compiler<H> {
+    /// Updates a table that this compiler compiled from a previous snapshot
+    /// of the same Gateway, after a change to a single object.
+    ///
+    /// The result is the same as that of [`Compiler::compile`], but only the
+    /// routes affected by the change are compiled and only the virtual hosts
+    /// they attach (or attached) to are modified. Changes to the Gateway
+    /// recompile the whole table.
+    pub fn recompile(&self, table: &mut RouteTable, snapshot: &Snapshot, changed: &Changed) {
+        let routes = match changed {
+            Changed::Gateway => None,
+            Changed::HttpRoute(key) => Some(vec![key.clone()]),
+            #[cfg(feature = "experimental")]
+            Changed::ReferenceGrant(key) => Some(cross_namespace_routes(snapshot, &key.namespace)),
+        };
+        match routes {
+            Some(keys) if table.filter_order == self.filter_order => {
+                for key in &keys {
+                    self.recompile_route(table, snapshot, key);
+                }
+            }
+            _ => *table = self.compile(snapshot),
+        }
+    }
+
+    fn recompile_route(&self, table: &mut RouteTable, snapshot: &Snapshot, key: &ObjectKey) {
+        // Remove the previous version of the route, noting the clusters that
+        // it referenced.
+        let mut clusters = BTreeSet::new();
+        let mut emptied = Vec::new();
+        for vhost in table.virtual_hosts.values_mut() {
+            let len = vhost.routes.len();
+            vhost.routes.retain(|r| {
+                if r.source != *key {
+                    return true;
+                }
+                clusters.extend(r.backends.iter().filter_map(|b| b.cluster.clone()));
+                false
+            });
+            if vhost.routes.is_empty() && len > 0 {
+                emptied.push(vhost.name.clone());
+            }
+        }
+        for name in &emptied {
+            table.virtual_hosts.remove(name);
+        }
+
+        if let Some(route) = snapshot.http_route(key) {
+            let vhosts = self.attach(table, snapshot.gateway(), snapshot.key(), key, route);
+            if !vhosts.is_empty() {
+                let compiled = compile_route(self, snapshot, key, route, &mut table.clusters);
+
+                // Routes are inserted where a full compilation would have
+                // placed them: after routes of higher or equal precedence
+                // that come from older routes.
+                let order = route_order(snapshot, key);
+                for name in &vhosts {
+                    let vhost = match table.virtual_hosts.get_mut(name) {
+                        Some(vhost) => vhost,
+                        None => continue,
+                    };
+                    for route in &compiled {
+                        let rank = precedence(&route.matcher);
+                        let index = vhost.routes.partition_point(|r| {
+                            let other = precedence(&r.matcher);
+                            other < rank
+                                || (other == rank && route_order(snapshot, &r.source) <= order)
+                        });
+                        vhost.routes.insert(index, route.clone());
+                    }
+                }
+            }
+        }
+
+        // Clusters are only removed once no route references them.
+        let backends = table
+            .virtual_hosts
+            .values()
+            .flat_map(|vh| &vh.routes)
+            .flat_map(|r| &r.backends);
+        for cluster in &clusters {
+            if !backends
+                .clone()
+                .any(|b| b.cluster.as_ref() == Some(cluster))
+            {
+                table.clusters.remove(cluster);
+            }
+        }
+    }
+}
+
+/// Orders routes as [`Compiler::compile`] does: older routes first, then by
+/// key.
+fn route_order<'s, 'k>(
+    snapshot: &'s Snapshot,
+    key: &'k ObjectKey,
+) -> (Option<&'s metav1::Time>, &'k ObjectKey) {
+    let created = snapshot
+        .http_route(key)
+        .and_then(|r| r.metadata.creation_timestamp.as_ref());
+    (created, key)
+}
+
+/// Returns the keys of the routes with backends in `namespace` that are not
+/// in the route's own namespace, i.e. those whose references may be permitted
+/// by ReferenceGrants in `namespace`.
+#[cfg(feature = "experimental")]
+fn cross_namespace_routes(snapshot: &Snapshot, namespace: &str) -> Vec<ObjectKey> {
+    snapshot
+        .http_routes()
+        .iter()
+        .filter(|route| {
+            let route_ns = route.metadata.namespace.as_deref().unwrap_or_default();
+            route_ns != namespace
+                && route
+                    .spec
+                    .rules
+                    .iter()
+                    .flatten()
+                    .flat_map(|r| r.backend_refs.iter().flatten())
+                    .filter_map(|b| b.backend_ref.as_ref())
+                    .any(|b| b.inner.namespace.as_deref() == Some(namespace))
+        })
+        .map(|route| ObjectKey::from_meta(&route.metadata))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        filter::FilterOrder,
+        scaffold,
+        snapshot::{Event, SnapshotStore},
+        *,
+    };
+    use k8s_openapi::chrono::{TimeZone, Utc};
+
+    const NAMESPACE: &str = "default";
+
+    fn gateway() -> Gateway {
+        let mut gateway = scaffold::gateway("gw", "gc", None);
+        gateway.metadata.namespace = Some(NAMESPACE.to_string());
+        gateway
+    }
+
+    /// Returns a route, created at `created` seconds, that forwards requests
+    /// for `host` to a Service.
+    fn route(name: &str, host: &str, service: &str, created: i64) -> HttpRoute {
+        let mut route = scaffold::http_route(name, host, service, 8080);
+        route.metadata.namespace = Some(NAMESPACE.to_string());
+        let time = Utc.timestamp_opt(created, 0).unwrap();
+        route.metadata.creation_timestamp = Some(metav1::Time(time));
+        scaffold::attach(&mut route, &gateway());
+        route
+    }
+
+    fn with_path(mut route: HttpRoute, prefix: &str) -> HttpRoute {
+        let rule = &mut route.spec.rules.as_mut().unwrap()[0];
+        rule.matches.as_mut().unwrap()[0].path = Some(HttpPathMatch::PathPrefix {
+            value: prefix.to_string(),
+        });
+        route
+    }
+
+    fn key(name: &str) -> ObjectKey {
+        ObjectKey::new(NAMESPACE, name)
+    }
+
+    /// Recompiles `table` after a change and checks that the result is that
+    /// of a full compilation.
+    fn recompile(
+        compiler: &Compiler,
+        table: &mut RouteTable,
+        store: &SnapshotStore,
+        changed: Changed,
+    ) {
+        let snapshot = store.get(&key("gw")).expect("gateway must be stored");
+        compiler.recompile(table, snapshot, &changed);
+        assert_eq!(*table, compiler.compile(snapshot), "after {:?}", changed);
+    }
+
+    fn store(routes: Vec<HttpRoute>) -> SnapshotStore {
+        let mut store = SnapshotStore::default();
+        store.apply(Event::Restarted(vec![gateway()]));
+        store.apply(Event::Restarted(routes));
+        store
+    }
+
+    #[test]
+    fn route_changes() {
+        let compiler = Compiler::default();
+        let mut store = store(vec![
+            route("a", "a.example.com", "a", 1),
+            route("b", "shared.example.com", "b", 2),
+        ]);
+        let mut table = compiler.compile(store.get(&key("gw")).unwrap());
+
+        // A more specific route on a shared virtual host is inserted before
+        // the existing one.
+        store.apply(Event::Applied(with_path(
+            route("c", "shared.example.com", "b", 3),
+            "/api",
+        )));
+        recompile(&compiler, &mut table, &store, Changed::HttpRoute(key("c")));
+        let shared = &table.virtual_hosts["80/shared.example.com"];
+        assert_eq!(shared.routes[0].source, key("c"));
+
+        // Moving a route off of a virtual host removes it once it is empty.
+        store.apply(Event::Applied(route("a", "shared.example.com", "a", 1)));
+        recompile(&compiler, &mut table, &store, Changed::HttpRoute(key("a")));
+        assert!(!table.virtual_hosts.contains_key("80/a.example.com"));
+
+        // Clusters are removed once no route references them.
+        store.apply(Event::Applied(route("a", "shared.example.com", "a2", 1)));
+        recompile(&compiler, &mut table, &store, Changed::HttpRoute(key("a")));
+        assert!(!table.clusters.contains_key("default/a:8080"));
+        assert!(table.clusters.contains_key("default/a2:8080"));
+
+        store.apply(Event::Deleted(route("b", "shared.example.com", "b", 2)));
+        recompile(&compiler, &mut table, &store, Changed::HttpRoute(key("b")));
+        assert!(table.clusters.contains_key("default/b:8080"));
+
+        store.apply(Event::Deleted(route("c", "shared.example.com", "b", 3)));
+        recompile(&compiler, &mut table, &store, Changed::HttpRoute(key("c")));
+        assert!(!table.clusters.contains_key("default/b:8080"));
+
+        store.apply(Event::Deleted(route("a", "shared.example.com", "a2", 1)));
+        recompile(&compiler, &mut table, &store, Changed::HttpRoute(key("a")));
+        assert!(table.virtual_hosts.is_empty());
+        assert!(table.clusters.is_empty());
+    }
+
+    #[test]
+    fn routes_are_ordered_by_age() {
+        let compiler = Compiler::default();
+        let mut store = store(vec![route("new", "example.com", "new", 20)]);
+        let mut table = compiler.compile(store.get(&key("gw")).unwrap());
+
+        // Routes with equal precedence are ordered from oldest to newest,
+        // regardless of the order in which they are recompiled.
+        store.apply(Event::Applied(route("old", "example.com", "old", 10)));
+        recompile(
+            &compiler,
+            &mut table,
+            &store,
+            Changed::HttpRoute(key("old")),
+        );
+        let sources = table.virtual_hosts["80/example.com"]
+            .routes
+            .iter()
+            .map(|r| r.source.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(sources, [key("old"), key("new")]);
+
+        store.apply(Event::Applied(route("mid", "example.com", "mid", 15)));
+        recompile(
+            &compiler,
+            &mut table,
+            &store,
+            Changed::HttpRoute(key("mid")),
+        );
+    }
+
+    #[test]
+    fn gateway_changes_recompile_the_table() {
+        let compiler = Compiler::default();
+        let mut store = store(vec![
+            route("a", "a.example.com", "a", 1),
+            route("b", "b.example.com", "b", 2),
+        ]);
+        let mut table = compiler.compile(store.get(&key("gw")).unwrap());
+
+        let mut gw = gateway();
+        gw.spec.listeners[0].hostname = Some("a.example.com".to_string());
+        store.apply(Event::Applied(gw));
+        recompile(&compiler, &mut table, &store, Changed::Gateway);
+        assert_eq!(table.virtual_hosts.len(), 1);
+    }
+
+    #[test]
+    fn filter_order_changes_recompile_the_table() {
+        let mut store = store(vec![route("a", "a.example.com", "a", 1)]);
+        let mut table = Compiler::default().compile(store.get(&key("gw")).unwrap());
+
+        let compiler = Compiler::default().with_filter_order(FilterOrder::Phased);
+        store.apply(Event::Applied(route("b", "b.example.com", "b", 2)));
+        recompile(&compiler, &mut table, &store, Changed::HttpRoute(key("b")));
+        assert_eq!(table.filter_order, FilterOrder::Phased);
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn reference_grants() {
+        use std::convert::TryFrom;
+
+        let mut gw = gateway();
+        let allowed = gw.spec.listeners[0].allowed_routes.as_mut().unwrap();
+        allowed.namespaces.as_mut().unwrap().from = Some("All".to_string());
+
+        // A route in another namespace with a backend in the Gateway's.
+        let mut cross = route("cross", "example.com", "web", 1);
+        cross.metadata.namespace = Some("apps".to_string());
+        cross.spec.inner.parent_refs = None;
+        scaffold::attach(&mut cross, &gw);
+        let rule = &mut cross.spec.rules.as_mut().unwrap()[0];
+        let backend = rule.backend_refs.as_mut().unwrap()[0]
+            .backend_ref
+            .as_mut()
+            .unwrap();
+        backend.inner.namespace = Some(Namespace::try_from(NAMESPACE).unwrap());
+
+        let compiler = Compiler::default();
+        let mut store = SnapshotStore::default();
+        store.apply(Event::Restarted(vec![gw]));
+        store.apply(Event::Restarted(vec![cross]));
+        let mut table = compiler.compile(store.get(&key("gw")).unwrap());
+        let backend = |table: &RouteTable| {
+            table.virtual_hosts["80/example.com"].routes[0].backends[0]
+                .cluster
+                .clone()
+        };
+        assert_eq!(backend(&table), None);
+
+        let grant = ReferenceGrant {
+            metadata: metav1::ObjectMeta {
+                namespace: Some(NAMESPACE.to_string()),
+                name: Some("grant".to_string()),
+                ..Default::default()
+            },
+            spec: ReferenceGrantSpec {
+                from: vec![ReferenceGrantFrom {
+                    group: Group::try_from(consts::GROUP).unwrap(),
+                    kind: Kind::try_from("HTTPRoute").unwrap(),
+                    namespace: Namespace::try_from("apps").unwrap(),
+                }],
+                to: vec![ReferenceGrantTo {
+                    group: Group::core(),
+                    kind: Kind::try_from("Service").unwrap(),
+                    name: None,
+                }],
+            },
+        };
+        store.apply(Event::Applied(grant.clone()));
+        recompile(
+            &compiler,
+            &mut table,
+            &store,
+            Changed::ReferenceGrant(key("grant")),
+        );
+        assert_eq!(backend(&table).as_deref(), Some("default/web:8080"));
+
+        store.apply(Event::Deleted(grant));
+        recompile(
+            &compiler,
+            &mut table,
+            &store,
+            Changed::ReferenceGrant(key("grant")),
+        );
+        assert_eq!(backend(&table), None);
+    }
+}
@@ -0,0 +1,625 @@
+use super::{Backend, Cluster, Route, RouteTable, VirtualHost};
+use crate::{
+    backend::{Upgrade, UpstreamProtocol},
+    *,
+};
+use std::{collections::BTreeSet, fmt::Write};
+
+/// Renders a compiled routing table into the configuration of a proxy.
+///
+/// Implementations provide hooks for clusters and virtual hosts; [`render`]
+/// invokes them for every cluster and then every virtual host of a table, in
+/// name order, so that the output is deterministic.
+///
+/// [`render`]: RouteTableRenderer::render
+pub trait RouteTableRenderer {
+    /// Writes configuration that precedes the clusters and virtual hosts of a
+    /// table. Writes nothing by default.
+    fn render_prelude(&self, _table: &RouteTable, _out: &mut String) -> std::fmt::Result {
+        Ok(())
+    }
+
+    /// Writes the configuration of a cluster.
+    fn render_cluster(
+        &self,
+        table: &RouteTable,
+        cluster: &Cluster,
+        out: &mut String,
+    ) -> std::fmt::Result;
+
+    /// Writes the configuration of a virtual host and its routes.
+    fn render_virtual_host(
+        &self,
+        table: &RouteTable,
+        vhost: &VirtualHost,
+        out: &mut String,
+    ) -> std::fmt::Result;
+
+    /// Renders the configuration of a routing table.
+    fn render(&self, table: &RouteTable) -> String {
+        let mut out = String::new();
+        self.render_prelude(table, &mut out)
+            .expect("writing to a String cannot fail");
+        for cluster in table.clusters.values() {
+            self.render_cluster(table, cluster, &mut out)
+                .expect("writing to a String cannot fail");
+        }
+        for vhost in table.virtual_hosts.values() {
+            self.render_virtual_host(table, vhost, &mut out)
+                .expect("writing to a String cannot fail");
+        }
+        out
+    }
+}
+
+/// Renders routing tables as the `http`-context configuration of NGINX: an
+/// `upstream` per cluster and a `server` per virtual host.
+///
+/// NGINX locations cannot express all of the Gateway API's semantics, so this
+/// renderer is a reference for simple implementations and test harnesses:
+///
+/// - Routes that match on headers, query parameters, or methods, and routes
+///   whose path is already handled by a route of higher precedence, are
+///   rendered as comments.
+/// - Only Service backends with a port are supported; requests forwarded to
+///   other backends receive 500 responses.
+/// - Backends that speak h2c or WebSocket over TLS are not supported. Routes
+///   to WebSocket backends forward the `Upgrade` and `Connection` headers.
+/// - `RequestHeaderModifier` filters set headers rather than appending to
+///   them, and `RequestMirror` and `ExtensionRef` filters are not supported;
+///   routes with extension filters or filters of unknown types respond with
+///   500s.
+/// - Redirects that do not specify a port omit it.
+/// - Per-backend filters are ignored.
+///
+/// NGINX cannot escape `$` in strings, so the rendered configuration declares
+/// a `$gateway_api_dollar` variable with which literal `$`s are written.
+#[derive(Clone, Debug)]
+pub struct Nginx {
+    cluster_domain: String,
+}
+
+// === impl Nginx ===
+
+impl Default for Nginx {
+    fn default() -> Self {
+        Self {
+            cluster_domain: "cluster.local".to_string(),
+        }
+    }
+}
+
+impl Nginx {
+    /// Sets the DNS domain of the cluster, used to address Services. Defaults
+    /// to `cluster.local`.
+    pub fn with_cluster_domain(mut self, domain: impl Into<String>) -> Self {
+        self.cluster_domain = domain.into();
+        self
+    }
+
+    fn upstream(table: &RouteTable, backend: &Backend) -> Option<String> {
+        let cluster = table.clusters.get(backend.cluster.as_ref()?)?;
+        if !is_supported(cluster) {
+            return None;
+        }
+        Some(ident(&cluster.name))
+    }
+
+    fn render_route(
+        &self,
+        table: &RouteTable,
+        route: &Route,
+        index: usize,
+        out: &mut String,
+    ) -> std::fmt::Result {
+        let prefix = match &route.matcher.path {
+            Some(HttpPathMatch::PathPrefix { value }) => Some(value.as_str()),
+            None => Some("/"),
+            _ => None,
+        };
+
+        for filter in &route.filters {
+            match filter {
+                HttpRouteFilter::RequestRedirect { request_redirect } => {
+                    return render_redirect(request_redirect, prefix, out);
+                }
+                HttpRouteFilter::ExtensionRef { extension_ref } => {
+                    writeln!(
+                        out,
+                        "        # unsupported ExtensionRef filter: {} {}",
+                        extension_ref.kind, extension_ref.name
+                    )?;
+                    return writeln!(out, "        return 500;");
+                }
+                #[cfg(feature = "unknown-filters")]
+                HttpRouteFilter::Unknown { type_name, .. } => {
+                    writeln!(out, "        # unsupported {} filter", type_name)?;
+                    return writeln!(out, "        return 500;");
+                }
+                _ => {}
+            }
+        }
+
+        for filter in &route.filters {
+            match filter {
+                HttpRouteFilter::RequestHeaderModifier {
+                    request_header_modifier,
+                } => {
+                    let filter = &**request_header_modifier;
+                    let set = filter.set.iter().flatten();
+                    for header in set.chain(filter.add.iter().flatten()) {
+                        writeln!(
+                            out,
+                            "        proxy_set_header {} {};",
+                            header.name,
+                            quote(&header.value)
+                        )?;
+                    }
+                    for name in filter.remove.iter().flatten() {
+                        writeln!(out, "        proxy_set_header {} \"\";", name)?;
+                    }
+                }
+                HttpRouteFilter::URLRewrite { url_rewrite } => {
+                    if let Some(hostname) = &url_rewrite.hostname {
+                        writeln!(out, "        proxy_set_header Host {};", quote(hostname))?;
+                    }
+                    match (&url_rewrite.path, prefix) {
+                        (Some(HttpPathModifier::ReplaceFullPath { replace_full_path }), _) => {
+                            writeln!(out, "        rewrite ^ {} break;", quote(replace_full_path))?;
+                        }
+                        (
+                            Some(HttpPathModifier::ReplacePrefixMatch {
+                                replace_prefix_match,
+                            }),
+                            Some(prefix),
+                        ) => {
+                            let (pattern, replacement) =
+                                replace_prefix(prefix, replace_prefix_match);
+                            writeln!(
+                                out,
+                                "        rewrite {} {} break;",
+                                quote_raw(&pattern),
+                                quote_raw(&replacement)
+                            )?;
+                        }
+                        _ => {}
+                    }
+                }
+                HttpRouteFilter::RequestMirror { .. } => {
+                    writeln!(out, "        # unsupported RequestMirror filter")?;
+                }
+                _ => {}
+            }
+        }
+
+        if table.upgrades(route).contains(&Upgrade::WebSocket) {
+            writeln!(out, "        proxy_http_version 1.1;")?;
+            writeln!(out, "        proxy_set_header Upgrade $http_upgrade;")?;
+            writeln!(out, "        proxy_set_header Connection $http_connection;")?;
+        }
+
+        let upstreams = route
+            .backends
+            .iter()
+            .filter(|b| b.weight > 0)
+            .map(|b| Self::upstream(table, b))
+            .collect::<Vec<_>>();
+        match upstreams.as_slice() {
+            [] | [None] => writeln!(out, "        return 500;"),
+            [Some(upstream)] => writeln!(out, "        proxy_pass http://{};", upstream),
+            _ => {
+                let var = format!("$route_{}", index);
+                writeln!(out, "        if ({} = \"\") {{", var)?;
+                writeln!(out, "            return 500;")?;
+                writeln!(out, "        }}")?;
+                writeln!(out, "        proxy_pass http://{};", var)
+            }
+        }
+    }
+}
+
+impl RouteTableRenderer for Nginx {
+    fn render_prelude(&self, _: &RouteTable, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "geo $gateway_api_dollar {{")?;
+        writeln!(out, "    default \"$\";")?;
+        writeln!(out, "}}\n")
+    }
+
+    fn render_cluster(
+        &self,
+        _: &RouteTable,
+        cluster: &Cluster,
+        out: &mut String,
+    ) -> std::fmt::Result {
+        let port = match cluster.port {
+            Some(port) if is_supported(cluster) => port,
+            _ => {
+                return writeln!(out, "# unsupported cluster: {}\n", cluster.name);
+            }
+        };
+        writeln!(out, "upstream {} {{", ident(&cluster.name))?;
+        writeln!(
+            out,
+            "    server {}.{}.svc.{}:{};",
+            cluster.backend, cluster.namespace, self.cluster_domain, port
+        )?;
+        writeln!(out, "}}\n")
+    }
+
+    fn render_virtual_host(
+        &self,
+        table: &RouteTable,
+        vhost: &VirtualHost,
+        out: &mut String,
+    ) -> std::fmt::Result {
+        // Weighted backends are selected by `split_clients`, which must be
+        // declared outside of the server.
+        let offset = table
+            .virtual_hosts
+            .range(..vhost.name.clone())
+            .map(|(_, v)| v.routes.len())
+            .sum::<usize>();
+        for (i, route) in vhost.routes.iter().enumerate() {
+            let backends = route
+                .backends
+                .iter()
+                .filter(|b| b.weight > 0)
+                .collect::<Vec<_>>();
+            if backends.len() > 1 {
+                render_split(table, &backends, offset + i, out)?;
+            }
+        }
+
+        writeln!(out, "server {{")?;
+        match &vhost.hostname {
+            Some(hostname) => {
+                writeln!(out, "    listen {};", vhost.port)?;
+                writeln!(out, "    server_name {};", hostname)?;
+            }
+            None => {
+                writeln!(out, "    listen {} default_server;", vhost.port)?;
+                writeln!(out, "    server_name _;")?;
+            }
+        }
+
+        let mut locations = BTreeSet::new();
+        for (i, route) in vhost.routes.iter().enumerate() {
+            let m = &route.matcher;
+            if m.headers.is_some() || m.query_params.is_some() || m.method.is_some() {
+                writeln!(
+                    out,
+                    "\n    # unsupported route {}: header, query parameter, and method matches",
+                    route.name
+                )?;
+                continue;
+            }
+
+            // Locations are not interpolated, so their paths are quoted
+            // verbatim.
+            let modifiers = match &m.path {
+                Some(HttpPathMatch::Exact { value }) => vec![format!("= {}", quote_raw(value))],
+                Some(HttpPathMatch::RegularExpression { value }) => {
+                    vec![format!("~ {}", quote_raw(value))]
+                }
+                // Prefixes match whole path segments.
+                Some(HttpPathMatch::PathPrefix { value }) if !value.ends_with('/') => {
+                    vec![
+                        format!("= {}", quote_raw(value)),
+                        format!("^~ {}", quote_raw(&format!("{}/", value))),
+                    ]
+                }
+                Some(HttpPathMatch::PathPrefix { value }) => {
+                    vec![format!("^~ {}", quote_raw(value))]
+                }
+                None => vec!["^~ /".to_string()],
+                Some(m) => {
+                    writeln!(
+                        out,
+                        "\n    # unsupported route {}: {} path match",
+                        route.name,
+                        m.type_name()
+                    )?;
+                    continue;
+                }
+            };
+            let modifiers = modifiers
+                .into_iter()
+                .filter(|m| locations.insert(m.clone()))
+                .collect::<Vec<_>>();
+            if modifiers.is_empty() {
+                writeln!(out, "\n    # shadowed route {}", route.name)?;
+                continue;
+            }
+            for modifier in modifiers {
+                writeln!(out, "\n    # {}", route.name)?;
+                writeln!(out, "    location {} {{", modifier)?;
+                self.render_route(table, route, offset + i, out)?;
+                writeln!(out, "    }}")?;
+            }
+        }
+        writeln!(out, "}}\n")
+    }
+}
+
+fn is_supported(cluster: &Cluster) -> bool {
+    cluster.group.is_empty()
+        && cluster.kind == "Service"
+        && cluster.port.is_some()
+        && matches!(
+            cluster.upstream_protocol(),
+            Some(UpstreamProtocol::Http1 | UpstreamProtocol::WebSocket)
+        )
+}
+
+fn render_split(
+    table: &RouteTable,
+    backends: &[&Backend],
+    index: usize,
+    out: &mut String,
+) -> std::fmt::Result {
+    let total = backends.iter().map(|b| u64::from(b.weight)).sum::<u64>();
+    writeln!(out, "split_clients \"${{request_id}}\" $route_{} {{", index)?;
+    for (i, backend) in backends.iter().enumerate() {
+        let upstream = Nginx::upstream(table, backend).unwrap_or_default();
+        if i + 1 == backends.len() {
+            writeln!(out, "    * {};", quote(&upstream))?;
+            break;
+        }
+        // Percentages have a precision of hundredths.
+        let share = u64::from(backend.weight) * 10_000 / total;
+        writeln!(
+            out,
+            "    {}.{:02}% {};",
+            share / 100,
+            share % 100,
+            quote(&upstream)
+        )?;
+    }
+    writeln!(out, "}}\n")
+}
+
+fn render_redirect(
+    redirect: &HttpRequestRedirectFilter,
+    prefix: Option<&str>,
+    out: &mut String,
+) -> std::fmt::Result {
+    let scheme = redirect.scheme.as_ref().map_or("$scheme", Scheme::as_str);
+    let host = redirect
+        .hostname
+        .as_deref()
+        .map_or_else(|| "$host".to_string(), escape_variables);
+    let port = redirect.port.map(|p| format!(":{}", p)).unwrap_or_default();
+    let flag = match redirect.status_code {
+        Some(301) => "permanent",
+        _ => "redirect",
+    };
+    let (pattern, path) = match (&redirect.path, prefix) {
+        (Some(HttpPathModifier::ReplaceFullPath { replace_full_path }), _) => {
+            ("^".to_string(), escape_variables(replace_full_path))
+        }
+        (
+            Some(HttpPathModifier::ReplacePrefixMatch {
+                replace_prefix_match,
+            }),
+            Some(prefix),
+        ) => replace_prefix(prefix, replace_prefix_match),
+        _ => ("^(.*)$".to_string(), "$1".to_string()),
+    };
+    writeln!(
+        out,
+        "        rewrite {} {} {};",
+        quote_raw(&pattern),
+        quote_raw(&format!("{}://{}{}{}", scheme, host, port, path)),
+        flag
+    )
+}
+
+/// Returns the `rewrite` pattern and replacement that replace a path prefix,
+/// as [`filter::replace_prefix_match`](crate::filter::replace_prefix_match)
+/// does.
+fn replace_prefix(prefix: &str, replacement: &str) -> (String, String) {
+    let prefix = regex_escape(prefix.trim_end_matches('/'));
+    let replacement = escape_variables(replacement.trim_end_matches('/'));
+    if replacement.is_empty() {
+        // Replacing a prefix with `/` must not produce an empty path.
+        return (format!("^{}(?:/(.*))?$", prefix), "/$1".to_string());
+    }
+    (format!("^{}(/.*)?$", prefix), format!("{}$1", replacement))
+}
+
+fn regex_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Returns an NGINX identifier for a name, e.g. `default_web_8080` for
+/// `default/web:8080`.
+fn ident(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Quotes a literal value, writing `$`s with the `$gateway_api_dollar`
+/// variable so that NGINX does not interpolate variables into it.
+fn quote(s: &str) -> String {
+    quote_raw(&escape_variables(s))
+}
+
+/// Quotes a value verbatim: a regular expression, a location, or a value in
+/// which `$`s introduce variables.
+fn quote_raw(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn escape_variables(s: &str) -> String {
+    s.replace('$', "${gateway_api_dollar}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{Event, ObjectKey, SnapshotStore};
+    use serde_json::json;
+
+    fn render(route_spec: serde_json::Value) -> String {
+        let gateway = serde_json::from_value::<Gateway>(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "web", "namespace": "default" },
+            "spec": {
+                "gatewayClassName": "acme",
+                "listeners": [{
+                    "name": "http",
+                    "port": 80,
+                    "protocol": "HTTP",
+                    "hostname": "web.example.com",
+                }],
+            },
+        }))
+        .unwrap();
+        let route = serde_json::from_value::<HttpRoute>(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": "app", "namespace": "default" },
+            "spec": route_spec,
+        }))
+        .unwrap();
+        let mut store = SnapshotStore::default();
+        store.apply(Event::Restarted(vec![gateway]));
+        store.apply(Event::Restarted(vec![route]));
+        let snapshot = store.get(&ObjectKey::new("default", "web")).unwrap();
+        Nginx::default().render(&super::super::compile(snapshot))
+    }
+
+    #[test]
+    fn renders_routes() {
+        let out = render(json!({
+            "parentRefs": [{ "name": "web" }],
+            "rules": [
+                {
+                    "matches": [{ "path": { "type": "PathPrefix", "value": "/a b;" } }],
+                    "filters": [{
+                        "type": "URLRewrite",
+                        "urlRewrite": {
+                            "path": { "type": "ReplacePrefixMatch", "replacePrefixMatch": "/$x" },
+                        },
+                    }],
+                    "backendRefs": [{ "name": "app", "port": 8080 }],
+                },
+                {
+                    "matches": [{ "path": { "type": "Exact", "value": "/x\"y" } }],
+                    "filters": [{
+                        "type": "RequestHeaderModifier",
+                        "requestHeaderModifier": {
+                            "set": [{ "name": "x-cost", "value": "$5" }],
+                        },
+                    }],
+                    "backendRefs": [
+                        { "name": "app", "port": 8080, "weight": 3 },
+                        { "name": "canary", "port": 8080, "weight": 1 },
+                    ],
+                },
+                {
+                    "matches": [{ "path": { "type": "PathPrefix", "value": "/old" } }],
+                    "filters": [{
+                        "type": "RequestRedirect",
+                        "requestRedirect": {
+                            "path": { "type": "ReplaceFullPath", "replaceFullPath": "/new$" },
+                            "statusCode": 301,
+                        },
+                    }],
+                },
+            ],
+        }));
+        assert_eq!(
+            out,
+            r#"geo $gateway_api_dollar {
+    default "$";
+}
+
+upstream default_app_8080 {
+    server app.default.svc.cluster.local:8080;
+}
+
+upstream default_canary_8080 {
+    server canary.default.svc.cluster.local:8080;
+}
+
+split_clients "${request_id}" $route_0 {
+    75.00% "default_app_8080";
+    * "default_canary_8080";
+}
+
+server {
+    listen 80;
+    server_name web.example.com;
+
+    # default/app/rule/1/match/0
+    location = "/x\"y" {
+        proxy_set_header x-cost "${gateway_api_dollar}5";
+        if ($route_0 = "") {
+            return 500;
+        }
+        proxy_pass http://$route_0;
+    }
+
+    # default/app/rule/0/match/0
+    location = "/a b;" {
+        rewrite "^/a b;(/.*)?$" "/${gateway_api_dollar}x$1" break;
+        proxy_pass http://default_app_8080;
+    }
+
+    # default/app/rule/0/match/0
+    location ^~ "/a b;/" {
+        rewrite "^/a b;(/.*)?$" "/${gateway_api_dollar}x$1" break;
+        proxy_pass http://default_app_8080;
+    }
+
+    # default/app/rule/2/match/0
+    location = "/old" {
+        rewrite "^" "$scheme://$host/new${gateway_api_dollar}" permanent;
+    }
+
+    # default/app/rule/2/match/0
+    location ^~ "/old/" {
+        rewrite "^" "$scheme://$host/new${gateway_api_dollar}" permanent;
+    }
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn replaces_prefixes() {
+        let cases = [
+            ("/foo", "/bar", "^/foo(/.*)?$", "/bar$1"),
+            ("/foo/", "/bar/", "^/foo(/.*)?$", "/bar$1"),
+            ("/foo", "/", "^/foo(?:/(.*))?$", "/$1"),
+            ("/a.b", "/$", "^/a\\.b(/.*)?$", "/${gateway_api_dollar}$1"),
+        ];
+        for (prefix, replacement, pattern, path) in cases {
+            assert_eq!(
+                replace_prefix(prefix, replacement),
+                (pattern.to_string(), path.to_string()),
+                "{} -> {}",
+                prefix,
+                replacement
+            );
+        }
+    }
+
+    #[test]
+    fn quotes_values() {
+        assert_eq!(quote(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+        assert_eq!(quote("$host"), r#""${gateway_api_dollar}host""#);
+        assert_eq!(quote_raw("$host"), r#""$host""#);
+    }
+}
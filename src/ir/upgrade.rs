@@ -0,0 +1,117 @@
+use super::RouteTable;
+use crate::backend::{Upgrade, UpstreamProtocol};
+use std::fmt;
+
+/// A cluster or route of a routing table that a dataplane cannot serve with
+/// the upgrades it supports.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum UpgradeError {
+    /// The cluster's `appProtocol` is not one of those defined by GEP-1911.
+    UnknownProtocol {
+        cluster: String,
+        app_protocol: String,
+    },
+
+    /// The cluster requires an upgrade that the dataplane does not support.
+    Unsupported { cluster: String, upgrade: Upgrade },
+
+    /// The route splits requests between WebSocket and h2c clusters. A
+    /// WebSocket upgrade cannot be forwarded over an HTTP/2 connection, so
+    /// such requests fail whenever they are forwarded to an h2c cluster.
+    WebSocketOverH2c {
+        route: String,
+        websocket: String,
+        h2c: String,
+    },
+}
+
+/// Checks that a dataplane that supports the `supported` upgrades can serve
+/// every cluster and route of a table.
+///
+/// Errors are reported for clusters in name order, then for routes in the
+/// order of their virtual hosts.
+pub fn check_upgrades(table: &RouteTable, supported: &[Upgrade]) -> Vec<UpgradeError> {
+    let mut errors = Vec::new();
+    for cluster in table.clusters.values() {
+        match cluster.upstream_protocol() {
+            None => errors.push(UpgradeError::UnknownProtocol {
+                cluster: cluster.name.clone(),
+                app_protocol: cluster.app_protocol.clone().unwrap_or_default(),
+            }),
+            Some(protocol) => {
+                if let Some(upgrade) = protocol.upgrade() {
+                    if !supported.contains(&upgrade) {
+                        errors.push(UpgradeError::Unsupported {
+                            cluster: cluster.name.clone(),
+                            upgrade,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    for vhost in table.virtual_hosts.values() {
+        for route in &vhost.routes {
+            let clusters = route
+                .backends
+                .iter()
+                .filter_map(|b| table.clusters.get(b.cluster.as_deref()?));
+            let mut websocket = None;
+            let mut h2c = None;
+            for cluster in clusters {
+                match cluster.upstream_protocol() {
+                    Some(UpstreamProtocol::WebSocket | UpstreamProtocol::SecureWebSocket) => {
+                        websocket.get_or_insert(&cluster.name);
+                    }
+                    Some(UpstreamProtocol::H2c) => {
+                        h2c.get_or_insert(&cluster.name);
+                    }
+                    _ => {}
+                }
+            }
+            if let (Some(websocket), Some(h2c)) = (websocket, h2c) {
+                errors.push(UpgradeError::WebSocketOverH2c {
+                    route: route.name.clone(),
+                    websocket: websocket.clone(),
+                    h2c: h2c.clone(),
+                });
+            }
+        }
+    }
+    errors
+}
+
+// === impl UpgradeError ===
+
+impl fmt::Display for UpgradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownProtocol {
+                cluster,
+                app_protocol,
+            } => write!(
+                f,
+                "cluster {} has unsupported appProtocol {:?}",
+                cluster, app_protocol
+            ),
+            Self::Unsupported { cluster, upgrade } => write!(
+                f,
+                "cluster {} requires {}, which is not supported",
+                cluster, upgrade
+            ),
+            Self::WebSocketOverH2c {
+                route,
+                websocket,
+                h2c,
+            } => write!(
+                f,
+                "route {} splits requests between WebSocket cluster {} and h2c cluster {}",
+                route, websocket, h2c
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UpgradeError {}
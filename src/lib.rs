@@ -1,5 +1,18 @@
 //! Unofficial Rust bindings for the [Kubernetes Gateway API][gh].
 //!
+//! Status conditions, label selectors, and object metadata are the
+//! [`k8s_openapi`] types themselves (`Condition`, `LabelSelector`, and
+//! `ObjectMeta` from `k8s_openapi::apimachinery::pkg::apis::meta::v1`), so
+//! they may be passed between this crate and other `k8s-openapi`-based code
+//! without conversion.
+//!
+//! The [`k8s_openapi`] and [`kube`] crates are re-exported, so that code
+//! that only uses them through this crate is guaranteed to use the same
+//! versions. Like kube, this crate does not select a Kubernetes API version,
+//! so it links with applications that target any version supported by
+//! k8s-openapi; applications enable exactly one of k8s-openapi's version
+//! features (e.g. `k8s-openapi/v1_25`).
+//!
 //! [gh]: https://github.com/kubernetes-sigs/gateway-api
 
 #![deny(warnings, rust_2018_idioms)]
@@ -14,17 +27,92 @@ mod httproute;
 mod object_reference;
 mod shared;
 
+pub mod addresses;
+pub mod attachment;
+pub mod audit;
+pub mod backend;
+pub mod canonical;
+pub mod conformance;
+pub mod consts;
+pub mod crd;
+pub mod describe;
+pub mod dynamic;
+pub mod explain;
+pub mod feature_gate;
+pub mod filter;
+pub mod health;
+pub mod hostname;
+pub mod impact;
+pub mod intern;
+pub mod ir;
+pub mod lint;
+pub mod listener;
+pub mod manifest;
+pub mod matcher;
+pub mod owner;
+pub mod patch;
+pub mod provision;
+pub mod rbac;
+pub mod resource;
+pub mod route;
+pub mod scaffold;
+pub mod schema;
+pub mod scope;
+pub mod selftest;
+pub mod simulate;
+pub mod snapshot;
+pub mod split;
+pub mod stats;
+pub mod status;
+pub mod table;
+pub mod tls;
+pub mod unversioned;
+pub mod validation;
+pub mod well_known;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "tower")]
+pub mod middleware;
+
+#[cfg(feature = "experimental")]
+pub mod policy;
+
+#[cfg(feature = "experimental")]
+pub mod sni;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "webhook")]
+pub mod webhook;
+
 pub use self::{gateway::*, gatewayclass::*, httproute::*, object_reference::*, shared::*};
+pub use k8s_openapi;
+pub use kube;
 
 #[cfg(feature = "experimental")]
 mod exp {
+    mod backendlbpolicy;
+    mod backendtrafficpolicy;
+    mod grpcroute;
     mod policy;
     mod referencegrant;
     mod tcproute;
     mod tlsroute;
     mod udproute;
 
-    pub use self::{policy::*, referencegrant::*, tcproute::*, tlsroute::*, udproute::*};
+    pub use self::{
+        backendlbpolicy::*, backendtrafficpolicy::*, grpcroute::*, policy::*, referencegrant::*,
+        tcproute::*, tlsroute::*, udproute::*,
+    };
 }
 
 #[cfg(feature = "experimental")]
@@ -0,0 +1,860 @@
+//! Cross-object checks for sets of Gateway API objects.
+//!
+//! The [`validation`](crate::validation) module checks objects in isolation.
+//! [`lint`] instead checks how the objects in a set (typically, all of the
+//! objects in a directory of manifests) relate to each other, so that CI
+//! tooling can catch mistakes that the API server would accept:
+//!
+//! ```ignore
+//! let objects = manifest::parse_yaml(&bytes)?;
+//! for finding in lint::lint(&compiler, &objects) {
+//!     eprintln!("{}", finding);
+//! }
+//! ```
+//!
+//! Routes are attached to listeners as by the caller's [`Compiler`], which
+//! should be configured with the labels of namespaces that listeners may
+//! select. Objects without a namespace are assumed to be in the `default`
+//! namespace.
+
+use crate::{
+    consts::GROUP,
+    ir::{self, Compiler},
+    manifest::GatewayApiObject,
+    validation::{ErrorReason, FieldPath, ValidationError},
+    *,
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+mod shadow;
+
+pub use self::shadow::shadowed_rules;
+
+const DEFAULT_NAMESPACE: &str = "default";
+
+/// A problem with how an object relates to other objects in a set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LintFinding {
+    /// The kind of the offending object, e.g. `HTTPRoute`.
+    pub kind: &'static str,
+
+    /// The namespace of the offending object.
+    pub namespace: String,
+
+    /// The name of the offending object.
+    pub name: String,
+
+    /// The offending field.
+    pub path: FieldPath,
+
+    /// Identifies the check that failed.
+    pub code: LintCode,
+
+    /// A human-readable description of the problem.
+    pub message: String,
+}
+
+/// Identifies a lint check.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum LintCode {
+    /// A route references a Gateway (or a listener of a Gateway) that is not in
+    /// the set.
+    ParentNotFound,
+
+    /// A route references a Gateway whose listeners do not allow routes from
+    /// the route's namespace.
+    NotAllowedByListeners,
+
+    /// A cross-namespace reference is not permitted by any ReferenceGrant in
+    /// the set.
+    ///
+    /// ReferenceGrants are only modeled when the `experimental` feature is
+    /// enabled, so this check is skipped otherwise.
+    RefNotPermitted,
+
+    /// Two listeners of a Gateway cannot share a port.
+    ConflictingListeners,
+
+    /// A route match can never be selected because an earlier match in the
+    /// same route is identical.
+    ShadowedMatch,
+
+    /// A route rule (or every rule of a route) can never be selected because
+    /// each of its matches is shadowed by a higher-precedence match, in the
+    /// same route or in another route attached to the same listeners.
+    ShadowedRule,
+}
+
+/// Checks the relationships between a set of objects, attaching routes as
+/// `compiler` does.
+///
+/// Findings are reported in the order of the objects in the set.
+pub fn lint(compiler: &Compiler, objects: &[GatewayApiObject]) -> Vec<LintFinding> {
+    let ctx = Context::new(compiler, objects);
+    let mut shadowed = shadowed_rules(compiler, objects);
+    let mut findings = Vec::new();
+    for obj in objects {
+        let mut report = |path: FieldPath, code: LintCode, message: String| {
+            findings.push(LintFinding {
+                kind: obj.kind(),
+                namespace: namespace(obj.metadata()).to_string(),
+                name: obj.name().unwrap_or_default().to_string(),
+                path,
+                code,
+                message,
+            })
+        };
+
+        match obj {
+            GatewayApiObject::GatewayClass(_) => {}
+            GatewayApiObject::Gateway(gw) => {
+                lint_listeners(gw, &mut report);
+                lint_certificate_refs(&ctx, gw, &mut report);
+            }
+            GatewayApiObject::HttpRoute(route) => {
+                let route_ns = namespace(&route.metadata);
+                let spec = FieldPath::root().field("spec");
+                ctx.lint_parent_refs(route_ns, &route.spec.inner, &spec, &mut report);
+
+                let rules = spec.field("rules");
+                for (i, rule) in route.spec.rules.iter().flatten().enumerate() {
+                    let refs = rules.index(i).field("backendRefs");
+                    for (j, backend) in rule.backend_refs.iter().flatten().enumerate() {
+                        if let Some(backend) = &backend.backend_ref {
+                            ctx.lint_backend_ref(
+                                "HTTPRoute",
+                                route_ns,
+                                &backend.inner,
+                                refs.index(j),
+                                &mut report,
+                            );
+                        }
+                    }
+                }
+                lint_shadowed_matches(route, &mut report);
+
+                let name = obj.name().unwrap_or_default();
+                let (route_findings, rest) = shadowed
+                    .into_iter()
+                    .partition(|f| f.namespace == route_ns && f.name == name);
+                shadowed = rest;
+                findings.extend(route_findings);
+            }
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::BackendLbPolicy(_)
+            | GatewayApiObject::ReferenceGrant(_)
+            | GatewayApiObject::XBackendTrafficPolicy(_) => {}
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::GrpcRoute(route) => {
+                let route_ns = namespace(&route.metadata);
+                let spec = FieldPath::root().field("spec");
+                ctx.lint_parent_refs(route_ns, &route.spec.inner, &spec, &mut report);
+
+                let rules = spec.field("rules");
+                for (i, rule) in route.spec.rules.iter().flatten().enumerate() {
+                    let refs = rules.index(i).field("backendRefs");
+                    for (j, backend) in rule.backend_refs.iter().flatten().enumerate() {
+                        if let Some(backend) = &backend.backend_ref {
+                            ctx.lint_backend_ref(
+                                "GRPCRoute",
+                                route_ns,
+                                &backend.inner,
+                                refs.index(j),
+                                &mut report,
+                            );
+                        }
+                    }
+                }
+            }
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::TcpRoute(route) => {
+                let rules = route.spec.rules.iter().map(|r| &r.backend_refs[..]);
+                ctx.lint_route(
+                    "TCPRoute",
+                    &route.metadata,
+                    &route.spec.inner,
+                    rules,
+                    &mut report,
+                );
+            }
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::TlsRoute(route) => {
+                let rules = route.spec.rules.iter().map(|r| &r.backend_refs[..]);
+                ctx.lint_route(
+                    "TLSRoute",
+                    &route.metadata,
+                    &route.spec.inner,
+                    rules,
+                    &mut report,
+                );
+            }
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::UdpRoute(route) => {
+                let rules = route.spec.rules.iter().map(|r| &r.backend_refs[..]);
+                ctx.lint_route(
+                    "UDPRoute",
+                    &route.metadata,
+                    &route.spec.inner,
+                    rules,
+                    &mut report,
+                );
+            }
+        }
+    }
+    findings
+}
+
+/// Indexes the objects in a set.
+struct Context<'a> {
+    gateways: HashMap<(&'a str, &'a str), &'a Gateway>,
+    namespace_labels: &'a BTreeMap<String, BTreeMap<String, String>>,
+
+    #[cfg(feature = "experimental")]
+    grants: Vec<ReferenceGrant>,
+}
+
+fn namespace(meta: &metav1::ObjectMeta) -> &str {
+    meta.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE)
+}
+
+/// Reports listeners that share a port but cannot be distinguished, either
+/// because they are of incompatible protocols or because they have the same
+/// hostname.
+fn lint_listeners(gw: &Gateway, report: &mut impl FnMut(FieldPath, LintCode, String)) {
+    // Protocols that may share a port, distinguishing listeners by hostname.
+    fn family(protocol: &str) -> &str {
+        match protocol {
+            "HTTPS" | "TLS" => "TLS",
+            p => p,
+        }
+    }
+
+    let listeners = FieldPath::root().field("spec").field("listeners");
+    for (i, listener) in gw.spec.listeners.iter().enumerate() {
+        let conflict = gw.spec.listeners[..i].iter().find(|other| {
+            other.port == listener.port
+                && (family(&other.protocol) != family(&listener.protocol)
+                    || other.hostname == listener.hostname)
+                && (other.protocol == "UDP") == (listener.protocol == "UDP")
+        });
+        if let Some(other) = conflict {
+            report(
+                listeners.index(i),
+                LintCode::ConflictingListeners,
+                format!(
+                    "listener {} conflicts with listener {} on port {}",
+                    listener.name, other.name, listener.port
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(feature = "experimental")]
+fn lint_certificate_refs(
+    ctx: &Context<'_>,
+    gw: &Gateway,
+    report: &mut impl FnMut(FieldPath, LintCode, String),
+) {
+    let gateway_ns = namespace(&gw.metadata);
+    let listeners = FieldPath::root().field("spec").field("listeners");
+    for (i, listener) in gw.spec.listeners.iter().enumerate() {
+        let tls = match &listener.tls {
+            Some(tls) => tls,
+            None => continue,
+        };
+        let path = listeners.index(i).field("tls");
+        if let Err(errors) = tls::validate_certificate_refs(gateway_ns, tls, |r| {
+            is_reference_permitted(&ctx.grants, r)
+        }) {
+            for e in errors {
+                if let tls::CertificateRefError::RefNotPermitted { .. } = e {
+                    report(
+                        path.field("certificateRefs"),
+                        LintCode::RefNotPermitted,
+                        e.to_string(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "experimental"))]
+fn lint_certificate_refs(
+    _: &Context<'_>,
+    _: &Gateway,
+    _: &mut impl FnMut(FieldPath, LintCode, String),
+) {
+}
+
+/// Reports route matches that are identical to an earlier match in the same
+/// route. Gateway API breaks ties between equally specific matches in favor of
+/// the first rule, so such matches can never be selected.
+fn lint_shadowed_matches(route: &HttpRoute, report: &mut impl FnMut(FieldPath, LintCode, String)) {
+    // A rule without matches matches all requests.
+    let default_match = HttpRouteMatch::default();
+
+    let rules = FieldPath::root().field("spec").field("rules");
+    let mut seen = Vec::<(&HttpRouteMatch, usize)>::new();
+    for (i, rule) in route.spec.rules.iter().flatten().enumerate() {
+        let rule_path = rules.index(i);
+        let (matches, implicit) = match rule.matches.as_deref() {
+            Some(matches) if !matches.is_empty() => (matches, false),
+            _ => (std::slice::from_ref(&default_match), true),
+        };
+        for (j, m) in matches.iter().enumerate() {
+            match seen.iter().find(|(s, _)| same_match(s, m)) {
+                Some((_, earlier)) => report(
+                    if implicit {
+                        rule_path.clone()
+                    } else {
+                        rule_path.field("matches").index(j)
+                    },
+                    LintCode::ShadowedMatch,
+                    format!(
+                        "match is shadowed by an identical match in rule {}",
+                        earlier
+                    ),
+                ),
+                None => seen.push((m, i)),
+            }
+        }
+    }
+}
+
+/// Returns true if two matches select the same requests.
+fn same_match(a: &HttpRouteMatch, b: &HttpRouteMatch) -> bool {
+    fn same_items<T: PartialEq>(a: &Option<Vec<T>>, b: &Option<Vec<T>>) -> bool {
+        let a = a.as_deref().unwrap_or_default();
+        let b = b.as_deref().unwrap_or_default();
+        a.len() == b.len() && a.iter().all(|x| b.contains(x))
+    }
+
+    let root = HttpPathMatch::PathPrefix {
+        value: "/".to_string(),
+    };
+    a.path.as_ref().unwrap_or(&root) == b.path.as_ref().unwrap_or(&root)
+        && a.method == b.method
+        && same_items(&a.headers, &b.headers)
+        && same_items(&a.query_params, &b.query_params)
+}
+
+// === impl Context ===
+
+impl<'a> Context<'a> {
+    fn new(compiler: &'a Compiler, objects: &'a [GatewayApiObject]) -> Self {
+        let mut gateways = HashMap::new();
+        #[cfg(feature = "experimental")]
+        let mut grants = Vec::new();
+        for obj in objects {
+            match obj {
+                GatewayApiObject::Gateway(gw) => {
+                    let name = gw.metadata.name.as_deref().unwrap_or_default();
+                    gateways.insert((namespace(&gw.metadata), name), gw);
+                }
+                #[cfg(feature = "experimental")]
+                GatewayApiObject::ReferenceGrant(grant) => {
+                    let mut grant = grant.clone();
+                    grant.metadata.namespace = Some(namespace(&grant.metadata).to_string());
+                    grants.push(grant);
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            gateways,
+            namespace_labels: compiler.namespace_labels(),
+            #[cfg(feature = "experimental")]
+            grants,
+        }
+    }
+
+    #[cfg(feature = "experimental")]
+    fn lint_route<'r>(
+        &self,
+        kind: &'static str,
+        meta: &metav1::ObjectMeta,
+        spec: &CommonRouteSpec,
+        rules: impl Iterator<Item = &'r [BackendRef]>,
+        report: &mut impl FnMut(FieldPath, LintCode, String),
+    ) {
+        let route_ns = namespace(meta);
+        let path = FieldPath::root().field("spec");
+        self.lint_parent_refs(route_ns, spec, &path, report);
+
+        let rules_path = path.field("rules");
+        for (i, backends) in rules.enumerate() {
+            let refs = rules_path.index(i).field("backendRefs");
+            for (j, backend) in backends.iter().enumerate() {
+                self.lint_backend_ref(kind, route_ns, &backend.inner, refs.index(j), report);
+            }
+        }
+    }
+
+    /// Reports parent references to Gateways that are not in the set, or whose
+    /// listeners do not admit the route.
+    fn lint_parent_refs(
+        &self,
+        route_ns: &str,
+        spec: &CommonRouteSpec,
+        path: &FieldPath,
+        report: &mut impl FnMut(FieldPath, LintCode, String),
+    ) {
+        let parent_refs = path.field("parentRefs");
+        for (i, parent) in spec.parent_refs.iter().flatten().enumerate() {
+            let group = parent.group.as_deref().unwrap_or(GROUP);
+            let kind = parent.kind.as_deref().unwrap_or("Gateway");
+            if group != GROUP || kind != "Gateway" {
+                continue;
+            }
+
+            let path = parent_refs.index(i);
+            let ns = parent.namespace.as_deref().unwrap_or(route_ns);
+            let gw = match self.gateways.get(&(ns, &*parent.name)) {
+                Some(gw) => gw,
+                None => {
+                    report(
+                        path,
+                        LintCode::ParentNotFound,
+                        format!("Gateway {}/{} not found", ns, parent.name),
+                    );
+                    continue;
+                }
+            };
+
+            let listeners = gw
+                .spec
+                .listeners
+                .iter()
+                .filter(|l| parent.section_name.as_deref().map_or(true, |s| s == l.name))
+                .filter(|l| parent.port.map_or(true, |p| p == l.port))
+                .collect::<Vec<_>>();
+            if listeners.is_empty() {
+                report(
+                    path,
+                    LintCode::ParentNotFound,
+                    format!("Gateway {}/{} has no matching listener", ns, parent.name),
+                );
+                continue;
+            }
+
+            let allowed = listeners
+                .iter()
+                .any(|l| ir::allows_namespace(l, ns, route_ns, self.namespace_labels));
+            if !allowed {
+                report(
+                    path,
+                    LintCode::NotAllowedByListeners,
+                    format!(
+                        "Gateway {}/{} does not allow routes from namespace {}",
+                        ns, parent.name, route_ns
+                    ),
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "experimental")]
+    fn lint_backend_ref(
+        &self,
+        route_kind: &str,
+        route_ns: &str,
+        backend: &BackendObjectReference,
+        path: FieldPath,
+        report: &mut impl FnMut(FieldPath, LintCode, String),
+    ) {
+        let ns = match backend.namespace.as_deref() {
+            Some(ns) if ns != route_ns => ns,
+            _ => return,
+        };
+        let reference = CrossNamespaceReference {
+            from_group: GROUP,
+            from_kind: route_kind,
+            from_namespace: route_ns,
+            to_group: backend.group.as_deref().unwrap_or(""),
+            to_kind: backend.kind.as_deref().unwrap_or("Service"),
+            to_namespace: ns,
+            to_name: &backend.name,
+        };
+        if let Err(e) = check_reference(&self.grants, &reference) {
+            report(
+                path.field("namespace"),
+                LintCode::RefNotPermitted,
+                e.to_string(),
+            );
+        }
+    }
+
+    #[cfg(not(feature = "experimental"))]
+    fn lint_backend_ref(
+        &self,
+        _: &str,
+        _: &str,
+        _: &BackendObjectReference,
+        _: FieldPath,
+        _: &mut impl FnMut(FieldPath, LintCode, String),
+    ) {
+    }
+}
+
+// === impl LintFinding ===
+
+impl LintFinding {
+    /// Converts the finding into a [`ValidationError`] on the offending
+    /// object.
+    pub fn to_validation_error(&self) -> ValidationError {
+        let reason = match self.code {
+            LintCode::ParentNotFound => ErrorReason::NotFound,
+            LintCode::NotAllowedByListeners | LintCode::RefNotPermitted => ErrorReason::Forbidden,
+            LintCode::ConflictingListeners | LintCode::ShadowedMatch | LintCode::ShadowedRule => {
+                ErrorReason::Invalid
+            }
+        };
+        ValidationError::new(self.path.clone(), reason, self.message.clone())
+    }
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}/{}: {}: {} ({})",
+            self.kind, self.namespace, self.name, self.path, self.message, self.code
+        )
+    }
+}
+
+// === impl LintCode ===
+
+impl LintCode {
+    /// Returns the name of the check, e.g. `ParentNotFound`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ParentNotFound => "ParentNotFound",
+            Self::NotAllowedByListeners => "NotAllowedByListeners",
+            Self::RefNotPermitted => "RefNotPermitted",
+            Self::ConflictingListeners => "ConflictingListeners",
+            Self::ShadowedMatch => "ShadowedMatch",
+            Self::ShadowedRule => "ShadowedRule",
+        }
+    }
+}
+
+impl fmt::Display for LintCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gateway(listeners: serde_json::Value) -> GatewayApiObject {
+        GatewayApiObject::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "web", "namespace": "infra" },
+            "spec": { "gatewayClassName": "acme", "listeners": listeners },
+        }))
+        .unwrap()
+    }
+
+    fn http_listener(allowed_routes: serde_json::Value) -> serde_json::Value {
+        json!({
+            "name": "http",
+            "port": 80,
+            "protocol": "HTTP",
+            "allowedRoutes": allowed_routes,
+        })
+    }
+
+    fn route(namespace: &str, spec: serde_json::Value) -> GatewayApiObject {
+        GatewayApiObject::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": "app", "namespace": namespace },
+            "spec": spec,
+        }))
+        .unwrap()
+    }
+
+    fn codes(findings: &[LintFinding]) -> Vec<(String, LintCode)> {
+        findings
+            .iter()
+            .map(|f| (f.path.to_string(), f.code))
+            .collect()
+    }
+
+    #[test]
+    fn reports_missing_parents() {
+        let objects = [
+            gateway(json!([http_listener(json!({}))])),
+            route(
+                "infra",
+                json!({
+                    "parentRefs": [
+                        { "name": "web" },
+                        { "name": "api" },
+                        { "name": "web", "sectionName": "https" },
+                        { "name": "web", "port": 8080 },
+                    ],
+                }),
+            ),
+        ];
+        assert_eq!(
+            codes(&lint(&Compiler::default(), &objects)),
+            [
+                ("spec.parentRefs[1]".to_string(), LintCode::ParentNotFound),
+                ("spec.parentRefs[2]".to_string(), LintCode::ParentNotFound),
+                ("spec.parentRefs[3]".to_string(), LintCode::ParentNotFound),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_routes_from_namespaces_that_listeners_do_not_allow() {
+        let parent = json!({ "parentRefs": [{ "name": "web", "namespace": "infra" }] });
+        let not_allowed = [(
+            "spec.parentRefs[0]".to_string(),
+            LintCode::NotAllowedByListeners,
+        )];
+
+        let same = [
+            gateway(json!([http_listener(json!({}))])),
+            route("apps", parent.clone()),
+        ];
+        assert_eq!(codes(&lint(&Compiler::default(), &same)), not_allowed);
+
+        let all = [
+            gateway(json!([http_listener(
+                json!({ "namespaces": { "from": "All" } })
+            )])),
+            route("apps", parent.clone()),
+        ];
+        assert!(lint(&Compiler::default(), &all).is_empty());
+
+        let selector = [
+            gateway(json!([http_listener(json!({
+                "namespaces": {
+                    "from": "Selector",
+                    "selector": { "matchLabels": { "gateway": "web" } },
+                },
+            }))])),
+            route("apps", parent),
+        ];
+        assert_eq!(codes(&lint(&Compiler::default(), &selector)), not_allowed);
+
+        let labels = BTreeMap::from([("gateway".to_string(), "web".to_string())]);
+        let compiler = Compiler::default().with_namespace_labels("apps", labels);
+        assert!(lint(&compiler, &selector).is_empty());
+    }
+
+    #[test]
+    fn reports_conflicting_listeners() {
+        let objects = [gateway(json!([
+            { "name": "http", "port": 80, "protocol": "HTTP" },
+            { "name": "tcp", "port": 80, "protocol": "TCP" },
+            { "name": "a", "port": 443, "protocol": "HTTPS", "hostname": "a.example.com" },
+            { "name": "b", "port": 443, "protocol": "TLS", "hostname": "b.example.com" },
+            { "name": "dns", "port": 80, "protocol": "UDP" },
+        ]))];
+        let findings = lint(&Compiler::default(), &objects);
+        assert_eq!(
+            codes(&findings),
+            [(
+                "spec.listeners[1]".to_string(),
+                LintCode::ConflictingListeners
+            )]
+        );
+        assert_eq!(
+            findings[0].message,
+            "listener tcp conflicts with listener http on port 80"
+        );
+    }
+
+    #[test]
+    fn reports_identical_matches() {
+        let objects = [
+            gateway(json!([http_listener(json!({}))])),
+            route(
+                "infra",
+                json!({
+                    "parentRefs": [{ "name": "web" }],
+                    "rules": [
+                        {
+                            "matches": [{
+                                "path": { "type": "PathPrefix", "value": "/" },
+                                "headers": [
+                                    { "name": "a", "value": "1" },
+                                    { "name": "b", "value": "2" },
+                                ],
+                            }],
+                        },
+                        {
+                            "matches": [
+                                { "path": { "type": "Exact", "value": "/x" } },
+                                {
+                                    "headers": [
+                                        { "name": "b", "value": "2" },
+                                        { "name": "a", "value": "1" },
+                                    ],
+                                },
+                            ],
+                        },
+                        {},
+                        {},
+                    ],
+                }),
+            ),
+        ];
+        let findings = lint(&Compiler::default(), &objects)
+            .into_iter()
+            .filter(|f| f.code == LintCode::ShadowedMatch)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codes(&findings),
+            [
+                (
+                    "spec.rules[1].matches[1]".to_string(),
+                    LintCode::ShadowedMatch
+                ),
+                ("spec.rules[3]".to_string(), LintCode::ShadowedMatch),
+            ]
+        );
+        assert_eq!(
+            findings[1].message,
+            "match is shadowed by an identical match in rule 2"
+        );
+    }
+
+    #[test]
+    fn reports_shadowed_rules() {
+        let mut objects = vec![gateway(json!([http_listener(json!({}))]))];
+        for (name, created) in [
+            ("new", "2021-01-01T00:00:00Z"),
+            ("old", "2020-01-01T00:00:00Z"),
+        ] {
+            objects.push(
+                GatewayApiObject::from_value(json!({
+                    "apiVersion": "gateway.networking.k8s.io/v1beta1",
+                    "kind": "HTTPRoute",
+                    "metadata": {
+                        "name": name,
+                        "namespace": "infra",
+                        "creationTimestamp": created,
+                    },
+                    "spec": {
+                        "parentRefs": [{ "name": "web" }],
+                        "rules": [{ "matches": [{ "path": { "type": "PathPrefix", "value": "/api" } }] }],
+                    },
+                }))
+                .unwrap(),
+            );
+        }
+        let findings = lint(&Compiler::default(), &objects);
+        assert_eq!(
+            codes(&findings),
+            [("spec.rules".to_string(), LintCode::ShadowedRule)]
+        );
+        assert_eq!(findings[0].name, "new");
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn reports_references_that_are_not_permitted() {
+        let grant = GatewayApiObject::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1alpha2",
+            "kind": "ReferenceGrant",
+            "metadata": { "name": "certs", "namespace": "certs" },
+            "spec": {
+                "from": [{
+                    "group": "gateway.networking.k8s.io",
+                    "kind": "Gateway",
+                    "namespace": "infra",
+                }],
+                "to": [{ "group": "", "kind": "Secret" }],
+            },
+        }))
+        .unwrap();
+        let objects = [
+            gateway(json!([{
+                "name": "https",
+                "port": 443,
+                "protocol": "HTTPS",
+                "tls": {
+                    "certificateRefs": [{ "name": "web", "namespace": "certs" }],
+                },
+            }])),
+            route(
+                "infra",
+                json!({
+                    "parentRefs": [{ "name": "web" }],
+                    "rules": [{
+                        "backendRefs": [
+                            { "name": "app", "port": 8080 },
+                            { "name": "app", "namespace": "apps", "port": 8080 },
+                        ],
+                    }],
+                }),
+            ),
+        ];
+        assert_eq!(
+            codes(&lint(&Compiler::default(), &objects)),
+            [
+                (
+                    "spec.listeners[0].tls.certificateRefs".to_string(),
+                    LintCode::RefNotPermitted
+                ),
+                (
+                    "spec.rules[0].backendRefs[1].namespace".to_string(),
+                    LintCode::RefNotPermitted
+                ),
+            ]
+        );
+
+        let mut objects = objects.to_vec();
+        objects.push(grant);
+        assert_eq!(
+            codes(&lint(&Compiler::default(), &objects)),
+            [(
+                "spec.rules[0].backendRefs[1].namespace".to_string(),
+                LintCode::RefNotPermitted
+            )]
+        );
+    }
+
+    #[test]
+    fn converts_findings_to_validation_errors() {
+        let reasons = [
+            (LintCode::ParentNotFound, ErrorReason::NotFound),
+            (LintCode::NotAllowedByListeners, ErrorReason::Forbidden),
+            (LintCode::RefNotPermitted, ErrorReason::Forbidden),
+            (LintCode::ConflictingListeners, ErrorReason::Invalid),
+            (LintCode::ShadowedMatch, ErrorReason::Invalid),
+            (LintCode::ShadowedRule, ErrorReason::Invalid),
+        ];
+        for (code, reason) in reasons {
+            let finding = LintFinding {
+                kind: "HTTPRoute",
+                namespace: "apps".to_string(),
+                name: "app".to_string(),
+                path: FieldPath::root().field("spec"),
+                code,
+                message: "problem".to_string(),
+            };
+            assert_eq!(finding.to_validation_error().reason, reason, "{}", code);
+            assert_eq!(
+                finding.to_string(),
+                format!("HTTPRoute apps/app: spec: problem ({})", code.as_str())
+            );
+        }
+    }
+}
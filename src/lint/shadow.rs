@@ -0,0 +1,419 @@
+//! Detection of HTTPRoute rules that are shadowed by higher-precedence rules.
+//!
+//! Routes are compiled into the routing tables of the Gateways they attach
+//! to, in which their matches are ordered by precedence. A match is shadowed
+//! if an earlier match in the same virtual host is satisfied by every request
+//! that satisfies it, so it never handles a request. A rule is reported when
+//! all of its matches are shadowed in every virtual host it attaches to, and
+//! a route is reported instead of its rules when all of them are.
+
+use super::{namespace, LintCode, LintFinding};
+use crate::{
+    ir::Compiler,
+    manifest::GatewayApiObject,
+    snapshot::{Event, ObjectKey, SnapshotStore},
+    validation::FieldPath,
+    *,
+};
+use std::collections::BTreeMap;
+
+/// The matches of a rule, in all of the virtual hosts it attaches to.
+#[derive(Default)]
+struct RuleMatches {
+    total: usize,
+
+    /// The rules that shadow the rule's matches, in the order in which they
+    /// were found.
+    shadowed_by: Vec<(ObjectKey, usize)>,
+}
+
+/// Returns findings for the HTTPRoute rules and routes in a set of objects
+/// that are entirely shadowed by higher-precedence rules in the routing tables
+/// compiled by `compiler`, ordered by route and rule.
+///
+/// These findings are also reported by [`lint`](super::lint).
+pub fn shadowed_rules(compiler: &Compiler, objects: &[GatewayApiObject]) -> Vec<LintFinding> {
+    let mut store = SnapshotStore::default();
+    store.apply(Event::Restarted(
+        objects
+            .iter()
+            .filter_map(|o| match o {
+                GatewayApiObject::Gateway(gw) => Some(with_namespace(gw.clone())),
+                _ => None,
+            })
+            .collect(),
+    ));
+    store.apply(Event::Restarted(
+        objects
+            .iter()
+            .filter_map(|o| match o {
+                GatewayApiObject::HttpRoute(r) => Some(with_namespace(r.clone())),
+                _ => None,
+            })
+            .collect(),
+    ));
+
+    let mut rules = BTreeMap::<(ObjectKey, usize), RuleMatches>::new();
+    for snapshot in store.snapshots() {
+        let table = compiler.compile(snapshot);
+        for vhost in table.virtual_hosts.values() {
+            for (i, route) in vhost.routes.iter().enumerate() {
+                let rule = rules
+                    .entry((route.source.clone(), route.rule_index))
+                    .or_default();
+                rule.total += 1;
+                let shadow = vhost.routes[..i]
+                    .iter()
+                    .find(|earlier| covers(&earlier.matcher, &route.matcher));
+                if let Some(shadow) = shadow {
+                    rule.shadowed_by
+                        .push((shadow.source.clone(), shadow.rule_index));
+                }
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    let mut routes = BTreeMap::<&ObjectKey, Vec<(usize, &RuleMatches)>>::new();
+    for ((key, index), matches) in &rules {
+        routes.entry(key).or_default().push((*index, matches));
+    }
+    for (key, rules) in routes {
+        let shadowed = rules
+            .iter()
+            .filter(|(_, m)| m.shadowed_by.len() == m.total)
+            .collect::<Vec<_>>();
+        let finding = |path: FieldPath, message: String| LintFinding {
+            kind: "HTTPRoute",
+            namespace: key.namespace.to_string(),
+            name: key.name.to_string(),
+            path,
+            code: LintCode::ShadowedRule,
+            message,
+        };
+
+        let rules_path = FieldPath::root().field("spec").field("rules");
+        if shadowed.len() == rules.len() && !rules.is_empty() {
+            let (_, first) = rules[0];
+            findings.push(finding(
+                rules_path,
+                format!(
+                    "all rules are shadowed by higher-precedence rules, e.g. {}",
+                    describe(key, &first.shadowed_by[0])
+                ),
+            ));
+            continue;
+        }
+        for (index, matches) in shadowed {
+            findings.push(finding(
+                rules_path.index(*index),
+                format!(
+                    "rule is shadowed by the higher-precedence {}",
+                    describe(key, &matches.shadowed_by[0])
+                ),
+            ));
+        }
+    }
+    findings
+}
+
+/// Describes a shadowing rule relative to the route it shadows.
+fn describe(route: &ObjectKey, (shadow, index): &(ObjectKey, usize)) -> String {
+    if shadow == route {
+        return format!("rule {}", index);
+    }
+    format!("rule {} of HTTPRoute {}", index, shadow)
+}
+
+/// Returns true if every request that satisfies `b` also satisfies `a`.
+///
+/// Regular expressions are only known to be satisfied by the same requests if
+/// they are identical.
+fn covers(a: &HttpRouteMatch, b: &HttpRouteMatch) -> bool {
+    if a.method.is_some() && a.method != b.method {
+        return false;
+    }
+
+    let root = HttpPathMatch::PathPrefix {
+        value: "/".to_string(),
+    };
+    let covers_path = match (
+        a.path.as_ref().unwrap_or(&root),
+        b.path.as_ref().unwrap_or(&root),
+    ) {
+        (HttpPathMatch::PathPrefix { value }, _) if value.trim_end_matches('/').is_empty() => true,
+        (HttpPathMatch::Exact { value: a }, HttpPathMatch::Exact { value: b }) => a == b,
+        (a @ HttpPathMatch::PathPrefix { .. }, HttpPathMatch::Exact { value }) => {
+            matcher::Matcher::new().http_path(a, value)
+        }
+        // A prefix matches the paths that extend it, all of which extend a
+        // prefix that it extends.
+        (a @ HttpPathMatch::PathPrefix { .. }, HttpPathMatch::PathPrefix { value }) => {
+            matcher::Matcher::new().http_path(a, value.trim_end_matches('/'))
+        }
+        (a, b) => a == b,
+    };
+
+    let covers_headers = a.headers.iter().flatten().all(|ah| {
+        b.headers
+            .iter()
+            .flatten()
+            .any(|bh| same_header_match(ah, bh))
+    });
+    let covers_query = a
+        .query_params
+        .iter()
+        .flatten()
+        .all(|aq| b.query_params.iter().flatten().any(|bq| aq == bq));
+    covers_path && covers_headers && covers_query
+}
+
+/// Returns true if two header matches are identical, comparing header names
+/// case-insensitively.
+fn same_header_match(a: &HttpHeaderMatch, b: &HttpHeaderMatch) -> bool {
+    match (a, b) {
+        (
+            HttpHeaderMatch::Exact {
+                name: an,
+                value: av,
+            },
+            HttpHeaderMatch::Exact {
+                name: bn,
+                value: bv,
+            },
+        )
+        | (
+            HttpHeaderMatch::RegularExpression {
+                name: an,
+                value: av,
+            },
+            HttpHeaderMatch::RegularExpression {
+                name: bn,
+                value: bv,
+            },
+        ) => an.eq_ignore_ascii_case(bn) && av == bv,
+        _ => false,
+    }
+}
+
+/// Defaults the namespace of an object, as for all objects that are linted.
+fn with_namespace<K: kube::Resource>(mut obj: K) -> K {
+    let ns = namespace(obj.meta()).to_string();
+    obj.meta_mut().namespace = Some(ns);
+    obj
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gateway(listeners: serde_json::Value) -> GatewayApiObject {
+        GatewayApiObject::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "web", "namespace": "infra" },
+            "spec": { "gatewayClassName": "acme", "listeners": listeners },
+        }))
+        .unwrap()
+    }
+
+    fn route(
+        namespace: &str,
+        name: &str,
+        created: &str,
+        rules: serde_json::Value,
+    ) -> GatewayApiObject {
+        GatewayApiObject::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": name, "namespace": namespace, "creationTimestamp": created },
+            "spec": {
+                "parentRefs": [{ "name": "web", "namespace": "infra" }],
+                "rules": rules,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn path_match(value: serde_json::Value) -> HttpRouteMatch {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn reports_rules_shadowed_within_a_route() {
+        let objects = [
+            gateway(json!([{ "name": "http", "port": 80, "protocol": "HTTP" }])),
+            route(
+                "infra",
+                "app",
+                "2020-01-01T00:00:00Z",
+                json!([
+                    { "matches": [{ "path": { "type": "PathPrefix", "value": "/api/" } }] },
+                    { "matches": [{ "path": { "type": "PathPrefix", "value": "/api" } }] },
+                    { "matches": [{ "path": { "type": "Exact", "value": "/" } }] },
+                ]),
+            ),
+        ];
+        let findings = shadowed_rules(&Compiler::default(), &objects);
+        assert_eq!(findings.len(), 1, "{:#?}", findings);
+        assert_eq!(findings[0].code, LintCode::ShadowedRule);
+        assert_eq!(findings[0].path.to_string(), "spec.rules[1]");
+        assert_eq!(
+            findings[0].message,
+            "rule is shadowed by the higher-precedence rule 0"
+        );
+    }
+
+    #[test]
+    fn reports_routes_shadowed_by_older_routes() {
+        let objects = [
+            gateway(json!([{ "name": "http", "port": 80, "protocol": "HTTP" }])),
+            route(
+                "infra",
+                "old",
+                "2020-01-01T00:00:00Z",
+                json!([{
+                    "matches": [{
+                        "path": { "type": "Exact", "value": "/x" },
+                        "headers": [{ "name": "Foo", "value": "bar" }],
+                    }],
+                }]),
+            ),
+            route(
+                "infra",
+                "new",
+                "2021-01-01T00:00:00Z",
+                json!([{
+                    "matches": [{
+                        "path": { "type": "Exact", "value": "/x" },
+                        "headers": [{ "name": "foo", "value": "bar" }],
+                    }],
+                }]),
+            ),
+        ];
+        let findings = shadowed_rules(&Compiler::default(), &objects);
+        assert_eq!(findings.len(), 1, "{:#?}", findings);
+        assert_eq!(findings[0].name, "new");
+        assert_eq!(findings[0].path.to_string(), "spec.rules");
+        assert_eq!(
+            findings[0].message,
+            "all rules are shadowed by higher-precedence rules, e.g. rule 0 of HTTPRoute infra/old"
+        );
+    }
+
+    #[test]
+    fn ignores_rules_that_are_selected_in_some_virtual_host() {
+        let objects = [
+            gateway(json!([
+                { "name": "a", "port": 80, "protocol": "HTTP", "hostname": "a.example.com" },
+                { "name": "b", "port": 80, "protocol": "HTTP", "hostname": "b.example.com" },
+            ])),
+            GatewayApiObject::from_value(json!({
+                "apiVersion": "gateway.networking.k8s.io/v1beta1",
+                "kind": "HTTPRoute",
+                "metadata": {
+                    "name": "old",
+                    "namespace": "infra",
+                    "creationTimestamp": "2020-01-01T00:00:00Z",
+                },
+                "spec": {
+                    "parentRefs": [{ "name": "web", "namespace": "infra" }],
+                    "hostnames": ["a.example.com"],
+                    "rules": [{}],
+                },
+            }))
+            .unwrap(),
+            route("infra", "new", "2021-01-01T00:00:00Z", json!([{}])),
+        ];
+        assert_eq!(shadowed_rules(&Compiler::default(), &objects), []);
+    }
+
+    #[test]
+    fn attaches_routes_with_the_compiler() {
+        let objects = [
+            gateway(json!([{
+                "name": "http",
+                "port": 80,
+                "protocol": "HTTP",
+                "allowedRoutes": {
+                    "namespaces": {
+                        "from": "Selector",
+                        "selector": { "matchLabels": { "gateway": "web" } },
+                    },
+                },
+            }])),
+            route("apps", "old", "2020-01-01T00:00:00Z", json!([{}])),
+            route("apps", "new", "2021-01-01T00:00:00Z", json!([{}])),
+        ];
+        assert_eq!(shadowed_rules(&Compiler::default(), &objects), []);
+
+        let labels = BTreeMap::from([("gateway".to_string(), "web".to_string())]);
+        let compiler = Compiler::default().with_namespace_labels("apps", labels);
+        let findings = shadowed_rules(&compiler, &objects);
+        assert_eq!(findings.len(), 1, "{:#?}", findings);
+        assert_eq!(findings[0].name, "new");
+    }
+
+    #[test]
+    fn covers_matches() {
+        let cases = [
+            (
+                json!({}),
+                json!({ "path": { "type": "Exact", "value": "/x" } }),
+                true,
+            ),
+            (
+                json!({ "path": { "type": "PathPrefix", "value": "/a" } }),
+                json!({ "path": { "type": "PathPrefix", "value": "/a/b/" } }),
+                true,
+            ),
+            (
+                json!({ "path": { "type": "PathPrefix", "value": "/a" } }),
+                json!({ "path": { "type": "PathPrefix", "value": "/ab" } }),
+                false,
+            ),
+            (
+                json!({ "path": { "type": "Exact", "value": "/a" } }),
+                json!({ "path": { "type": "PathPrefix", "value": "/a" } }),
+                false,
+            ),
+            (
+                json!({ "path": { "type": "RegularExpression", "value": "/a.*" } }),
+                json!({ "path": { "type": "RegularExpression", "value": "/a.*" } }),
+                true,
+            ),
+            (
+                json!({ "path": { "type": "RegularExpression", "value": "/a.*" } }),
+                json!({ "path": { "type": "Exact", "value": "/ab" } }),
+                false,
+            ),
+            (json!({ "method": "GET" }), json!({}), false),
+            (json!({}), json!({ "method": "GET" }), true),
+            (
+                json!({ "headers": [{ "name": "Foo", "value": "bar" }] }),
+                json!({ "headers": [{ "name": "foo", "value": "bar" }, { "name": "x", "value": "y" }] }),
+                true,
+            ),
+            (
+                json!({ "headers": [{ "name": "foo", "value": "bar" }] }),
+                json!({ "headers": [{ "type": "RegularExpression", "name": "foo", "value": "bar" }] }),
+                false,
+            ),
+            (
+                json!({ "queryParams": [{ "name": "q", "value": "1" }] }),
+                json!({}),
+                false,
+            ),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(
+                covers(&path_match(a.clone()), &path_match(b.clone())),
+                expected,
+                "{} covers {}",
+                a,
+                b
+            );
+        }
+    }
+}
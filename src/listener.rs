@@ -0,0 +1,79 @@
+//! Listener selection and isolation.
+//!
+//! Listeners on the same port may have overlapping hostnames, e.g.
+//! `*.example.com` and `foo.example.com`. Listeners are isolated from each
+//! other (GEP-1294): a request is handled only by the listener with the most
+//! specific hostname that matches it, so routes attached to the
+//! `*.example.com` listener never handle requests for `foo.example.com`,
+//! even when they list that hostname.
+//!
+//! A listener hostname is more specific than another if it is an exact
+//! hostname and the other is a wildcard, if both are wildcards and it has
+//! more characters, or if the other listener has no hostname at all.
+
+use crate::*;
+
+/// Returns the listener on `port` that handles requests for `host`, i.e.
+/// the listener with the most specific hostname that matches it.
+///
+/// `host` must not include a port. Listeners with equally specific hostnames
+/// (which necessarily conflict) are resolved in favor of the first.
+pub fn select<'l>(
+    listeners: impl IntoIterator<Item = &'l Listener>,
+    port: PortNumber,
+    host: &str,
+) -> Option<&'l Listener> {
+    let mut selected: Option<&'l Listener> = None;
+    for listener in listeners {
+        if listener.port != port || !hostname_matches(listener.hostname.as_deref(), host) {
+            continue;
+        }
+        let more_specific = selected.map_or(true, |s| {
+            specificity(listener.hostname.as_deref()) > specificity(s.hostname.as_deref())
+        });
+        if more_specific {
+            selected = Some(listener);
+        }
+    }
+    selected
+}
+
+/// Returns true if requests for `hostname` that match `listener` are instead
+/// handled by another listener on the same port with a more specific
+/// hostname.
+///
+/// `hostname` may be a wildcard, in which case it is isolated if another
+/// listener's hostname covers all of the hostnames it matches. Route
+/// hostnames that are isolated from a listener must be ignored when
+/// attaching routes to it.
+pub fn is_isolated<'l>(
+    listeners: impl IntoIterator<Item = &'l Listener>,
+    listener: &Listener,
+    hostname: &str,
+) -> bool {
+    let own = specificity(listener.hostname.as_deref());
+    listeners.into_iter().any(|other| {
+        other.port == listener.port
+            && other.name != listener.name
+            && specificity(other.hostname.as_deref()) > own
+            && hostname_matches(other.hostname.as_deref(), hostname)
+    })
+}
+
+/// Returns true if a listener `hostname` matches `host`, which may itself be
+/// a wildcard. A listener without a hostname matches all hosts.
+pub(crate) fn hostname_matches(hostname: Option<&str>, host: &str) -> bool {
+    match hostname {
+        None => true,
+        Some(pattern) => hostname::matches(pattern, host).is_some(),
+    }
+}
+
+/// Orders listener hostnames from least to most specific.
+pub(crate) fn specificity(hostname: Option<&str>) -> (u8, usize) {
+    match hostname {
+        None => (0, 0),
+        Some(h) if h.starts_with('*') => (1, h.len()),
+        Some(h) => (2, h.len()),
+    }
+}
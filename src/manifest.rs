@@ -0,0 +1,484 @@
+//! Loading of Gateway API objects from manifests.
+//!
+//! Manifests frequently mix several kinds (and several API versions of the
+//! same kind) in a single multi-document YAML stream. [`GatewayApiObject`]
+//! models any object known to this crate so that such manifests can be
+//! ingested in a single call.
+//!
+//! Manifests that hold a single object of a known kind, as CLIs and tests
+//! commonly read, can be decoded directly into that kind:
+//!
+//! ```ignore
+//! let route = HttpRoute::from_yaml(&std::fs::read_to_string(path)?)?;
+//! println!("{}", route.to_yaml()?);
+//! ```
+//!
+//! [`detect_kind`] identifies the kind of such a manifest without decoding
+//! it, e.g. to dispatch to a kind-specific handler.
+
+#[cfg(feature = "yaml")]
+use crate::{canonical, unversioned};
+use crate::{consts, *};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::fmt;
+
+/// Any Gateway API object known to this crate.
+#[derive(Clone, Debug)]
+pub enum GatewayApiObject {
+    GatewayClass(GatewayClass),
+    Gateway(Gateway),
+    HttpRoute(HttpRoute),
+
+    #[cfg(feature = "experimental")]
+    BackendLbPolicy(BackendLbPolicy),
+
+    #[cfg(feature = "experimental")]
+    GrpcRoute(GrpcRoute),
+
+    #[cfg(feature = "experimental")]
+    ReferenceGrant(ReferenceGrant),
+
+    #[cfg(feature = "experimental")]
+    TcpRoute(TcpRoute),
+
+    #[cfg(feature = "experimental")]
+    TlsRoute(TlsRoute),
+
+    #[cfg(feature = "experimental")]
+    UdpRoute(UdpRoute),
+
+    #[cfg(feature = "experimental")]
+    XBackendTrafficPolicy(XBackendTrafficPolicy),
+}
+
+/// Errors encountered while loading Gateway API objects.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input could not be parsed as YAML.
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Error),
+
+    /// The object does not have an `apiVersion` and a `kind`.
+    MissingTypeMeta,
+
+    /// The `apiVersion` and `kind` do not identify an object known to this
+    /// crate.
+    UnknownKind { api_version: String, kind: String },
+
+    /// The object is known to this crate, but is not of the kind it was
+    /// decoded as.
+    UnexpectedKind {
+        expected: &'static str,
+        api_version: String,
+        kind: String,
+    },
+
+    /// The object could not be decoded as the kind it claims to be.
+    Decode {
+        kind: &'static str,
+        source: serde_json::Error,
+    },
+}
+
+/// Parses all Gateway API objects from a (possibly multi-document) YAML
+/// manifest.
+///
+/// Empty documents and documents that are not in a Gateway API group (e.g.
+/// Services or Deployments that are commonly bundled with routes) are
+/// skipped. `v1` `List` documents are
+/// flattened into their items.
+#[cfg(feature = "yaml")]
+pub fn parse_yaml(bytes: &[u8]) -> Result<Vec<GatewayApiObject>, Error> {
+    use serde::Deserialize;
+
+    let mut objects = Vec::new();
+    for doc in serde_yaml::Deserializer::from_slice(bytes) {
+        let value = serde_json::Value::deserialize(doc).map_err(Error::Yaml)?;
+        collect(value, &mut objects)?;
+    }
+    Ok(objects)
+}
+
+/// Returns the kind of the single object in a YAML manifest, e.g.
+/// `HTTPRoute`, if it is a Gateway API object known to this crate in a
+/// version in which its kind is served.
+#[cfg(feature = "yaml")]
+pub fn detect_kind(yaml: &str) -> Result<&'static str, Error> {
+    let value = serde_yaml::from_str::<serde_json::Value>(yaml).map_err(Error::Yaml)?;
+    let (api_version, kind) = type_meta(&value)?;
+    known_kind(api_version, kind)
+        .map(|k| k.kind)
+        .ok_or_else(|| Error::unknown_kind(api_version, kind))
+}
+
+/// Decodes the single object in a YAML manifest as kind `K`, from any version
+/// in which the kind is served.
+#[cfg(feature = "yaml")]
+fn from_yaml<K>(yaml: &str) -> Result<K, Error>
+where
+    K: kube::Resource<DynamicType = ()> + serde::de::DeserializeOwned,
+{
+    let value = serde_yaml::from_str::<serde_json::Value>(yaml).map_err(Error::Yaml)?;
+    let (api_version, kind) = type_meta(&value)?;
+    if known_kind(api_version, kind).is_none() {
+        return Err(Error::unknown_kind(api_version, kind));
+    }
+
+    let expected = unversioned::kind_name(&K::kind(&()));
+    if kind != expected {
+        return Err(Error::UnexpectedKind {
+            expected,
+            api_version: api_version.to_string(),
+            kind: kind.to_string(),
+        });
+    }
+    serde_json::from_value(value).map_err(|source| Error::Decode {
+        kind: expected,
+        source,
+    })
+}
+
+macro_rules! impl_yaml {
+    ($($(#[$attr:meta])* $ty:ty),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[cfg(feature = "yaml")]
+            impl $ty {
+                /// Decodes the single object in a YAML manifest, which may be
+                /// written in any version in which the kind is served.
+                pub fn from_yaml(yaml: &str) -> Result<Self, Error> {
+                    from_yaml(yaml)
+                }
+
+                /// Encodes this object as a YAML manifest, omitting unset
+                /// fields.
+                pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+                    canonical::to_yaml(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_yaml!(
+    crate::GatewayClass,
+    crate::Gateway,
+    crate::HttpRoute,
+    #[cfg(feature = "experimental")]
+    crate::BackendLbPolicy,
+    #[cfg(feature = "experimental")]
+    crate::GrpcRoute,
+    #[cfg(feature = "experimental")]
+    crate::ReferenceGrant,
+    #[cfg(feature = "experimental")]
+    crate::TcpRoute,
+    #[cfg(feature = "experimental")]
+    crate::TlsRoute,
+    #[cfg(feature = "experimental")]
+    crate::UdpRoute,
+    #[cfg(feature = "experimental")]
+    crate::XBackendTrafficPolicy,
+);
+
+#[cfg(feature = "yaml")]
+fn collect(value: serde_json::Value, objects: &mut Vec<GatewayApiObject>) -> Result<(), Error> {
+    if value.is_null() {
+        return Ok(());
+    }
+
+    let (api_version, kind) = type_meta(&value)?;
+    if api_version == "v1" && kind == "List" {
+        if let serde_json::Value::Object(mut list) = value {
+            if let Some(serde_json::Value::Array(items)) = list.remove("items") {
+                for item in items {
+                    collect(item, objects)?;
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    if !is_gateway_api(api_version) {
+        return Ok(());
+    }
+
+    objects.push(GatewayApiObject::from_value(value)?);
+    Ok(())
+}
+
+/// Returns the kind identified by an `apiVersion` and `kind`, if this crate
+/// decodes it in that version.
+pub(crate) fn known_kind(api_version: &str, kind: &str) -> Option<&'static consts::KindInfo> {
+    let (group, version) = api_version.split_once('/')?;
+    consts::KINDS.iter().find(|k| {
+        k.group == group && k.kind == kind && k.versions.contains(&version) && k.is_modeled()
+    })
+}
+
+/// Returns whether an `apiVersion` is in one of the Gateway API groups.
+#[cfg(feature = "yaml")]
+pub(crate) fn is_gateway_api(api_version: &str) -> bool {
+    let group = api_version.split_once('/').map_or("", |(g, _)| g);
+    consts::KINDS.iter().any(|k| k.group == group)
+}
+
+fn type_meta(value: &serde_json::Value) -> Result<(&str, &str), Error> {
+    let api_version = value.get("apiVersion").and_then(|v| v.as_str());
+    let kind = value.get("kind").and_then(|v| v.as_str());
+    match (api_version, kind) {
+        (Some(api_version), Some(kind)) => Ok((api_version, kind)),
+        _ => Err(Error::MissingTypeMeta),
+    }
+}
+
+// === impl GatewayApiObject ===
+
+impl GatewayApiObject {
+    /// Decodes an object from its JSON representation, dispatching on its
+    /// `apiVersion` and `kind`.
+    ///
+    /// All served versions of a kind are accepted and decoded into the type
+    /// defined by this crate.
+    pub fn from_value(value: serde_json::Value) -> Result<Self, Error> {
+        fn decode<T: serde::de::DeserializeOwned>(
+            kind: &'static str,
+            value: serde_json::Value,
+        ) -> Result<T, Error> {
+            serde_json::from_value(value).map_err(|source| Error::Decode { kind, source })
+        }
+
+        let (api_version, kind) = type_meta(&value)?;
+        let kind = match known_kind(api_version, kind) {
+            Some(known) => known.kind,
+            None => return Err(Error::unknown_kind(api_version, kind)),
+        };
+
+        match kind {
+            consts::kind::GATEWAY_CLASS => decode(kind, value).map(Self::GatewayClass),
+            consts::kind::GATEWAY => decode(kind, value).map(Self::Gateway),
+            consts::kind::HTTP_ROUTE => decode(kind, value).map(Self::HttpRoute),
+
+            #[cfg(feature = "experimental")]
+            consts::kind::BACKEND_LB_POLICY => decode(kind, value).map(Self::BackendLbPolicy),
+            #[cfg(feature = "experimental")]
+            consts::kind::GRPC_ROUTE => decode(kind, value).map(Self::GrpcRoute),
+            #[cfg(feature = "experimental")]
+            consts::kind::REFERENCE_GRANT => decode(kind, value).map(Self::ReferenceGrant),
+            #[cfg(feature = "experimental")]
+            consts::kind::TCP_ROUTE => decode(kind, value).map(Self::TcpRoute),
+            #[cfg(feature = "experimental")]
+            consts::kind::TLS_ROUTE => decode(kind, value).map(Self::TlsRoute),
+            #[cfg(feature = "experimental")]
+            consts::kind::UDP_ROUTE => decode(kind, value).map(Self::UdpRoute),
+            #[cfg(feature = "experimental")]
+            consts::kind::X_BACKEND_TRAFFIC_POLICY => {
+                decode(kind, value).map(Self::XBackendTrafficPolicy)
+            }
+
+            _ => Err(Error::unknown_kind(api_version, kind)),
+        }
+    }
+
+    /// Decodes the single object in a YAML manifest, dispatching on its
+    /// `apiVersion` and `kind`.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Self, Error> {
+        let value = serde_yaml::from_str(yaml).map_err(Error::Yaml)?;
+        Self::from_value(value)
+    }
+
+    /// Encodes the object as a YAML manifest, omitting unset fields.
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        canonical::to_yaml(self)
+    }
+
+    /// Returns the kind of the object, e.g. `HTTPRoute`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::GatewayClass(_) => "GatewayClass",
+            Self::Gateway(_) => "Gateway",
+            Self::HttpRoute(_) => "HTTPRoute",
+            #[cfg(feature = "experimental")]
+            Self::BackendLbPolicy(_) => "BackendLBPolicy",
+            #[cfg(feature = "experimental")]
+            Self::GrpcRoute(_) => "GRPCRoute",
+            #[cfg(feature = "experimental")]
+            Self::ReferenceGrant(_) => "ReferenceGrant",
+            #[cfg(feature = "experimental")]
+            Self::TcpRoute(_) => "TCPRoute",
+            #[cfg(feature = "experimental")]
+            Self::TlsRoute(_) => "TLSRoute",
+            #[cfg(feature = "experimental")]
+            Self::UdpRoute(_) => "UDPRoute",
+            #[cfg(feature = "experimental")]
+            Self::XBackendTrafficPolicy(_) => "XBackendTrafficPolicy",
+        }
+    }
+
+    /// Returns the object's metadata.
+    pub fn metadata(&self) -> &metav1::ObjectMeta {
+        match self {
+            Self::GatewayClass(o) => &o.metadata,
+            Self::Gateway(o) => &o.metadata,
+            Self::HttpRoute(o) => &o.metadata,
+            #[cfg(feature = "experimental")]
+            Self::BackendLbPolicy(o) => &o.metadata,
+            #[cfg(feature = "experimental")]
+            Self::GrpcRoute(o) => &o.metadata,
+            #[cfg(feature = "experimental")]
+            Self::ReferenceGrant(o) => &o.metadata,
+            #[cfg(feature = "experimental")]
+            Self::TcpRoute(o) => &o.metadata,
+            #[cfg(feature = "experimental")]
+            Self::TlsRoute(o) => &o.metadata,
+            #[cfg(feature = "experimental")]
+            Self::UdpRoute(o) => &o.metadata,
+            #[cfg(feature = "experimental")]
+            Self::XBackendTrafficPolicy(o) => &o.metadata,
+        }
+    }
+
+    /// Returns the object's name, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.metadata().name.as_deref()
+    }
+
+    /// Returns the object's namespace, if it has one.
+    pub fn namespace(&self) -> Option<&str> {
+        self.metadata().namespace.as_deref()
+    }
+}
+
+impl serde::Serialize for GatewayApiObject {
+    fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::GatewayClass(o) => o.serialize(ser),
+            Self::Gateway(o) => o.serialize(ser),
+            Self::HttpRoute(o) => o.serialize(ser),
+            #[cfg(feature = "experimental")]
+            Self::BackendLbPolicy(o) => o.serialize(ser),
+            #[cfg(feature = "experimental")]
+            Self::GrpcRoute(o) => o.serialize(ser),
+            #[cfg(feature = "experimental")]
+            Self::ReferenceGrant(o) => o.serialize(ser),
+            #[cfg(feature = "experimental")]
+            Self::TcpRoute(o) => o.serialize(ser),
+            #[cfg(feature = "experimental")]
+            Self::TlsRoute(o) => o.serialize(ser),
+            #[cfg(feature = "experimental")]
+            Self::UdpRoute(o) => o.serialize(ser),
+            #[cfg(feature = "experimental")]
+            Self::XBackendTrafficPolicy(o) => o.serialize(ser),
+        }
+    }
+}
+
+impl From<GatewayClass> for GatewayApiObject {
+    fn from(o: GatewayClass) -> Self {
+        Self::GatewayClass(o)
+    }
+}
+
+impl From<Gateway> for GatewayApiObject {
+    fn from(o: Gateway) -> Self {
+        Self::Gateway(o)
+    }
+}
+
+impl From<HttpRoute> for GatewayApiObject {
+    fn from(o: HttpRoute) -> Self {
+        Self::HttpRoute(o)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<BackendLbPolicy> for GatewayApiObject {
+    fn from(o: BackendLbPolicy) -> Self {
+        Self::BackendLbPolicy(o)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<GrpcRoute> for GatewayApiObject {
+    fn from(o: GrpcRoute) -> Self {
+        Self::GrpcRoute(o)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<ReferenceGrant> for GatewayApiObject {
+    fn from(o: ReferenceGrant) -> Self {
+        Self::ReferenceGrant(o)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<TcpRoute> for GatewayApiObject {
+    fn from(o: TcpRoute) -> Self {
+        Self::TcpRoute(o)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<TlsRoute> for GatewayApiObject {
+    fn from(o: TlsRoute) -> Self {
+        Self::TlsRoute(o)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<UdpRoute> for GatewayApiObject {
+    fn from(o: UdpRoute) -> Self {
+        Self::UdpRoute(o)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<XBackendTrafficPolicy> for GatewayApiObject {
+    fn from(o: XBackendTrafficPolicy) -> Self {
+        Self::XBackendTrafficPolicy(o)
+    }
+}
+
+// === impl Error ===
+
+impl Error {
+    fn unknown_kind(api_version: &str, kind: &str) -> Self {
+        Self::UnknownKind {
+            api_version: api_version.to_string(),
+            kind: kind.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "yaml")]
+            Self::Yaml(e) => write!(f, "invalid YAML: {}", e),
+            Self::MissingTypeMeta => write!(f, "object is missing apiVersion or kind"),
+            Self::UnknownKind { api_version, kind } => {
+                write!(f, "unknown Gateway API kind: {} {}", api_version, kind)
+            }
+            Self::UnexpectedKind {
+                expected,
+                api_version,
+                kind,
+            } => write!(f, "expected {}, found {} {}", expected, api_version, kind),
+            Self::Decode { kind, source } => write!(f, "invalid {}: {}", kind, source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "yaml")]
+            Self::Yaml(e) => Some(e),
+            Self::Decode { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Evaluation of route matches against requests.
+//!
+//! Dataplanes that consume routes directly (rather than through a proxy
+//! configuration) need to decide whether a request satisfies a rule's
+//! matches. [`Matcher`] implements the matching semantics described by the
+//! Gateway API for HTTPRoute and, with the `experimental` feature, GRPCRoute,
+//! against a minimal, borrowed view of a request.
+//!
+//! The dialect of `RegularExpression` matches is implementation-specific, so
+//! regular expressions are evaluated by a caller-provided [`RegexEngine`]. By
+//! default, a matcher uses [`NoRegex`], with which regular expression matches
+//! never match.
+//!
+//! ```
+//! # use k8s_gateway_api::{HttpPathMatch, HttpRouteMatch, matcher::{HttpRequest, Matcher}};
+//! let m = HttpRouteMatch {
+//!     path: Some(HttpPathMatch::PathPrefix { value: "/api".to_string() }),
+//!     ..HttpRouteMatch::default()
+//! };
+//! let req = HttpRequest::new("GET", "/api/users?limit=10");
+//! assert!(Matcher::new().http_match(&m, &req));
+//! ```
+
+use crate::*;
+
+/// The parts of an HTTP request that may be matched by an HTTPRoute.
+#[derive(Copy, Clone, Debug)]
+pub struct HttpRequest<'a> {
+    /// The request method, e.g. `GET`.
+    pub method: &'a str,
+
+    /// The request path, without the query string.
+    pub path: &'a str,
+
+    /// The raw query string, without the leading `?`.
+    pub query: Option<&'a str>,
+
+    /// The request headers. Repeated headers may appear more than once.
+    pub headers: &'a [HttpHeader],
+}
+
+/// The parts of a gRPC request that may be matched by a GRPCRoute.
+#[cfg(feature = "experimental")]
+#[derive(Copy, Clone, Debug)]
+pub struct GrpcRequest<'a> {
+    /// The fully-qualified service name, e.g. `helloworld.Greeter`.
+    pub service: &'a str,
+
+    /// The method name, e.g. `SayHello`.
+    pub method: &'a str,
+
+    /// The request headers. Repeated headers may appear more than once.
+    pub headers: &'a [HttpHeader],
+}
+
+/// Evaluates `RegularExpression` matches.
+///
+/// Implementations decide the regular expression dialect and whether patterns
+/// are anchored.
+pub trait RegexEngine {
+    /// Returns true if `value` matches `pattern`.
+    fn is_match(&self, pattern: &str, value: &str) -> bool;
+}
+
+/// A [`RegexEngine`] for implementations that do not support regular
+/// expressions; no value matches any pattern.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoRegex;
+
+/// Evaluates route matches against requests.
+#[derive(Clone, Debug, Default)]
+pub struct Matcher<R = NoRegex> {
+    regex: R,
+}
+
+// === impl HttpRequest ===
+
+impl<'a> HttpRequest<'a> {
+    /// Returns a request without headers from a method and a request target
+    /// (i.e. a path and an optional query string).
+    pub fn new(method: &'a str, target: &'a str) -> Self {
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (target, None),
+        };
+        Self {
+            method,
+            path,
+            query,
+            headers: &[],
+        }
+    }
+
+    /// Sets the request headers.
+    pub fn with_headers(self, headers: &'a [HttpHeader]) -> Self {
+        Self { headers, ..self }
+    }
+
+    /// Returns the values of the query parameter `name`, in order.
+    ///
+    /// Parameter names are compared case-sensitively and values are not
+    /// percent-decoded.
+    pub fn query_params(&self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.query
+            .into_iter()
+            .flat_map(|q| q.split('&'))
+            .filter_map(move |param| {
+                let (n, v) = param.split_once('=').unwrap_or((param, ""));
+                if n == name {
+                    Some(v)
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+// === impl GrpcRequest ===
+
+#[cfg(feature = "experimental")]
+impl<'a> GrpcRequest<'a> {
+    /// Returns a request from the `:path` of an HTTP/2 gRPC request, i.e.
+    /// `/<service>/<method>`, or `None` if the path is not of that form.
+    pub fn from_path(path: &'a str) -> Option<Self> {
+        let (service, method) = path.strip_prefix('/')?.split_once('/')?;
+        if service.is_empty() || method.is_empty() || method.contains('/') {
+            return None;
+        }
+        Some(Self {
+            service,
+            method,
+            headers: &[],
+        })
+    }
+
+    /// Sets the request headers.
+    pub fn with_headers(self, headers: &'a [HttpHeader]) -> Self {
+        Self { headers, ..self }
+    }
+}
+
+// === impl RegexEngine ===
+
+impl<F: Fn(&str, &str) -> bool> RegexEngine for F {
+    fn is_match(&self, pattern: &str, value: &str) -> bool {
+        (self)(pattern, value)
+    }
+}
+
+impl RegexEngine for NoRegex {
+    fn is_match(&self, _: &str, _: &str) -> bool {
+        false
+    }
+}
+
+// === impl Matcher ===
+
+impl Matcher {
+    /// Returns a matcher that does not support regular expressions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<R: RegexEngine> Matcher<R> {
+    /// Evaluates `RegularExpression` matches with the given engine.
+    pub fn with_regex<S: RegexEngine>(self, regex: S) -> Matcher<S> {
+        Matcher { regex }
+    }
+
+    /// Returns the index of the first of a rule's matches that `req`
+    /// satisfies.
+    ///
+    /// A rule without matches matches all requests, as if it had a single
+    /// `PathPrefix` match on `/`; `Some(0)` is returned in that case.
+    pub fn http_rule(&self, rule: &HttpRouteRule, req: &HttpRequest<'_>) -> Option<usize> {
+        match rule.matches.as_deref() {
+            None | Some([]) => Some(0),
+            Some(matches) => matches.iter().position(|m| self.http_match(m, req)),
+        }
+    }
+
+    /// Returns true if `req` satisfies all of the conditions of `m`.
+    pub fn http_match(&self, m: &HttpRouteMatch, req: &HttpRequest<'_>) -> bool {
+        if let Some(method) = m.method.as_deref() {
+            if method != req.method {
+                return false;
+            }
+        }
+
+        if let Some(path) = &m.path {
+            if !self.http_path(path, req.path) {
+                return false;
+            }
+        }
+
+        let headers = m.headers.iter().flatten().map(|h| match h {
+            HttpHeaderMatch::Exact { name, value } => Some((name, false, value)),
+            HttpHeaderMatch::RegularExpression { name, value } => Some((name, true, value)),
+            HttpHeaderMatch::Unknown(_) => None,
+        });
+        if !self.headers(headers, req.headers) {
+            return false;
+        }
+
+        // Query parameter names are compared case-sensitively and only the
+        // first match for each name is considered.
+        let mut seen = Vec::<&str>::new();
+        m.query_params.iter().flatten().all(|q| {
+            let (name, regex, value) = match q {
+                HttpQueryParamMatch::Exact { name, value } => (name, false, value),
+                HttpQueryParamMatch::RegularExpression { name, value } => (name, true, value),
+                HttpQueryParamMatch::Unknown(_) => return false,
+            };
+            if seen.contains(&name.as_str()) {
+                return true;
+            }
+            seen.push(name);
+            req.query_params(name).any(|v| self.value(regex, value, v))
+        })
+    }
+
+    /// Returns true if `path` satisfies the path match.
+    ///
+    /// A `PathPrefix` match compares whole path elements: `/foo` matches
+    /// `/foo` and `/foo/bar`, but not `/foobar`. A trailing `/` in the prefix
+    /// is ignored.
+    pub fn http_path(&self, m: &HttpPathMatch, path: &str) -> bool {
+        match m {
+            HttpPathMatch::Exact { value } => path == value,
+            HttpPathMatch::PathPrefix { value } => {
+                let prefix = value.trim_end_matches('/');
+                match path.strip_prefix(prefix) {
+                    Some(rest) => rest.is_empty() || rest.starts_with('/'),
+                    None => false,
+                }
+            }
+            HttpPathMatch::RegularExpression { value } => self.regex.is_match(value, path),
+            HttpPathMatch::Unknown(_) => false,
+        }
+    }
+
+    /// Returns the index of the first of a rule's matches that `req`
+    /// satisfies.
+    ///
+    /// A rule without matches matches all requests; `Some(0)` is returned in
+    /// that case.
+    #[cfg(feature = "experimental")]
+    pub fn grpc_rule(&self, rule: &GrpcRouteRule, req: &GrpcRequest<'_>) -> Option<usize> {
+        match rule.matches.as_deref() {
+            None | Some([]) => Some(0),
+            Some(matches) => matches.iter().position(|m| self.grpc_match(m, req)),
+        }
+    }
+
+    /// Returns true if `req` satisfies all of the conditions of `m`.
+    #[cfg(feature = "experimental")]
+    pub fn grpc_match(&self, m: &GrpcRouteMatch, req: &GrpcRequest<'_>) -> bool {
+        if let Some(method) = &m.method {
+            if !self.grpc_method(method, req.service, req.method) {
+                return false;
+            }
+        }
+
+        let headers = m.headers.iter().flatten().map(|h| match h {
+            GrpcHeaderMatch::Exact { name, value } => Some((name, false, value)),
+            GrpcHeaderMatch::RegularExpression { name, value } => Some((name, true, value)),
+            GrpcHeaderMatch::Unknown(_) => None,
+        });
+        self.headers(headers, req.headers)
+    }
+
+    /// Returns true if the service and method satisfy the method match.
+    ///
+    /// An omitted or empty service or method matches any value.
+    #[cfg(feature = "experimental")]
+    pub fn grpc_method(&self, m: &GrpcMethodMatch, service: &str, method: &str) -> bool {
+        let (regex, s, m) = match m {
+            GrpcMethodMatch::Exact { service, method } => (false, service, method),
+            GrpcMethodMatch::RegularExpression { service, method } => (true, service, method),
+            GrpcMethodMatch::Unknown(_) => return false,
+        };
+        let matches = |expected: &Option<String>, actual: &str| match expected.as_deref() {
+            None | Some("") => true,
+            Some(expected) => self.value(regex, expected, actual),
+        };
+        matches(s, service) && matches(m, method)
+    }
+
+    /// Returns true if the headers satisfy all of the `(name, is_regex,
+    /// value)` matches. `None` is a match of an unknown type, which no headers
+    /// satisfy.
+    ///
+    /// Header names are compared case-insensitively and only the first match
+    /// for each name is considered. A repeated request header satisfies a
+    /// match if any of its values does.
+    fn headers<'m>(
+        &self,
+        matches: impl Iterator<Item = Option<(&'m String, bool, &'m String)>>,
+        headers: &[HttpHeader],
+    ) -> bool {
+        let mut seen = Vec::<&str>::new();
+        for m in matches {
+            let (name, regex, value) = match m {
+                Some(m) => m,
+                None => return false,
+            };
+            if seen.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                continue;
+            }
+            seen.push(name);
+
+            let found = headers
+                .iter()
+                .filter(|h| h.name.eq_ignore_ascii_case(name))
+                .any(|h| self.value(regex, value, &h.value));
+            if !found {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn value(&self, regex: bool, expected: &str, actual: &str) -> bool {
+        if regex {
+            self.regex.is_match(expected, actual)
+        } else {
+            expected == actual
+        }
+    }
+}
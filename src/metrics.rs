@@ -0,0 +1,210 @@
+//! Prometheus gauges describing the status of Gateways and routes.
+//!
+//! The functions in this module translate statuses into gauge families with
+//! consistent names and labels, so that implementations expose uniform
+//! metrics. Families are plain data, so they may be registered with any
+//! Prometheus client, or rendered in the text exposition format with
+//! [`encode`]:
+//!
+//! ```
+//! # use k8s_gateway_api::{metrics, Gateway};
+//! # let gateways: Vec<Gateway> = vec![];
+//! let text = metrics::encode(&[
+//!     metrics::gateway_programmed(&gateways),
+//!     metrics::attached_routes(&gateways),
+//! ]);
+//! ```
+
+use crate::{route::AnyRoute, *};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::fmt::Write;
+
+/// The namespace of the object a sample describes.
+pub const NAMESPACE_LABEL: &str = "namespace";
+
+/// The name of the object a sample describes.
+pub const NAME_LABEL: &str = "name";
+
+/// The kind of the route a sample describes, e.g. `HTTPRoute`.
+pub const KIND_LABEL: &str = "kind";
+
+/// The name of the GatewayClass of a Gateway.
+pub const GATEWAY_CLASS_LABEL: &str = "gateway_class";
+
+/// The name of a Gateway's listener.
+pub const LISTENER_LABEL: &str = "listener";
+
+/// The namespace of a route's parent.
+pub const PARENT_NAMESPACE_LABEL: &str = "parent_namespace";
+
+/// The name of a route's parent.
+pub const PARENT_NAME_LABEL: &str = "parent_name";
+
+/// The section of a route's parent, or empty if the route attaches to the
+/// whole parent.
+pub const PARENT_SECTION_LABEL: &str = "parent_section";
+
+/// The name of the controller that wrote a route's parent status.
+pub const CONTROLLER_LABEL: &str = "controller";
+
+/// A family of gauges that share a name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GaugeFamily {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub samples: Vec<Sample>,
+}
+
+/// The value of a gauge with a set of labels.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sample {
+    pub labels: Vec<(&'static str, String)>,
+    pub value: i64,
+}
+
+/// Returns the `gateway_programmed` family: 1 for each Gateway whose
+/// `Programmed` condition is `True`, and 0 for the others.
+pub fn gateway_programmed<'g>(gateways: impl IntoIterator<Item = &'g Gateway>) -> GaugeFamily {
+    let samples = gateways
+        .into_iter()
+        .map(|gw| Sample {
+            labels: vec![
+                (NAMESPACE_LABEL, namespace(&gw.metadata)),
+                (NAME_LABEL, name(&gw.metadata)),
+                (GATEWAY_CLASS_LABEL, gw.spec.gateway_class_name.clone()),
+            ],
+            value: is_true(
+                gw.status.as_ref().and_then(|s| s.conditions.as_deref()),
+                "Programmed",
+            ),
+        })
+        .collect();
+    GaugeFamily {
+        name: "gateway_programmed",
+        help: "Whether the Gateway's Programmed condition is True.",
+        samples,
+    }
+}
+
+/// Returns the `attached_routes` family: the number of routes attached to
+/// each listener, as reported in the Gateways' statuses.
+pub fn attached_routes<'g>(gateways: impl IntoIterator<Item = &'g Gateway>) -> GaugeFamily {
+    let mut samples = Vec::new();
+    for gw in gateways {
+        let listeners = gw.status.as_ref().and_then(|s| s.listeners.as_deref());
+        for listener in listeners.unwrap_or_default() {
+            samples.push(Sample {
+                labels: vec![
+                    (NAMESPACE_LABEL, namespace(&gw.metadata)),
+                    (NAME_LABEL, name(&gw.metadata)),
+                    (LISTENER_LABEL, listener.name.to_string()),
+                ],
+                value: i64::from(listener.attached_routes),
+            });
+        }
+    }
+    GaugeFamily {
+        name: "attached_routes",
+        help: "The number of routes attached to the Gateway listener.",
+        samples,
+    }
+}
+
+/// Returns the `route_accepted` family: for each parent in the routes'
+/// statuses, 1 if the route's `Accepted` condition is `True` and 0
+/// otherwise.
+pub fn route_accepted<'r>(routes: impl IntoIterator<Item = &'r AnyRoute>) -> GaugeFamily {
+    let mut samples = Vec::new();
+    for route in routes {
+        let route_ns = namespace(route.metadata());
+        for parent in route.status_parents() {
+            let parent_ref = &parent.parent_ref;
+            samples.push(Sample {
+                labels: vec![
+                    (KIND_LABEL, route.kind().kind().to_string()),
+                    (NAMESPACE_LABEL, route_ns.clone()),
+                    (NAME_LABEL, name(route.metadata())),
+                    (
+                        PARENT_NAMESPACE_LABEL,
+                        parent_ref
+                            .namespace
+                            .as_ref()
+                            .map_or_else(|| route_ns.clone(), ToString::to_string),
+                    ),
+                    (PARENT_NAME_LABEL, parent_ref.name.to_string()),
+                    (
+                        PARENT_SECTION_LABEL,
+                        parent_ref
+                            .section_name
+                            .as_ref()
+                            .map(ToString::to_string)
+                            .unwrap_or_default(),
+                    ),
+                    (CONTROLLER_LABEL, parent.controller_name.to_string()),
+                ],
+                value: is_true(Some(&parent.conditions), "Accepted"),
+            });
+        }
+    }
+    GaugeFamily {
+        name: "route_accepted",
+        help: "Whether the route's Accepted condition is True for the parent.",
+        samples,
+    }
+}
+
+/// Renders gauge families in the Prometheus text exposition format.
+pub fn encode(families: &[GaugeFamily]) -> String {
+    let mut out = String::new();
+    for family in families {
+        family.encode(&mut out);
+    }
+    out
+}
+
+// === impl GaugeFamily ===
+
+impl GaugeFamily {
+    /// Appends the family to `out` in the Prometheus text exposition format.
+    pub fn encode(&self, out: &mut String) {
+        // Writing to a `String` cannot fail.
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} gauge", self.name);
+        for sample in &self.samples {
+            out.push_str(self.name);
+            if !sample.labels.is_empty() {
+                let labels = sample
+                    .labels
+                    .iter()
+                    .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+                    .collect::<Vec<_>>();
+                let _ = write!(out, "{{{}}}", labels.join(","));
+            }
+            let _ = writeln!(out, " {}", sample.value);
+        }
+    }
+}
+
+fn namespace(meta: &metav1::ObjectMeta) -> String {
+    meta.namespace.clone().unwrap_or_default()
+}
+
+fn name(meta: &metav1::ObjectMeta) -> String {
+    meta.name.clone().unwrap_or_default()
+}
+
+fn is_true(conditions: Option<&[metav1::Condition]>, type_: &str) -> i64 {
+    let is_true = conditions
+        .unwrap_or_default()
+        .iter()
+        .any(|c| c.type_ == type_ && c.status == "True");
+    i64::from(is_true)
+}
+
+/// Escapes a label value: backslashes, double quotes, and line feeds.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
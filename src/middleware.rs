@@ -0,0 +1,236 @@
+//! tower middleware that applies route filters to requests.
+//!
+//! [`FilterLayer`] wraps a service (e.g. a hyper client, or an axum router)
+//! so that the `RequestHeaderModifier`, `URLRewrite`, and `RequestRedirect`
+//! filters of a route are applied to each request before it is forwarded:
+//!
+//! ```ignore
+//! let svc = ServiceBuilder::new()
+//!     .layer(FilterLayer::new(route.filters.clone()).with_matched_prefix("/api"))
+//!     .service(client);
+//! ```
+//!
+//! Requests are redirected by responding directly, without calling the inner
+//! service. `RequestMirror` and `ExtensionRef` filters are ignored, since
+//! their effects depend on the dataplane. Requests to which a filter of an
+//! unknown type applies receive 500 responses.
+
+use crate::{filter, *};
+use http::{
+    header::{self, HeaderValue},
+    uri::{Authority, PathAndQuery},
+    Request, Response, StatusCode, Uri,
+};
+use std::{
+    convert::TryFrom,
+    future::{ready, Ready},
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{util::Either, BoxError, Layer, Service};
+
+/// Applies a chain of route filters to requests.
+#[derive(Clone, Debug)]
+pub struct FilterLayer {
+    filters: Arc<[HttpRouteFilter]>,
+    matched_prefix: Arc<str>,
+}
+
+/// A service that applies a chain of route filters to requests before
+/// forwarding them to an inner service.
+#[derive(Clone, Debug)]
+pub struct Filter<S> {
+    inner: S,
+    filters: Arc<[HttpRouteFilter]>,
+    matched_prefix: Arc<str>,
+}
+
+// === impl FilterLayer ===
+
+impl FilterLayer {
+    /// Returns a layer that applies `filters` in order.
+    pub fn new(filters: impl IntoIterator<Item = HttpRouteFilter>) -> Self {
+        Self {
+            filters: filters.into_iter().collect(),
+            matched_prefix: "/".into(),
+        }
+    }
+
+    /// Sets the `PathPrefix` that requests matched, which `ReplacePrefixMatch`
+    /// path modifiers replace. Defaults to `/`, the prefix matched by rules
+    /// without matches.
+    pub fn with_matched_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.matched_prefix = prefix.into().into();
+        self
+    }
+}
+
+impl<S> Layer<S> for FilterLayer {
+    type Service = Filter<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Filter {
+            inner,
+            filters: self.filters.clone(),
+            matched_prefix: self.matched_prefix.clone(),
+        }
+    }
+}
+
+// === impl Filter ===
+
+impl<S, B, R> Service<Request<B>> for Filter<S>
+where
+    S: Service<Request<B>, Response = Response<R>>,
+    S::Error: Into<BoxError>,
+    R: Default,
+{
+    type Response = Response<R>;
+    type Error = BoxError;
+    type Future = Either<S::Future, Ready<Result<Response<R>, BoxError>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        for f in self.filters.iter() {
+            match f {
+                HttpRouteFilter::RequestHeaderModifier {
+                    request_header_modifier,
+                } => filter::modify_request_headers(request_header_modifier, req.headers_mut()),
+                HttpRouteFilter::URLRewrite { url_rewrite } => {
+                    rewrite(&mut req, url_rewrite, &self.matched_prefix)
+                }
+                HttpRouteFilter::RequestRedirect { request_redirect } => {
+                    let rsp = redirect(&req, request_redirect, &self.matched_prefix);
+                    return Either::B(ready(Ok(rsp)));
+                }
+                HttpRouteFilter::RequestMirror { .. } | HttpRouteFilter::ExtensionRef { .. } => {}
+                #[cfg(feature = "unknown-filters")]
+                HttpRouteFilter::Unknown { .. } => {
+                    let mut rsp = Response::new(R::default());
+                    *rsp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                    return Either::B(ready(Ok(rsp)));
+                }
+            }
+        }
+        Either::A(self.inner.call(req))
+    }
+}
+
+/// Rewrites the host and path of a request, preserving its query.
+fn rewrite<B>(req: &mut Request<B>, rewrite: &HttpUrlRewriteFilter, prefix: &str) {
+    let mut parts = req.uri().clone().into_parts();
+
+    if let Some(hostname) = &rewrite.hostname {
+        if let Ok(value) = HeaderValue::from_str(hostname) {
+            req.headers_mut().insert(header::HOST, value);
+        }
+        // Absolute-form (e.g. HTTP/2) requests carry the host in their URI.
+        if let Some(authority) = &parts.authority {
+            let authority = match authority.port_u16() {
+                Some(port) => Authority::try_from(format!("{}:{}", hostname, port)),
+                None => Authority::try_from(hostname.as_str()),
+            };
+            if let Ok(authority) = authority {
+                parts.authority = Some(authority);
+            }
+        }
+    }
+
+    if let Some(modifier) = &rewrite.path {
+        let path = rewrite_path(modifier, prefix, req.uri().path());
+        let path_and_query = match req.uri().query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path,
+        };
+        if let Ok(path_and_query) = PathAndQuery::try_from(path_and_query) {
+            parts.path_and_query = Some(path_and_query);
+        }
+    }
+
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *req.uri_mut() = uri;
+    }
+}
+
+/// Returns a response that redirects a request.
+///
+/// The scheme, hostname, and port of the request are used unless the filter
+/// overrides them. If the filter sets the scheme but not the port, the port
+/// is omitted; well-known ports are always omitted.
+fn redirect<B, R: Default>(
+    req: &Request<B>,
+    redirect: &HttpRequestRedirectFilter,
+    prefix: &str,
+) -> Response<R> {
+    let scheme = redirect
+        .scheme
+        .as_ref()
+        .map(Scheme::as_str)
+        .or_else(|| req.uri().scheme_str())
+        .unwrap_or("http");
+
+    let authority = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.parse::<Authority>().ok())
+        .or_else(|| req.uri().authority().cloned());
+    let hostname = match (&redirect.hostname, &authority) {
+        (Some(hostname), _) => hostname.as_str(),
+        (None, Some(authority)) => authority.host(),
+        (None, None) => "",
+    };
+    let port = match redirect.port {
+        Some(port) => Some(port),
+        None if redirect.scheme.is_some() => None,
+        None => authority.as_ref().and_then(Authority::port_u16),
+    };
+    let port = port.filter(|p| !matches!((scheme, *p), ("http", 80) | ("https", 443)));
+
+    let path = match &redirect.path {
+        Some(modifier) => rewrite_path(modifier, prefix, req.uri().path()),
+        None => req.uri().path().to_string(),
+    };
+
+    let mut location = format!("{}://{}", scheme, hostname);
+    if let Some(port) = port {
+        location.push_str(&format!(":{}", port));
+    }
+    location.push_str(&path);
+    if let Some(query) = req.uri().query() {
+        location.push('?');
+        location.push_str(query);
+    }
+
+    let status = redirect
+        .status_code
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::FOUND);
+    let mut rsp = Response::new(R::default());
+    match HeaderValue::try_from(location) {
+        Ok(location) => {
+            *rsp.status_mut() = status;
+            rsp.headers_mut().insert(header::LOCATION, location);
+        }
+        Err(_) => *rsp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR,
+    }
+    rsp
+}
+
+/// Returns the path of a request after a path modifier is applied.
+///
+/// Paths that do not match the prefix that `ReplacePrefixMatch` replaces are
+/// not modified.
+fn rewrite_path(modifier: &HttpPathModifier, prefix: &str, path: &str) -> String {
+    match modifier {
+        HttpPathModifier::ReplaceFullPath { replace_full_path } => replace_full_path.clone(),
+        HttpPathModifier::ReplacePrefixMatch {
+            replace_prefix_match,
+        } => filter::replace_prefix_match(path, prefix, replace_prefix_match)
+            .unwrap_or_else(|| path.to_string()),
+        _ => path.to_string(),
+    }
+}
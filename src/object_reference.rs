@@ -1,4 +1,6 @@
 use crate::*;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::fmt;
 
 /// LocalObjectReference identifies an API object within the namespace of the
 /// referrer.
@@ -105,3 +107,184 @@ pub struct BackendObjectReference {
     /// or this field.
     pub port: Option<PortNumber>,
 }
+
+/// Describes a reference from an object in one namespace to an object in
+/// another namespace.
+///
+/// Such references are only valid when permitted by a ReferenceGrant in the
+/// referent's namespace.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CrossNamespaceReference<'a> {
+    /// The group of the referring object, e.g. `gateway.networking.k8s.io`.
+    pub from_group: &'a str,
+
+    /// The kind of the referring object, e.g. `Gateway`.
+    pub from_kind: &'a str,
+
+    /// The namespace of the referring object.
+    pub from_namespace: &'a str,
+
+    /// The group of the referent. The core API group is empty.
+    pub to_group: &'a str,
+
+    /// The kind of the referent, e.g. `Secret`.
+    pub to_kind: &'a str,
+
+    /// The namespace of the referent.
+    pub to_namespace: &'a str,
+
+    /// The name of the referent.
+    pub to_name: &'a str,
+}
+
+/// The reason a reference could not be resolved.
+///
+/// Each variant corresponds to a reason of the referrer's "ResolvedRefs"
+/// condition, and its `Display` implementation provides the condition
+/// message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ReferenceError {
+    /// The referent does not exist.
+    NotFound {
+        kind: String,
+        namespace: String,
+        name: String,
+    },
+
+    /// The reference is to another namespace and is not permitted by a
+    /// ReferenceGrant.
+    RefNotPermitted {
+        kind: String,
+        namespace: String,
+        name: String,
+    },
+
+    /// The reference is to a kind that is not supported.
+    InvalidKind { group: String, kind: String },
+
+    /// The referent does not expose the referenced port.
+    PortNotFound {
+        namespace: String,
+        name: String,
+        port: PortNumber,
+    },
+
+    /// The reference omits a port that is required and cannot be inferred
+    /// from the referent, which exposes the given ports.
+    PortRequired {
+        namespace: String,
+        name: String,
+        ports: Vec<i32>,
+    },
+
+    /// A listener's certificate reference is invalid.
+    InvalidCertificateRef(String),
+
+    /// The referent's port has an `appProtocol` that the implementation
+    /// does not support.
+    UnsupportedProtocol {
+        namespace: String,
+        name: String,
+        port: PortNumber,
+        app_protocol: String,
+    },
+}
+
+// === impl ReferenceError ===
+
+impl ReferenceError {
+    /// Returns the reason to set on the "ResolvedRefs" condition.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } | Self::PortNotFound { .. } => "BackendNotFound",
+            Self::RefNotPermitted { .. } => "RefNotPermitted",
+            Self::InvalidKind { .. } => "InvalidKind",
+            Self::PortRequired { .. } => "UnsupportedValue",
+            Self::InvalidCertificateRef(_) => "InvalidCertificateRef",
+            Self::UnsupportedProtocol { .. } => "UnsupportedProtocol",
+        }
+    }
+
+    /// Returns a "ResolvedRefs" condition with status `False` describing the
+    /// error.
+    pub fn to_condition(
+        &self,
+        observed_generation: Option<i64>,
+        last_transition_time: metav1::Time,
+    ) -> metav1::Condition {
+        metav1::Condition {
+            type_: "ResolvedRefs".to_string(),
+            status: "False".to_string(),
+            reason: self.reason().to_string(),
+            message: self.to_string(),
+            observed_generation,
+            last_transition_time,
+        }
+    }
+}
+
+impl fmt::Display for ReferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound {
+                kind,
+                namespace,
+                name,
+            } => write!(f, "{} {}/{} not found", kind, namespace, name),
+            Self::RefNotPermitted {
+                kind,
+                namespace,
+                name,
+            } => write!(
+                f,
+                "reference to {} {}/{} is not permitted by any ReferenceGrant",
+                kind, namespace, name
+            ),
+            Self::InvalidKind { group, kind } if group.is_empty() => {
+                write!(f, "unsupported kind: {}", kind)
+            }
+            Self::InvalidKind { group, kind } => write!(f, "unsupported kind: {}.{}", kind, group),
+            Self::PortNotFound {
+                namespace,
+                name,
+                port,
+            } => write!(f, "Service {}/{} has no port {}", namespace, name, port),
+            Self::PortRequired {
+                namespace,
+                name,
+                ports,
+            } => {
+                write!(
+                    f,
+                    "port must be specified for Service {}/{}",
+                    namespace, name
+                )?;
+                if ports.is_empty() {
+                    return write!(f, ", which has no ports");
+                }
+                write!(f, ", which has ports ")?;
+                for (i, port) in ports.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", port)?;
+                }
+                Ok(())
+            }
+            Self::InvalidCertificateRef(message) => f.write_str(message),
+            Self::UnsupportedProtocol {
+                namespace,
+                name,
+                port,
+                app_protocol,
+            } => write!(
+                f,
+                "port {} of Service {}/{} has unsupported appProtocol {:?}",
+                port, namespace, name, app_protocol
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReferenceError {}
@@ -0,0 +1,142 @@
+//! Owner references for resources generated from Gateway API objects.
+//!
+//! Implementations that provision infrastructure per Gateway (e.g. a Service,
+//! a Deployment, and a Secret) should make the Gateway the controller of each
+//! generated resource, so that it is garbage-collected with the Gateway and
+//! so that other controllers do not adopt it:
+//!
+//! ```ignore
+//! let mut meta = ObjectMeta::default();
+//! meta.set_gateway_name(gateway.name_any());
+//! owner::set_controller(&mut meta, &gateway)?;
+//! ```
+//!
+//! Owner references cannot span namespaces, so generated resources must be
+//! created in the namespace of their owner.
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use kube::Resource;
+use std::fmt;
+
+/// Errors encountered while setting the controller of a resource.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OwnerError {
+    /// The owner has not been created, so it has no name or UID to refer to.
+    NotCreated,
+
+    /// The resource is in a different namespace than its owner.
+    NamespaceMismatch {
+        owner: Option<String>,
+        resource: Option<String>,
+    },
+
+    /// The resource is already controlled by another object.
+    AlreadyControlled { kind: String, name: String },
+}
+
+/// Returns a reference to `owner` that marks it as the controller of a
+/// dependent resource and blocks the owner's foreground deletion until the
+/// dependent is deleted, or `None` if the owner has no name or UID.
+pub fn controller_ref<K: Resource<DynamicType = ()>>(owner: &K) -> Option<metav1::OwnerReference> {
+    let mut reference = owner_ref(owner)?;
+    reference.controller = Some(true);
+    reference.block_owner_deletion = Some(true);
+    Some(reference)
+}
+
+/// Returns a non-controller reference to `owner`, or `None` if the owner has
+/// no name or UID.
+pub fn owner_ref<K: Resource<DynamicType = ()>>(owner: &K) -> Option<metav1::OwnerReference> {
+    let meta = owner.meta();
+    Some(metav1::OwnerReference {
+        api_version: K::api_version(&()).into_owned(),
+        kind: K::kind(&()).into_owned(),
+        name: meta.name.clone()?,
+        uid: meta.uid.clone()?,
+        controller: None,
+        block_owner_deletion: None,
+    })
+}
+
+/// Returns the reference to the controller of a resource, if it has one.
+pub fn controller_of(meta: &metav1::ObjectMeta) -> Option<&metav1::OwnerReference> {
+    meta.owner_references
+        .iter()
+        .flatten()
+        .find(|r| r.controller == Some(true))
+}
+
+/// Returns true if the resource has an owner reference to `owner`.
+///
+/// References are matched by UID, so a resource is not owned by a
+/// re-created object of the same name.
+pub fn is_owned_by<K: Resource>(meta: &metav1::ObjectMeta, owner: &K) -> bool {
+    let uid = match owner.meta().uid.as_deref() {
+        Some(uid) => uid,
+        None => return false,
+    };
+    meta.owner_references.iter().flatten().any(|r| r.uid == uid)
+}
+
+/// Returns true if `owner` is the controller of the resource.
+pub fn is_controlled_by<K: Resource>(meta: &metav1::ObjectMeta, owner: &K) -> bool {
+    match (controller_of(meta), owner.meta().uid.as_deref()) {
+        (Some(reference), Some(uid)) => reference.uid == uid,
+        _ => false,
+    }
+}
+
+/// Makes `owner` the controller of the resource described by `meta`.
+///
+/// Any existing reference to the owner is replaced. Fails if the resource is
+/// controlled by another object, or if it is not in the owner's namespace.
+pub fn set_controller<K: Resource<DynamicType = ()>>(
+    meta: &mut metav1::ObjectMeta,
+    owner: &K,
+) -> Result<(), OwnerError> {
+    let reference = controller_ref(owner).ok_or(OwnerError::NotCreated)?;
+
+    let owner_ns = owner.meta().namespace.as_deref();
+    if owner_ns.is_some() && meta.namespace.as_deref() != owner_ns {
+        return Err(OwnerError::NamespaceMismatch {
+            owner: owner_ns.map(ToString::to_string),
+            resource: meta.namespace.clone(),
+        });
+    }
+
+    if let Some(other) = controller_of(meta) {
+        if other.uid != reference.uid {
+            return Err(OwnerError::AlreadyControlled {
+                kind: other.kind.clone(),
+                name: other.name.clone(),
+            });
+        }
+    }
+
+    let references = meta.owner_references.get_or_insert_with(Vec::new);
+    references.retain(|r| r.uid != reference.uid);
+    references.push(reference);
+    Ok(())
+}
+
+// === impl OwnerError ===
+
+impl fmt::Display for OwnerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotCreated => write!(f, "owner has no name or UID"),
+            Self::NamespaceMismatch { owner, resource } => write!(
+                f,
+                "resource in namespace {:?} cannot be owned by an object in namespace {:?}",
+                resource.as_deref().unwrap_or_default(),
+                owner.as_deref().unwrap_or_default()
+            ),
+            Self::AlreadyControlled { kind, name } => {
+                write!(f, "resource is already controlled by {} {}", kind, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OwnerError {}
@@ -0,0 +1,259 @@
+//! Generation of JSON Patches between two versions of an object.
+//!
+//! Mutating admission webhooks respond with an RFC 6902 JSON Patch that
+//! transforms the submitted object into the mutated one. [`diff`] computes
+//! such a patch from the two versions, so that webhooks can mutate typed
+//! objects (e.g. to apply defaults or inject annotations) rather than build
+//! JSON pointers by hand:
+//!
+//! ```
+//! # use k8s_gateway_api::{patch::{self, PatchOperation}, HttpRoute, HttpRouteSpec};
+//! # let route = HttpRoute::new("web", HttpRouteSpec {
+//! #     inner: Default::default(),
+//! #     hostnames: None,
+//! #     rules: None,
+//! # });
+//! let mut mutated = route.clone();
+//! mutated
+//!     .metadata
+//!     .annotations
+//!     .get_or_insert_with(Default::default)
+//!     .insert("example.com/owner".to_string(), "team-a".to_string());
+//! let ops = patch::diff(&route, &mutated).unwrap();
+//! assert_eq!(
+//!     ops,
+//!     vec![PatchOperation::Add {
+//!         path: "/metadata/annotations".to_string(),
+//!         value: serde_json::json!({"example.com/owner": "team-a"}),
+//!     }],
+//! );
+//! ```
+
+use crate::validation::FieldPath;
+use serde::Serialize;
+use serde_json::Value;
+
+/// An operation of a JSON Patch.
+///
+/// Operations serialize as described by RFC 6902, e.g.
+/// `{"op":"add","path":"/spec/hostnames","value":["example.com"]}`.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Adds a value to an object or inserts it into an array.
+    Add { path: String, value: Value },
+
+    /// Removes a value.
+    Remove { path: String },
+
+    /// Replaces a value.
+    Replace { path: String, value: Value },
+}
+
+/// Returns the JSON Patch that transforms the serialization of `before` into
+/// that of `after`.
+///
+/// Fields are added, removed, or replaced individually. Arrays are patched
+/// element by element, so a patch that inserts an element at the start of an
+/// array replaces each of its elements; patches are correct, but not
+/// necessarily minimal.
+pub fn diff<T: Serialize>(before: &T, after: &T) -> serde_json::Result<Vec<PatchOperation>> {
+    let before = serde_json::to_value(before)?;
+    let after = serde_json::to_value(after)?;
+    Ok(diff_values(&before, &after))
+}
+
+/// Returns the JSON Patch that transforms `before` into `after`.
+pub fn diff_values(before: &Value, after: &Value) -> Vec<PatchOperation> {
+    let mut ops = Vec::new();
+    diff_at(&FieldPath::root(), before, after, &mut ops);
+    ops
+}
+
+fn diff_at(path: &FieldPath, before: &Value, after: &Value, ops: &mut Vec<PatchOperation>) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (k, v) in before {
+                let path = path.key(k.as_str());
+                match after.get(k) {
+                    Some(after) => diff_at(&path, v, after, ops),
+                    None => ops.push(PatchOperation::Remove {
+                        path: path.to_json_pointer(),
+                    }),
+                }
+            }
+            for (k, v) in after {
+                if !before.contains_key(k) {
+                    ops.push(PatchOperation::Add {
+                        path: path.key(k.as_str()).to_json_pointer(),
+                        value: v.clone(),
+                    });
+                }
+            }
+        }
+
+        (Value::Array(before), Value::Array(after)) => {
+            for (i, (b, a)) in before.iter().zip(after).enumerate() {
+                diff_at(&path.index(i), b, a, ops);
+            }
+            // Trailing elements are removed from the end, so that the indices
+            // of the remaining elements do not shift.
+            for i in (after.len()..before.len()).rev() {
+                ops.push(PatchOperation::Remove {
+                    path: path.index(i).to_json_pointer(),
+                });
+            }
+            for (i, v) in after.iter().enumerate().skip(before.len()) {
+                ops.push(PatchOperation::Add {
+                    path: path.index(i).to_json_pointer(),
+                    value: v.clone(),
+                });
+            }
+        }
+
+        (before, after) if before != after => ops.push(PatchOperation::Replace {
+            path: path.to_json_pointer(),
+            value: after.clone(),
+        }),
+
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn add(path: &str, value: Value) -> PatchOperation {
+        PatchOperation::Add {
+            path: path.to_string(),
+            value,
+        }
+    }
+
+    fn remove(path: &str) -> PatchOperation {
+        PatchOperation::Remove {
+            path: path.to_string(),
+        }
+    }
+
+    fn replace(path: &str, value: Value) -> PatchOperation {
+        PatchOperation::Replace {
+            path: path.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn diffs_equal_values() {
+        let value = json!({ "spec": { "hostnames": ["a.example.com"] } });
+        assert_eq!(diff_values(&value, &value), []);
+    }
+
+    #[test]
+    fn shrinks_arrays_from_the_end() {
+        assert_eq!(
+            diff_values(&json!(["a", "b", "c", "d"]), &json!(["a", "x"])),
+            [replace("/1", json!("x")), remove("/3"), remove("/2")]
+        );
+        assert_eq!(
+            diff_values(&json!({ "list": ["a"] }), &json!({ "list": [] })),
+            [remove("/list/0")]
+        );
+    }
+
+    #[test]
+    fn grows_arrays_in_order() {
+        assert_eq!(
+            diff_values(&json!(["a"]), &json!(["b", "c", "d"])),
+            [
+                replace("/0", json!("b")),
+                add("/1", json!("c")),
+                add("/2", json!("d")),
+            ]
+        );
+        assert_eq!(
+            diff_values(&json!({ "list": [] }), &json!({ "list": [{ "a": 1 }] })),
+            [add("/list/0", json!({ "a": 1 }))]
+        );
+    }
+
+    #[test]
+    fn escapes_pointer_tokens() {
+        let before = json!({
+            "metadata": { "annotations": { "example.com/owner": "a", "a~b": "x" } },
+        });
+        let after = json!({
+            "metadata": { "annotations": { "example.com/owner": "b", "~/": "y" } },
+        });
+        assert_eq!(
+            diff_values(&before, &after),
+            [
+                remove("/metadata/annotations/a~0b"),
+                replace("/metadata/annotations/example.com~1owner", json!("b")),
+                add("/metadata/annotations/~0~1", json!("y")),
+            ]
+        );
+    }
+
+    #[test]
+    fn replaces_values_of_other_types() {
+        assert_eq!(
+            diff_values(&json!({ "a": [1] }), &json!({ "a": { "b": 1 } })),
+            [replace("/a", json!({ "b": 1 }))]
+        );
+        assert_eq!(
+            diff_values(&json!({ "a": null }), &json!({ "a": 1 })),
+            [replace("/a", json!(1))]
+        );
+        assert_eq!(
+            diff_values(&json!(1), &json!("1")),
+            [replace("", json!("1"))]
+        );
+    }
+
+    /// Applying a diff with an RFC 6902 implementation yields the target.
+    #[cfg(feature = "webhook")]
+    #[test]
+    fn round_trips_through_json_patch() {
+        let cases = [
+            (json!({}), json!({ "a": 1 })),
+            (json!(["a", "b", "c", "d"]), json!(["a"])),
+            (json!(["a"]), json!(["a", "b", "c"])),
+            (
+                json!({ "a~b": { "c/d": [1, 2, 3] }, "e": null }),
+                json!({ "a~b": { "c/d": [3], "~1": true }, "f": [] }),
+            ),
+            (
+                json!({
+                    "metadata": { "name": "web" },
+                    "spec": {
+                        "hostnames": ["a.example.com", "b.example.com"],
+                        "rules": [{ "backendRefs": [{ "name": "a", "port": 80 }] }],
+                    },
+                }),
+                json!({
+                    "metadata": {
+                        "name": "web",
+                        "annotations": { "example.com/owner": "team-a" },
+                    },
+                    "spec": {
+                        "hostnames": ["c.example.com"],
+                        "rules": [
+                            { "backendRefs": [{ "name": "b", "port": 80 }] },
+                            { "matches": [{ "path": { "value": "/" } }] },
+                        ],
+                    },
+                }),
+            ),
+        ];
+        for (before, after) in cases {
+            let ops = serde_json::to_value(diff_values(&before, &after)).unwrap();
+            let patch = serde_json::from_value::<json_patch::Patch>(ops).unwrap();
+            let mut patched = before.clone();
+            json_patch::patch(&mut patched, &patch).unwrap();
+            assert_eq!(patched, after, "{} => {}", before, after);
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Resolution of attached policies.
+//!
+//! Policies attach to the objects named by their target references
+//! (GEP-713). A policy that targets an object without a `sectionName` applies
+//! to the whole object, including each of its sections (Gateway listeners,
+//! HTTPRoute rules, or Service ports); a policy that names a section applies
+//! only to that section. [`targeting`] finds the policies that apply to an
+//! object.
+//!
+//! Inherited policies configure `defaults` and `overrides` that are merged
+//! along the hierarchy GatewayClass, Gateway, Route, and backend.
+//! [`effective`] computes the configuration that applies at the bottom of such
+//! a hierarchy: defaults on more specific objects take precedence over
+//! defaults on less specific ones, and overrides on less specific objects take
+//! precedence over everything below them. Policies attached to the same object
+//! are ordered by age: the oldest policy wins, and policies created at the
+//! same time are ordered by namespace and name.
+//!
+//! Configurations are merged field-by-field through their JSON
+//! representation, so a field that one policy leaves unset may be set by
+//! another.
+
+use crate::*;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// An object, or a section of an object, that a policy may target.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PolicyTarget {
+    /// The group of the object; empty for the core API group.
+    pub group: String,
+
+    /// The kind of the object, e.g. `Gateway`.
+    pub kind: String,
+
+    /// The namespace of the object, or `None` for cluster-scoped objects.
+    pub namespace: Option<String>,
+
+    /// The name of the object.
+    pub name: String,
+
+    /// The name of a section within the object, e.g. a listener name.
+    pub section_name: Option<String>,
+}
+
+/// A policy that targets Gateway API objects.
+pub trait Policy {
+    /// Returns the policy's metadata.
+    fn metadata(&self) -> &metav1::ObjectMeta;
+
+    /// Returns the objects that the policy targets. Namespaces of local
+    /// references are resolved to the policy's namespace.
+    fn targets(&self) -> Vec<PolicyTarget>;
+}
+
+/// A policy whose configuration is inherited by the objects below its target
+/// in the hierarchy.
+pub trait InheritedPolicy: Policy {
+    /// The configuration that may be specified as defaults or overrides.
+    type Config: serde::Serialize + serde::de::DeserializeOwned;
+
+    /// Returns the configuration that more specific policies may override.
+    fn defaults(&self) -> Option<&Self::Config>;
+
+    /// Returns the configuration that more specific policies may not
+    /// override.
+    fn overrides(&self) -> Option<&Self::Config>;
+}
+
+/// Returns the policies that apply to `object`, from highest to lowest
+/// precedence.
+///
+/// If `object` names a section, policies that target the whole object are
+/// included along with those that target the section.
+pub fn targeting<'p, P: Policy>(
+    policies: impl IntoIterator<Item = &'p P>,
+    object: &PolicyTarget,
+) -> Vec<&'p P> {
+    let mut matched = policies
+        .into_iter()
+        .filter(|p| p.targets().iter().any(|t| t.applies_to(object)))
+        .collect::<Vec<_>>();
+    matched.sort_by(|a, b| precedence(*a, *b));
+    matched
+}
+
+/// Returns the effective configuration at the bottom of `hierarchy`, or
+/// `None` if no policy applies to any of its objects.
+///
+/// `hierarchy` lists objects from least to most specific, e.g. a
+/// GatewayClass, a Gateway, an HTTPRoute, and a Service. Objects that name a
+/// section are treated as two levels: the whole object, and below it the
+/// section.
+pub fn effective<'p, P: InheritedPolicy + 'p>(
+    hierarchy: &[PolicyTarget],
+    policies: impl IntoIterator<Item = &'p P>,
+) -> serde_json::Result<Option<P::Config>> {
+    let policies = policies.into_iter().collect::<Vec<_>>();
+
+    let mut levels = Vec::new();
+    for object in hierarchy {
+        let whole = PolicyTarget {
+            section_name: None,
+            ..object.clone()
+        };
+        levels.push(targeting_exactly(&policies, &whole));
+        if object.section_name.is_some() {
+            levels.push(targeting_exactly(&policies, object));
+        }
+    }
+    if levels.iter().all(Vec::is_empty) {
+        return Ok(None);
+    }
+
+    // Configurations are merged from lowest to highest precedence, so that
+    // later merges replace the fields set by earlier ones.
+    let mut config = Value::Object(serde_json::Map::new());
+    for level in &levels {
+        for policy in level.iter().rev() {
+            if let Some(defaults) = policy.defaults() {
+                merge(&mut config, canonical::to_value(defaults)?);
+            }
+        }
+    }
+    for level in levels.iter().rev() {
+        for policy in level.iter().rev() {
+            if let Some(overrides) = policy.overrides() {
+                merge(&mut config, canonical::to_value(overrides)?);
+            }
+        }
+    }
+    serde_json::from_value(config).map(Some)
+}
+
+/// Returns the policies that target exactly `object`: the whole object if it
+/// does not name a section, and otherwise only that section.
+fn targeting_exactly<'p, P: Policy>(policies: &[&'p P], object: &PolicyTarget) -> Vec<&'p P> {
+    let mut matched = policies
+        .iter()
+        .copied()
+        .filter(|p| {
+            p.targets()
+                .iter()
+                .any(|t| t.applies_to(object) && t.section_name == object.section_name)
+        })
+        .collect::<Vec<_>>();
+    matched.sort_by(|a, b| precedence(*a, *b));
+    matched
+}
+
+/// Orders policies from highest to lowest precedence: oldest first, then by
+/// namespace and name.
+fn precedence<P: Policy>(a: &P, b: &P) -> Ordering {
+    let (a, b) = (a.metadata(), b.metadata());
+    let created = |m: &metav1::ObjectMeta| m.creation_timestamp.as_ref().map(|t| t.0);
+    match (created(a), created(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        // Policies without a timestamp have not been created yet.
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+    .then_with(|| a.namespace.cmp(&b.namespace))
+    .then_with(|| a.name.cmp(&b.name))
+}
+
+/// Merges `value` into `into`, replacing fields that are set in both. Both
+/// values must be canonical, i.e. without nulls.
+fn merge(into: &mut Value, value: Value) {
+    match (into, value) {
+        (Value::Object(into), Value::Object(fields)) => {
+            for (k, v) in fields {
+                match into.get_mut(&k) {
+                    Some(existing) => merge(existing, v),
+                    None => {
+                        into.insert(k, v);
+                    }
+                }
+            }
+        }
+        (into, value) => *into = value,
+    }
+}
+
+// === impl PolicyTarget ===
+
+impl PolicyTarget {
+    /// Returns the target of a reference that may name another namespace.
+    pub fn from_ref(r: &PolicyTargetReference, policy_namespace: Option<&str>) -> Self {
+        Self {
+            group: r.group.to_string(),
+            kind: r.kind.to_string(),
+            namespace: r
+                .namespace
+                .as_deref()
+                .or(policy_namespace)
+                .map(ToString::to_string),
+            name: r.name.clone(),
+            section_name: None,
+        }
+    }
+
+    /// Returns the target of a reference in the policy's namespace.
+    pub fn from_local_ref(r: &LocalPolicyTargetReference, policy_namespace: Option<&str>) -> Self {
+        Self {
+            group: r.group.to_string(),
+            kind: r.kind.to_string(),
+            namespace: policy_namespace.map(ToString::to_string),
+            name: r.name.clone(),
+            section_name: None,
+        }
+    }
+
+    /// Returns the target of a reference in the policy's namespace that may
+    /// name a section.
+    pub fn from_local_section_ref(
+        r: &LocalPolicyTargetReferenceWithSectionName,
+        policy_namespace: Option<&str>,
+    ) -> Self {
+        Self {
+            section_name: r.section_name.as_deref().map(ToString::to_string),
+            ..Self::from_local_ref(&r.inner, policy_namespace)
+        }
+    }
+
+    /// Returns true if a policy with this target applies to `object`.
+    ///
+    /// Policy targets are resolved against the policy's namespace, so they
+    /// never match namespaced objects in other namespaces. Cluster-scoped
+    /// objects, like GatewayClasses, are matched by name alone.
+    pub fn applies_to(&self, object: &PolicyTarget) -> bool {
+        self.group == object.group
+            && self.kind == object.kind
+            && self.name == object.name
+            && (object.namespace.is_none() || self.namespace == object.namespace)
+            && match &self.section_name {
+                None => true,
+                Some(section) => object.section_name.as_ref() == Some(section),
+            }
+    }
+}
+
+// === impl Policy ===
+
+impl Policy for BackendLbPolicy {
+    fn metadata(&self) -> &metav1::ObjectMeta {
+        &self.metadata
+    }
+
+    fn targets(&self) -> Vec<PolicyTarget> {
+        let ns = self.metadata.namespace.as_deref();
+        self.spec
+            .target_refs
+            .iter()
+            .map(|r| PolicyTarget::from_local_ref(r, ns))
+            .collect()
+    }
+}
+
+impl Policy for XBackendTrafficPolicy {
+    fn metadata(&self) -> &metav1::ObjectMeta {
+        &self.metadata
+    }
+
+    fn targets(&self) -> Vec<PolicyTarget> {
+        let ns = self.metadata.namespace.as_deref();
+        self.spec
+            .target_refs
+            .iter()
+            .map(|r| PolicyTarget::from_local_ref(r, ns))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Config {
+        timeout: Option<String>,
+        retries: Option<u32>,
+    }
+
+    struct TestPolicy {
+        metadata: metav1::ObjectMeta,
+        targets: Vec<PolicyTarget>,
+        defaults: Option<Config>,
+        overrides: Option<Config>,
+    }
+
+    impl Policy for TestPolicy {
+        fn metadata(&self) -> &metav1::ObjectMeta {
+            &self.metadata
+        }
+
+        fn targets(&self) -> Vec<PolicyTarget> {
+            self.targets.clone()
+        }
+    }
+
+    impl InheritedPolicy for TestPolicy {
+        type Config = Config;
+
+        fn defaults(&self) -> Option<&Config> {
+            self.defaults.as_ref()
+        }
+
+        fn overrides(&self) -> Option<&Config> {
+            self.overrides.as_ref()
+        }
+    }
+
+    fn target(kind: &str, namespace: Option<&str>, name: &str) -> PolicyTarget {
+        PolicyTarget {
+            group: match kind {
+                "Service" => String::new(),
+                _ => consts::GROUP.to_string(),
+            },
+            kind: kind.to_string(),
+            namespace: namespace.map(ToString::to_string),
+            name: name.to_string(),
+            section_name: None,
+        }
+    }
+
+    fn section(target: PolicyTarget, section: &str) -> PolicyTarget {
+        PolicyTarget {
+            section_name: Some(section.to_string()),
+            ..target
+        }
+    }
+
+    /// Returns a policy in the `infra` namespace whose local reference
+    /// resolves to `target`.
+    fn policy(name: &str, target: PolicyTarget, timeout: &str) -> TestPolicy {
+        TestPolicy {
+            metadata: metav1::ObjectMeta {
+                namespace: Some("infra".to_string()),
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+            targets: vec![PolicyTarget {
+                namespace: Some("infra".to_string()),
+                ..target
+            }],
+            defaults: Some(Config {
+                timeout: Some(timeout.to_string()),
+                ..Default::default()
+            }),
+            overrides: None,
+        }
+    }
+
+    fn hierarchy() -> Vec<PolicyTarget> {
+        vec![
+            target("GatewayClass", None, "gc"),
+            section(target("Gateway", Some("infra"), "gw"), "http"),
+            target("HTTPRoute", Some("infra"), "route"),
+            target("Service", Some("infra"), "web"),
+        ]
+    }
+
+    fn timeout(policies: &[TestPolicy]) -> Option<String> {
+        effective(&hierarchy(), policies)
+            .unwrap()
+            .and_then(|c| c.timeout)
+    }
+
+    #[test]
+    fn effective_per_target_kind() {
+        for (kind, target) in [
+            ("GatewayClass", target("GatewayClass", None, "gc")),
+            ("Gateway", target("Gateway", None, "gw")),
+            ("listener", section(target("Gateway", None, "gw"), "http")),
+            ("HTTPRoute", target("HTTPRoute", None, "route")),
+            ("Service", target("Service", None, "web")),
+        ] {
+            let policies = [policy("p", target, "1s")];
+            assert_eq!(timeout(&policies).as_deref(), Some("1s"), "{}", kind);
+        }
+    }
+
+    #[test]
+    fn effective_gateway_class_matches_targeting() {
+        let policies = [policy("p", target("GatewayClass", None, "gc"), "1s")];
+        let gc = target("GatewayClass", None, "gc");
+        assert_eq!(targeting(&policies, &gc).len(), 1);
+        assert_eq!(
+            effective(&[gc], &policies).unwrap(),
+            Some(Config {
+                timeout: Some("1s".to_string()),
+                retries: None,
+            })
+        );
+    }
+
+    #[test]
+    fn effective_ignores_other_objects() {
+        let policies = [
+            policy("class", target("GatewayClass", None, "other"), "1s"),
+            policy(
+                "listener",
+                section(target("Gateway", None, "gw"), "https"),
+                "2s",
+            ),
+            policy("route", target("HTTPRoute", None, "other"), "3s"),
+        ];
+        assert_eq!(effective(&hierarchy(), &policies).unwrap(), None);
+
+        // Namespaced targets never match objects in other namespaces.
+        let mut p = policy("p", target("Service", None, "web"), "1s");
+        p.targets[0].namespace = Some("other".to_string());
+        assert_eq!(timeout(&[p]), None);
+    }
+
+    #[test]
+    fn effective_defaults_and_overrides() {
+        // More specific defaults win.
+        let policies = [
+            policy("class", target("GatewayClass", None, "gc"), "1s"),
+            policy("gateway", target("Gateway", None, "gw"), "2s"),
+            policy(
+                "listener",
+                section(target("Gateway", None, "gw"), "http"),
+                "3s",
+            ),
+            policy("route", target("HTTPRoute", None, "route"), "4s"),
+        ];
+        assert_eq!(timeout(&policies).as_deref(), Some("4s"));
+
+        // Less specific overrides win, and fields are merged.
+        let mut class = policy("class", target("GatewayClass", None, "gc"), "1s");
+        class.overrides = class.defaults.take();
+        let mut route = policy("route", target("HTTPRoute", None, "route"), "4s");
+        route.defaults.as_mut().unwrap().retries = Some(3);
+        assert_eq!(
+            effective(&hierarchy(), &[class, route]).unwrap(),
+            Some(Config {
+                timeout: Some("1s".to_string()),
+                retries: Some(3),
+            })
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Generation of the resources that serve a Gateway in-cluster.
+//!
+//! Implementations that deploy a proxy per Gateway generate the same child
+//! resources for each one: a `LoadBalancer` Service that exposes the
+//! listeners' ports, a Deployment that runs the proxy, and a ConfigMap that
+//! holds its configuration. [`Provisioner`] generates these resources from a
+//! Gateway; implementations provide the proxy's pod template and
+//! configuration, and may override any other part:
+//!
+//! ```ignore
+//! impl Provisioner for Envoy {
+//!     fn pod_template(&self, gateway: &Gateway, ports: &[ListenerPort]) -> PodTemplateSpec {
+//!         envoy_pod(&self.image, ports)
+//!     }
+//!
+//!     fn config(&self, gateway: &Gateway) -> BTreeMap<String, String> {
+//!         [("envoy.yaml".to_string(), self.bootstrap(gateway))].into()
+//!     }
+//! }
+//!
+//! let resources = Envoy::default().provision(&gateway);
+//! ```
+//!
+//! Every generated resource is named after the Gateway, created in its
+//! namespace, labeled with [`GATEWAY_NAME_LABEL`], and controlled by the
+//! Gateway, so that it is garbage-collected with it.
+
+use crate::{addresses, owner, well_known::GATEWAY_NAME_LABEL, *};
+use k8s_openapi::{
+    api::{
+        apps::v1::{Deployment, DeploymentSpec},
+        core::v1::{ConfigMap, PodTemplateSpec, Service, ServicePort, ServiceSpec},
+    },
+    apimachinery::pkg::{apis::meta::v1 as metav1, util::intstr::IntOrString},
+};
+use std::collections::BTreeMap;
+
+/// A port on which a Gateway accepts connections, shared by all of the
+/// listeners with the same port number and transport protocol.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListenerPort {
+    /// The name of the Service port, e.g. `tcp-443`.
+    pub name: String,
+
+    /// The port number, as specified by the listeners.
+    pub port: u16,
+
+    /// The port on which the proxy's containers listen.
+    pub target_port: u16,
+
+    /// The transport protocol: `TCP` or `UDP`.
+    pub protocol: &'static str,
+
+    /// The names of the listeners on the port, in the order in which they
+    /// are specified.
+    pub listeners: Vec<String>,
+}
+
+/// The resources generated for a Gateway.
+#[derive(Clone, Debug)]
+pub struct Resources {
+    pub service: Service,
+    pub deployment: Deployment,
+    pub config_map: ConfigMap,
+}
+
+/// Generates the resources that serve a Gateway.
+pub trait Provisioner {
+    /// Returns the template of the proxy's pods, whose containers listen on
+    /// the `target_port` of each of `ports`.
+    ///
+    /// The labels that select the pods are added to the template, and need
+    /// not be set.
+    fn pod_template(&self, gateway: &Gateway, ports: &[ListenerPort]) -> PodTemplateSpec;
+
+    /// Returns the proxy's configuration, by file name.
+    fn config(&self, gateway: &Gateway) -> BTreeMap<String, String>;
+
+    /// Returns the name of the resources generated for a Gateway. Defaults to
+    /// the Gateway's name.
+    fn resource_name(&self, gateway: &Gateway) -> String {
+        gateway.metadata.name.clone().unwrap_or_default()
+    }
+
+    /// Returns the port on which the proxy listens for a listener port.
+    /// Defaults to the listener port, so implementations whose proxies cannot
+    /// bind privileged ports should override it, e.g. to map 80 to 8080.
+    fn target_port(&self, port: u16) -> u16 {
+        port
+    }
+
+    /// Returns the number of replicas of the proxy. Defaults to 1.
+    fn replicas(&self, _gateway: &Gateway) -> i32 {
+        1
+    }
+
+    /// Returns the Gateway's ports, with target ports mapped by
+    /// [`target_port`](Self::target_port).
+    fn ports(&self, gateway: &Gateway) -> Vec<ListenerPort> {
+        let mut ports = listener_ports(&gateway.spec);
+        for port in &mut ports {
+            port.target_port = self.target_port(port.port);
+        }
+        ports
+    }
+
+    /// Returns the labels that select the proxy's pods.
+    fn selector(&self, gateway: &Gateway) -> BTreeMap<String, String> {
+        let name = gateway.metadata.name.clone().unwrap_or_default();
+        [(GATEWAY_NAME_LABEL.to_string(), name)].into()
+    }
+
+    /// Returns the metadata of a resource generated for a Gateway.
+    ///
+    /// The Gateway is made the controller of the resource, unless it has not
+    /// been created and so has no UID.
+    fn metadata(&self, gateway: &Gateway) -> metav1::ObjectMeta {
+        let mut meta = metav1::ObjectMeta {
+            name: Some(self.resource_name(gateway)),
+            namespace: gateway.metadata.namespace.clone(),
+            labels: Some(self.selector(gateway)),
+            ..Default::default()
+        };
+        meta.owner_references = owner::controller_ref(gateway).map(|r| vec![r]);
+        meta
+    }
+
+    /// Returns a `LoadBalancer` Service that exposes the Gateway's ports.
+    ///
+    /// If the Gateway requests an IP address, the first one is requested as
+    /// the Service's `loadBalancerIP`.
+    fn service(&self, gateway: &Gateway) -> Service {
+        let ports = self
+            .ports(gateway)
+            .into_iter()
+            .map(|p| ServicePort {
+                name: Some(p.name),
+                port: p.port.into(),
+                target_port: Some(IntOrString::Int(p.target_port.into())),
+                protocol: Some(p.protocol.to_string()),
+                ..Default::default()
+            })
+            .collect();
+        let load_balancer_ip = gateway
+            .spec
+            .addresses
+            .iter()
+            .flatten()
+            .find(|a| addresses::address_type(a.r#type.as_deref()) == addresses::IP_ADDRESS)
+            .map(|a| a.value.clone());
+        Service {
+            metadata: self.metadata(gateway),
+            spec: Some(ServiceSpec {
+                type_: Some("LoadBalancer".to_string()),
+                selector: Some(self.selector(gateway)),
+                ports: Some(ports),
+                load_balancer_ip,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a Deployment that runs the proxy.
+    fn deployment(&self, gateway: &Gateway) -> Deployment {
+        let selector = self.selector(gateway);
+        let mut template = self.pod_template(gateway, &self.ports(gateway));
+        template
+            .metadata
+            .get_or_insert_with(Default::default)
+            .labels
+            .get_or_insert_with(Default::default)
+            .extend(selector.clone());
+        Deployment {
+            metadata: self.metadata(gateway),
+            spec: Some(DeploymentSpec {
+                replicas: Some(self.replicas(gateway)),
+                selector: metav1::LabelSelector {
+                    match_labels: Some(selector),
+                    ..Default::default()
+                },
+                template,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a ConfigMap that holds the proxy's configuration.
+    fn config_map(&self, gateway: &Gateway) -> ConfigMap {
+        ConfigMap {
+            metadata: self.metadata(gateway),
+            data: Some(self.config(gateway)),
+            ..Default::default()
+        }
+    }
+
+    /// Returns all of the resources generated for a Gateway.
+    fn provision(&self, gateway: &Gateway) -> Resources {
+        Resources {
+            service: self.service(gateway),
+            deployment: self.deployment(gateway),
+            config_map: self.config_map(gateway),
+        }
+    }
+}
+
+/// Groups a Gateway's listeners by port number and transport protocol,
+/// ordered by port and then protocol.
+///
+/// UDP listeners are served over UDP; listeners of every other protocol,
+/// including implementation-specific ones, are served over TCP. Target ports
+/// are the listener ports.
+pub fn listener_ports(spec: &GatewaySpec) -> Vec<ListenerPort> {
+    let mut ports = BTreeMap::<(u16, &'static str), ListenerPort>::new();
+    for listener in &spec.listeners {
+        let protocol = transport_protocol(&listener.protocol);
+        ports
+            .entry((listener.port, protocol))
+            .or_insert_with(|| ListenerPort {
+                name: format!("{}-{}", protocol.to_ascii_lowercase(), listener.port),
+                port: listener.port,
+                target_port: listener.port,
+                protocol,
+                listeners: Vec::new(),
+            })
+            .listeners
+            .push(listener.name.clone());
+    }
+    ports.into_values().collect()
+}
+
+/// Returns the transport protocol over which a listener protocol is served:
+/// `UDP` for `UDP` listeners and `TCP` otherwise.
+pub fn transport_protocol(protocol: &str) -> &'static str {
+    if protocol == "UDP" {
+        "UDP"
+    } else {
+        "TCP"
+    }
+}
+
+/// Returns the addresses that a `LoadBalancer` Service has been assigned, to
+/// be reported in the Gateway's `status.addresses`.
+pub fn status_addresses(service: &Service) -> Vec<GatewayStatusAddress> {
+    let ingress = service
+        .status
+        .as_ref()
+        .and_then(|s| s.load_balancer.as_ref())
+        .and_then(|lb| lb.ingress.as_ref());
+    let mut assigned = Vec::new();
+    for ingress in ingress.into_iter().flatten() {
+        if let Some(ip) = &ingress.ip {
+            assigned.push(GatewayStatusAddress {
+                r#type: Some(addresses::IP_ADDRESS.to_string()),
+                value: ip.clone(),
+            });
+        }
+        if let Some(hostname) = &ingress.hostname {
+            assigned.push(GatewayStatusAddress {
+                r#type: Some(addresses::HOSTNAME.to_string()),
+                value: hostname.clone(),
+            });
+        }
+    }
+    assigned
+}
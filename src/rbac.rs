@@ -0,0 +1,198 @@
+//! Generation of the RBAC rules that implementations need.
+//!
+//! A Gateway API implementation watches GatewayClasses, Gateways, and the
+//! route kinds it supports, writes their statuses, and reads the Secrets,
+//! Services, and EndpointSlices they reference. [`Permissions`] describes
+//! such an implementation and produces the rules of its ClusterRole, so that
+//! deployment manifests stay in sync with the kinds that are supported:
+//!
+//! ```
+//! # use k8s_gateway_api::{rbac::Permissions, route::RouteKind};
+//! let role = Permissions::new()
+//!     .with_route_kinds(vec![RouteKind::Http])
+//!     .cluster_role("gateway-controller");
+//! let status = &role.rules.unwrap()[1];
+//! assert_eq!(
+//!     status.resources.as_deref().unwrap(),
+//!     ["gatewayclasses/status", "gateways/status", "httproutes/status"],
+//! );
+//! ```
+
+use crate::{
+    consts::{self, plural, GROUP},
+    route::RouteKind,
+};
+use k8s_openapi::{
+    api::rbac::v1::{ClusterRole, PolicyRule, Role},
+    apimachinery::pkg::apis::meta::v1 as metav1,
+};
+
+/// The verbs needed to read and watch objects.
+pub const READ_VERBS: &[&str] = &["get", "list", "watch"];
+
+/// The verbs needed to write objects' statuses.
+pub const STATUS_VERBS: &[&str] = &["get", "patch", "update"];
+
+/// Describes the objects that an implementation reads and writes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Permissions {
+    route_kinds: Vec<RouteKind>,
+    reference_grants: bool,
+    backends: bool,
+    secrets: bool,
+}
+
+// === impl Permissions ===
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Permissions {
+    /// Returns the permissions of an implementation that supports HTTPRoutes,
+    /// honors ReferenceGrants, resolves Service backends to their
+    /// EndpointSlices, and terminates TLS with certificates from Secrets.
+    pub fn new() -> Self {
+        Self {
+            route_kinds: vec![RouteKind::Http],
+            reference_grants: true,
+            backends: true,
+            secrets: true,
+        }
+    }
+
+    /// Sets the route kinds that the implementation supports.
+    pub fn with_route_kinds(mut self, kinds: impl IntoIterator<Item = RouteKind>) -> Self {
+        self.route_kinds = kinds.into_iter().collect();
+        self.route_kinds.sort();
+        self.route_kinds.dedup();
+        self
+    }
+
+    /// Sets whether the implementation reads ReferenceGrants.
+    pub fn with_reference_grants(mut self, enabled: bool) -> Self {
+        self.reference_grants = enabled;
+        self
+    }
+
+    /// Sets whether the implementation reads Services and EndpointSlices.
+    pub fn with_backends(mut self, enabled: bool) -> Self {
+        self.backends = enabled;
+        self
+    }
+
+    /// Sets whether the implementation reads Secrets.
+    pub fn with_secrets(mut self, enabled: bool) -> Self {
+        self.secrets = enabled;
+        self
+    }
+
+    /// Returns the rules that grant the permissions.
+    pub fn rules(&self) -> Vec<PolicyRule> {
+        self.rules_for(true)
+    }
+
+    /// Returns the rules that grant the permissions on namespaced objects.
+    ///
+    /// A Role cannot grant access to cluster-scoped objects, so the
+    /// GatewayClass rules are omitted.
+    pub fn namespaced_rules(&self) -> Vec<PolicyRule> {
+        self.rules_for(false)
+    }
+
+    /// Returns a ClusterRole that grants the permissions.
+    pub fn cluster_role(&self, name: impl Into<String>) -> ClusterRole {
+        ClusterRole {
+            metadata: metav1::ObjectMeta {
+                name: Some(name.into()),
+                ..Default::default()
+            },
+            rules: Some(self.rules()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a Role that grants the permissions on namespaced objects in
+    /// `namespace`, for implementations that only serve a single namespace.
+    pub fn role(&self, namespace: impl Into<String>, name: impl Into<String>) -> Role {
+        Role {
+            metadata: metav1::ObjectMeta {
+                namespace: Some(namespace.into()),
+                name: Some(name.into()),
+                ..Default::default()
+            },
+            rules: Some(self.namespaced_rules()),
+        }
+    }
+
+    /// Returns a ClusterRole that grants the permissions, as a YAML document.
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self, name: impl Into<String>) -> Result<String, serde_yaml::Error> {
+        use k8s_openapi::Resource;
+
+        let mut value = serde_json::to_value(self.cluster_role(name))
+            .map_err(<serde_yaml::Error as serde::ser::Error>::custom)?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("apiVersion".to_string(), ClusterRole::API_VERSION.into());
+            obj.insert("kind".to_string(), ClusterRole::KIND.into());
+        }
+        crate::canonical::to_yaml(&value)
+    }
+
+    fn rules_for(&self, cluster_scoped: bool) -> Vec<PolicyRule> {
+        let mut resources = Vec::new();
+        if cluster_scoped {
+            resources.push(plural::GATEWAY_CLASS);
+        }
+        resources.push(plural::GATEWAY);
+        resources.extend(self.route_kinds.iter().map(|k| route_plural(*k)));
+
+        let mut rules = vec![
+            rule(GROUP, resources.iter().copied(), READ_VERBS),
+            rule(
+                GROUP,
+                resources.iter().map(|r| format!("{}/status", r)),
+                STATUS_VERBS,
+            ),
+        ];
+        if self.reference_grants {
+            rules.push(rule(GROUP, Some(plural::REFERENCE_GRANT), READ_VERBS));
+        }
+
+        let mut core = Vec::new();
+        if self.secrets {
+            core.push("secrets");
+        }
+        if self.backends {
+            core.push("services");
+        }
+        if !core.is_empty() {
+            rules.push(rule("", core, READ_VERBS));
+        }
+        if self.backends {
+            rules.push(rule("discovery.k8s.io", Some("endpointslices"), READ_VERBS));
+        }
+        rules
+    }
+}
+
+fn route_plural(kind: RouteKind) -> &'static str {
+    consts::find(kind.kind())
+        .expect("route kinds must be defined by the Gateway API")
+        .plural
+}
+
+fn rule<R: ToString>(
+    group: &str,
+    resources: impl IntoIterator<Item = R>,
+    verbs: &[&str],
+) -> PolicyRule {
+    PolicyRule {
+        api_groups: Some(vec![group.to_string()]),
+        resources: Some(resources.into_iter().map(|r| r.to_string()).collect()),
+        verbs: verbs.iter().map(|v| v.to_string()).collect(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,158 @@
+//! Type-level metadata for every kind defined by this crate.
+//!
+//! [`consts::KINDS`] describes kinds by name, for code that has no typed
+//! object at hand. Generic code that does work with typed objects (garbage
+//! collectors, backup tools, informers) instead needs the same metadata from
+//! a type parameter. Every kind implements [`GatewayApiResource`], and
+//! [`visit_kinds`] calls a [`KindVisitor`] once for each kind, so such code
+//! can handle every kind without matching on them:
+//!
+//! ```ignore
+//! struct Inventory(Vec<String>);
+//!
+//! impl KindVisitor for Inventory {
+//!     fn visit<K: GatewayApiResource>(&mut self) {
+//!         let gvk = K::gvk();
+//!         let versions = K::served_versions().join(", ");
+//!         let plural = <K as GatewayApiResource>::plural();
+//!         self.0.push(format!("{}.{} ({}): {}", plural, gvk.group, K::scope(), versions));
+//!     }
+//! }
+//!
+//! let mut inventory = Inventory(Vec::new());
+//! resource::visit_kinds(&mut inventory);
+//! ```
+
+use crate::consts::{self, KindInfo};
+use kube::{core::GroupVersionKind, CustomResourceExt, Resource};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+
+/// Whether objects of a kind belong to a namespace.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ResourceScope {
+    Namespaced,
+    Cluster,
+}
+
+/// A kind defined by the Gateway API.
+pub trait GatewayApiResource:
+    Resource<DynamicType = ()>
+    + CustomResourceExt
+    + Clone
+    + fmt::Debug
+    + DeserializeOwned
+    + Serialize
+    + Send
+    + Sync
+    + 'static
+    + sealed::Sealed
+{
+    /// Returns the description of the kind in [`consts::KINDS`].
+    fn kind_info() -> &'static KindInfo;
+
+    /// Returns the group, version, and kind of the type, in the version this
+    /// crate models.
+    fn gvk() -> GroupVersionKind {
+        GroupVersionKind::gvk(&Self::group(&()), &Self::version(&()), &Self::kind(&()))
+    }
+
+    /// Returns the plural resource name of the kind, e.g. `httproutes`.
+    ///
+    /// This is the same name as [`Resource::plural`], without a dynamic type,
+    /// so calls must be qualified with this trait when both are in scope.
+    fn plural() -> &'static str {
+        Self::kind_info().plural
+    }
+
+    /// Returns whether objects of the kind belong to a namespace.
+    fn scope() -> ResourceScope {
+        if Self::kind_info().namespaced {
+            ResourceScope::Namespaced
+        } else {
+            ResourceScope::Cluster
+        }
+    }
+
+    /// Returns the versions in which the kind is served, from oldest to
+    /// newest.
+    fn served_versions() -> &'static [&'static str] {
+        Self::kind_info().versions
+    }
+}
+
+/// Handles each kind visited by [`visit_kinds`].
+pub trait KindVisitor {
+    /// Handles the kind `K`.
+    fn visit<K: GatewayApiResource>(&mut self);
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_resource {
+    ($($(#[$attr:meta])* $ty:ty => $kind:expr),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            impl sealed::Sealed for $ty {}
+
+            $(#[$attr])*
+            impl GatewayApiResource for $ty {
+                fn kind_info() -> &'static KindInfo {
+                    consts::find($kind).expect("kind must be defined")
+                }
+            }
+        )+
+
+        /// Calls `visitor` once for each kind defined by this crate, in the
+        /// order of [`consts::KINDS`].
+        ///
+        /// Kinds that are only modeled with the `experimental` feature are
+        /// visited only when it is enabled.
+        pub fn visit_kinds<V: KindVisitor + ?Sized>(visitor: &mut V) {
+            $(
+                $(#[$attr])*
+                visitor.visit::<$ty>();
+            )+
+        }
+    };
+}
+
+impl_resource!(
+    crate::GatewayClass => consts::kind::GATEWAY_CLASS,
+    crate::Gateway => consts::kind::GATEWAY,
+    crate::HttpRoute => consts::kind::HTTP_ROUTE,
+    #[cfg(feature = "experimental")]
+    crate::BackendLbPolicy => consts::kind::BACKEND_LB_POLICY,
+    #[cfg(feature = "experimental")]
+    crate::GrpcRoute => consts::kind::GRPC_ROUTE,
+    #[cfg(feature = "experimental")]
+    crate::ReferenceGrant => consts::kind::REFERENCE_GRANT,
+    #[cfg(feature = "experimental")]
+    crate::TcpRoute => consts::kind::TCP_ROUTE,
+    #[cfg(feature = "experimental")]
+    crate::TlsRoute => consts::kind::TLS_ROUTE,
+    #[cfg(feature = "experimental")]
+    crate::UdpRoute => consts::kind::UDP_ROUTE,
+    #[cfg(feature = "experimental")]
+    crate::XBackendTrafficPolicy => consts::kind::X_BACKEND_TRAFFIC_POLICY,
+);
+
+// === impl ResourceScope ===
+
+impl ResourceScope {
+    /// Returns the scope as it is written in a CRD, e.g. `Namespaced`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Namespaced => "Namespaced",
+            Self::Cluster => "Cluster",
+        }
+    }
+}
+
+impl fmt::Display for ResourceScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
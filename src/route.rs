@@ -0,0 +1,308 @@
+//! Kind-agnostic access to routes.
+//!
+//! All route kinds share their parent references and status, and most share
+//! hostnames. [`AnyRoute`] wraps a route of any kind so that attachment and
+//! status logic can be written once for all of them.
+
+use crate::{consts::GROUP, manifest::GatewayApiObject, *};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::{convert::TryFrom, fmt};
+
+/// A kind of route.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum RouteKind {
+    Http,
+
+    #[cfg(feature = "experimental")]
+    Grpc,
+
+    #[cfg(feature = "experimental")]
+    Tcp,
+
+    #[cfg(feature = "experimental")]
+    Tls,
+
+    #[cfg(feature = "experimental")]
+    Udp,
+}
+
+/// A route of any kind.
+#[derive(Clone, Debug)]
+pub enum AnyRoute {
+    Http(HttpRoute),
+
+    #[cfg(feature = "experimental")]
+    Grpc(GrpcRoute),
+
+    #[cfg(feature = "experimental")]
+    Tcp(TcpRoute),
+
+    #[cfg(feature = "experimental")]
+    Tls(TlsRoute),
+
+    #[cfg(feature = "experimental")]
+    Udp(UdpRoute),
+}
+
+// === impl RouteKind ===
+
+impl RouteKind {
+    /// All route kinds known to this crate.
+    pub const ALL: &'static [Self] = &[
+        Self::Http,
+        #[cfg(feature = "experimental")]
+        Self::Grpc,
+        #[cfg(feature = "experimental")]
+        Self::Tcp,
+        #[cfg(feature = "experimental")]
+        Self::Tls,
+        #[cfg(feature = "experimental")]
+        Self::Udp,
+    ];
+
+    /// Returns the route kind with the given group and kind, if it is known
+    /// to this crate.
+    pub fn from_group_kind(group: &str, kind: &str) -> Option<Self> {
+        if group != GROUP {
+            return None;
+        }
+        Self::ALL.iter().copied().find(|k| k.kind() == kind)
+    }
+
+    /// Returns the API group of the route kind.
+    pub fn group(&self) -> &'static str {
+        GROUP
+    }
+
+    /// Returns the name of the route kind, e.g. `HTTPRoute`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Http => "HTTPRoute",
+            #[cfg(feature = "experimental")]
+            Self::Grpc => "GRPCRoute",
+            #[cfg(feature = "experimental")]
+            Self::Tcp => "TCPRoute",
+            #[cfg(feature = "experimental")]
+            Self::Tls => "TLSRoute",
+            #[cfg(feature = "experimental")]
+            Self::Udp => "UDPRoute",
+        }
+    }
+
+    /// Returns true if routes of this kind match requests by hostname.
+    pub fn has_hostnames(&self) -> bool {
+        match self {
+            Self::Http => true,
+            #[cfg(feature = "experimental")]
+            Self::Grpc | Self::Tls => true,
+            #[cfg(feature = "experimental")]
+            Self::Tcp | Self::Udp => false,
+        }
+    }
+
+    /// Returns true if a listener's `allowedRoutes.kinds` admits routes of
+    /// this kind. Listeners that do not restrict kinds admit all kinds.
+    pub fn is_allowed_by(&self, kinds: Option<&[RouteGroupKind]>) -> bool {
+        match kinds {
+            None | Some([]) => true,
+            Some(kinds) => kinds
+                .iter()
+                .any(|k| k.group.as_deref().unwrap_or(GROUP) == GROUP && k.kind == self.kind()),
+        }
+    }
+}
+
+impl fmt::Display for RouteKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.kind())
+    }
+}
+
+// === impl AnyRoute ===
+
+impl AnyRoute {
+    /// Returns the kind of the route.
+    pub fn kind(&self) -> RouteKind {
+        match self {
+            Self::Http(_) => RouteKind::Http,
+            #[cfg(feature = "experimental")]
+            Self::Grpc(_) => RouteKind::Grpc,
+            #[cfg(feature = "experimental")]
+            Self::Tcp(_) => RouteKind::Tcp,
+            #[cfg(feature = "experimental")]
+            Self::Tls(_) => RouteKind::Tls,
+            #[cfg(feature = "experimental")]
+            Self::Udp(_) => RouteKind::Udp,
+        }
+    }
+
+    /// Returns the route's metadata.
+    pub fn metadata(&self) -> &metav1::ObjectMeta {
+        match self {
+            Self::Http(r) => &r.metadata,
+            #[cfg(feature = "experimental")]
+            Self::Grpc(r) => &r.metadata,
+            #[cfg(feature = "experimental")]
+            Self::Tcp(r) => &r.metadata,
+            #[cfg(feature = "experimental")]
+            Self::Tls(r) => &r.metadata,
+            #[cfg(feature = "experimental")]
+            Self::Udp(r) => &r.metadata,
+        }
+    }
+
+    /// Returns the route's namespace, if it is set.
+    pub fn namespace(&self) -> Option<&str> {
+        self.metadata().namespace.as_deref()
+    }
+
+    /// Returns the route's name, if it is set.
+    pub fn name(&self) -> Option<&str> {
+        self.metadata().name.as_deref()
+    }
+
+    /// Returns the spec fields common to all routes.
+    pub fn common_spec(&self) -> &CommonRouteSpec {
+        match self {
+            Self::Http(r) => &r.spec.inner,
+            #[cfg(feature = "experimental")]
+            Self::Grpc(r) => &r.spec.inner,
+            #[cfg(feature = "experimental")]
+            Self::Tcp(r) => &r.spec.inner,
+            #[cfg(feature = "experimental")]
+            Self::Tls(r) => &r.spec.inner,
+            #[cfg(feature = "experimental")]
+            Self::Udp(r) => &r.spec.inner,
+        }
+    }
+
+    /// Returns the route's parent references.
+    pub fn parent_refs(&self) -> &[ParentReference] {
+        self.common_spec()
+            .parent_refs
+            .as_deref()
+            .unwrap_or_default()
+    }
+
+    /// Returns the route's hostnames, or `None` if routes of its kind do not
+    /// match by hostname.
+    ///
+    /// An empty list means that the route matches all hostnames.
+    pub fn hostnames(&self) -> Option<&[Hostname]> {
+        let hostnames = match self {
+            Self::Http(r) => &r.spec.hostnames,
+            #[cfg(feature = "experimental")]
+            Self::Grpc(r) => &r.spec.hostnames,
+            #[cfg(feature = "experimental")]
+            Self::Tls(r) => &r.spec.hostnames,
+            #[cfg(feature = "experimental")]
+            Self::Tcp(_) | Self::Udp(_) => return None,
+        };
+        Some(hostnames.as_deref().unwrap_or_default())
+    }
+
+    /// Returns the route's status, if it is set.
+    pub fn status(&self) -> Option<&RouteStatus> {
+        match self {
+            Self::Http(r) => r.status.as_ref().map(|s| &s.inner),
+            #[cfg(feature = "experimental")]
+            Self::Grpc(r) => r.status.as_ref().map(|s| &s.inner),
+            #[cfg(feature = "experimental")]
+            Self::Tcp(r) => r.status.as_ref().map(|s| &s.inner),
+            #[cfg(feature = "experimental")]
+            Self::Tls(r) => r.status.as_ref().map(|s| &s.inner),
+            #[cfg(feature = "experimental")]
+            Self::Udp(r) => r.status.as_ref().map(|s| &s.inner),
+        }
+    }
+
+    /// Returns the statuses of the route for each of its parents.
+    pub fn status_parents(&self) -> &[RouteParentStatus] {
+        self.status().map_or(&[], |s| &s.parents[..])
+    }
+
+    /// Replaces the route's status.
+    pub fn set_status(&mut self, inner: RouteStatus) {
+        match self {
+            Self::Http(r) => r.status = Some(HttpRouteStatus { inner }),
+            #[cfg(feature = "experimental")]
+            Self::Grpc(r) => r.status = Some(GrpcRouteStatus { inner }),
+            #[cfg(feature = "experimental")]
+            Self::Tcp(r) => r.status = Some(TcpRouteStatus { inner }),
+            #[cfg(feature = "experimental")]
+            Self::Tls(r) => r.status = Some(TlsRouteStatus { inner }),
+            #[cfg(feature = "experimental")]
+            Self::Udp(r) => r.status = Some(UdpRouteStatus { inner }),
+        }
+    }
+}
+
+impl From<HttpRoute> for AnyRoute {
+    fn from(r: HttpRoute) -> Self {
+        Self::Http(r)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<GrpcRoute> for AnyRoute {
+    fn from(r: GrpcRoute) -> Self {
+        Self::Grpc(r)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<TcpRoute> for AnyRoute {
+    fn from(r: TcpRoute) -> Self {
+        Self::Tcp(r)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<TlsRoute> for AnyRoute {
+    fn from(r: TlsRoute) -> Self {
+        Self::Tls(r)
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl From<UdpRoute> for AnyRoute {
+    fn from(r: UdpRoute) -> Self {
+        Self::Udp(r)
+    }
+}
+
+impl From<AnyRoute> for GatewayApiObject {
+    fn from(r: AnyRoute) -> Self {
+        match r {
+            AnyRoute::Http(r) => Self::HttpRoute(r),
+            #[cfg(feature = "experimental")]
+            AnyRoute::Grpc(r) => Self::GrpcRoute(r),
+            #[cfg(feature = "experimental")]
+            AnyRoute::Tcp(r) => Self::TcpRoute(r),
+            #[cfg(feature = "experimental")]
+            AnyRoute::Tls(r) => Self::TlsRoute(r),
+            #[cfg(feature = "experimental")]
+            AnyRoute::Udp(r) => Self::UdpRoute(r),
+        }
+    }
+}
+
+/// Fails with the original object if it is not a route.
+impl TryFrom<GatewayApiObject> for AnyRoute {
+    type Error = GatewayApiObject;
+
+    fn try_from(obj: GatewayApiObject) -> Result<Self, Self::Error> {
+        match obj {
+            GatewayApiObject::HttpRoute(r) => Ok(Self::Http(r)),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::GrpcRoute(r) => Ok(Self::Grpc(r)),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::TcpRoute(r) => Ok(Self::Tcp(r)),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::TlsRoute(r) => Ok(Self::Tls(r)),
+            #[cfg(feature = "experimental")]
+            GatewayApiObject::UdpRoute(r) => Ok(Self::Udp(r)),
+            obj => Err(obj),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Generation of example objects.
+//!
+//! CLIs that create objects (e.g. `create httproute --dry-run`) and tools
+//! that generate documentation need complete objects from a few arguments.
+//! The generators in this module return objects with every field that the
+//! CRDs default set to its default, as they would be read back from the API
+//! server, so that the output shows users the full shape of each object:
+//!
+//! ```
+//! # use k8s_gateway_api::{scaffold, validation::Validate};
+//! let gateway = scaffold::gateway("web", "example", Some("*.example.com"));
+//! let mut route = scaffold::http_route("store", "store.example.com", "store", 8080);
+//! scaffold::attach(&mut route, &gateway);
+//! assert!(gateway.validate().is_ok());
+//! assert!(route.validate().is_ok());
+//! ```
+//!
+//! Objects are valid if their arguments are, which may be checked with
+//! [`Validate`](crate::validation::Validate). Objects are not assigned a
+//! namespace, so they are created in the namespace of the client that
+//! applies them.
+
+use crate::{consts::GROUP, *};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::convert::TryFrom;
+
+/// The name of the listener of a generated Gateway.
+pub const LISTENER_NAME: &str = "http";
+
+/// Returns a GatewayClass managed by the given controller.
+pub fn gateway_class(name: &str, controller_name: GatewayController) -> GatewayClass {
+    GatewayClass {
+        metadata: metadata(name),
+        spec: GatewayClassSpec {
+            controller_name,
+            parameters_ref: None,
+            description: None,
+        },
+        status: None,
+    }
+}
+
+/// Returns a Gateway of the given class with a single HTTP listener on port
+/// 80, named [`LISTENER_NAME`], that allows routes from its namespace.
+pub fn gateway(name: &str, class_name: &str, hostname: Option<&str>) -> Gateway {
+    Gateway {
+        metadata: metadata(name),
+        spec: GatewaySpec {
+            gateway_class_name: class_name.to_string(),
+            listeners: vec![Listener {
+                name: LISTENER_NAME.to_string(),
+                hostname: hostname.map(Into::into),
+                port: 80,
+                protocol: "HTTP".to_string(),
+                tls: None,
+                allowed_routes: Some(AllowedRoutes {
+                    namespaces: Some(RouteNamespaces {
+                        from: Some("Same".to_string()),
+                        selector: None,
+                    }),
+                    kinds: None,
+                }),
+            }],
+            addresses: None,
+        },
+        status: None,
+    }
+}
+
+/// Returns an HTTPRoute that forwards all requests for `host` to a port of
+/// a Service.
+///
+/// The route has no parents; see [`attach`].
+pub fn http_route(name: &str, host: &str, service: &str, port: PortNumber) -> HttpRoute {
+    let backend = BackendObjectReference {
+        group: Some(Group::try_from("").expect("core group must be valid")),
+        kind: Some(kind("Service")),
+        name: service.to_string(),
+        namespace: None,
+        port: Some(port),
+    };
+    HttpRoute {
+        metadata: metadata(name),
+        spec: HttpRouteSpec {
+            inner: CommonRouteSpec { parent_refs: None },
+            hostnames: Some(vec![host.to_string()]),
+            rules: Some(vec![HttpRouteRule {
+                name: None,
+                matches: Some(vec![HttpRouteMatch {
+                    path: Some(HttpPathMatch::PathPrefix {
+                        value: "/".to_string(),
+                    }),
+                    ..HttpRouteMatch::default()
+                }]),
+                filters: None,
+                backend_refs: Some(vec![HttpBackendRef {
+                    backend_ref: Some(BackendRef {
+                        weight: Some(1),
+                        inner: backend,
+                    }),
+                    filters: None,
+                }]),
+            }]),
+        },
+        status: None,
+    }
+}
+
+/// Adds a reference to a Gateway to the parents of a route.
+///
+/// The reference only names the Gateway's namespace if it has one, so a
+/// route and Gateway without namespaces are created in the same namespace.
+///
+/// # Panics
+///
+/// If the Gateway's namespace is not a valid namespace name.
+pub fn attach(route: &mut HttpRoute, gateway: &Gateway) {
+    let namespace = gateway
+        .metadata
+        .namespace
+        .as_deref()
+        .filter(|ns| Some(*ns) != route.metadata.namespace.as_deref())
+        .map(|ns| Namespace::try_from(ns).expect("namespace must be valid"));
+    let parent_ref = ParentReference {
+        group: Some(Group::try_from(GROUP).expect("group must be valid")),
+        kind: Some(kind("Gateway")),
+        namespace,
+        name: gateway.metadata.name.clone().unwrap_or_default(),
+        section_name: None,
+        port: None,
+    };
+    route
+        .spec
+        .inner
+        .parent_refs
+        .get_or_insert_with(Vec::new)
+        .push(parent_ref);
+}
+
+fn metadata(name: &str) -> metav1::ObjectMeta {
+    metav1::ObjectMeta {
+        name: Some(name.to_string()),
+        ..metav1::ObjectMeta::default()
+    }
+}
+
+fn kind(kind: &str) -> Kind {
+    Kind::try_from(kind).expect("kind must be valid")
+}
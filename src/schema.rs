@@ -0,0 +1,134 @@
+//! JSON Schemas for Gateway API objects.
+//!
+//! The schemas embedded in CRDs are restricted to the structural subset of
+//! OpenAPI v3 and describe neither `apiVersion` nor `kind`. [`schemas`]
+//! instead returns standalone JSON Schema (draft 7) documents that describe
+//! complete objects, suitable for documentation generators and editor
+//! integrations such as YAML language server schema catalogs:
+//!
+//! ```ignore
+//! for s in schema::schemas() {
+//!     let path = format!("{}-{}.json", s.kind.to_lowercase(), s.version);
+//!     std::fs::write(path, serde_json::to_vec_pretty(&s.schema)?)?;
+//! }
+//! ```
+
+use crate::{consts::GROUP, *};
+use schemars::{
+    gen::SchemaSettings,
+    schema::{InstanceType, RootSchema, Schema, SchemaObject},
+    JsonSchema,
+};
+
+/// The JSON Schema of a kind at one of its API versions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KindSchema {
+    /// The API group, i.e. `gateway.networking.k8s.io`.
+    pub group: &'static str,
+
+    /// The API version, e.g. `v1beta1`.
+    pub version: &'static str,
+
+    /// The kind, e.g. `HTTPRoute`.
+    pub kind: &'static str,
+
+    /// The schema of an object of this kind and version.
+    pub schema: RootSchema,
+}
+
+/// Returns the schema of every kind known to this crate, at every API version
+/// that is decoded into this crate's types.
+pub fn schemas() -> Vec<KindSchema> {
+    let mut schemas = Vec::new();
+    for version in ["v1alpha2", "v1beta1"] {
+        schemas.push(KindSchema::new::<GatewayClass>(version, "GatewayClass"));
+        schemas.push(KindSchema::new::<Gateway>(version, "Gateway"));
+        schemas.push(KindSchema::new::<HttpRoute>(version, "HTTPRoute"));
+    }
+
+    #[cfg(feature = "experimental")]
+    {
+        schemas.push(KindSchema::new::<BackendLbPolicy>(
+            "v1alpha2",
+            "BackendLBPolicy",
+        ));
+        schemas.push(KindSchema::new::<GrpcRoute>("v1alpha2", "GRPCRoute"));
+        schemas.push(KindSchema::new::<ReferenceGrant>(
+            "v1alpha2",
+            "ReferenceGrant",
+        ));
+        schemas.push(KindSchema::new::<TcpRoute>("v1alpha2", "TCPRoute"));
+        schemas.push(KindSchema::new::<TlsRoute>("v1alpha2", "TLSRoute"));
+        schemas.push(KindSchema::new::<UdpRoute>("v1alpha2", "UDPRoute"));
+    }
+
+    schemas
+}
+
+/// Returns the schema of a kind at an API version, if it is known to this
+/// crate.
+pub fn schema_for(version: &str, kind: &str) -> Option<KindSchema> {
+    schemas()
+        .into_iter()
+        .find(|s| s.version == version && s.kind == kind)
+}
+
+/// Returns a string schema that only admits `value`.
+fn constant(value: String) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        enum_values: Some(vec![value.into()]),
+        ..Default::default()
+    }
+    .into()
+}
+
+// === impl KindSchema ===
+
+impl KindSchema {
+    fn new<K: JsonSchema>(version: &'static str, kind: &'static str) -> Self {
+        let mut schema = SchemaSettings::draft07()
+            .into_generator()
+            .into_root_schema_for::<K>();
+
+        // The derived schemas of custom resources omit the type metadata and
+        // the object metadata, as they are validated by the API server.
+        let api_version = format!("{}/{}", GROUP, version);
+        let object = schema.schema.object();
+        object
+            .properties
+            .insert("apiVersion".to_string(), constant(api_version.clone()));
+        object
+            .properties
+            .insert("kind".to_string(), constant(kind.to_string()));
+        object.properties.insert(
+            "metadata".to_string(),
+            SchemaObject {
+                instance_type: Some(InstanceType::Object.into()),
+                ..Default::default()
+            }
+            .into(),
+        );
+        object.required.insert("apiVersion".to_string());
+        object.required.insert("kind".to_string());
+        schema.schema.metadata().title = Some(format!("{} {}", kind, api_version));
+
+        Self {
+            group: GROUP,
+            version,
+            kind,
+            schema,
+        }
+    }
+
+    /// Returns the `apiVersion` of objects described by this schema, e.g.
+    /// `gateway.networking.k8s.io/v1beta1`.
+    pub fn api_version(&self) -> String {
+        format!("{}/{}", self.group, self.version)
+    }
+
+    /// Returns the schema as a JSON value.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(&self.schema).expect("schema must serialize")
+    }
+}
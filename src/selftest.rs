@@ -0,0 +1,823 @@
+//! Self-tests of dataplanes against the routing semantics of the Gateway API.
+//!
+//! Dataplanes that route requests themselves, rather than configuring a
+//! proxy from a [`RouteTable`](ir::RouteTable), must reproduce rules that are
+//! easy to get subtly wrong: how listener and route hostnames intersect,
+//! which of several matching rules takes precedence, and the order in which
+//! filters are applied. [`run`] checks a [`RoutingBehavior`] against a
+//! battery of such [`cases`], without a cluster:
+//!
+//! ```ignore
+//! let report = selftest::run(&mut MyDataplane::default());
+//! report.assert_ok();
+//! ```
+//!
+//! Each [`Case`] is a [`Snapshot`] of a Gateway and its HTTPRoutes, with
+//! requests to that Gateway. The expected routing of each request is
+//! computed by this crate's reference logic, i.e. the [`ir`] compiler and the
+//! [`Matcher`], so dataplanes may add cases of their own with [`run_cases`].
+
+use crate::{
+    filter::FilterOrder,
+    ir::{Compiler, RouteTable},
+    matcher::{HttpRequest, Matcher},
+    simulate::Request,
+    snapshot::{Event, ObjectKey, Snapshot, SnapshotStore},
+    *,
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use k8s_openapi::chrono::{TimeZone, Utc};
+use std::{convert::TryFrom, fmt};
+
+/// The routing of a dataplane under test.
+pub trait RoutingBehavior {
+    /// Configures the dataplane with the Gateway and HTTPRoutes of a
+    /// snapshot, replacing the configuration of any earlier case.
+    ///
+    /// The dataplane must apply filters in `filter_order`.
+    fn configure(&mut self, snapshot: &Snapshot, filter_order: FilterOrder);
+
+    /// Returns how the dataplane routes a request to the configured Gateway,
+    /// or `None` if no rule handles it.
+    fn route(&mut self, request: &Request<'_>) -> Option<Routed>;
+}
+
+/// The rule that handles a request, and the filters applied to it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Routed {
+    /// The HTTPRoute that defines the rule.
+    pub route: ObjectKey,
+
+    /// The index of the rule within the HTTPRoute.
+    pub rule_index: usize,
+
+    /// The filters applied to requests forwarded to the rule's first valid
+    /// backend, in the order in which they are applied, or the rule's
+    /// filters if it has no valid backends.
+    pub filters: Vec<HttpRouteFilter>,
+}
+
+/// The routing rule that a case exercises.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    /// Routes only handle requests for hostnames that match both the route
+    /// and the listener, and listeners with more specific hostnames are
+    /// isolated from less specific ones.
+    HostnameIntersection,
+
+    /// When several rules match a request, the most specific match handles
+    /// it, then the oldest route, then the first route by namespace and
+    /// name, then the first rule and match.
+    Precedence,
+
+    /// A rule's filters are applied before its backend's, in the order
+    /// determined by the [`FilterOrder`].
+    FilterOrdering,
+}
+
+/// Requests to a Gateway, and the snapshot that configures it.
+#[derive(Clone, Debug)]
+pub struct Case {
+    /// Identifies the case, e.g. `listener-isolation`.
+    pub name: String,
+
+    /// The routing rule that the case exercises.
+    pub category: Category,
+
+    /// The Gateway and HTTPRoutes with which the dataplane is configured.
+    pub snapshot: Snapshot,
+
+    /// The order in which the dataplane must apply filters.
+    pub filter_order: FilterOrder,
+
+    /// The requests whose routing is checked.
+    pub requests: Vec<CaseRequest>,
+}
+
+/// A request of a [`Case`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CaseRequest {
+    /// The port on which the request is received.
+    pub port: PortNumber,
+
+    /// The requested hostname, without a port.
+    pub host: String,
+
+    /// The request method, e.g. `GET`.
+    pub method: String,
+
+    /// The request target, i.e. a path and an optional query string.
+    pub target: String,
+
+    /// The request headers.
+    pub headers: Vec<HttpHeader>,
+}
+
+/// The result of running cases against a dataplane.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// The number of requests that were checked.
+    pub checked: usize,
+
+    /// The requests that the dataplane did not route as expected.
+    pub failures: Vec<Failure>,
+}
+
+/// A request that the dataplane did not route as expected.
+#[derive(Clone, Debug)]
+pub struct Failure {
+    /// The name of the case.
+    pub case: String,
+
+    /// The routing rule that the case exercises.
+    pub category: Category,
+
+    /// The request.
+    pub request: CaseRequest,
+
+    /// How the reference logic routes the request.
+    pub expected: Option<Routed>,
+
+    /// How the dataplane routed the request.
+    pub actual: Option<Routed>,
+}
+
+/// Checks a dataplane against every case returned by [`cases`].
+pub fn run<B: RoutingBehavior + ?Sized>(behavior: &mut B) -> Report {
+    run_cases(&cases(), behavior)
+}
+
+/// Checks a dataplane against the given cases, in order.
+pub fn run_cases<B: RoutingBehavior + ?Sized>(cases: &[Case], behavior: &mut B) -> Report {
+    let mut report = Report::default();
+    for case in cases {
+        let table = Compiler::default()
+            .with_filter_order(case.filter_order)
+            .compile(&case.snapshot);
+        behavior.configure(&case.snapshot, case.filter_order);
+        for req in &case.requests {
+            let request = Request {
+                gateway: case.snapshot.key(),
+                port: req.port,
+                host: &req.host,
+                http: HttpRequest::new(&req.method, &req.target).with_headers(&req.headers),
+            };
+            let expected = case.reference(&table, &request);
+            let actual = behavior.route(&request);
+            report.checked += 1;
+            if actual != expected {
+                report.failures.push(Failure {
+                    case: case.name.clone(),
+                    category: case.category,
+                    request: req.clone(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    report
+}
+
+/// Returns the cases that [`run`] checks.
+pub fn cases() -> Vec<Case> {
+    let mut cases = hostname_cases();
+    cases.extend(precedence_cases());
+    cases.extend(filter_cases());
+    cases
+}
+
+fn hostname_cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    let gw = gateway(&[("http", Some("*.example.com"))]);
+    let routes = vec![route(
+        &gw,
+        "foo",
+        &["foo.example.com"],
+        vec![rule(vec![prefix("/")])],
+    )];
+    cases.push(
+        Case::new(
+            "wildcard-listener-exact-route",
+            Category::HostnameIntersection,
+            snapshot(gw, routes),
+        )
+        .with_request(get("foo.example.com", "/"))
+        .with_request(get("FOO.example.com", "/"))
+        .with_request(get("bar.example.com", "/"))
+        .with_request(get("example.com", "/")),
+    );
+
+    let gw = gateway(&[("http", Some("foo.example.com"))]);
+    let routes = vec![route(
+        &gw,
+        "any",
+        &["*.example.com"],
+        vec![rule(vec![prefix("/")])],
+    )];
+    cases.push(
+        Case::new(
+            "exact-listener-wildcard-route",
+            Category::HostnameIntersection,
+            snapshot(gw, routes),
+        )
+        .with_request(get("foo.example.com", "/"))
+        .with_request(get("bar.example.com", "/")),
+    );
+
+    let gw = gateway(&[("http", Some("*.example.com"))]);
+    let routes = vec![
+        route(&gw, "any", &[], vec![rule(vec![prefix("/")])]),
+        route(
+            &gw,
+            "other",
+            &["foo.example.org"],
+            vec![rule(vec![prefix("/other")])],
+        ),
+    ];
+    cases.push(
+        Case::new(
+            "disjoint-hostnames",
+            Category::HostnameIntersection,
+            snapshot(gw, routes),
+        )
+        .with_request(get("a.b.example.com", "/other"))
+        .with_request(get("foo.example.org", "/other")),
+    );
+
+    let gw = gateway(&[("http", None)]);
+    let hostnames = ["*.example.com", "bar.example.org"];
+    let routes = vec![route(&gw, "foo", &hostnames, vec![rule(vec![prefix("/")])])];
+    cases.push(
+        Case::new(
+            "listener-without-hostname",
+            Category::HostnameIntersection,
+            snapshot(gw, routes),
+        )
+        .with_request(get("a.example.com", "/"))
+        .with_request(get("example.com", "/"))
+        .with_request(get("bar.example.org", "/"))
+        .with_request(get("baz.example.org", "/")),
+    );
+
+    // The wildcard route lists foo.example.com, but requests for it are
+    // handled by the more specific listener.
+    let gw = gateway(&[
+        ("wildcard", Some("*.example.com")),
+        ("foo", Some("foo.example.com")),
+    ]);
+    let routes = vec![
+        attach_to(
+            route(
+                &gw,
+                "wildcard",
+                &["*.example.com", "foo.example.com"],
+                vec![rule(vec![prefix("/")])],
+            ),
+            "wildcard",
+        ),
+        attach_to(
+            route(&gw, "foo", &[], vec![rule(vec![prefix("/foo")])]),
+            "foo",
+        ),
+    ];
+    cases.push(
+        Case::new(
+            "listener-isolation",
+            Category::HostnameIntersection,
+            snapshot(gw, routes),
+        )
+        .with_request(get("bar.example.com", "/"))
+        .with_request(get("foo.example.com", "/"))
+        .with_request(get("foo.example.com", "/foo")),
+    );
+
+    cases
+}
+
+fn precedence_cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    let gw = gateway(&[("http", None)]);
+    let rules = vec![
+        rule(vec![prefix("/")]),
+        rule(vec![prefix("/foo")]),
+        rule(vec![prefix("/foo/bar")]),
+        rule(vec![exact("/foo")]),
+    ];
+    let routes = vec![route(&gw, "paths", &[], rules)];
+    cases.push(
+        Case::new(
+            "path-specificity",
+            Category::Precedence,
+            snapshot(gw, routes),
+        )
+        .with_request(get("example.com", "/foo"))
+        .with_request(get("example.com", "/foo/"))
+        .with_request(get("example.com", "/foo/bar/baz"))
+        .with_request(get("example.com", "/foo/barbaz"))
+        .with_request(get("example.com", "/foobar")),
+    );
+
+    let gw = gateway(&[("http", None)]);
+    let rules = vec![
+        rule(vec![prefix("/")]),
+        rule(vec![HttpRouteMatch {
+            query_params: Some(vec![query("debug", "1"), query("trace", "1")]),
+            ..prefix("/")
+        }]),
+        rule(vec![HttpRouteMatch {
+            headers: Some(vec![header("x-canary", "1")]),
+            ..prefix("/")
+        }]),
+        rule(vec![HttpRouteMatch {
+            headers: Some(vec![header("x-canary", "1"), header("x-user", "a")]),
+            ..prefix("/")
+        }]),
+        rule(vec![HttpRouteMatch {
+            method: Some("POST".to_string()),
+            ..prefix("/")
+        }]),
+    ];
+    let routes = vec![route(&gw, "conditions", &[], rules)];
+    cases.push(
+        Case::new(
+            "match-conditions",
+            Category::Precedence,
+            snapshot(gw, routes),
+        )
+        .with_request(get("example.com", "/"))
+        .with_request(get("example.com", "/?debug=1&trace=1"))
+        .with_request(get("example.com", "/?debug=1&trace=1").with_header("x-canary", "1"))
+        .with_request(
+            get("example.com", "/")
+                .with_header("x-canary", "1")
+                .with_header("x-user", "a"),
+        )
+        .with_request(
+            CaseRequest::new(80, "example.com", "POST", "/")
+                .with_header("x-canary", "1")
+                .with_header("x-user", "a"),
+        ),
+    );
+
+    // The oldest route takes precedence over routes with earlier names, and
+    // routes created at the same time are ordered by name.
+    let gw = gateway(&[("http", None)]);
+    let routes = vec![
+        created(route(&gw, "a", &[], vec![rule(vec![prefix("/")])]), 2),
+        created(route(&gw, "c", &[], vec![rule(vec![prefix("/")])]), 1),
+        route(&gw, "e", &[], vec![rule(vec![prefix("/shared")])]),
+        route(&gw, "d", &[], vec![rule(vec![prefix("/shared")])]),
+    ];
+    cases.push(
+        Case::new(
+            "route-age-and-name",
+            Category::Precedence,
+            snapshot(gw, routes),
+        )
+        .with_request(get("example.com", "/"))
+        .with_request(get("example.com", "/shared")),
+    );
+
+    let gw = gateway(&[("http", None)]);
+    let rules = vec![
+        rule(vec![prefix("/a"), exact("/b")]),
+        rule(vec![exact("/b"), prefix("/a")]),
+    ];
+    let routes = vec![route(&gw, "rules", &[], rules)];
+    cases.push(
+        Case::new("rule-order", Category::Precedence, snapshot(gw, routes))
+            .with_request(get("example.com", "/a"))
+            .with_request(get("example.com", "/b")),
+    );
+
+    cases
+}
+
+fn filter_cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    let gw = gateway(&[("http", None)]);
+    let mut rewrite = rule(vec![prefix("/")]);
+    rewrite.filters = Some(vec![
+        mirror("mirror"),
+        url_rewrite("rewritten.example.com"),
+        header_modifier("x-rule"),
+        extension("rule"),
+    ]);
+    with_backend_filters(
+        &mut rewrite,
+        vec![header_modifier("x-backend"), mirror("backend-mirror")],
+    );
+    let mut redirect = rule(vec![prefix("/redirect")]);
+    redirect.filters = Some(vec![
+        redirect_to("redirected.example.com"),
+        header_modifier("x-redirect"),
+        mirror("mirror"),
+    ]);
+    let routes = vec![route(&gw, "filters", &[], vec![rewrite, redirect])];
+    let snapshot = snapshot(gw, routes);
+
+    for (name, order) in [
+        ("specified-filter-order", FilterOrder::Specified),
+        ("phased-filter-order", FilterOrder::Phased),
+    ] {
+        cases.push(
+            Case::new(name, Category::FilterOrdering, snapshot.clone())
+                .with_filter_order(order)
+                .with_request(get("example.com", "/"))
+                .with_request(get("example.com", "/redirect")),
+        );
+    }
+
+    cases
+}
+
+// === impl Routed ===
+
+impl Routed {
+    fn from_route(route: &ir::Route) -> Self {
+        let filters = route
+            .backends
+            .iter()
+            .find(|b| b.cluster.is_some())
+            .map_or(&route.filters, |b| &b.filters);
+        Self {
+            route: route.source.clone(),
+            rule_index: route.rule_index,
+            filters: filters.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Routed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} rule {}", self.route, self.rule_index)?;
+        if !self.filters.is_empty() {
+            let filters = self
+                .filters
+                .iter()
+                .map(|f| f.type_name())
+                .collect::<Vec<_>>();
+            write!(f, " with filters [{}]", filters.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// === impl Category ===
+
+impl Category {
+    /// Returns the name of the category, e.g. `HostnameIntersection`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HostnameIntersection => "HostnameIntersection",
+            Self::Precedence => "Precedence",
+            Self::FilterOrdering => "FilterOrdering",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+// === impl Case ===
+
+impl Case {
+    /// Returns a case without requests, in which filters are applied in the
+    /// order in which they are specified.
+    pub fn new(name: impl Into<String>, category: Category, snapshot: Snapshot) -> Self {
+        Self {
+            name: name.into(),
+            category,
+            snapshot,
+            filter_order: FilterOrder::default(),
+            requests: Vec::new(),
+        }
+    }
+
+    /// Sets the order in which the dataplane must apply filters.
+    pub fn with_filter_order(mut self, order: FilterOrder) -> Self {
+        self.filter_order = order;
+        self
+    }
+
+    /// Adds a request to the case.
+    pub fn with_request(mut self, request: CaseRequest) -> Self {
+        self.requests.push(request);
+        self
+    }
+
+    /// Routes a request as a dataplane configured from the compiled table
+    /// of the case's snapshot would.
+    fn reference(&self, table: &RouteTable, request: &Request<'_>) -> Option<Routed> {
+        let listeners = &self.snapshot.gateway().spec.listeners;
+        listener::select(listeners, request.port, request.host)?;
+        let matcher = Matcher::new();
+        let route = table
+            .virtual_host(request.port, request.host)?
+            .routes
+            .iter()
+            .find(|r| matcher.http_match(&r.matcher, &request.http))?;
+        Some(Routed::from_route(route))
+    }
+}
+
+// === impl CaseRequest ===
+
+impl CaseRequest {
+    /// Returns a request without headers.
+    pub fn new(
+        port: PortNumber,
+        host: impl Into<String>,
+        method: impl Into<String>,
+        target: impl Into<String>,
+    ) -> Self {
+        Self {
+            port,
+            host: host.into(),
+            method: method.into(),
+            target: target.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Adds a header to the request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push(HttpHeader {
+            name: name.into(),
+            value: value.into(),
+        });
+        self
+    }
+}
+
+impl fmt::Display for CaseRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}:{}{}",
+            self.method, self.host, self.port, self.target
+        )?;
+        for header in &self.headers {
+            write!(f, " {}={}", header.name, header.value)?;
+        }
+        Ok(())
+    }
+}
+
+// === impl Report ===
+
+impl Report {
+    /// Returns true if every request was routed as expected.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Panics with a description of all failures, if there are any.
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("{}", self);
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests checked, {} failures",
+            self.checked,
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+// === impl Failure ===
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |routed: &Option<Routed>| match routed {
+            Some(routed) => routed.to_string(),
+            None => "not routed".to_string(),
+        };
+        write!(
+            f,
+            "{}/{}: {}: expected {}, got {}",
+            self.category,
+            self.case,
+            self.request,
+            describe(&self.expected),
+            describe(&self.actual)
+        )
+    }
+}
+
+// === Case construction ===
+
+const NAMESPACE: &str = "default";
+
+fn get(host: &str, target: &str) -> CaseRequest {
+    CaseRequest::new(80, host, "GET", target)
+}
+
+fn snapshot(gateway: Gateway, routes: Vec<HttpRoute>) -> Snapshot {
+    let key = ObjectKey::from_meta(&gateway.metadata);
+    let mut store = SnapshotStore::default();
+    store.apply(Event::Restarted(vec![gateway]));
+    store.apply(Event::Restarted(routes));
+    store.get(&key).expect("gateway must be stored").clone()
+}
+
+/// Returns a Gateway with HTTP listeners on port 80 that allow routes from
+/// its namespace.
+fn gateway(listeners: &[(&str, Option<&str>)]) -> Gateway {
+    let mut gateway = scaffold::gateway("selftest", "selftest", None);
+    gateway.metadata.namespace = Some(NAMESPACE.to_string());
+    let template = gateway.spec.listeners.remove(0);
+    gateway.spec.listeners = listeners
+        .iter()
+        .map(|(name, hostname)| Listener {
+            name: name.to_string(),
+            hostname: hostname.map(Into::into),
+            ..template.clone()
+        })
+        .collect();
+    gateway
+}
+
+fn route(
+    gateway: &Gateway,
+    name: &str,
+    hostnames: &[&str],
+    rules: Vec<HttpRouteRule>,
+) -> HttpRoute {
+    let mut route = scaffold::http_route(name, "", name, 8080);
+    route.metadata.namespace = Some(NAMESPACE.to_string());
+    route.spec.hostnames = if hostnames.is_empty() {
+        None
+    } else {
+        Some(hostnames.iter().map(|h| h.to_string()).collect())
+    };
+    let backend_refs = route
+        .spec
+        .rules
+        .as_mut()
+        .and_then(|r| r[0].backend_refs.take());
+    route.spec.rules = Some(
+        rules
+            .into_iter()
+            .map(|rule| HttpRouteRule {
+                backend_refs: rule.backend_refs.or_else(|| backend_refs.clone()),
+                ..rule
+            })
+            .collect(),
+    );
+    scaffold::attach(&mut route, gateway);
+    route
+}
+
+/// Limits a route to a single listener of its Gateway.
+fn attach_to(mut route: HttpRoute, listener: &str) -> HttpRoute {
+    for parent_ref in route.spec.inner.parent_refs.iter_mut().flatten() {
+        parent_ref.section_name =
+            Some(SectionName::try_from(listener).expect("listener name must be valid"));
+    }
+    route
+}
+
+fn created(mut route: HttpRoute, secs: i64) -> HttpRoute {
+    let time = Utc.timestamp_opt(secs, 0).unwrap();
+    route.metadata.creation_timestamp = Some(metav1::Time(time));
+    route
+}
+
+/// Returns a rule that forwards to the route's Service; see [`route`].
+fn rule(matches: Vec<HttpRouteMatch>) -> HttpRouteRule {
+    HttpRouteRule {
+        name: None,
+        matches: Some(matches),
+        filters: None,
+        backend_refs: None,
+    }
+}
+
+fn with_backend_filters(rule: &mut HttpRouteRule, filters: Vec<HttpRouteFilter>) {
+    let backend = HttpBackendRef {
+        backend_ref: Some(BackendRef {
+            weight: Some(1),
+            inner: service("filters"),
+        }),
+        filters: Some(filters),
+    };
+    rule.backend_refs = Some(vec![backend]);
+}
+
+fn prefix(value: &str) -> HttpRouteMatch {
+    HttpRouteMatch {
+        path: Some(HttpPathMatch::PathPrefix {
+            value: value.to_string(),
+        }),
+        ..HttpRouteMatch::default()
+    }
+}
+
+fn exact(value: &str) -> HttpRouteMatch {
+    HttpRouteMatch {
+        path: Some(HttpPathMatch::Exact {
+            value: value.to_string(),
+        }),
+        ..HttpRouteMatch::default()
+    }
+}
+
+fn header(name: &str, value: &str) -> HttpHeaderMatch {
+    HttpHeaderMatch::Exact {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn query(name: &str, value: &str) -> HttpQueryParamMatch {
+    HttpQueryParamMatch::Exact {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn service(name: &str) -> BackendObjectReference {
+    BackendObjectReference {
+        group: Some(Group::try_from("").expect("core group must be valid")),
+        kind: Some(Kind::try_from("Service").expect("kind must be valid")),
+        name: name.to_string(),
+        namespace: None,
+        port: Some(8080),
+    }
+}
+
+fn header_modifier(name: &str) -> HttpRouteFilter {
+    HttpRouteFilter::RequestHeaderModifier {
+        request_header_modifier: Box::new(HttpRequestHeaderFilter {
+            set: Some(vec![HttpHeader {
+                name: name.to_string(),
+                value: "1".to_string(),
+            }]),
+            add: None,
+            remove: None,
+        }),
+    }
+}
+
+fn mirror(service_name: &str) -> HttpRouteFilter {
+    HttpRouteFilter::RequestMirror {
+        request_mirror: Box::new(HttpRequestMirrorFilter {
+            backend_ref: service(service_name),
+            percent: None,
+            fraction: None,
+        }),
+    }
+}
+
+fn url_rewrite(hostname: &str) -> HttpRouteFilter {
+    HttpRouteFilter::URLRewrite {
+        url_rewrite: Box::new(HttpUrlRewriteFilter {
+            hostname: Some(precise(hostname)),
+            path: None,
+        }),
+    }
+}
+
+fn redirect_to(hostname: &str) -> HttpRouteFilter {
+    HttpRouteFilter::RequestRedirect {
+        request_redirect: Box::new(HttpRequestRedirectFilter {
+            scheme: None,
+            hostname: Some(precise(hostname)),
+            path: None,
+            port: None,
+            status_code: None,
+        }),
+    }
+}
+
+fn extension(name: &str) -> HttpRouteFilter {
+    HttpRouteFilter::ExtensionRef {
+        extension_ref: Box::new(LocalObjectReference {
+            group: Group::try_from("filters.example.com").expect("group must be valid"),
+            kind: Kind::try_from("Filter").expect("kind must be valid"),
+            name: name.to_string(),
+        }),
+    }
+}
+
+fn precise(hostname: &str) -> PreciseHostname {
+    PreciseHostname::try_from(hostname.to_string()).expect("hostname must be precise")
+}
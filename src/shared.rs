@@ -1,5 +1,147 @@
+use crate::BackendObjectReference;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
 
+/// Defines a string newtype that is validated when it is constructed or
+/// deserialized. The length and pattern constraints are reflected in the
+/// type's JSON schema.
+///
+/// The length is checked first; `validate` then checks the pattern, and
+/// returns the reason that a value does not match it, so that errors explain
+/// which part of the value is invalid.
+macro_rules! validated_string {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident {
+            min_length: $min:expr,
+            max_length: $max:expr,
+            pattern: $pattern:expr,
+            validate: $validate:expr,
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(
+            Clone,
+            Debug,
+            Eq,
+            PartialEq,
+            Hash,
+            Ord,
+            PartialOrd,
+            serde::Deserialize,
+            serde::Serialize,
+        )]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            /// Returns the value as a string slice.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = InvalidValue;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                let len = value.len();
+                let validate: fn(&str) -> Result<(), &'static str> = $validate;
+                let reason = if !($min..=$max).contains(&len) {
+                    if len > $max {
+                        format!("must be no more than {} characters", $max)
+                    } else if len == 0 {
+                        "must not be empty".to_string()
+                    } else {
+                        format!("must be at least {} characters", $min)
+                    }
+                } else {
+                    match validate(&value) {
+                        Ok(()) => return Ok(Self(value)),
+                        Err(reason) => reason.to_string(),
+                    }
+                };
+                Err(InvalidValue::new(stringify!($name), value, reason))
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = InvalidValue;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                Self::try_from(value.to_string())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = InvalidValue;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::try_from(s)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from($name(value): $name) -> Self {
+                value
+            }
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl std::borrow::Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl schemars::JsonSchema for $name {
+            fn schema_name() -> String {
+                stringify!($name).to_string()
+            }
+
+            fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+                string_schema($min, $max, $pattern)
+            }
+        }
+    };
+}
+
 /// ParentReference identifies an API object (usually a Gateway) that can be considered
 /// a parent of this resource (usually a route). The only kind of parent resource
 /// with "Core" support is Gateway. This API may be extended in the future to
@@ -110,6 +252,7 @@ pub struct CommonRouteSpec {
     /// may choose to merge compatible Gateway Listeners together. If that is
     /// the case, the list of routes attached to those resources should also be
     /// merged.
+    #[schemars(length(max = "crate::validation::MAX_PARENT_REFS"))]
     pub parent_refs: Option<Vec<ParentReference>>,
 }
 
@@ -141,9 +284,9 @@ pub struct BackendRef {
     /// Support for this field varies based on the context where used.
     pub weight: Option<u16>,
 
-    pub name: String,
-
-    pub port: PortNumber,
+    /// BackendObjectReference references a Kubernetes object.
+    #[serde(flatten)]
+    pub inner: BackendObjectReference,
 }
 
 /// RouteConditionType is a type of condition for a route.
@@ -199,6 +342,13 @@ pub struct RouteParentStatus {
     pub conditions: Vec<metav1::Condition>,
 }
 
+impl RouteParentStatus {
+    /// Returns true if this status was written by the given controller.
+    pub fn is_written_by(&self, controller: &GatewayController) -> bool {
+        self.controller_name == *controller
+    }
+}
+
 /// RouteStatus defines the common attributes that all Routes MUST include
 /// within their status.
 #[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
@@ -217,9 +367,33 @@ pub struct RouteStatus {
     ///
     /// A maximum of 32 Gateways will be represented in this list. An empty list
     /// means the route has not been attached to any Gateway.
+    #[schemars(length(max = "crate::status::MAX_PARENT_STATUSES"))]
     pub parents: Vec<RouteParentStatus>,
 }
 
+impl RouteStatus {
+    /// Returns the parent statuses written by the given controller.
+    pub fn parents_written_by<'a>(
+        &'a self,
+        controller: &'a GatewayController,
+    ) -> impl Iterator<Item = &'a RouteParentStatus> + 'a {
+        self.parents
+            .iter()
+            .filter(move |p| p.is_written_by(controller))
+    }
+
+    /// Returns the status written by the given controller for a parent.
+    pub fn parent_status(
+        &self,
+        controller: &GatewayController,
+        parent_ref: &ParentReference,
+    ) -> Option<&RouteParentStatus> {
+        self.parents
+            .iter()
+            .find(|p| p.is_written_by(controller) && p.parent_ref == *parent_ref)
+    }
+}
+
 /// Hostname is the fully qualified domain name of a network host. This matches
 /// the RFC 1123 definition of a hostname with 2 notable exceptions:
 ///
@@ -236,43 +410,74 @@ pub struct RouteStatus {
 /// character. No other punctuation is allowed.
 pub type Hostname = String;
 
-/// PreciseHostname is the fully qualified domain name of a network host. This
-/// matches the RFC 1123 definition of a hostname with 1 notable exception that
-/// numeric IP addresses are not allowed.
-///
-/// Note that as per RFC1035 and RFC1123, a *label* must consist of lower case
-/// alphanumeric characters or '-', and must start and end with an alphanumeric
-/// character. No other punctuation is allowed.
-pub type PreciseHostname = String;
+validated_string! {
+    /// PreciseHostname is the fully qualified domain name of a network host. This
+    /// matches the RFC 1123 definition of a hostname with 1 notable exception that
+    /// numeric IP addresses are not allowed.
+    ///
+    /// Note that as per RFC1035 and RFC1123, a *label* must consist of lower case
+    /// alphanumeric characters or '-', and must start and end with an alphanumeric
+    /// character. No other punctuation is allowed.
+    ///
+    /// Unlike a [`Hostname`], a PreciseHostname may not be a wildcard, so a
+    /// `Hostname` is converted with `TryFrom`:
+    ///
+    /// ```
+    /// # use k8s_gateway_api::PreciseHostname;
+    /// # use std::convert::TryFrom;
+    /// assert!(PreciseHostname::try_from("foo.example.com".to_string()).is_ok());
+    /// assert!(PreciseHostname::try_from("*.example.com".to_string()).is_err());
+    /// ```
+    pub struct PreciseHostname {
+        min_length: 1,
+        max_length: 253,
+        pattern: r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*$",
+        validate: validate_precise_hostname,
+    }
+}
 
-/// Group refers to a Kubernetes Group. It must either be an empty string or a
-/// RFC 1123 subdomain.
-///
-/// This validation is based off of the corresponding Kubernetes validation:
-/// <https://github.com/kubernetes/apimachinery/blob/02cfb53916346d085a6c6c7c66f882e3c6b0eca6/pkg/util/validation/validation.go#L208>
-///
-/// Valid values include:
-///
-/// * "" - empty string implies core Kubernetes API group
-/// * "networking.k8s.io"
-/// * "foo.example.com"
-///
-/// Invalid values include:
-///
-/// * "example.com/bar" - "/" is an invalid character
-pub type Group = String;
+validated_string! {
+    /// Group refers to a Kubernetes Group. It must either be an empty string or a
+    /// RFC 1123 subdomain.
+    ///
+    /// This validation is based off of the corresponding Kubernetes validation:
+    /// <https://github.com/kubernetes/apimachinery/blob/02cfb53916346d085a6c6c7c66f882e3c6b0eca6/pkg/util/validation/validation.go#L208>
+    ///
+    /// Valid values include:
+    ///
+    /// * "" - empty string implies core Kubernetes API group
+    /// * "networking.k8s.io"
+    /// * "foo.example.com"
+    ///
+    /// Invalid values include:
+    ///
+    /// * "example.com/bar" - "/" is an invalid character
+    pub struct Group {
+        min_length: 0,
+        max_length: 253,
+        pattern: r"^$|^[a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*$",
+        validate: |s| if s.is_empty() { Ok(()) } else { validate_subdomain(s) },
+    }
+}
 
-/// Kind refers to a Kubernetes Kind.
-///
-/// Valid values include:
-///
-/// * "Service"
-/// * "HTTPRoute"
-///
-/// Invalid values include:
-///
-/// * "invalid/kind" - "/" is an invalid character
-pub type Kind = String;
+validated_string! {
+    /// Kind refers to a Kubernetes Kind.
+    ///
+    /// Valid values include:
+    ///
+    /// * "Service"
+    /// * "HTTPRoute"
+    ///
+    /// Invalid values include:
+    ///
+    /// * "invalid/kind" - "/" is an invalid character
+    pub struct Kind {
+        min_length: 1,
+        max_length: 63,
+        pattern: r"^[a-zA-Z]([-a-zA-Z0-9]*[a-zA-Z0-9])?$",
+        validate: validate_kind,
+    }
+}
 
 /// ObjectName refers to the name of a Kubernetes object.
 ///
@@ -280,50 +485,71 @@ pub type Kind = String;
 /// 1123 labels, or RFC 1035 labels.
 pub type ObjectName = String;
 
-/// Namespace refers to a Kubernetes namespace. It must be a RFC 1123 label.
-///
-/// This validation is based off of the corresponding Kubernetes validation:
-/// <https://github.com/kubernetes/apimachinery/blob/02cfb53916346d085a6c6c7c66f882e3c6b0eca6/pkg/util/validation/validation.go#L187>
-///
-/// This is used for Namespace name validation here:
-/// <https://github.com/kubernetes/apimachinery/blob/02cfb53916346d085a6c6c7c66f882e3c6b0eca6/pkg/api/validation/generic.go#L63>
-///
-/// Valid values include:
-///
-/// * "example"
-///
-/// Invalid values include:
-///
-/// * "example.com" - "." is an invalid character
-pub type Namespace = String;
+validated_string! {
+    /// Namespace refers to a Kubernetes namespace. It must be a RFC 1123 label.
+    ///
+    /// This validation is based off of the corresponding Kubernetes validation:
+    /// <https://github.com/kubernetes/apimachinery/blob/02cfb53916346d085a6c6c7c66f882e3c6b0eca6/pkg/util/validation/validation.go#L187>
+    ///
+    /// This is used for Namespace name validation here:
+    /// <https://github.com/kubernetes/apimachinery/blob/02cfb53916346d085a6c6c7c66f882e3c6b0eca6/pkg/api/validation/generic.go#L63>
+    ///
+    /// Valid values include:
+    ///
+    /// * "example"
+    ///
+    /// Invalid values include:
+    ///
+    /// * "example.com" - "." is an invalid character
+    pub struct Namespace {
+        min_length: 1,
+        max_length: 63,
+        pattern: r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?$",
+        validate: validate_label,
+    }
+}
 
-/// SectionName is the name of a section in a Kubernetes resource.
-///
-/// This validation is based off of the corresponding Kubernetes validation:
-/// <https://github.com/kubernetes/apimachinery/blob/02cfb53916346d085a6c6c7c66f882e3c6b0eca6/pkg/util/validation/validation.go#L208>
-///
-/// Valid values include:
-///
-/// * "example.com"
-/// * "foo.example.com"
-///
-/// Invalid values include:
-///
-/// * "example.com/bar" - "/" is an invalid character
-pub type SectionName = String;
+validated_string! {
+    /// SectionName is the name of a section in a Kubernetes resource.
+    ///
+    /// This validation is based off of the corresponding Kubernetes validation:
+    /// <https://github.com/kubernetes/apimachinery/blob/02cfb53916346d085a6c6c7c66f882e3c6b0eca6/pkg/util/validation/validation.go#L208>
+    ///
+    /// Valid values include:
+    ///
+    /// * "example.com"
+    /// * "foo.example.com"
+    ///
+    /// Invalid values include:
+    ///
+    /// * "example.com/bar" - "/" is an invalid character
+    pub struct SectionName {
+        min_length: 1,
+        max_length: 253,
+        pattern: r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*$",
+        validate: validate_subdomain,
+    }
+}
 
-/// GatewayController is the name of a Gateway API controller. It must be a
-/// domain prefixed path.
-///
-/// Valid values include:
-///
-/// * "example.com/bar"
-///
-/// Invalid values include:
-///
-/// * "example.com" - must include path
-/// * "foo.example.com" - must include path
-pub type GatewayController = String;
+validated_string! {
+    /// GatewayController is the name of a Gateway API controller. It must be a
+    /// domain prefixed path.
+    ///
+    /// Valid values include:
+    ///
+    /// * "example.com/bar"
+    ///
+    /// Invalid values include:
+    ///
+    /// * "example.com" - must include path
+    /// * "foo.example.com" - must include path
+    pub struct GatewayController {
+        min_length: 1,
+        max_length: 253,
+        pattern: r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*\/[A-Za-z0-9\/\-._~%!$&'()*+,;=:]+$",
+        validate: validate_controller_name,
+    }
+}
 
 /// AnnotationKey is the key of an annotation in Gateway API. This is used for
 /// validation of maps such as TLS options. This matches the Kubernetes
@@ -351,3 +577,358 @@ pub type AnnotationValue = String;
 
 /// AddressType defines how a network address is represented as a text string.
 pub type AddressType = String;
+
+/// InvalidValue indicates that a string does not satisfy the constraints of a
+/// Gateway API type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvalidValue {
+    type_name: &'static str,
+    value: String,
+    reason: String,
+}
+
+// === impl Group ===
+
+impl Group {
+    /// Returns the group of the core Kubernetes API, i.e. an empty string.
+    pub fn core() -> Self {
+        Self(String::new())
+    }
+
+    /// Returns true if this refers to the core Kubernetes API group.
+    pub fn is_core(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self::core()
+    }
+}
+
+// === impl InvalidValue ===
+
+impl InvalidValue {
+    pub(crate) fn new(type_name: &'static str, value: String, reason: String) -> Self {
+        Self {
+            type_name,
+            value,
+            reason,
+        }
+    }
+
+    /// Returns the name of the type that the value was parsed as.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the invalid value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Describes the constraint that the value violates.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl std::fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid {} {:?}: {}",
+            self.type_name, self.value, self.reason
+        )
+    }
+}
+
+impl std::error::Error for InvalidValue {}
+
+/// Returns the `type` of a deserialized object of an unknown type, or an empty
+/// string if it has none.
+pub(crate) fn unknown_type(raw: &serde_json::Value) -> &str {
+    raw.get("type")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+}
+
+/// Deserializes an internally tagged enum that keeps objects of unknown types
+/// in an `Unknown` variant.
+///
+/// An untagged fallback variant would also accept objects of known types with
+/// missing or invalid fields, which would then be silently ignored. Instead,
+/// the `type` is read first: objects without one are of the `default` type,
+/// if the API defines one; objects of a type in `known` must be valid, and
+/// are decoded by `decode`; and only objects whose type is some other string
+/// are kept as is by `unknown`.
+pub(crate) fn deserialize_tagged<'de, D, T>(
+    de: D,
+    known: &[&str],
+    default: Option<&str>,
+    decode: fn(serde_json::Value) -> serde_json::Result<T>,
+    unknown: fn(serde_json::Value) -> T,
+) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::{de::Error, Deserialize};
+
+    let mut fields = serde_json::Map::deserialize(de)?;
+    match fields.get("type") {
+        Some(serde_json::Value::String(t)) if known.contains(&t.as_str()) => {}
+        Some(serde_json::Value::String(_)) => return Ok(unknown(fields.into())),
+        Some(_) => return Err(D::Error::custom("`type` must be a string")),
+        None => match default {
+            Some(t) => {
+                fields.insert("type".to_string(), t.into());
+            }
+            None => return Err(D::Error::missing_field("type")),
+        },
+    }
+    decode(fields.into()).map_err(D::Error::custom)
+}
+
+/// Implements `Deserialize` for an internally tagged enum with an
+/// `Unknown(serde_json::Value)` fallback with [`deserialize_tagged`], given
+/// the enum's known types and its default type, and `Serialize` so that
+/// unknown objects are serialized as they were deserialized.
+///
+/// The enum must derive both traits with `#[serde(remote = "Self")]`, so that
+/// the derived implementations are inherent functions that handle the known
+/// types, and skip its `Unknown` variant.
+macro_rules! impl_tagged_serde {
+    ($ty:ty, [$($known:literal),+ $(,)?], $default:expr) => {
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                crate::shared::deserialize_tagged(
+                    de,
+                    &[$($known),+],
+                    $default,
+                    Self::deserialize,
+                    Self::Unknown,
+                )
+            }
+        }
+
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Self::Unknown(raw) => serde::Serialize::serialize(raw, ser),
+                    known => Self::serialize(known, ser),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use impl_tagged_serde;
+
+/// Deserializes a `BackendRef` that is flattened into a route's backend
+/// reference, alongside its filters.
+///
+/// Serde treats any error while deserializing a flattened `Option` as an
+/// absent value, so a backend reference with a misspelled or mistyped field
+/// would silently be dropped. Instead, the reference is absent only if none of
+/// its fields are present, and otherwise must be valid.
+pub(crate) fn deserialize_flattened_backend_ref<'de, D>(
+    de: D,
+) -> Result<Option<BackendRef>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::{de::Error, Deserialize};
+
+    let fields = serde_json::Map::deserialize(de)?;
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    BackendRef::deserialize(serde_json::Value::Object(fields))
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+// === validation helpers ===
+
+fn string_schema(min: u32, max: u32, pattern: &str) -> schemars::schema::Schema {
+    use schemars::schema::{InstanceType, SchemaObject, StringValidation};
+
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            min_length: Some(min),
+            max_length: Some(max),
+            pattern: Some(pattern.to_string()),
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Returns true if `s` is a lowercase RFC 1123 subdomain.
+fn is_dns_subdomain(s: &str) -> bool {
+    !s.is_empty() && s.len() <= 253 && s.split('.').all(is_dns_label)
+}
+
+/// Returns true if `s` is a lowercase RFC 1123 label.
+fn is_dns_label(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    match (bytes.first(), bytes.last()) {
+        (Some(first), Some(last)) => {
+            bytes.len() <= 63
+                && is_lower_alnum(*first)
+                && is_lower_alnum(*last)
+                && bytes.iter().all(|b| is_lower_alnum(*b) || *b == b'-')
+        }
+        _ => false,
+    }
+}
+
+fn is_lower_alnum(b: u8) -> bool {
+    b.is_ascii_lowercase() || b.is_ascii_digit()
+}
+
+fn validate_subdomain(s: &str) -> Result<(), &'static str> {
+    if is_dns_subdomain(s) {
+        Ok(())
+    } else {
+        Err("must be a lowercase RFC 1123 subdomain")
+    }
+}
+
+fn validate_label(s: &str) -> Result<(), &'static str> {
+    if is_dns_label(s) {
+        Ok(())
+    } else {
+        Err("must be a lowercase RFC 1123 label")
+    }
+}
+
+fn validate_precise_hostname(s: &str) -> Result<(), &'static str> {
+    if s.starts_with('*') {
+        return Err("must not be a wildcard");
+    }
+    validate_subdomain(s)
+}
+
+fn validate_kind(s: &str) -> Result<(), &'static str> {
+    let bytes = s.as_bytes();
+    if !bytes.first().map_or(false, u8::is_ascii_alphabetic) {
+        return Err("must start with a letter");
+    }
+    if !bytes.last().map_or(false, u8::is_ascii_alphanumeric) {
+        return Err("must end with a letter or digit");
+    }
+    if !bytes
+        .iter()
+        .all(|b| b.is_ascii_alphanumeric() || *b == b'-')
+    {
+        return Err("must consist of letters, digits, or '-'");
+    }
+    Ok(())
+}
+
+fn validate_controller_name(s: &str) -> Result<(), &'static str> {
+    let is_path_char = |c: char| c.is_ascii_alphanumeric() || "/-._~%!$&'()*+,;=:".contains(c);
+    let (domain, path) = s.split_once('/').ok_or("must be a domain prefixed path")?;
+    if !is_dns_subdomain(domain) {
+        return Err("must be prefixed by a lowercase RFC 1123 subdomain");
+    }
+    if path.is_empty() || !path.chars().all(is_path_char) {
+        return Err("must have a non-empty path of valid URI characters");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn reason<T>(value: &str) -> String
+    where
+        T: TryFrom<String, Error = InvalidValue> + std::fmt::Debug,
+    {
+        T::try_from(value.to_string())
+            .unwrap_err()
+            .reason()
+            .to_string()
+    }
+
+    #[test]
+    fn gateway_controller_reasons() {
+        assert!(GatewayController::try_from("example.com/bar").is_ok());
+        assert!(GatewayController::try_from("example.com/bar/baz:v1").is_ok());
+
+        assert_eq!(reason::<GatewayController>(""), "must not be empty");
+        assert_eq!(
+            reason::<GatewayController>(&format!("example.com/{}", "a".repeat(250))),
+            "must be no more than 253 characters"
+        );
+        assert_eq!(
+            reason::<GatewayController>("example.com"),
+            "must be a domain prefixed path"
+        );
+        assert_eq!(
+            reason::<GatewayController>("Example.com/bar"),
+            "must be prefixed by a lowercase RFC 1123 subdomain"
+        );
+        for value in ["example.com/", "example.com/b ar", "example.com/bär"] {
+            assert_eq!(
+                reason::<GatewayController>(value),
+                "must have a non-empty path of valid URI characters",
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn name_reasons() {
+        assert!(Group::try_from("").is_ok());
+        assert_eq!(
+            reason::<Group>("example.com/bar"),
+            "must be a lowercase RFC 1123 subdomain"
+        );
+        assert_eq!(
+            reason::<Group>(&"a".repeat(254)),
+            "must be no more than 253 characters"
+        );
+
+        assert_eq!(reason::<Kind>(""), "must not be empty");
+        assert_eq!(reason::<Kind>("1Route"), "must start with a letter");
+        assert_eq!(reason::<Kind>("Route-"), "must end with a letter or digit");
+        assert_eq!(
+            reason::<Kind>("invalid/kind"),
+            "must consist of letters, digits, or '-'"
+        );
+
+        assert_eq!(
+            reason::<Namespace>("example.com"),
+            "must be a lowercase RFC 1123 label"
+        );
+        assert_eq!(
+            reason::<SectionName>("example.com/bar"),
+            "must be a lowercase RFC 1123 subdomain"
+        );
+
+        assert_eq!(
+            reason::<PreciseHostname>("*.example.com"),
+            "must not be a wildcard"
+        );
+        assert_eq!(
+            reason::<PreciseHostname>("foo..example.com"),
+            "must be a lowercase RFC 1123 subdomain"
+        );
+    }
+
+    #[test]
+    fn invalid_value_display() {
+        let error = Namespace::try_from("Default").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid Namespace \"Default\": must be a lowercase RFC 1123 label"
+        );
+    }
+}
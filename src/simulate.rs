@@ -0,0 +1,340 @@
+//! Simulation of how a Gateway routes a request.
+//!
+//! [`route_request`] follows a request through the same steps as a dataplane
+//! configured from the [`ir`] would: it selects the listener that receives
+//! the request, checks which HTTPRoutes attach to that listener, finds the
+//! rule match that handles the request, and resolves the filters and backends
+//! of that rule. Each step is recorded in the returned [`Decision`]'s trace,
+//! so that tools can explain why a request was (or was not) routed somewhere.
+//! Routes are attached and compiled by the caller's [`Compiler`], so that the
+//! simulation honors its namespace labels, filter order, and feature gates.
+//!
+//! `RegularExpression` matches are never satisfied, since their dialect is
+//! implementation-specific.
+
+use crate::{
+    ir::{Compiler, Detached},
+    manifest::GatewayApiObject,
+    matcher::{HttpRequest, Matcher},
+    snapshot::{Event, ObjectKey, SnapshotStore},
+    *,
+};
+
+/// A request received by a Gateway.
+#[derive(Copy, Clone, Debug)]
+pub struct Request<'a> {
+    /// The Gateway that receives the request.
+    pub gateway: &'a ObjectKey,
+
+    /// The port on which the request is received.
+    pub port: PortNumber,
+
+    /// The requested hostname, without a port.
+    pub host: &'a str,
+
+    /// The HTTP request.
+    pub http: HttpRequest<'a>,
+}
+
+/// The outcome of a simulated request, with a trace of the steps that led to
+/// it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decision {
+    pub outcome: Outcome,
+    pub trace: Vec<Step>,
+}
+
+/// How a Gateway handles a request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// The Gateway does not exist.
+    NoGateway,
+
+    /// No listener receives requests for the host on the port.
+    NoListener,
+
+    /// No route matches the request, so it receives a 404 response.
+    NotFound,
+
+    /// The request is redirected by a `RequestRedirect` filter.
+    Redirect(HttpRequestRedirectFilter),
+
+    /// The request is forwarded to one of the backends, chosen in proportion
+    /// to their weights.
+    Forward(Vec<ir::Backend>),
+
+    /// The matching rule has no valid backends, so the request receives a
+    /// 500 response.
+    NoBackend,
+}
+
+/// A step in the handling of a request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    /// The listener with the most specific hostname matching the request was
+    /// selected.
+    ListenerSelected { listener: String },
+
+    /// A route that references the Gateway attaches to the listener and
+    /// matches the requested host.
+    RouteAttached { route: ObjectKey },
+
+    /// A route that references the Gateway does not handle the request.
+    RouteSkipped {
+        route: ObjectKey,
+        reason: SkipReason,
+    },
+
+    /// A match of a rule is satisfied by the request. Matches are evaluated
+    /// in precedence order, and the first one satisfied handles the request.
+    MatchSelected {
+        route: ObjectKey,
+        rule_index: usize,
+        matcher: HttpRouteMatch,
+    },
+
+    /// The rule's filters are applied to the request.
+    FiltersApplied { filters: Vec<HttpRouteFilter> },
+
+    /// A backend of the rule was considered for forwarding.
+    BackendConsidered {
+        backend: ir::Backend,
+
+        /// The proportion of requests forwarded to the backend, from 0 to 1,
+        /// or 0 if the backend is invalid.
+        share: f64,
+    },
+}
+
+/// The reason a route does not handle a request.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SkipReason {
+    /// None of the route's parent references select the listener.
+    NotReferenced,
+
+    /// The listener's `allowedRoutes` does not include HTTPRoutes.
+    KindNotAllowed,
+
+    /// The listener's `allowedRoutes` does not include the route's namespace.
+    NamespaceNotAllowed,
+
+    /// None of the route's hostnames match the requested host.
+    HostnameMismatch,
+}
+
+/// Simulates how the Gateway in `request` routes it, given all of the
+/// Gateway API objects in a cluster and the compiler that configures the
+/// Gateway.
+pub fn route_request(
+    compiler: &Compiler,
+    objects: &[GatewayApiObject],
+    request: &Request<'_>,
+) -> Decision {
+    let mut trace = Vec::new();
+    let outcome = simulate(compiler, objects, request, &mut trace);
+    Decision { outcome, trace }
+}
+
+fn simulate(
+    compiler: &Compiler,
+    objects: &[GatewayApiObject],
+    request: &Request<'_>,
+    trace: &mut Vec<Step>,
+) -> Outcome {
+    let mut store = SnapshotStore::default();
+    store.apply(Event::Restarted(
+        objects
+            .iter()
+            .filter_map(|o| match o {
+                GatewayApiObject::Gateway(gw) => Some(gw.clone()),
+                _ => None,
+            })
+            .collect(),
+    ));
+    store.apply(Event::Restarted(
+        objects
+            .iter()
+            .filter_map(|o| match o {
+                GatewayApiObject::HttpRoute(r) => Some(r.clone()),
+                _ => None,
+            })
+            .collect(),
+    ));
+    #[cfg(feature = "experimental")]
+    store.apply(Event::Restarted(
+        objects
+            .iter()
+            .filter_map(|o| match o {
+                GatewayApiObject::ReferenceGrant(g) => Some(g.clone()),
+                _ => None,
+            })
+            .collect(),
+    ));
+
+    let snapshot = match store.get(request.gateway) {
+        Some(snapshot) => snapshot,
+        None => return Outcome::NoGateway,
+    };
+    let gateway = snapshot.gateway();
+
+    let listener = match listener::select(&gateway.spec.listeners, request.port, request.host) {
+        Some(listener) => listener,
+        None => return Outcome::NoListener,
+    };
+    trace.push(Step::ListenerSelected {
+        listener: listener.name.clone(),
+    });
+
+    let mut routes = snapshot.http_routes().iter().collect::<Vec<_>>();
+    routes.sort_by_key(|r| ObjectKey::from_meta(&r.metadata));
+    for route in routes {
+        let route_key = ObjectKey::from_meta(&route.metadata);
+        let reason = match compiler.attachment(snapshot.key(), listener, &route_key, route) {
+            Err(Detached::NotReferenced) => Some(SkipReason::NotReferenced),
+            Err(Detached::KindNotAllowed) => Some(SkipReason::KindNotAllowed),
+            Err(Detached::NamespaceNotAllowed) => Some(SkipReason::NamespaceNotAllowed),
+            Ok(()) => {
+                let hostnames = route.spec.hostnames.as_deref().unwrap_or_default();
+                let host_matches = hostnames.is_empty()
+                    || hostnames
+                        .iter()
+                        .any(|h| hostname::matches(h, request.host).is_some());
+                if host_matches {
+                    None
+                } else {
+                    Some(SkipReason::HostnameMismatch)
+                }
+            }
+        };
+        trace.push(match reason {
+            Some(reason) => Step::RouteSkipped {
+                route: route_key,
+                reason,
+            },
+            None => Step::RouteAttached { route: route_key },
+        });
+    }
+
+    let table = compiler.compile(snapshot);
+    let route = table
+        .virtual_host(request.port, request.host)
+        .and_then(|vhost| {
+            let matcher = Matcher::new();
+            vhost
+                .routes
+                .iter()
+                .find(|r| matcher.http_match(&r.matcher, &request.http))
+        });
+    let route = match route {
+        Some(route) => route,
+        None => return Outcome::NotFound,
+    };
+    trace.push(Step::MatchSelected {
+        route: route.source.clone(),
+        rule_index: route.rule_index,
+        matcher: route.matcher.clone(),
+    });
+
+    if !route.filters.is_empty() {
+        trace.push(Step::FiltersApplied {
+            filters: route.filters.clone(),
+        });
+    }
+    let redirect = route.filters.iter().find_map(|f| match f {
+        HttpRouteFilter::RequestRedirect { request_redirect } => Some(request_redirect),
+        _ => None,
+    });
+    if let Some(redirect) = redirect {
+        return Outcome::Redirect((**redirect).clone());
+    }
+
+    let total = route
+        .backends
+        .iter()
+        .filter(|b| b.cluster.is_some())
+        .map(|b| u64::from(b.weight))
+        .sum::<u64>();
+    for backend in &route.backends {
+        let share = match backend.cluster {
+            Some(_) if total > 0 => f64::from(backend.weight) / total as f64,
+            _ => 0.0,
+        };
+        trace.push(Step::BackendConsidered {
+            backend: backend.clone(),
+            share,
+        });
+    }
+
+    // Requests for invalid backends receive 500 responses, so the request
+    // only fails outright if no backend is valid.
+    if total == 0 {
+        return Outcome::NoBackend;
+    }
+    Outcome::Forward(route.backends.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn attaches_routes_with_the_compiler() {
+        let gateway = json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "web", "namespace": "infra" },
+            "spec": {
+                "gatewayClassName": "acme",
+                "listeners": [{
+                    "name": "http",
+                    "port": 80,
+                    "protocol": "HTTP",
+                    "allowedRoutes": {
+                        "namespaces": {
+                            "from": "Selector",
+                            "selector": { "matchLabels": { "gateway": "web" } },
+                        },
+                    },
+                }],
+            },
+        });
+        let route = json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": "app", "namespace": "apps" },
+            "spec": {
+                "parentRefs": [{ "name": "web", "namespace": "infra" }],
+                "rules": [{ "backendRefs": [{ "name": "app", "port": 8080 }] }],
+            },
+        });
+        let objects = [gateway, route]
+            .into_iter()
+            .map(|o| GatewayApiObject::from_value(o).unwrap())
+            .collect::<Vec<_>>();
+        let gw_key = ObjectKey::new("infra", "web");
+        let request = Request {
+            gateway: &gw_key,
+            port: 80,
+            host: "example.com",
+            http: HttpRequest::new("GET", "/"),
+        };
+        let route_key = ObjectKey::new("apps", "app");
+
+        let decision = route_request(&Compiler::default(), &objects, &request);
+        assert_eq!(decision.outcome, Outcome::NotFound);
+        assert!(decision.trace.contains(&Step::RouteSkipped {
+            route: route_key.clone(),
+            reason: SkipReason::NamespaceNotAllowed,
+        }));
+
+        let labels = BTreeMap::from([("gateway".to_string(), "web".to_string())]);
+        let compiler = Compiler::default().with_namespace_labels("apps", labels);
+        let decision = route_request(&compiler, &objects, &request);
+        assert!(matches!(decision.outcome, Outcome::Forward(_)));
+        assert!(decision
+            .trace
+            .contains(&Step::RouteAttached { route: route_key }));
+    }
+}
@@ -0,0 +1,764 @@
+//! Per-Gateway snapshots of the resources that configure a Gateway.
+//!
+//! Controllers typically reconcile one Gateway at a time, but the resources
+//! that configure a Gateway are spread across many kinds and namespaces.
+//! [`SnapshotStore`] indexes watched resources and maintains a [`Snapshot`]
+//! for every Gateway, containing the Gateway itself, the HTTPRoutes that
+//! reference it, the Secrets referenced by its listeners, and (when the
+//! `experimental` feature is enabled) the ReferenceGrants that may permit its
+//! cross-namespace references.
+//!
+//! A store may be restricted to a [`Scope`], in which case resources outside
+//! of the scope are ignored, as if they did not exist. A store may also share
+//! the strings of the keys it builds through an [`Interner`].
+//!
+//! Watch events are applied incrementally: only the snapshots of Gateways
+//! that are affected by an event are rebuilt, and the keys of those Gateways
+//! are returned so that they may be queued for reconciliation. Snapshots share
+//! their contents via [`Arc`], so they are cheap to clone and to hand off to
+//! other tasks.
+//!
+//! ```ignore
+//! let mut store = SnapshotStore::default();
+//! for key in store.apply(Event::Applied(route)) {
+//!     if let Some(snapshot) = store.get(&key) {
+//!         reconcile(snapshot.clone());
+//!     }
+//! }
+//! ```
+
+use crate::{consts::GROUP, intern::Interner, scope::Scope, *};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::Arc,
+};
+
+/// A change to a watched resource.
+///
+/// This mirrors the events emitted by `kube::runtime::watcher`.
+#[derive(Clone, Debug)]
+pub enum Event<K> {
+    /// The resource was created or updated.
+    Applied(K),
+
+    /// The resource was deleted.
+    Deleted(K),
+
+    /// The watch was restarted, and these are all of the resources of this
+    /// kind that currently exist.
+    Restarted(Vec<K>),
+}
+
+/// Identifies a namespaced resource.
+///
+/// Keys are cloned into every index and snapshot that refers to a resource,
+/// so their strings are shared rather than copied. An [`Interner`] also
+/// shares them between keys of the same resource.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ObjectKey {
+    pub namespace: Arc<str>,
+    pub name: Arc<str>,
+}
+
+/// The resources that configure a single Gateway.
+///
+/// Cloning a snapshot is cheap.
+#[derive(Clone, Debug)]
+pub struct Snapshot(Arc<SnapshotInner>);
+
+#[derive(Debug)]
+struct SnapshotInner {
+    key: ObjectKey,
+    gateway: Arc<Gateway>,
+    http_routes: Vec<Arc<HttpRoute>>,
+    secret_refs: BTreeSet<ObjectKey>,
+    namespaces: BTreeSet<Arc<str>>,
+
+    #[cfg(feature = "experimental")]
+    reference_grants: Vec<Arc<ReferenceGrant>>,
+}
+
+/// Maintains a [`Snapshot`] for each Gateway from a stream of watch events.
+#[derive(Debug, Default)]
+pub struct SnapshotStore {
+    scope: Scope,
+    interner: Option<Interner>,
+
+    gateways: BTreeMap<ObjectKey, Arc<Gateway>>,
+    http_routes: BTreeMap<ObjectKey, Arc<HttpRoute>>,
+
+    /// Indexes HTTPRoutes by the Gateways they reference.
+    http_routes_by_gateway: BTreeMap<ObjectKey, BTreeSet<ObjectKey>>,
+
+    #[cfg(feature = "experimental")]
+    reference_grants: BTreeMap<ObjectKey, Arc<ReferenceGrant>>,
+
+    snapshots: BTreeMap<ObjectKey, Snapshot>,
+}
+
+/// A kind of resource that may be tracked by a [`SnapshotStore`].
+pub trait SnapshotResource: Sized + sealed::Sealed {
+    #[doc(hidden)]
+    fn meta(&self) -> &metav1::ObjectMeta;
+
+    #[doc(hidden)]
+    fn upsert(store: &mut SnapshotStore, obj: Self, affected: &mut BTreeSet<ObjectKey>);
+
+    #[doc(hidden)]
+    fn remove(store: &mut SnapshotStore, key: &ObjectKey, affected: &mut BTreeSet<ObjectKey>);
+
+    #[doc(hidden)]
+    fn keys(store: &SnapshotStore) -> Vec<ObjectKey>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+// === impl ObjectKey ===
+
+impl ObjectKey {
+    /// Returns the key of the named object in a namespace.
+    pub fn new(namespace: impl Into<Arc<str>>, name: impl Into<Arc<str>>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            name: name.into(),
+        }
+    }
+
+    /// Returns the key of an object from its metadata.
+    pub fn from_meta(meta: &metav1::ObjectMeta) -> Self {
+        Self::new(
+            meta.namespace.as_deref().unwrap_or_default(),
+            meta.name.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
+// `Arc<str>` does not implement `Default` on older toolchains.
+impl Default for ObjectKey {
+    fn default() -> Self {
+        Self::new("", "")
+    }
+}
+
+impl fmt::Display for ObjectKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.namespace, self.name)
+    }
+}
+
+// === impl Snapshot ===
+
+impl Snapshot {
+    /// Returns the key of the Gateway.
+    pub fn key(&self) -> &ObjectKey {
+        &self.0.key
+    }
+
+    /// Returns the Gateway.
+    pub fn gateway(&self) -> &Arc<Gateway> {
+        &self.0.gateway
+    }
+
+    /// Returns the HTTPRoutes that reference the Gateway, ordered by key.
+    ///
+    /// Routes are included regardless of whether the Gateway's listeners
+    /// allow them to attach.
+    pub fn http_routes(&self) -> &[Arc<HttpRoute>] {
+        &self.0.http_routes
+    }
+
+    /// Returns the HTTPRoute with the given key, if it references the
+    /// Gateway.
+    pub fn http_route(&self, key: &ObjectKey) -> Option<&Arc<HttpRoute>> {
+        let index = self
+            .0
+            .http_routes
+            .binary_search_by(|r| {
+                let namespace = r.metadata.namespace.as_deref().unwrap_or_default();
+                let name = r.metadata.name.as_deref().unwrap_or_default();
+                (namespace, name).cmp(&(&*key.namespace, &*key.name))
+            })
+            .ok()?;
+        Some(&self.0.http_routes[index])
+    }
+
+    /// Returns the Secrets referenced by the Gateway's listeners.
+    pub fn secret_refs(&self) -> &BTreeSet<ObjectKey> {
+        &self.0.secret_refs
+    }
+
+    /// Returns the namespaces of the Gateway, its routes, and the objects they
+    /// reference.
+    pub fn namespaces(&self) -> &BTreeSet<Arc<str>> {
+        &self.0.namespaces
+    }
+
+    /// Returns the ReferenceGrants in the namespaces of the Gateway, its
+    /// routes, and their references, ordered by key.
+    #[cfg(feature = "experimental")]
+    pub fn reference_grants(&self) -> &[Arc<ReferenceGrant>] {
+        &self.0.reference_grants
+    }
+
+    /// Returns a snapshot with the same contents, except for the
+    /// ReferenceGrant with the given key.
+    #[cfg(feature = "experimental")]
+    pub(crate) fn without_reference_grant(&self, key: &ObjectKey) -> Self {
+        let inner = &self.0;
+        let reference_grants = inner
+            .reference_grants
+            .iter()
+            .filter(|g| ObjectKey::from_meta(&g.metadata) != *key)
+            .cloned()
+            .collect();
+        Self(Arc::new(SnapshotInner {
+            key: inner.key.clone(),
+            gateway: inner.gateway.clone(),
+            http_routes: inner.http_routes.clone(),
+            secret_refs: inner.secret_refs.clone(),
+            namespaces: inner.namespaces.clone(),
+            reference_grants,
+        }))
+    }
+
+    /// Returns true if both snapshots share the same contents, i.e. one was
+    /// cloned from the other.
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Arc::ptr_eq(&a.0, &b.0)
+    }
+}
+
+// === impl SnapshotStore ===
+
+impl SnapshotStore {
+    /// Returns a store that ignores resources outside of `scope`.
+    pub fn with_scope(scope: Scope) -> Self {
+        Self {
+            scope,
+            ..Self::default()
+        }
+    }
+
+    /// Returns a store that interns the strings of the keys it builds.
+    pub fn with_interner(mut self, interner: Interner) -> Self {
+        self.interner = Some(interner);
+        self
+    }
+
+    /// Returns the scope of resources that the store tracks.
+    pub fn scope(&self) -> &Scope {
+        &self.scope
+    }
+
+    /// Returns the snapshot of a Gateway, if the Gateway exists.
+    pub fn get(&self, gateway: &ObjectKey) -> Option<&Snapshot> {
+        self.snapshots.get(gateway)
+    }
+
+    /// Iterates over the snapshots of all Gateways, ordered by key.
+    pub fn snapshots(&self) -> impl Iterator<Item = &Snapshot> {
+        self.snapshots.values()
+    }
+
+    /// Applies a watch event, returning the keys of the Gateways whose
+    /// snapshots were rebuilt or removed.
+    pub fn apply<K: SnapshotResource>(&mut self, event: Event<K>) -> BTreeSet<ObjectKey> {
+        let mut affected = BTreeSet::new();
+        match event {
+            // A resource may leave the scope when it is updated, e.g. if its
+            // labels change, in which case it is no longer tracked.
+            Event::Applied(obj) if !self.scope.contains(obj.meta()) => {
+                let key = self.key_from_meta(obj.meta());
+                K::remove(self, &key, &mut affected);
+            }
+            Event::Applied(obj) => K::upsert(self, obj, &mut affected),
+            Event::Deleted(obj) => {
+                let key = self.key_from_meta(obj.meta());
+                K::remove(self, &key, &mut affected);
+            }
+            Event::Restarted(mut objs) => {
+                objs.retain(|o| self.scope.contains(o.meta()));
+                let current = objs
+                    .iter()
+                    .map(|o| self.key_from_meta(o.meta()))
+                    .collect::<BTreeSet<_>>();
+                for key in K::keys(self) {
+                    if !current.contains(&key) {
+                        K::remove(self, &key, &mut affected);
+                    }
+                }
+                for obj in objs {
+                    K::upsert(self, obj, &mut affected);
+                }
+            }
+        }
+
+        // Events may reference Gateways that do not exist, which have no
+        // snapshot to rebuild.
+        affected.retain(|key| {
+            let existed = self.snapshots.contains_key(key);
+            self.rebuild(key);
+            existed || self.snapshots.contains_key(key)
+        });
+        affected
+    }
+
+    fn rebuild(&mut self, key: &ObjectKey) {
+        let gateway = match self.gateways.get(key) {
+            Some(gw) => gw.clone(),
+            None => {
+                self.snapshots.remove(key);
+                return;
+            }
+        };
+
+        let mut namespaces = BTreeSet::new();
+        namespaces.insert(key.namespace.clone());
+
+        let mut secret_refs = BTreeSet::new();
+        for tls in gateway.spec.listeners.iter().filter_map(|l| l.tls.as_ref()) {
+            for cert in tls.certificate_refs.iter().flatten() {
+                let ns = cert.namespace.as_deref().unwrap_or(&key.namespace);
+                let secret = self.key(ns, &cert.name);
+                namespaces.insert(secret.namespace.clone());
+                secret_refs.insert(secret);
+            }
+        }
+
+        let http_routes = self
+            .http_routes_by_gateway
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter_map(|k| self.http_routes.get(k).cloned())
+            .collect::<Vec<_>>();
+        for route in &http_routes {
+            let route_ns = route.metadata.namespace.as_deref().unwrap_or_default();
+            namespaces.insert(self.intern(route_ns));
+            for backend in route
+                .spec
+                .rules
+                .iter()
+                .flatten()
+                .flat_map(|r| r.backend_refs.iter().flatten())
+                .filter_map(|b| b.backend_ref.as_ref())
+            {
+                if let Some(ns) = backend.inner.namespace.as_deref() {
+                    namespaces.insert(self.intern(ns));
+                }
+            }
+        }
+
+        #[cfg(feature = "experimental")]
+        let reference_grants = self
+            .reference_grants
+            .iter()
+            .filter(|(k, _)| namespaces.contains(&k.namespace))
+            .map(|(_, g)| g.clone())
+            .collect();
+
+        let snapshot = Snapshot(Arc::new(SnapshotInner {
+            key: key.clone(),
+            gateway,
+            http_routes,
+            secret_refs,
+            namespaces,
+            #[cfg(feature = "experimental")]
+            reference_grants,
+        }));
+        self.snapshots.insert(key.clone(), snapshot);
+    }
+
+    fn intern(&self, s: &str) -> Arc<str> {
+        match &self.interner {
+            Some(interner) => interner.intern(s),
+            None => s.into(),
+        }
+    }
+
+    fn key(&self, namespace: &str, name: &str) -> ObjectKey {
+        ObjectKey::new(self.intern(namespace), self.intern(name))
+    }
+
+    fn key_from_meta(&self, meta: &metav1::ObjectMeta) -> ObjectKey {
+        self.key(
+            meta.namespace.as_deref().unwrap_or_default(),
+            meta.name.as_deref().unwrap_or_default(),
+        )
+    }
+
+    /// Returns the keys of the Gateways referenced by a route.
+    fn parent_gateways(&self, route_ns: &str, spec: &CommonRouteSpec) -> BTreeSet<ObjectKey> {
+        parent_refs(route_ns, spec)
+            .map(|(ns, name)| self.key(ns, name))
+            .collect()
+    }
+
+    /// Adds the Gateways whose snapshots include resources in `namespace`.
+    #[cfg(feature = "experimental")]
+    fn affected_by_namespace(&self, namespace: &str, affected: &mut BTreeSet<ObjectKey>) {
+        for (key, snapshot) in &self.snapshots {
+            if snapshot.0.namespaces.contains(namespace) {
+                affected.insert(key.clone());
+            }
+        }
+    }
+}
+
+/// Returns the keys of the Gateways referenced by a route.
+pub(crate) fn parent_gateways(route_ns: &str, spec: &CommonRouteSpec) -> BTreeSet<ObjectKey> {
+    parent_refs(route_ns, spec)
+        .map(|(ns, name)| ObjectKey::new(ns, name))
+        .collect()
+}
+
+/// Returns the namespaces and names of the Gateways referenced by a route.
+fn parent_refs<'a>(
+    route_ns: &'a str,
+    spec: &'a CommonRouteSpec,
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+    spec.parent_refs
+        .iter()
+        .flatten()
+        .filter(|p| p.group.as_deref().unwrap_or(GROUP) == GROUP)
+        .filter(|p| p.kind.as_deref().unwrap_or("Gateway") == "Gateway")
+        .map(move |p| (p.namespace.as_deref().unwrap_or(route_ns), &*p.name))
+}
+
+// === impl Gateway ===
+
+impl sealed::Sealed for Gateway {}
+
+impl SnapshotResource for Gateway {
+    fn meta(&self) -> &metav1::ObjectMeta {
+        &self.metadata
+    }
+
+    fn upsert(store: &mut SnapshotStore, obj: Self, affected: &mut BTreeSet<ObjectKey>) {
+        let key = store.key_from_meta(&obj.metadata);
+        store.gateways.insert(key.clone(), Arc::new(obj));
+        affected.insert(key);
+    }
+
+    fn remove(store: &mut SnapshotStore, key: &ObjectKey, affected: &mut BTreeSet<ObjectKey>) {
+        if store.gateways.remove(key).is_some() {
+            affected.insert(key.clone());
+        }
+    }
+
+    fn keys(store: &SnapshotStore) -> Vec<ObjectKey> {
+        store.gateways.keys().cloned().collect()
+    }
+}
+
+// === impl HttpRoute ===
+
+impl sealed::Sealed for HttpRoute {}
+
+impl SnapshotResource for HttpRoute {
+    fn meta(&self) -> &metav1::ObjectMeta {
+        &self.metadata
+    }
+
+    fn upsert(store: &mut SnapshotStore, obj: Self, affected: &mut BTreeSet<ObjectKey>) {
+        let key = store.key_from_meta(&obj.metadata);
+        Self::remove(store, &key, affected);
+
+        let parents = store.parent_gateways(&key.namespace, &obj.spec.inner);
+        for gw in parents {
+            store
+                .http_routes_by_gateway
+                .entry(gw.clone())
+                .or_default()
+                .insert(key.clone());
+            affected.insert(gw);
+        }
+        store.http_routes.insert(key, Arc::new(obj));
+    }
+
+    fn remove(store: &mut SnapshotStore, key: &ObjectKey, affected: &mut BTreeSet<ObjectKey>) {
+        let route = match store.http_routes.remove(key) {
+            Some(route) => route,
+            None => return,
+        };
+        for gw in store.parent_gateways(&key.namespace, &route.spec.inner) {
+            if let Some(routes) = store.http_routes_by_gateway.get_mut(&gw) {
+                routes.remove(key);
+                if routes.is_empty() {
+                    store.http_routes_by_gateway.remove(&gw);
+                }
+            }
+            affected.insert(gw);
+        }
+    }
+
+    fn keys(store: &SnapshotStore) -> Vec<ObjectKey> {
+        store.http_routes.keys().cloned().collect()
+    }
+}
+
+// === impl ReferenceGrant ===
+
+#[cfg(feature = "experimental")]
+impl sealed::Sealed for ReferenceGrant {}
+
+#[cfg(feature = "experimental")]
+impl SnapshotResource for ReferenceGrant {
+    fn meta(&self) -> &metav1::ObjectMeta {
+        &self.metadata
+    }
+
+    fn upsert(store: &mut SnapshotStore, obj: Self, affected: &mut BTreeSet<ObjectKey>) {
+        let key = store.key_from_meta(&obj.metadata);
+        store.affected_by_namespace(&key.namespace, affected);
+        store.reference_grants.insert(key, Arc::new(obj));
+    }
+
+    fn remove(store: &mut SnapshotStore, key: &ObjectKey, affected: &mut BTreeSet<ObjectKey>) {
+        if store.reference_grants.remove(key).is_some() {
+            store.affected_by_namespace(&key.namespace, affected);
+        }
+    }
+
+    fn keys(store: &SnapshotStore) -> Vec<ObjectKey> {
+        store.reference_grants.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gateway(name: &str, labels: serde_json::Value) -> Gateway {
+        serde_json::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": name, "namespace": "gw", "labels": labels },
+            "spec": {
+                "gatewayClassName": "test",
+                "listeners": [{ "name": "http", "port": 80, "protocol": "HTTP" }],
+            },
+        }))
+        .unwrap()
+    }
+
+    fn route(name: &str, parents: &[&str], labels: serde_json::Value) -> HttpRoute {
+        let parent_refs = parents
+            .iter()
+            .map(|name| json!({ "name": name, "namespace": "gw" }))
+            .collect::<Vec<_>>();
+        serde_json::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": name, "namespace": "app", "labels": labels },
+            "spec": { "parentRefs": parent_refs },
+        }))
+        .unwrap()
+    }
+
+    fn gateways(names: &[&str]) -> BTreeSet<ObjectKey> {
+        names
+            .iter()
+            .map(|name| ObjectKey::new("gw", *name))
+            .collect()
+    }
+
+    fn routes(store: &SnapshotStore, gateway: &str) -> Vec<String> {
+        store
+            .get(&ObjectKey::new("gw", gateway))
+            .unwrap()
+            .http_routes()
+            .iter()
+            .map(|r| r.metadata.name.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn rebuilds_reparented_routes() {
+        let mut store = SnapshotStore::default();
+        let none = serde_json::Value::Null;
+        assert_eq!(
+            store.apply(Event::Restarted(vec![
+                gateway("a", none.clone()),
+                gateway("b", none.clone())
+            ])),
+            gateways(&["a", "b"])
+        );
+
+        assert_eq!(
+            store.apply(Event::Applied(route("web", &["a"], none.clone()))),
+            gateways(&["a"])
+        );
+        assert_eq!(routes(&store, "a"), ["web"]);
+
+        // Moving a route rebuilds both its old and new parents.
+        assert_eq!(
+            store.apply(Event::Applied(route("web", &["b"], none.clone()))),
+            gateways(&["a", "b"])
+        );
+        assert!(routes(&store, "a").is_empty());
+        assert_eq!(routes(&store, "b"), ["web"]);
+
+        // Gateways that do not exist have no snapshots to rebuild.
+        assert_eq!(
+            store.apply(Event::Applied(route(
+                "web",
+                &["b", "missing"],
+                none.clone()
+            ))),
+            gateways(&["b"])
+        );
+        assert!(store.get(&ObjectKey::new("gw", "missing")).is_none());
+
+        // Creating a Gateway picks up the routes that already reference it.
+        assert_eq!(
+            store.apply(Event::Applied(gateway("missing", none.clone()))),
+            gateways(&["missing"])
+        );
+        assert_eq!(routes(&store, "missing"), ["web"]);
+
+        assert_eq!(
+            store.apply(Event::Deleted(route("web", &[], none))),
+            gateways(&["b", "missing"])
+        );
+        assert!(routes(&store, "b").is_empty());
+    }
+
+    #[test]
+    fn prunes_resources_missing_on_restart() {
+        let mut store = SnapshotStore::default();
+        let none = serde_json::Value::Null;
+        store.apply(Event::Restarted(vec![
+            gateway("a", none.clone()),
+            gateway("b", none.clone()),
+        ]));
+        store.apply(Event::Restarted(vec![
+            route("r1", &["a"], none.clone()),
+            route("r2", &["b"], none.clone()),
+        ]));
+        assert_eq!(routes(&store, "a"), ["r1"]);
+
+        // Routes missing from a restart are removed from their parents.
+        assert_eq!(
+            store.apply(Event::Restarted(vec![route("r2", &["b"], none.clone())])),
+            gateways(&["a", "b"])
+        );
+        assert!(routes(&store, "a").is_empty());
+        assert_eq!(routes(&store, "b"), ["r2"]);
+
+        // Gateways missing from a restart are reported so that their removal
+        // may be handled.
+        assert_eq!(
+            store.apply(Event::Restarted(vec![gateway("a", none)])),
+            gateways(&["a", "b"])
+        );
+        assert!(store.get(&ObjectKey::new("gw", "b")).is_none());
+        assert_eq!(store.snapshots().count(), 1);
+
+        assert_eq!(
+            store.apply(Event::<HttpRoute>::Restarted(vec![])),
+            gateways(&[])
+        );
+    }
+
+    #[test]
+    fn untracks_resources_leaving_the_scope() {
+        let selector = serde_json::from_value(json!({ "matchLabels": { "tenant": "a" } })).unwrap();
+        let mut store = SnapshotStore::with_scope(Scope::default().with_selector(selector));
+        let tenant = json!({ "tenant": "a" });
+        let other = json!({ "tenant": "b" });
+
+        assert_eq!(
+            store.apply(Event::Applied(gateway("gw", other.clone()))),
+            gateways(&[])
+        );
+        assert_eq!(
+            store.apply(Event::Applied(gateway("gw", tenant.clone()))),
+            gateways(&["gw"])
+        );
+        assert_eq!(
+            store.apply(Event::Applied(route("web", &["gw"], tenant.clone()))),
+            gateways(&["gw"])
+        );
+        assert_eq!(routes(&store, "gw"), ["web"]);
+
+        // A route whose labels no longer match is removed from its parents.
+        assert_eq!(
+            store.apply(Event::Applied(route("web", &["gw"], other.clone()))),
+            gateways(&["gw"])
+        );
+        assert!(routes(&store, "gw").is_empty());
+
+        // Restarts ignore resources outside of the scope.
+        assert_eq!(
+            store.apply(Event::Restarted(vec![
+                route("web", &["gw"], other.clone()),
+                route("api", &["gw"], tenant),
+            ])),
+            gateways(&["gw"])
+        );
+        assert_eq!(routes(&store, "gw"), ["api"]);
+
+        assert_eq!(
+            store.apply(Event::Applied(gateway("gw", other))),
+            gateways(&["gw"])
+        );
+        assert!(store.get(&ObjectKey::new("gw", "gw")).is_none());
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn rebuilds_gateways_affected_by_reference_grants() {
+        let mut store = SnapshotStore::default();
+        let none = serde_json::Value::Null;
+        store.apply(Event::Restarted(vec![
+            gateway("a", none.clone()),
+            gateway("b", none.clone()),
+        ]));
+        store.apply(Event::Applied(route("web", &["a"], none)));
+
+        let grant = |namespace: &str| -> ReferenceGrant {
+            serde_json::from_value(json!({
+                "apiVersion": "gateway.networking.k8s.io/v1alpha2",
+                "kind": "ReferenceGrant",
+                "metadata": { "name": "grant", "namespace": namespace },
+                "spec": {
+                    "from": [{ "group": GROUP, "kind": "HTTPRoute", "namespace": "app" }],
+                    "to": [{ "group": "", "kind": "Service" }],
+                },
+            }))
+            .unwrap()
+        };
+
+        // Only Gateways with resources in the grant's namespace are affected.
+        assert_eq!(store.apply(Event::Applied(grant("app"))), gateways(&["a"]));
+        assert_eq!(
+            store
+                .get(&ObjectKey::new("gw", "a"))
+                .unwrap()
+                .reference_grants()
+                .len(),
+            1
+        );
+        assert_eq!(
+            store.apply(Event::Applied(grant("gw"))),
+            gateways(&["a", "b"])
+        );
+        assert_eq!(
+            store.apply(Event::Restarted(vec![grant("gw")])),
+            gateways(&["a", "b"])
+        );
+        assert!(store
+            .get(&ObjectKey::new("gw", "a"))
+            .unwrap()
+            .reference_grants()
+            .iter()
+            .all(|g| g.metadata.namespace.as_deref() == Some("gw")));
+        assert_eq!(store.apply(Event::Applied(grant("other"))), gateways(&[]));
+    }
+}
@@ -0,0 +1,91 @@
+//! Selection of TLSRoutes by server name indication.
+//!
+//! Proxies that pass TLS connections through to backends route them by the
+//! SNI of the client's ClientHello. [`select`] chooses the TLSRoute that
+//! handles a connection to a listener, using the same hostname semantics as
+//! HTTPRoutes:
+//!
+//! ```ignore
+//! let routes = attached_tls_routes(&listener);
+//! match sni::select(listener.hostname.as_deref(), &routes, client_hello.sni) {
+//!     Some(selected) => forward(selected.backends()),
+//!     None => reject(),
+//! }
+//! ```
+
+use crate::{hostname, ir, *};
+use std::cmp::Ordering;
+
+/// The TLSRoute selected for a connection.
+#[derive(Copy, Clone, Debug)]
+pub struct Selected<'r> {
+    /// The selected route.
+    pub route: &'r TlsRoute,
+
+    /// The hostname through which the route matched the SNI, or `None` if
+    /// the route and listener match all hostnames.
+    pub hostname: Option<&'r str>,
+}
+
+/// Returns the route that handles connections with the given SNI on a
+/// listener, if any.
+///
+/// `routes` must be the TLSRoutes attached to the listener. A route's
+/// hostnames are limited to those that intersect with the listener's
+/// hostname. The route with the most specific matching hostname is selected:
+/// an exact hostname is preferred to wildcards, a longer wildcard is
+/// preferred to a shorter one, and any matching hostname is preferred to a
+/// route that matches all hostnames. Ties are broken in favor of the oldest
+/// route, and then by namespace and name.
+pub fn select<'r>(
+    listener_hostname: Option<&'r str>,
+    routes: &'r [TlsRoute],
+    sni: &str,
+) -> Option<Selected<'r>> {
+    routes
+        .iter()
+        .filter_map(|route| {
+            let hostnames = route.spec.hostnames.as_deref().unwrap_or_default();
+            ir::intersect_hostnames(listener_hostname, hostnames)
+                .into_iter()
+                .filter(|h| h.map_or(true, |h| hostname::matches(h, sni).is_some()))
+                .max_by(|a, b| specificity(*a).cmp(&specificity(*b)))
+                .map(|hostname| Selected { route, hostname })
+        })
+        .max_by(|a, b| {
+            specificity(a.hostname)
+                .cmp(&specificity(b.hostname))
+                .then_with(|| precedence(a.route, b.route))
+        })
+}
+
+/// Orders hostnames from least to most specific. `None` matches all
+/// hostnames.
+fn specificity(hostname: Option<&str>) -> (bool, bool, usize) {
+    match hostname {
+        None => (false, false, 0),
+        Some(h) => (true, !hostname::is_wildcard(h), h.len()),
+    }
+}
+
+/// Orders routes so that older routes, and then routes with lesser keys, are
+/// greater.
+fn precedence(a: &TlsRoute, b: &TlsRoute) -> Ordering {
+    let key = |r: &TlsRoute| {
+        (
+            r.metadata.creation_timestamp.clone(),
+            r.metadata.namespace.clone(),
+            r.metadata.name.clone(),
+        )
+    };
+    key(b).cmp(&key(a))
+}
+
+// === impl Selected ===
+
+impl<'r> Selected<'r> {
+    /// Iterates over the backends of all of the route's rules.
+    pub fn backends(&self) -> impl Iterator<Item = &'r BackendRef> {
+        self.route.spec.rules.iter().flat_map(|r| &r.backend_refs)
+    }
+}
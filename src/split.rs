@@ -0,0 +1,176 @@
+//! Deterministic weighted traffic splitting.
+//!
+//! A [`SplitPlan`] lists the backends of a route with their weights, and a
+//! [`WeightedPicker`] built from it selects a backend for a caller-provided
+//! hash, e.g. of a request ID or a client address. Selection depends only on
+//! the plan and the hash, not on process state or randomness, so replicas of
+//! a dataplane split traffic identically, including across restarts.
+//!
+//! ```
+//! # use k8s_gateway_api::split::{SplitPlan, WeightedPicker};
+//! let plan = SplitPlan::new([("stable", 90), ("canary", 10)]);
+//! let picker = WeightedPicker::from(plan);
+//! assert_eq!(picker.pick_key("request-1"), picker.pick_key("request-1"));
+//! ```
+//!
+//! The plan of a compiled route may account for the health of its backends
+//! (see [`health`](crate::health)), so that requests selected for a backend
+//! without ready endpoints receive a 503 response rather than being
+//! forwarded.
+
+use crate::{canonical, health::BackendHealthSource, ir};
+
+/// Items to select between, in proportion to their weights.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SplitPlan<T> {
+    items: Vec<(T, u32)>,
+}
+
+/// Where requests selected for a backend of a compiled route are sent.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Destination<'r> {
+    /// Requests are forwarded to the named cluster.
+    Cluster(&'r str),
+
+    /// The backend is invalid; requests receive a 500 response.
+    Invalid,
+
+    /// The named cluster has no ready endpoints; requests receive a 503
+    /// response.
+    Unavailable(&'r str),
+}
+
+/// Selects items of a [`SplitPlan`] by hash.
+#[derive(Clone, Debug)]
+pub struct WeightedPicker<T> {
+    items: Vec<T>,
+
+    /// The cumulative weight of each item, including its own.
+    bounds: Vec<u64>,
+}
+
+// === impl SplitPlan ===
+
+impl<T> SplitPlan<T> {
+    /// Returns a plan of weighted items. Items with a weight of zero are
+    /// never selected, and are omitted.
+    pub fn new(items: impl IntoIterator<Item = (T, u32)>) -> Self {
+        Self {
+            items: items.into_iter().filter(|(_, w)| *w > 0).collect(),
+        }
+    }
+
+    /// Returns the items of the plan with their weights.
+    pub fn items(&self) -> &[(T, u32)] {
+        &self.items
+    }
+
+    /// Returns the sum of the weights of all items.
+    pub fn total_weight(&self) -> u64 {
+        self.items.iter().map(|(_, w)| u64::from(*w)).sum()
+    }
+
+    /// Returns true if there is nothing to select, i.e. if every item has a
+    /// weight of zero.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<'r> SplitPlan<Option<&'r str>> {
+    /// Returns the plan of a compiled route: the names of the clusters of its
+    /// backends, or `None` for invalid backends, to which requests must not
+    /// be forwarded.
+    pub fn from_route(route: &'r ir::Route) -> Self {
+        Self::new(
+            route
+                .backends
+                .iter()
+                .map(|b| (b.cluster.as_deref(), b.weight)),
+        )
+    }
+}
+
+impl<'r> SplitPlan<Destination<'r>> {
+    /// Returns the plan of a compiled route, consulting `health` for the
+    /// readiness of each of its clusters.
+    ///
+    /// Backends that are not ready keep their weight, so that the requests
+    /// selected for them fail instead of overloading the remaining backends.
+    pub fn from_route_with_health(
+        table: &'r ir::RouteTable,
+        route: &'r ir::Route,
+        health: &impl BackendHealthSource,
+    ) -> Self {
+        Self::new(route.backends.iter().map(|b| {
+            let cluster = b.cluster.as_deref().and_then(|c| table.clusters.get(c));
+            let destination = match cluster {
+                Some(c) if health.is_ready(c) => Destination::Cluster(&c.name),
+                Some(c) => Destination::Unavailable(&c.name),
+                None => Destination::Invalid,
+            };
+            (destination, b.weight)
+        }))
+    }
+}
+
+// === impl Destination ===
+
+impl Destination<'_> {
+    /// Returns the status of the response that requests receive instead of
+    /// being forwarded, if any.
+    pub fn error_status(&self) -> Option<u16> {
+        match self {
+            Self::Cluster(_) => None,
+            Self::Invalid => Some(500),
+            Self::Unavailable(_) => Some(503),
+        }
+    }
+}
+
+// === impl WeightedPicker ===
+
+impl<T> WeightedPicker<T> {
+    /// Returns the item selected by `hash`, or `None` if the plan is empty.
+    ///
+    /// Hashes are mapped onto the plan's total weight by their high bits, so
+    /// uniformly distributed hashes select items in proportion to their
+    /// weights. The same hash always selects the same item of a plan.
+    pub fn pick(&self, hash: u64) -> Option<&T> {
+        let total = *self.bounds.last()?;
+        let point = ((u128::from(hash) * u128::from(total)) >> 64) as u64;
+        let i = self.bounds.partition_point(|bound| *bound <= point);
+        self.items.get(i)
+    }
+
+    /// Returns the item selected by a key, hashed with a hash function that
+    /// is stable across processes and platforms.
+    pub fn pick_key(&self, key: impl AsRef<[u8]>) -> Option<&T> {
+        // FNV-1a does not mix its high bits well for short keys, so the hash
+        // is finalized before it is mapped onto the weights.
+        self.pick(mix(canonical::fnv1a(key.as_ref())))
+    }
+}
+
+impl<T> From<SplitPlan<T>> for WeightedPicker<T> {
+    fn from(plan: SplitPlan<T>) -> Self {
+        let mut total = 0;
+        let (items, bounds) = plan
+            .items
+            .into_iter()
+            .map(|(item, weight)| {
+                total += u64::from(weight);
+                (item, total)
+            })
+            .unzip();
+        Self { items, bounds }
+    }
+}
+
+/// The finalizer of SplitMix64, which spreads every input bit over the
+/// output.
+fn mix(mut h: u64) -> u64 {
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
@@ -0,0 +1,135 @@
+//! Statistics on the Gateway API features used by routes.
+//!
+//! Before upgrading an implementation, or the Gateway API itself, platform
+//! teams need to know which features their users actually depend on: which
+//! filters and match types are used, and how many routes need more than
+//! core support. [`summarize`] counts them for the routes of a Gateway:
+//!
+//! ```ignore
+//! let summary = stats::summarize(snapshot);
+//! for (filter, count) in &summary.filters {
+//!     println!("{}: {}", filter, count);
+//! }
+//! ```
+//!
+//! To summarize the routes of many Gateways, [`Summary::record`] each
+//! distinct route once, as routes may reference several Gateways.
+
+use crate::{
+    conformance::{self, ConformanceLevel, SupportedFeature},
+    snapshot::Snapshot,
+    *,
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Counts of the features used by a set of HTTPRoutes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Summary {
+    /// The number of routes.
+    pub routes: usize,
+
+    /// The number of rules of all routes.
+    pub rules: usize,
+
+    /// The number of routes in each namespace.
+    pub routes_by_namespace: BTreeMap<Arc<str>, usize>,
+
+    /// The number of filters of each type, e.g. `RequestHeaderModifier`,
+    /// including the filters of backends.
+    pub filters: BTreeMap<String, usize>,
+
+    /// The number of path matches of each type, e.g. `PathPrefix`.
+    ///
+    /// Matches without a path, and rules without matches, match all paths as
+    /// if by a `PathPrefix` match on `/`, and are counted as such.
+    pub path_matches: BTreeMap<String, usize>,
+
+    /// The number of header matches of each type, e.g. `Exact`.
+    pub header_matches: BTreeMap<String, usize>,
+
+    /// The number of query parameter matches of each type, e.g. `Exact`.
+    pub query_param_matches: BTreeMap<String, usize>,
+
+    /// The number of matches on the request's method.
+    pub method_matches: usize,
+
+    /// The number of routes at each conformance level, i.e. by the least
+    /// portable feature each route uses.
+    pub levels: BTreeMap<ConformanceLevel, usize>,
+
+    /// The number of routes that use each extended feature.
+    pub features: BTreeMap<SupportedFeature, usize>,
+}
+
+/// Summarizes the features used by the HTTPRoutes of a Gateway.
+///
+/// Routes are counted whether or not the Gateway's listeners allow them to
+/// attach.
+pub fn summarize(snapshot: &Snapshot) -> Summary {
+    let mut summary = Summary::default();
+    for route in snapshot.http_routes() {
+        summary.record(route);
+    }
+    summary
+}
+
+// === impl Summary ===
+
+impl Summary {
+    /// Adds the features used by a route to the summary.
+    pub fn record(&mut self, route: &HttpRoute) {
+        self.routes += 1;
+        let namespace = route.metadata.namespace.as_deref().unwrap_or_default();
+        *self
+            .routes_by_namespace
+            .entry(Arc::from(namespace))
+            .or_default() += 1;
+        *self
+            .levels
+            .entry(conformance::http_route_level(route))
+            .or_default() += 1;
+        for feature in conformance::http_route_features(route) {
+            *self.features.entry(feature).or_default() += 1;
+        }
+
+        for rule in route.spec.rules.iter().flatten() {
+            self.rules += 1;
+
+            let matches = rule.matches.as_deref().unwrap_or_default();
+            if matches.is_empty() {
+                count(&mut self.path_matches, "PathPrefix");
+            }
+            for m in matches {
+                let path = m.path.as_ref().map_or("PathPrefix", |p| p.type_name());
+                count(&mut self.path_matches, path);
+                for header in m.headers.iter().flatten() {
+                    count(&mut self.header_matches, header.type_name());
+                }
+                for param in m.query_params.iter().flatten() {
+                    count(&mut self.query_param_matches, param.type_name());
+                }
+                if m.method.is_some() {
+                    self.method_matches += 1;
+                }
+            }
+
+            let backend_filters = rule
+                .backend_refs
+                .iter()
+                .flatten()
+                .flat_map(|b| b.filters.iter().flatten());
+            for filter in rule.filters.iter().flatten().chain(backend_filters) {
+                count(&mut self.filters, filter.type_name());
+            }
+        }
+    }
+}
+
+fn count(counts: &mut BTreeMap<String, usize>, type_name: &str) {
+    match counts.get_mut(type_name) {
+        Some(n) => *n += 1,
+        None => {
+            counts.insert(type_name.to_string(), 1);
+        }
+    }
+}
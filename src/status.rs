@@ -0,0 +1,529 @@
+//! Helpers for writing route and Gateway status.
+//!
+//! Several controllers may write to the status of the same route (one per
+//! parent Gateway implementation) or the same Gateway (e.g. a controller and
+//! an address allocator). The patches built here only contain the entries
+//! owned by the caller so that concurrent writers don't clobber each other.
+//!
+//! A route's `status.parents` may hold at most [`MAX_PARENT_STATUSES`]
+//! entries, which are shared by every controller that writes to it.
+//! [`truncate_parents`] enforces the limit, keeping the caller's own entries,
+//! and [`RouteStatusPatch::merge`] applies it to every status it builds.
+//!
+//! Writers that race, or that disagree on how to spell a parent reference,
+//! may leave several entries for the same parent and controller.
+//! [`merge_parents`] combines entries into one consistent list, e.g. to
+//! aggregate the statuses observed by several tools or to repair a corrupted
+//! status.
+//!
+//! Conditions record the `metadata.generation` they were computed from as
+//! their `observedGeneration`. The `stale_*_conditions` helpers report
+//! conditions that describe an older generation of their object, e.g. so that
+//! readiness checks can wait for a controller to catch up with a change.
+
+use crate::*;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+
+/// The maximum number of entries in a route's `status.parents`.
+pub const MAX_PARENT_STATUSES: usize = 32;
+
+/// Builds a patch for the `parents` of a route's status, containing only the
+/// entries written by a single controller.
+///
+/// The route `status.parents` list is atomic, so it cannot be partially
+/// updated with server-side apply. Instead, the patch is merged with the
+/// current status, replacing this controller's entries and preserving entries
+/// written by other controllers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteStatusPatch {
+    controller_name: GatewayController,
+    parents: Vec<RouteParentStatus>,
+}
+
+/// Builds a server-side apply patch for a Gateway's status, containing only
+/// the conditions, listener statuses, and addresses set by the caller.
+///
+/// Gateway listener statuses and conditions are map-lists keyed by name and
+/// type respectively, so entries written by other field managers are
+/// preserved by the API server.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GatewayStatusPatch {
+    addresses: Option<Vec<GatewayStatusAddress>>,
+    conditions: Vec<metav1::Condition>,
+    listeners: Vec<ListenerStatus>,
+}
+
+/// Identifies the status entry that holds a condition.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConditionScope<'a> {
+    /// The object's `status.conditions`.
+    Object,
+
+    /// The conditions of the Gateway listener with the given name.
+    Listener(&'a str),
+
+    /// The conditions of a route for one of its parents.
+    Parent(&'a RouteParentStatus),
+}
+
+/// A condition that was computed from an older generation of its object.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StaleCondition<'a> {
+    /// The status entry that holds the condition.
+    pub scope: ConditionScope<'a>,
+
+    /// The stale condition.
+    pub condition: &'a metav1::Condition,
+}
+
+/// Identifies the entry of a parent and controller in a route's status,
+/// ordered by controller name and then parent reference.
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct ParentKey<'a> {
+    controller_name: &'a GatewayController,
+    group: &'a str,
+    kind: &'a str,
+    namespace: &'a str,
+    name: &'a str,
+    section_name: Option<&'a str>,
+    port: Option<PortNumber>,
+}
+
+/// Truncates a route's parent statuses to [`MAX_PARENT_STATUSES`] entries,
+/// returning the entries that were dropped.
+///
+/// Entries written by `controller` are kept in preference to those written by
+/// other controllers. Among entries of the same preference, those that sort
+/// last by controller name and then parent reference (group, kind, namespace,
+/// name, section name, and port) are dropped, so that every controller that
+/// truncates the same list drops the same entries, regardless of their order.
+/// The entries that are kept retain their order.
+pub fn truncate_parents(
+    parents: &mut Vec<RouteParentStatus>,
+    controller: &GatewayController,
+) -> Vec<RouteParentStatus> {
+    if parents.len() <= MAX_PARENT_STATUSES {
+        return Vec::new();
+    }
+
+    let mut ranked = (0..parents.len()).collect::<Vec<_>>();
+    ranked.sort_by(|a, b| {
+        let (a, b) = (&parents[*a], &parents[*b]);
+        // Sorts this controller's entries first.
+        let other = |p: &RouteParentStatus| !p.is_written_by(controller);
+        other(a)
+            .cmp(&other(b))
+            .then_with(|| a.controller_name.cmp(&b.controller_name))
+            .then_with(|| cmp_parent_refs(&a.parent_ref, &b.parent_ref))
+    });
+    let mut keep = vec![false; parents.len()];
+    for i in ranked.into_iter().take(MAX_PARENT_STATUSES) {
+        keep[i] = true;
+    }
+
+    let mut dropped = Vec::new();
+    let mut kept = Vec::with_capacity(MAX_PARENT_STATUSES);
+    for (parent, keep) in parents.drain(..).zip(keep) {
+        if keep {
+            kept.push(parent);
+        } else {
+            dropped.push(parent);
+        }
+    }
+    *parents = kept;
+    dropped
+}
+
+/// Merges route parent statuses, possibly written by several controllers,
+/// into a single list with one entry for each parent and controller.
+///
+/// Parent references are compared with their defaults applied, so a
+/// reference that omits its group, kind, or namespace (which defaults to the
+/// route's `namespace`) is the same parent as one that spells them out.
+/// When a parent has several entries from the same controller, the entry
+/// whose conditions observed the latest generation is kept, and ties are won
+/// by the entry whose canonical JSON sorts last. The merged entries are ordered by
+/// controller name and then parent reference, so merging the same entries in
+/// any order produces the same list.
+///
+/// The merged list is not truncated; see [`truncate_parents`].
+pub fn merge_parents<'a>(
+    namespace: &'a str,
+    parents: impl IntoIterator<Item = &'a RouteParentStatus>,
+) -> Vec<RouteParentStatus> {
+    let mut merged = Vec::<(ParentKey<'a>, &'a RouteParentStatus)>::new();
+    for parent in parents {
+        let key = ParentKey::new(parent, namespace);
+        match merged.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, p)) => {
+                if supersedes(parent, p) {
+                    *p = parent;
+                }
+            }
+            None => merged.push((key, parent)),
+        }
+    }
+    merged.sort_by(|(a, _), (b, _)| a.cmp(b));
+    merged.into_iter().map(|(_, p)| p.clone()).collect()
+}
+
+/// Returns true if `a` replaces `b` as the entry for a parent: if it observed
+/// a later generation, or the same generation and its content sorts after
+/// `b`'s, so that the entry that is kept does not depend on the entries'
+/// order.
+fn supersedes(a: &RouteParentStatus, b: &RouteParentStatus) -> bool {
+    let content = |p: &RouteParentStatus| canonical::to_string(p).expect("statuses must serialize");
+    observed_generation(a)
+        .cmp(&observed_generation(b))
+        .then_with(|| content(a).cmp(&content(b)))
+        .is_gt()
+}
+
+/// Returns the latest generation observed by a parent status's conditions.
+fn observed_generation(parent: &RouteParentStatus) -> Option<i64> {
+    parent
+        .conditions
+        .iter()
+        .filter_map(|c| c.observed_generation)
+        .max()
+}
+
+fn cmp_parent_refs(a: &ParentReference, b: &ParentReference) -> std::cmp::Ordering {
+    a.group
+        .cmp(&b.group)
+        .then_with(|| a.kind.cmp(&b.kind))
+        .then_with(|| a.namespace.cmp(&b.namespace))
+        .then_with(|| a.name.cmp(&b.name))
+        .then_with(|| a.section_name.cmp(&b.section_name))
+        .then_with(|| a.port.cmp(&b.port))
+}
+
+/// Returns true if `condition` was computed from a generation older than
+/// `generation`.
+///
+/// Conditions without an `observedGeneration` are considered stale, since
+/// they cannot be shown to be current. If the object's generation is unknown,
+/// no condition is stale.
+pub fn is_stale(condition: &metav1::Condition, generation: Option<i64>) -> bool {
+    match generation {
+        Some(generation) => condition
+            .observed_generation
+            .map_or(true, |observed| observed < generation),
+        None => false,
+    }
+}
+
+/// Returns the stale conditions of a GatewayClass.
+pub fn stale_gateway_class_conditions(class: &GatewayClass) -> Vec<StaleCondition<'_>> {
+    let generation = class.metadata.generation;
+    let conditions = class.status.as_ref().and_then(|s| s.conditions.as_deref());
+    stale(
+        ConditionScope::Object,
+        conditions.unwrap_or_default(),
+        generation,
+    )
+    .collect()
+}
+
+/// Returns the stale conditions of a Gateway and its listeners.
+pub fn stale_gateway_conditions(gateway: &Gateway) -> Vec<StaleCondition<'_>> {
+    let generation = gateway.metadata.generation;
+    let status = match gateway.status.as_ref() {
+        Some(status) => status,
+        None => return Vec::new(),
+    };
+    let conditions = status.conditions.as_deref().unwrap_or_default();
+    let listeners = status
+        .listeners
+        .iter()
+        .flatten()
+        .flat_map(|l| stale(ConditionScope::Listener(&l.name), &l.conditions, generation));
+    stale(ConditionScope::Object, conditions, generation)
+        .chain(listeners)
+        .collect()
+}
+
+/// Returns the stale conditions of a route for each of its parents.
+///
+/// Each parent's conditions are written by a different controller; filter
+/// the results by the parent's `controller_name` to check a single
+/// controller.
+pub fn stale_route_conditions(route: &route::AnyRoute) -> Vec<StaleCondition<'_>> {
+    let generation = route.metadata().generation;
+    route
+        .status_parents()
+        .iter()
+        .flat_map(|p| stale(ConditionScope::Parent(p), &p.conditions, generation))
+        .collect()
+}
+
+fn stale<'a>(
+    scope: ConditionScope<'a>,
+    conditions: &'a [metav1::Condition],
+    generation: Option<i64>,
+) -> impl Iterator<Item = StaleCondition<'a>> {
+    conditions
+        .iter()
+        .filter(move |c| is_stale(c, generation))
+        .map(move |condition| StaleCondition { scope, condition })
+}
+
+// === impl RouteStatusPatch ===
+
+impl RouteStatusPatch {
+    /// Creates an empty patch for the given controller.
+    pub fn new(controller_name: GatewayController) -> Self {
+        Self {
+            controller_name,
+            parents: Vec::new(),
+        }
+    }
+
+    /// Sets the conditions for a parent, replacing any conditions previously
+    /// set on this patch for the same parent.
+    pub fn parent(
+        mut self,
+        parent_ref: ParentReference,
+        conditions: Vec<metav1::Condition>,
+    ) -> Self {
+        self.parents.retain(|p| p.parent_ref != parent_ref);
+        self.parents.push(RouteParentStatus {
+            parent_ref,
+            controller_name: self.controller_name.clone(),
+            conditions,
+        });
+        self
+    }
+
+    /// Returns the parent statuses in this patch.
+    pub fn parents(&self) -> &[RouteParentStatus] {
+        &self.parents
+    }
+
+    /// Merges this patch into the current status of a route.
+    ///
+    /// All entries previously written by this controller are dropped and
+    /// replaced by the entries in this patch. Entries written by other
+    /// controllers are preserved in their original order, unless they must be
+    /// dropped to keep the status within [`MAX_PARENT_STATUSES`] entries (see
+    /// [`truncate_parents`]).
+    pub fn merge(&self, current: Option<&RouteStatus>) -> RouteStatus {
+        let mut parents = current
+            .map(|s| {
+                s.parents
+                    .iter()
+                    .filter(|p| p.controller_name != self.controller_name)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            controller = %self.controller_name,
+            preserved = parents.len(),
+            written = self.parents.len(),
+            "Merged route status"
+        );
+        parents.extend(self.parents.iter().cloned());
+        let _dropped = truncate_parents(&mut parents, &self.controller_name);
+        #[cfg(feature = "tracing")]
+        if !_dropped.is_empty() {
+            tracing::warn!(
+                controller = %self.controller_name,
+                dropped = _dropped.len(),
+                "Truncated route status parents"
+            );
+        }
+        RouteStatus { parents }
+    }
+
+    /// Builds a JSON merge patch that replaces the route's status with the
+    /// merged status.
+    ///
+    /// When a `resource_version` is provided, it is included in the patch as a
+    /// precondition so that the write fails with a conflict if another
+    /// controller updated the route after `current` was read.
+    pub fn to_merge_patch(
+        &self,
+        current: Option<&RouteStatus>,
+        resource_version: Option<&str>,
+    ) -> serde_json::Value {
+        let mut patch = serde_json::json!({
+            "status": self.merge(current),
+        });
+        if let Some(rv) = resource_version {
+            patch["metadata"] = serde_json::json!({ "resourceVersion": rv });
+        }
+        patch
+    }
+}
+
+// === impl GatewayStatusPatch ===
+
+impl GatewayStatusPatch {
+    /// Creates an empty patch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the addresses bound to the Gateway.
+    pub fn addresses(mut self, addresses: Vec<GatewayStatusAddress>) -> Self {
+        self.addresses = Some(addresses);
+        self
+    }
+
+    /// Sets a Gateway condition, replacing any condition previously set on
+    /// this patch with the same type.
+    pub fn condition(mut self, condition: metav1::Condition) -> Self {
+        self.conditions.retain(|c| c.type_ != condition.type_);
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Sets the status of a listener, replacing any status previously set on
+    /// this patch for the same listener.
+    pub fn listener(mut self, status: ListenerStatus) -> Self {
+        self.listeners.retain(|l| l.name != status.name);
+        self.listeners.push(status);
+        self
+    }
+
+    /// Builds a server-side apply patch for the Gateway's status subresource.
+    pub fn to_apply_patch(&self) -> serde_json::Value {
+        use kube::Resource;
+
+        serde_json::json!({
+            "apiVersion": Gateway::api_version(&()),
+            "kind": Gateway::kind(&()),
+            "status": self,
+        })
+    }
+}
+
+/// Serializes the patch as a partial `GatewayStatus`, omitting any fields
+/// that were not set.
+impl serde::Serialize for GatewayStatusPatch {
+    fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = ser.serialize_map(None)?;
+        if let Some(addresses) = &self.addresses {
+            map.serialize_entry("addresses", addresses)?;
+        }
+        if !self.conditions.is_empty() {
+            map.serialize_entry("conditions", &self.conditions)?;
+        }
+        if !self.listeners.is_empty() {
+            map.serialize_entry("listeners", &self.listeners)?;
+        }
+        map.end()
+    }
+}
+
+// === impl ParentKey ===
+
+impl<'a> ParentKey<'a> {
+    fn new(parent: &'a RouteParentStatus, namespace: &'a str) -> Self {
+        let parent_ref = &parent.parent_ref;
+        Self {
+            controller_name: &parent.controller_name,
+            group: parent_ref.group.as_deref().unwrap_or(consts::GROUP),
+            kind: parent_ref.kind.as_deref().unwrap_or("Gateway"),
+            namespace: parent_ref.namespace.as_deref().unwrap_or(namespace),
+            name: &parent_ref.name,
+            section_name: parent_ref.section_name.as_deref(),
+            port: parent_ref.port,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parent(
+        controller: &str,
+        parent_ref: serde_json::Value,
+        generation: i64,
+        status: &str,
+    ) -> RouteParentStatus {
+        serde_json::from_value(json!({
+            "parentRef": parent_ref,
+            "controllerName": controller,
+            "conditions": [{
+                "type": "Accepted",
+                "status": status,
+                "reason": "Accepted",
+                "message": "",
+                "observedGeneration": generation,
+                "lastTransitionTime": "2020-01-01T00:00:00Z",
+            }],
+        }))
+        .unwrap()
+    }
+
+    /// Returns every ordering of `items`.
+    fn permutations<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+        if items.len() <= 1 {
+            return vec![items.to_vec()];
+        }
+        let mut all = Vec::new();
+        for i in 0..items.len() {
+            let mut rest = items.to_vec();
+            let first = rest.remove(i);
+            for mut p in permutations(&rest) {
+                p.insert(0, first.clone());
+                all.push(p);
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn merges_parents_independently_of_order() {
+        let parents = [
+            parent("example.com/a", json!({ "name": "web" }), 1, "True"),
+            parent(
+                "example.com/a",
+                json!({ "name": "web", "namespace": "apps" }),
+                1,
+                "False",
+            ),
+            parent("example.com/a", json!({ "name": "web" }), 0, "Unknown"),
+            parent("example.com/b", json!({ "name": "web" }), 1, "True"),
+            parent("example.com/a", json!({ "name": "api" }), 1, "True"),
+        ];
+        let expected = merge_parents("apps", &parents);
+        assert_eq!(
+            expected
+                .iter()
+                .map(|p| (
+                    p.controller_name.as_str(),
+                    p.parent_ref.name.as_str(),
+                    p.conditions[0].status.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("example.com/a", "api", "True"),
+                ("example.com/a", "web", "True"),
+                ("example.com/b", "web", "True"),
+            ]
+        );
+        for permutation in permutations(&parents) {
+            assert_eq!(merge_parents("apps", &permutation), expected);
+        }
+    }
+
+    #[test]
+    fn keeps_the_latest_generation() {
+        let parents = [
+            parent("example.com/a", json!({ "name": "web" }), 2, "False"),
+            parent("example.com/a", json!({ "name": "web" }), 1, "True"),
+        ];
+        for permutation in permutations(&parents) {
+            let merged = merge_parents("apps", &permutation);
+            assert_eq!(merged, [parents[0].clone()]);
+        }
+    }
+}
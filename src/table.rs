@@ -0,0 +1,249 @@
+//! `kubectl get`-style tables.
+//!
+//! The columns of each kind are the same as the `additionalPrinterColumns` of
+//! its CRD, so that CLIs built on this crate render the tables users are
+//! familiar with. Columns with a non-zero priority (i.e. those only shown by
+//! `kubectl get -o wide`) are omitted.
+//!
+//! ```
+//! # use k8s_gateway_api::{table, HttpRoute};
+//! # use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+//! # let routes: Vec<HttpRoute> = Vec::new();
+//! # let now = metav1::Time(k8s_openapi::chrono::Utc::now());
+//! let table = table::table(&routes, &now);
+//! println!("{}", table);
+//! ```
+
+use crate::*;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::fmt;
+
+/// A table of objects of one kind.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Table {
+    /// The column headers, e.g. `NAME`.
+    pub headers: Vec<&'static str>,
+
+    /// The rows, each with one value per header.
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A kind with printer columns.
+pub trait PrinterColumns {
+    /// The column headers, starting with `NAME`.
+    const HEADERS: &'static [&'static str];
+
+    /// Returns the values of the columns for this object. Ages are computed
+    /// relative to `now`.
+    fn row(&self, now: &metav1::Time) -> Vec<String>;
+}
+
+/// Returns a table of `objects`.
+pub fn table<'o, T: PrinterColumns + 'o>(
+    objects: impl IntoIterator<Item = &'o T>,
+    now: &metav1::Time,
+) -> Table {
+    Table {
+        headers: T::HEADERS.to_vec(),
+        rows: objects.into_iter().map(|o| o.row(now)).collect(),
+    }
+}
+
+/// Formats the age of an object as `kubectl` does, e.g. `5m3s` or `12d`.
+pub fn age(created: Option<&metav1::Time>, now: &metav1::Time) -> String {
+    let created = match created {
+        Some(created) => created,
+        None => return "<unknown>".to_string(),
+    };
+    human_duration((now.0 - created.0).num_seconds())
+}
+
+/// Formats a duration with the precision used by `kubectl`: two units for
+/// short durations of each magnitude, and one unit otherwise.
+fn human_duration(seconds: i64) -> String {
+    if seconds < -1 {
+        return "<invalid>".to_string();
+    }
+    if seconds < 0 {
+        return "0s".to_string();
+    }
+    if seconds < 60 * 2 {
+        return format!("{}s", seconds);
+    }
+
+    let two_units = |n: i64, unit: &str, m: i64, sub: &str| {
+        if m == 0 {
+            format!("{}{}", n, unit)
+        } else {
+            format!("{}{}{}{}", n, unit, m, sub)
+        }
+    };
+
+    let minutes = seconds / 60;
+    if minutes < 10 {
+        return two_units(minutes, "m", seconds % 60, "s");
+    }
+    if minutes < 60 * 3 {
+        return format!("{}m", minutes);
+    }
+
+    let hours = minutes / 60;
+    if hours < 8 {
+        return two_units(hours, "h", minutes % 60, "m");
+    }
+    if hours < 48 {
+        return format!("{}h", hours);
+    }
+    if hours < 24 * 8 {
+        return two_units(hours / 24, "d", hours % 24, "h");
+    }
+    if hours < 24 * 365 * 2 {
+        return format!("{}d", hours / 24);
+    }
+    if hours < 24 * 365 * 8 {
+        let days = hours / 24;
+        return two_units(days / 365, "y", days % 365, "d");
+    }
+    format!("{}y", hours / 24 / 365)
+}
+
+fn name(meta: &metav1::ObjectMeta) -> String {
+    meta.name.clone().unwrap_or_default()
+}
+
+/// Returns the status of the condition with the given type, or an empty
+/// string if it is not set.
+fn condition_status(conditions: Option<&[metav1::Condition]>, type_: &str) -> String {
+    conditions
+        .unwrap_or_default()
+        .iter()
+        .find(|c| c.type_ == type_)
+        .map(|c| c.status.clone())
+        .unwrap_or_default()
+}
+
+/// Formats hostnames as `kubectl` formats string lists, e.g.
+/// `["foo.example.com"]`.
+fn hostnames(hostnames: Option<&[Hostname]>) -> String {
+    match hostnames {
+        Some(hostnames) if !hostnames.is_empty() => {
+            serde_json::to_string(hostnames).expect("strings must serialize")
+        }
+        _ => String::new(),
+    }
+}
+
+// === impl Table ===
+
+/// Renders the table with columns separated by three spaces, as `kubectl`
+/// does.
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut widths = self.headers.iter().map(|h| h.len()).collect::<Vec<_>>();
+        for row in &self.rows {
+            for (w, v) in widths.iter_mut().zip(row) {
+                *w = (*w).max(v.chars().count());
+            }
+        }
+
+        let mut write_row = |values: &mut dyn Iterator<Item = &str>| -> fmt::Result {
+            let mut line = String::new();
+            for (i, (v, w)) in values.zip(&widths).enumerate() {
+                if i > 0 {
+                    line.push_str("   ");
+                }
+                line.push_str(v);
+                line.extend(std::iter::repeat(' ').take(w - v.chars().count()));
+            }
+            writeln!(f, "{}", line.trim_end())
+        };
+        write_row(&mut self.headers.iter().copied())?;
+        for row in &self.rows {
+            write_row(&mut row.iter().map(String::as_str))?;
+        }
+        Ok(())
+    }
+}
+
+// === impl PrinterColumns ===
+
+impl PrinterColumns for GatewayClass {
+    const HEADERS: &'static [&'static str] = &["NAME", "CONTROLLER", "ACCEPTED", "AGE"];
+
+    fn row(&self, now: &metav1::Time) -> Vec<String> {
+        let conditions = self.status.as_ref().and_then(|s| s.conditions.as_deref());
+        vec![
+            name(&self.metadata),
+            self.spec.controller_name.to_string(),
+            condition_status(conditions, GATEWAY_CLASS_CONDITION_ACCEPTED),
+            age(self.metadata.creation_timestamp.as_ref(), now),
+        ]
+    }
+}
+
+impl PrinterColumns for Gateway {
+    const HEADERS: &'static [&'static str] = &["NAME", "CLASS", "ADDRESS", "PROGRAMMED", "AGE"];
+
+    fn row(&self, now: &metav1::Time) -> Vec<String> {
+        let status = self.status.as_ref();
+        let address = status
+            .and_then(|s| s.addresses.as_deref())
+            .and_then(|a| a.first())
+            .map(|a| a.value.clone())
+            .unwrap_or_default();
+        let conditions = status.and_then(|s| s.conditions.as_deref());
+        vec![
+            name(&self.metadata),
+            self.spec.gateway_class_name.clone(),
+            address,
+            condition_status(conditions, "Programmed"),
+            age(self.metadata.creation_timestamp.as_ref(), now),
+        ]
+    }
+}
+
+impl PrinterColumns for HttpRoute {
+    const HEADERS: &'static [&'static str] = &["NAME", "HOSTNAMES", "AGE"];
+
+    fn row(&self, now: &metav1::Time) -> Vec<String> {
+        vec![
+            name(&self.metadata),
+            hostnames(self.spec.hostnames.as_deref()),
+            age(self.metadata.creation_timestamp.as_ref(), now),
+        ]
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl PrinterColumns for GrpcRoute {
+    const HEADERS: &'static [&'static str] = &["NAME", "HOSTNAMES", "AGE"];
+
+    fn row(&self, now: &metav1::Time) -> Vec<String> {
+        vec![
+            name(&self.metadata),
+            hostnames(self.spec.hostnames.as_deref()),
+            age(self.metadata.creation_timestamp.as_ref(), now),
+        ]
+    }
+}
+
+/// Implements [`PrinterColumns`] for kinds whose only column is their age.
+macro_rules! impl_age_columns {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            #[cfg(feature = "experimental")]
+            impl PrinterColumns for $ty {
+                const HEADERS: &'static [&'static str] = &["NAME", "AGE"];
+
+                fn row(&self, now: &metav1::Time) -> Vec<String> {
+                    vec![
+                        name(&self.metadata),
+                        age(self.metadata.creation_timestamp.as_ref(), now),
+                    ]
+                }
+            }
+        )+
+    };
+}
+
+impl_age_columns!(ReferenceGrant, TcpRoute, TlsRoute, UdpRoute);
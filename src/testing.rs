@@ -0,0 +1,936 @@
+//! Utilities for testing Gateway API controllers and tooling.
+//!
+//! [`FakeApiServer`] is an in-memory fake of the Kubernetes API server. It
+//! implements the HTTP surface used by [`kube::Api`] so that reconcile logic
+//! can be exercised against a real [`kube::Client`] without envtest or a
+//! cluster:
+//!
+//! ```ignore
+//! let server = FakeApiServer::new();
+//! server.insert(&route);
+//!
+//! let routes = kube::Api::<HttpRoute>::namespaced(server.client(), "default");
+//! reconcile(routes).await?;
+//!
+//! let route = server.get::<HttpRoute>(Some("default"), "route").unwrap();
+//! assert_eq!(route.status.unwrap().inner.parents.len(), 1);
+//! ```
+//!
+//! The server is preloaded with the Gateway API CRDs: requests for unknown
+//! Gateway API resources or versions fail with `404 Not Found` and resource
+//! scopes are enforced. Requests for resources in other API groups (e.g.
+//! Services or EndpointSlices) are stored without validation.
+//!
+//! This is not a faithful reimplementation of the API server. In particular:
+//!
+//! * Watches are not supported.
+//! * Strategic merge patches and server-side apply patches are applied as JSON
+//!   merge patches; field ownership is not tracked.
+//! * Label selectors only support equality requirements (`key=value`).
+//! * Only the `resourceVersion`, `uid`, and `generation` metadata fields are
+//!   managed by the server.
+//!
+//! The [`golden`] module checks that upstream manifests round-trip through
+//! this crate's types.
+
+pub mod golden;
+
+use crate::consts;
+use hyper::{Body, Request, Response, StatusCode};
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::Resource;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+/// An in-memory fake of the Kubernetes API server.
+///
+/// Clones share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct FakeApiServer {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    crds: BTreeMap<(String, String), Crd>,
+    objects: BTreeMap<Key, Value>,
+    resource_version: u64,
+}
+
+/// The subset of a CRD used to validate requests.
+#[derive(Debug)]
+struct Crd {
+    kind: String,
+    namespaced: bool,
+    versions: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    group: String,
+    plural: String,
+    namespace: Option<String>,
+    name: String,
+}
+
+/// A parsed API request path.
+#[derive(Debug)]
+struct ResourcePath {
+    group: String,
+    version: String,
+    plural: String,
+    namespace: Option<String>,
+    name: Option<String>,
+    status: bool,
+}
+
+type ResponseFuture = Pin<Box<dyn Future<Output = Result<Response<Body>, Infallible>> + Send>>;
+
+// === impl FakeApiServer ===
+
+impl FakeApiServer {
+    /// Creates a server preloaded with the Gateway API CRDs.
+    pub fn new() -> Self {
+        let server = Self::default();
+        {
+            let mut state = server.lock();
+            for kind in consts::KINDS {
+                state.crds.insert(
+                    (kind.group.to_string(), kind.plural.to_string()),
+                    Crd {
+                        kind: kind.kind.to_string(),
+                        namespaced: kind.namespaced,
+                        versions: kind.versions.iter().map(|v| v.to_string()).collect(),
+                    },
+                );
+            }
+        }
+        server
+    }
+
+    /// Returns a client that sends all requests to this server. The client's
+    /// default namespace is `default`.
+    pub fn client(&self) -> kube::Client {
+        kube::Client::new(self.clone(), "default")
+    }
+
+    /// Registers a CRD so that requests for its resources are validated.
+    pub fn register(&self, crd: &CustomResourceDefinition) {
+        let spec = &crd.spec;
+        self.lock().crds.insert(
+            (spec.group.clone(), spec.names.plural.clone()),
+            Crd {
+                kind: spec.names.kind.clone(),
+                namespaced: spec.scope == "Namespaced",
+                versions: spec.versions.iter().map(|v| v.name.clone()).collect(),
+            },
+        );
+    }
+
+    /// Stores an object, replacing any existing object with the same name.
+    ///
+    /// The object's `resourceVersion`, `uid`, and `generation` are set as if it
+    /// had been created by a client.
+    pub fn insert<K>(&self, obj: &K)
+    where
+        K: Resource<DynamicType = ()> + Serialize,
+    {
+        let key = Key {
+            group: K::group(&()).to_string(),
+            plural: K::plural(&()).to_string(),
+            namespace: obj.meta().namespace.clone(),
+            name: obj.meta().name.clone().expect("objects must have a name"),
+        };
+        let obj = serde_json::to_value(obj).expect("objects must serialize");
+        self.lock().store(key, obj, None);
+    }
+
+    /// Returns a stored object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stored object cannot be decoded as a `K`.
+    pub fn get<K>(&self, namespace: Option<&str>, name: &str) -> Option<K>
+    where
+        K: Resource<DynamicType = ()> + DeserializeOwned,
+    {
+        let key = Key {
+            group: K::group(&()).to_string(),
+            plural: K::plural(&()).to_string(),
+            namespace: namespace.map(Into::into),
+            name: name.to_string(),
+        };
+        let obj = self.lock().objects.get(&key).cloned()?;
+        Some(serde_json::from_value(obj).expect("stored object must decode"))
+    }
+
+    /// Returns all stored objects of a kind, optionally restricted to a
+    /// namespace, ordered by namespace and name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a stored object cannot be decoded as a `K`.
+    pub fn list<K>(&self, namespace: Option<&str>) -> Vec<K>
+    where
+        K: Resource<DynamicType = ()> + DeserializeOwned,
+    {
+        let (group, plural) = (K::group(&()), K::plural(&()));
+        self.lock()
+            .objects
+            .iter()
+            .filter(|(k, _)| k.group == group && k.plural == plural)
+            .filter(|(k, _)| namespace.is_none() || k.namespace.as_deref() == namespace)
+            .map(|(_, obj)| serde_json::from_value(obj.clone()).expect("stored object must decode"))
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .expect("fake API server state must not be poisoned")
+    }
+
+    fn handle(&self, req: &Request<()>, body: &[u8]) -> Response<Body> {
+        let path = match ResourcePath::parse(req.uri().path()) {
+            Some(path) => path,
+            None => return not_found("the server could not find the requested resource"),
+        };
+        let body = if body.is_empty() {
+            Value::Null
+        } else {
+            match serde_json::from_slice(body) {
+                Ok(body) => body,
+                Err(e) => return status(StatusCode::BAD_REQUEST, "BadRequest", &e.to_string()),
+            }
+        };
+
+        let mut state = self.lock();
+        let kind = match state.validate(&path) {
+            Ok(kind) => kind,
+            Err(()) => return not_found("the server could not find the requested resource"),
+        };
+        let query = req.uri().query().unwrap_or("");
+
+        match (req.method(), path.key()) {
+            (&hyper::Method::GET, None) => {
+                if query_param(query, "watch").as_deref() == Some("true") {
+                    return status(
+                        StatusCode::METHOD_NOT_ALLOWED,
+                        "MethodNotAllowed",
+                        "watches are not supported",
+                    );
+                }
+                let selector = query_param(query, "labelSelector").unwrap_or_default();
+                state.list(&path, kind, &selector)
+            }
+            (&hyper::Method::GET, Some(key)) => match state.objects.get(&key) {
+                Some(obj) => ok(StatusCode::OK, path.versioned(obj.clone())),
+                None => not_found(&format!("{} {:?} not found", path.plural, key.name)),
+            },
+            (&hyper::Method::POST, None) => state.create(&path, body),
+            (&hyper::Method::PUT, Some(key)) => state.replace(&path, key, body),
+            (&hyper::Method::PATCH, Some(key)) => {
+                let content_type = req
+                    .headers()
+                    .get(hyper::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("");
+                state.patch(&path, key, content_type, body)
+            }
+            (&hyper::Method::DELETE, Some(key)) => match state.objects.remove(&key) {
+                Some(obj) => ok(StatusCode::OK, path.versioned(obj)),
+                None => not_found(&format!("{} {:?} not found", path.plural, key.name)),
+            },
+            _ => status(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "MethodNotAllowed",
+                "the server does not allow this method on the requested resource",
+            ),
+        }
+    }
+}
+
+impl tower::Service<Request<Body>> for FakeApiServer {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = ResponseFuture;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let server = self.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = match hyper::body::to_bytes(body).await {
+                Ok(body) => body,
+                Err(e) => {
+                    return Ok(status(
+                        StatusCode::BAD_REQUEST,
+                        "BadRequest",
+                        &e.to_string(),
+                    ))
+                }
+            };
+            Ok(server.handle(&Request::from_parts(parts, ()), &body))
+        })
+    }
+}
+
+// === impl State ===
+
+impl State {
+    /// Validates a request against the registered CRDs, returning the kind of
+    /// the requested resource, if known.
+    ///
+    /// Fails if the request is for an unknown resource in a group defined by a
+    /// registered CRD.
+    fn validate(&self, path: &ResourcePath) -> Result<Option<String>, ()> {
+        let registered = self.crds.keys().any(|(group, _)| *group == path.group);
+        let crd = match self.crds.get(&(path.group.clone(), path.plural.clone())) {
+            Some(crd) => crd,
+            None if registered => return Err(()),
+            None => return Ok(None),
+        };
+
+        let scoped = match (&path.namespace, &path.name) {
+            (Some(_), _) => crd.namespaced,
+            // Namespaced resources may be listed across all namespaces.
+            (None, None) => true,
+            (None, Some(_)) => !crd.namespaced,
+        };
+        if !scoped || !crd.versions.contains(&path.version) {
+            return Err(());
+        }
+        Ok(Some(crd.kind.clone()))
+    }
+
+    fn list(&self, path: &ResourcePath, kind: Option<String>, selector: &str) -> Response<Body> {
+        let items = self
+            .objects
+            .iter()
+            .filter(|(k, _)| k.group == path.group && k.plural == path.plural)
+            .filter(|(k, _)| path.namespace.is_none() || k.namespace == path.namespace)
+            .filter(|(_, obj)| matches_labels(obj, selector))
+            .map(|(_, obj)| path.versioned(obj.clone()))
+            .collect::<Vec<_>>();
+        let kind = kind.map(|k| format!("{}List", k));
+        ok(
+            StatusCode::OK,
+            json!({
+                "apiVersion": path.api_version(),
+                "kind": kind.as_deref().unwrap_or("List"),
+                "metadata": { "resourceVersion": self.resource_version.to_string() },
+                "items": items,
+            }),
+        )
+    }
+
+    fn create(&mut self, path: &ResourcePath, mut obj: Value) -> Response<Body> {
+        let name = match obj.pointer("/metadata/name").and_then(Value::as_str) {
+            Some(name) => name.to_string(),
+            None => {
+                return status(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "Invalid",
+                    "metadata.name: Required value",
+                )
+            }
+        };
+        let key = Key {
+            name,
+            ..path.collection_key()
+        };
+        if self.objects.contains_key(&key) {
+            return already_exists(&path.plural, &key.name);
+        }
+        if let Some(ns) = &key.namespace {
+            obj["metadata"]["namespace"] = Value::String(ns.clone());
+        }
+        let obj = self.store(key, obj, None);
+        ok(StatusCode::CREATED, path.versioned(obj))
+    }
+
+    fn replace(&mut self, path: &ResourcePath, key: Key, mut obj: Value) -> Response<Body> {
+        let current = match self.objects.get(&key) {
+            Some(current) => current.clone(),
+            None => return not_found(&format!("{} {:?} not found", path.plural, key.name)),
+        };
+        if let Some(rv) = obj
+            .pointer("/metadata/resourceVersion")
+            .and_then(Value::as_str)
+        {
+            if Some(rv)
+                != current
+                    .pointer("/metadata/resourceVersion")
+                    .and_then(Value::as_str)
+            {
+                return conflict(&path.plural, &key.name);
+            }
+        }
+
+        // Writes to the main resource may not change the status and writes to
+        // the status subresource may only change the status.
+        if path.status {
+            let status = obj.get("status").cloned();
+            obj = current.clone();
+            set_status(&mut obj, status);
+        } else {
+            set_status(&mut obj, current.get("status").cloned());
+        }
+        let obj = self.store(key, obj, Some(&current));
+        ok(StatusCode::OK, path.versioned(obj))
+    }
+
+    fn patch(
+        &mut self,
+        path: &ResourcePath,
+        key: Key,
+        content_type: &str,
+        patch: Value,
+    ) -> Response<Body> {
+        let apply = content_type.starts_with("application/apply-patch");
+        if !apply
+            && !content_type.starts_with("application/merge-patch+json")
+            && !content_type.starts_with("application/strategic-merge-patch+json")
+        {
+            return status(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "UnsupportedMediaType",
+                &format!("unsupported patch type: {:?}", content_type),
+            );
+        }
+
+        let current = match self.objects.get(&key) {
+            Some(current) => Some(current.clone()),
+            // Server-side apply creates objects that don't exist.
+            None if apply && !path.status => None,
+            None => return not_found(&format!("{} {:?} not found", path.plural, key.name)),
+        };
+        if let Some(rv) = patch
+            .pointer("/metadata/resourceVersion")
+            .and_then(Value::as_str)
+        {
+            let current_rv = current
+                .as_ref()
+                .and_then(|c| c.pointer("/metadata/resourceVersion"))
+                .and_then(Value::as_str);
+            if Some(rv) != current_rv {
+                return conflict(&path.plural, &key.name);
+            }
+        }
+
+        let mut obj = current.clone().unwrap_or_else(
+            || json!({ "metadata": { "name": key.name, "namespace": key.namespace } }),
+        );
+        if path.status {
+            let mut status = obj.get("status").cloned().unwrap_or(Value::Null);
+            if let Some(patch) = patch.get("status") {
+                merge_patch(&mut status, patch);
+            }
+            set_status(&mut obj, Some(status));
+        } else {
+            let status = obj.get("status").cloned();
+            merge_patch(&mut obj, &patch);
+            set_status(&mut obj, status);
+        }
+        let code = if current.is_some() {
+            StatusCode::OK
+        } else {
+            StatusCode::CREATED
+        };
+        let obj = self.store(key, obj, current.as_ref());
+        ok(code, path.versioned(obj))
+    }
+
+    /// Stores an object, setting its server-managed metadata.
+    fn store(&mut self, key: Key, mut obj: Value, current: Option<&Value>) -> Value {
+        self.resource_version += 1;
+        let rv = self.resource_version;
+
+        let generation = match current {
+            None => 1,
+            Some(current) => {
+                let generation = current
+                    .pointer("/metadata/generation")
+                    .and_then(Value::as_i64)
+                    .unwrap_or(1);
+                if current.get("spec") != obj.get("spec") {
+                    generation + 1
+                } else {
+                    generation
+                }
+            }
+        };
+        let uid = current
+            .and_then(|c| c.pointer("/metadata/uid"))
+            .cloned()
+            .unwrap_or_else(|| Value::String(format!("00000000-0000-0000-0000-{:012x}", rv)));
+
+        if !obj.get("metadata").map_or(false, Value::is_object) {
+            obj["metadata"] = json!({});
+        }
+        let meta = &mut obj["metadata"];
+        meta["resourceVersion"] = Value::String(rv.to_string());
+        meta["generation"] = Value::from(generation);
+        meta["uid"] = uid;
+        strip_nulls(meta);
+
+        self.objects.insert(key, obj.clone());
+        obj
+    }
+}
+
+// === impl ResourcePath ===
+
+impl ResourcePath {
+    fn parse(path: &str) -> Option<Self> {
+        let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+        let (group, version, rest) = match segments.as_slice() {
+            ["api", version, rest @ ..] => ("", *version, rest),
+            ["apis", group, version, rest @ ..] => (*group, *version, rest),
+            _ => return None,
+        };
+        let (namespace, rest) = match rest {
+            ["namespaces", ns, rest @ ..] if !rest.is_empty() => (Some(ns.to_string()), rest),
+            rest => (None, rest),
+        };
+        let (plural, name, status) = match rest {
+            [plural] => (*plural, None, false),
+            [plural, name] => (*plural, Some(name.to_string()), false),
+            [plural, name, "status"] => (*plural, Some(name.to_string()), true),
+            _ => return None,
+        };
+        Some(Self {
+            group: group.to_string(),
+            version: version.to_string(),
+            plural: plural.to_string(),
+            namespace,
+            name,
+            status,
+        })
+    }
+
+    fn api_version(&self) -> String {
+        if self.group.is_empty() {
+            self.version.clone()
+        } else {
+            format!("{}/{}", self.group, self.version)
+        }
+    }
+
+    fn collection_key(&self) -> Key {
+        Key {
+            group: self.group.clone(),
+            plural: self.plural.clone(),
+            namespace: self.namespace.clone(),
+            name: String::new(),
+        }
+    }
+
+    fn key(&self) -> Option<Key> {
+        let name = self.name.clone()?;
+        Some(Key {
+            name,
+            ..self.collection_key()
+        })
+    }
+
+    /// Returns the object as served at the requested version.
+    fn versioned(&self, mut obj: Value) -> Value {
+        if obj.is_object() {
+            obj["apiVersion"] = Value::String(self.api_version());
+        }
+        obj
+    }
+}
+
+fn set_status(obj: &mut Value, status: Option<Value>) {
+    if let Some(obj) = obj.as_object_mut() {
+        match status {
+            Some(status) if !status.is_null() => {
+                obj.insert("status".to_string(), status);
+            }
+            _ => {
+                obj.remove("status");
+            }
+        }
+    }
+}
+
+/// Applies a JSON merge patch, as described by RFC 7386.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        patch => {
+            *target = patch.clone();
+            return;
+        }
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (k, v) in patch {
+            if v.is_null() {
+                target.remove(k);
+            } else {
+                merge_patch(target.entry(k.clone()).or_insert(Value::Null), v);
+            }
+        }
+    }
+}
+
+fn strip_nulls(value: &mut Value) {
+    if let Value::Object(map) = value {
+        map.retain(|_, v| !v.is_null());
+    }
+}
+
+/// Evaluates a label selector consisting of equality requirements.
+fn matches_labels(obj: &Value, selector: &str) -> bool {
+    let labels = obj.pointer("/metadata/labels");
+    selector
+        .split(',')
+        .filter(|req| !req.is_empty())
+        .all(|req| {
+            let (key, value) = match req.split_once("==").or_else(|| req.split_once('=')) {
+                Some(kv) => kv,
+                None => return labels.and_then(|l| l.get(req)).is_some(),
+            };
+            labels.and_then(|l| l.get(key)).and_then(Value::as_str) == Some(value)
+        })
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        if k == name {
+            Some(percent_decode(v))
+        } else {
+            None
+        }
+    })
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn ok(code: StatusCode, obj: Value) -> Response<Body> {
+    let body = serde_json::to_vec(&obj).expect("values must serialize");
+    Response::builder()
+        .status(code)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("responses must be valid")
+}
+
+/// Builds a failure response with a `Status` body, as returned by the API
+/// server.
+fn status(code: StatusCode, reason: &str, message: &str) -> Response<Body> {
+    ok(
+        code,
+        json!({
+            "apiVersion": "v1",
+            "kind": "Status",
+            "metadata": {},
+            "status": "Failure",
+            "message": message,
+            "reason": reason,
+            "code": code.as_u16(),
+        }),
+    )
+}
+
+fn not_found(message: &str) -> Response<Body> {
+    status(StatusCode::NOT_FOUND, "NotFound", message)
+}
+
+fn already_exists(plural: &str, name: &str) -> Response<Body> {
+    status(
+        StatusCode::CONFLICT,
+        "AlreadyExists",
+        &format!("{} {:?} already exists", plural, name),
+    )
+}
+
+fn conflict(plural: &str, name: &str) -> Response<Body> {
+    status(
+        StatusCode::CONFLICT,
+        "Conflict",
+        &format!(
+            "Operation cannot be fulfilled on {} {:?}: the object has been modified",
+            plural, name
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HttpRoute;
+    use hyper::Method;
+    use tower::ServiceExt;
+
+    fn route(namespace: &str, name: &str, labels: Value) -> HttpRoute {
+        serde_json::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": name, "namespace": namespace, "labels": labels },
+            "spec": { "hostnames": ["example.com"] },
+        }))
+        .unwrap()
+    }
+
+    fn routes_path(namespace: Option<&str>) -> String {
+        HttpRoute::url_path(&(), namespace)
+    }
+
+    async fn send(
+        server: &FakeApiServer,
+        method: Method,
+        uri: &str,
+        content_type: &str,
+        body: Value,
+    ) -> (StatusCode, Value) {
+        let body = if body.is_null() {
+            Body::empty()
+        } else {
+            Body::from(serde_json::to_vec(&body).unwrap())
+        };
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(hyper::header::CONTENT_TYPE, content_type)
+            .body(body)
+            .unwrap();
+        let rsp = server.clone().oneshot(req).await.unwrap();
+        let code = rsp.status();
+        let body = hyper::body::to_bytes(rsp.into_body()).await.unwrap();
+        (code, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn get(server: &FakeApiServer, uri: &str) -> (StatusCode, Value) {
+        send(server, Method::GET, uri, "", Value::Null).await
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn gets_objects() {
+        let server = FakeApiServer::new();
+        server.insert(&route("default", "web", Value::Null));
+
+        let uri = format!("{}/web", routes_path(Some("default")));
+        let (code, obj) = get(&server, &uri).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(obj["apiVersion"], "gateway.networking.k8s.io/v1beta1");
+        assert_eq!(obj["metadata"]["name"], "web");
+        assert_eq!(obj["metadata"]["resourceVersion"], "1");
+        assert_eq!(obj["metadata"]["generation"], 1);
+
+        let uri = format!("{}/other", routes_path(Some("default")));
+        let (code, obj) = get(&server, &uri).await;
+        assert_eq!(code, StatusCode::NOT_FOUND);
+        assert_eq!(obj["reason"], "NotFound");
+
+        // Namespaced resources may not be fetched without a namespace, and
+        // unknown versions of Gateway API resources are not served.
+        let (code, _) = get(&server, &format!("{}/web", routes_path(None))).await;
+        assert_eq!(code, StatusCode::NOT_FOUND);
+        let uri = "/apis/gateway.networking.k8s.io/v0/namespaces/default/httproutes/web";
+        assert_eq!(get(&server, uri).await.0, StatusCode::NOT_FOUND);
+
+        let route = server.get::<HttpRoute>(Some("default"), "web").unwrap();
+        assert_eq!(route.metadata.resource_version.as_deref(), Some("1"));
+        assert!(server.get::<HttpRoute>(Some("other"), "web").is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn lists_objects() {
+        let server = FakeApiServer::new();
+        server.insert(&route("a", "web", json!({ "app": "web" })));
+        server.insert(&route("b", "web", json!({ "app": "web" })));
+        server.insert(&route("b", "api", json!({ "app": "api" })));
+
+        let names = |list: &Value| {
+            list["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|obj| {
+                    format!(
+                        "{}/{}",
+                        obj["metadata"]["namespace"].as_str().unwrap(),
+                        obj["metadata"]["name"].as_str().unwrap()
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let (code, list) = get(&server, &routes_path(None)).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(list["kind"], "HTTPRouteList");
+        assert_eq!(list["metadata"]["resourceVersion"], "3");
+        assert_eq!(names(&list), ["a/web", "b/api", "b/web"]);
+
+        let (_, list) = get(&server, &routes_path(Some("b"))).await;
+        assert_eq!(names(&list), ["b/api", "b/web"]);
+
+        let uri = format!("{}?labelSelector=app%3Dweb", routes_path(None));
+        let (_, list) = get(&server, &uri).await;
+        assert_eq!(names(&list), ["a/web", "b/web"]);
+
+        assert_eq!(server.list::<HttpRoute>(None).len(), 3);
+        assert_eq!(server.list::<HttpRoute>(Some("a")).len(), 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn patches_objects() {
+        let server = FakeApiServer::new();
+        server.insert(&route("default", "web", Value::Null));
+        let uri = format!("{}/web", routes_path(Some("default")));
+        let merge = "application/merge-patch+json";
+
+        // Spec changes bump the generation; status changes are ignored.
+        let patch = json!({
+            "metadata": { "labels": { "app": "web" } },
+            "spec": { "hostnames": null },
+            "status": { "parents": [] },
+        });
+        let (code, obj) = send(&server, Method::PATCH, &uri, merge, patch).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(obj["metadata"]["labels"]["app"], "web");
+        assert_eq!(obj["metadata"]["generation"], 2);
+        assert_eq!(obj["metadata"]["resourceVersion"], "2");
+        assert!(obj["spec"].get("hostnames").is_none());
+        assert!(obj.get("status").is_none());
+
+        // The status subresource only changes the status.
+        let patch = json!({
+            "metadata": { "labels": { "app": "other" } },
+            "status": { "parents": [] },
+        });
+        let status_uri = format!("{}/status", uri);
+        let (code, obj) = send(&server, Method::PATCH, &status_uri, merge, patch).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(obj["metadata"]["labels"]["app"], "web");
+        assert_eq!(obj["metadata"]["generation"], 2);
+        assert_eq!(obj["status"], json!({ "parents": [] }));
+
+        // Server-side apply creates missing objects; merge patches don't.
+        let missing = format!("{}/api", routes_path(Some("default")));
+        let patch = json!({ "spec": {} });
+        let (code, _) = send(&server, Method::PATCH, &missing, merge, patch.clone()).await;
+        assert_eq!(code, StatusCode::NOT_FOUND);
+        let apply = "application/apply-patch+yaml";
+        let (code, obj) = send(&server, Method::PATCH, &missing, apply, patch.clone()).await;
+        assert_eq!(code, StatusCode::CREATED);
+        assert_eq!(obj["metadata"]["namespace"], "default");
+        assert_eq!(obj["metadata"]["generation"], 1);
+
+        let json_patch = "application/json-patch+json";
+        let (code, obj) = send(&server, Method::PATCH, &uri, json_patch, json!([])).await;
+        assert_eq!(code, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(obj["reason"], "UnsupportedMediaType");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rejects_watches() {
+        let server = FakeApiServer::new();
+        let uri = format!("{}?watch=true", routes_path(Some("default")));
+        let (code, obj) = get(&server, &uri).await;
+        assert_eq!(code, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(obj["reason"], "MethodNotAllowed");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rejects_stale_resource_versions() {
+        let server = FakeApiServer::new();
+        let (code, obj) = send(
+            &server,
+            Method::POST,
+            &routes_path(Some("default")),
+            "application/json",
+            serde_json::to_value(route("default", "web", Value::Null)).unwrap(),
+        )
+        .await;
+        assert_eq!(code, StatusCode::CREATED);
+        assert_eq!(obj["metadata"]["resourceVersion"], "1");
+        let uri = format!("{}/web", routes_path(Some("default")));
+
+        let (code, obj) = send(
+            &server,
+            Method::POST,
+            &routes_path(Some("default")),
+            "application/json",
+            obj.clone(),
+        )
+        .await;
+        assert_eq!(code, StatusCode::CONFLICT);
+        assert_eq!(obj["reason"], "AlreadyExists");
+
+        let mut current = get(&server, &uri).await.1;
+        current["spec"]["hostnames"] = json!(["a.example.com"]);
+        let (code, updated) = send(
+            &server,
+            Method::PUT,
+            &uri,
+            "application/json",
+            current.clone(),
+        )
+        .await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(updated["metadata"]["resourceVersion"], "2");
+
+        // Replacing or patching with the previous version conflicts.
+        let (code, obj) = send(&server, Method::PUT, &uri, "application/json", current).await;
+        assert_eq!(code, StatusCode::CONFLICT);
+        assert_eq!(obj["reason"], "Conflict");
+        let patch = json!({ "metadata": { "resourceVersion": "1" }, "spec": {} });
+        let merge = "application/merge-patch+json";
+        let (code, _) = send(&server, Method::PATCH, &uri, merge, patch).await;
+        assert_eq!(code, StatusCode::CONFLICT);
+
+        // Writes without a resourceVersion are unconditional.
+        let patch = json!({ "spec": { "hostnames": ["b.example.com"] } });
+        let (code, obj) = send(&server, Method::PATCH, &uri, merge, patch).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(obj["metadata"]["resourceVersion"], "3");
+
+        let route = server.get::<HttpRoute>(Some("default"), "web").unwrap();
+        assert_eq!(
+            route.spec.hostnames,
+            Some(vec!["b.example.com".to_string()])
+        );
+        assert_eq!(route.metadata.generation, Some(3));
+    }
+}
@@ -0,0 +1,531 @@
+//! Compatibility checks against upstream Gateway API manifests.
+//!
+//! The upstream gateway-api repository ships example and conformance manifests
+//! (e.g. under `examples/` and `conformance/tests/`). Every Gateway API object
+//! in these manifests should survive decoding into this crate's types and
+//! encoding back to JSON without losing or altering any fields. These checks
+//! are exposed so that forks and vendored copies of this crate can run them
+//! against their own checkout of the upstream manifests:
+//!
+//! ```ignore
+//! let report = golden::check_dir("gateway-api/examples")?;
+//! report.assert_ok();
+//! ```
+//!
+//! Null fields are equivalent to absent fields, so they are ignored when
+//! comparing objects, as are differences in API versions. Documents that are not in the
+//! `gateway.networking.k8s.io` API group, or that are of kinds not known to
+//! this crate (e.g. experimental kinds when the `experimental` feature is
+//! disabled), are skipped.
+//!
+//! Several types flatten one struct into another (e.g. `HTTPRouteSpec` and
+//! `CommonRouteSpec`), so their fields share a single JSON object. A field
+//! that is declared by both structs would be encoded twice, and decoded into
+//! only one of them. The encoding of every checked object is therefore also
+//! checked for duplicate keys, as are JSON documents themselves, since
+//! decoding keeps only the last value of a duplicated key. Together with
+//! [`Report::unchecked_kinds`], this allows a set of golden objects, such as
+//! JSON served by an API server, to audit the serialization of every kind:
+//!
+//! ```ignore
+//! let report = golden::check_dir("fixtures/golden")?;
+//! report.assert_ok();
+//! assert!(report.unchecked_kinds().is_empty());
+//! ```
+
+use crate::{
+    consts,
+    manifest::{self, GatewayApiObject},
+};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The result of checking one or more manifests.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// The number of objects that were checked.
+    pub checked: usize,
+
+    /// The number of documents that were skipped.
+    pub skipped: usize,
+
+    /// The number of objects that were checked, by kind.
+    pub kinds: BTreeMap<&'static str, usize>,
+
+    /// The objects that could not be round-tripped.
+    pub failures: Vec<Failure>,
+}
+
+/// An object that could not be round-tripped.
+#[derive(Debug)]
+pub struct Failure {
+    /// Identifies the document, e.g. `examples/http-routing.yaml#2`.
+    pub source: String,
+
+    /// Identifies the object, e.g. `HTTPRoute default/foo`, if it could be
+    /// determined.
+    pub object: Option<String>,
+
+    /// What went wrong.
+    pub problem: Problem,
+}
+
+/// Describes why an object could not be round-tripped.
+#[derive(Debug)]
+pub enum Problem {
+    /// The document could not be parsed or decoded.
+    Decode(manifest::Error),
+
+    /// The JSON document could not be parsed.
+    Json(serde_json::Error),
+
+    /// The JSON document contains keys more than once in the same object, at
+    /// the given paths. Only the last of each key's values is decoded.
+    DuplicateKeys(Vec<String>),
+
+    /// Encoding the object produced keys more than once in the same object,
+    /// at the given paths, because structs that are flattened into one
+    /// another declare the same field.
+    FlattenCollision(Vec<String>),
+
+    /// The object was decoded, but encoding it did not reproduce the
+    /// original document.
+    Mismatch(Vec<Difference>),
+}
+
+/// A field that differs between a document and its round-tripped encoding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+    /// The JSON pointer to the field, e.g. `/spec/rules/0/matches`.
+    pub path: String,
+
+    /// The field's value in the original document, if it was present.
+    pub expected: Option<Value>,
+
+    /// The field's value in the round-tripped encoding, if it was present.
+    pub actual: Option<Value>,
+}
+
+/// Checks every `.yaml` and `.yml` manifest and every `.json` document under
+/// `dir`, recursively.
+///
+/// Files are checked in lexicographic order of their paths so that reports
+/// are stable.
+pub fn check_dir(dir: impl AsRef<Path>) -> io::Result<Report> {
+    let mut files = Vec::new();
+    find_manifests(dir.as_ref(), &mut files)?;
+    files.sort();
+
+    let mut report = Report::default();
+    for file in files {
+        let bytes = fs::read(&file)?;
+        let source = file.display().to_string();
+        if file.extension().and_then(|e| e.to_str()) == Some("json") {
+            report.extend(check_json(&source, &bytes));
+        } else {
+            report.extend(check_yaml(&source, &bytes));
+        }
+    }
+    Ok(report)
+}
+
+/// Checks the Gateway API object in a JSON document, e.g. as served by the
+/// API server. `source` identifies the document in the report.
+pub fn check_json(source: &str, bytes: &[u8]) -> Report {
+    let mut report = Report::default();
+    let fail = |problem| Failure {
+        source: source.to_string(),
+        object: None,
+        problem,
+    };
+    match duplicate_keys(bytes) {
+        Ok(paths) if paths.is_empty() => {}
+        Ok(paths) => {
+            report.failures.push(fail(Problem::DuplicateKeys(paths)));
+            return report;
+        }
+        Err(e) => {
+            report.failures.push(fail(Problem::Json(e)));
+            return report;
+        }
+    }
+    match serde_json::from_slice(bytes) {
+        Ok(value) => report.check_value(source.to_string(), value),
+        Err(e) => report.failures.push(fail(Problem::Json(e))),
+    }
+    report
+}
+
+/// Checks every Gateway API object in a (possibly multi-document) YAML
+/// manifest. `source` identifies the manifest in the report.
+pub fn check_yaml(source: &str, bytes: &[u8]) -> Report {
+    use serde::Deserialize;
+
+    let mut report = Report::default();
+    for (i, doc) in serde_yaml::Deserializer::from_slice(bytes).enumerate() {
+        let source = format!("{}#{}", source, i);
+        match Value::deserialize(doc) {
+            Ok(value) => report.check_value(source, value),
+            Err(e) => {
+                // The YAML stream cannot be resumed after a syntax error.
+                report.failures.push(Failure {
+                    source,
+                    object: None,
+                    problem: Problem::Decode(manifest::Error::Yaml(e)),
+                });
+                break;
+            }
+        }
+    }
+    report
+}
+
+/// Checks a single object's JSON representation.
+pub fn check_value(source: &str, value: Value) -> Report {
+    let mut report = Report::default();
+    report.check_value(source.to_string(), value);
+    report
+}
+
+/// Returns the paths, as JSON pointers, of keys that appear more than once in
+/// the same object of a JSON document, in the order in which they appear.
+pub fn duplicate_keys(json: &[u8]) -> serde_json::Result<Vec<String>> {
+    let mut paths = Vec::new();
+    let mut de = serde_json::Deserializer::from_slice(json);
+    Keys {
+        path: String::new(),
+        duplicates: &mut paths,
+    }
+    .deserialize(&mut de)?;
+    de.end()?;
+    Ok(paths)
+}
+
+fn find_manifests(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_manifests(&path, files)?;
+        } else if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("json" | "yaml" | "yml")
+        ) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Compares two values, ignoring null fields, and records the paths at which
+/// they differ.
+fn diff(
+    path: &mut String,
+    expected: Option<&Value>,
+    actual: Option<&Value>,
+    out: &mut Vec<Difference>,
+) {
+    let expected = expected.filter(|v| !v.is_null());
+    let actual = actual.filter(|v| !v.is_null());
+    match (expected, actual) {
+        (Some(Value::Object(e)), Some(Value::Object(a))) => {
+            let mut keys = e.keys().chain(a.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape(key));
+                diff(path, e.get(key), a.get(key), out);
+                path.truncate(len);
+            }
+        }
+        (Some(Value::Array(e)), Some(Value::Array(a))) if e.len() == a.len() => {
+            for (i, (e, a)) in e.iter().zip(a).enumerate() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&i.to_string());
+                diff(path, Some(e), Some(a), out);
+                path.truncate(len);
+            }
+        }
+        (e, a) if e == a => {}
+        (e, a) => out.push(Difference {
+            path: path.clone(),
+            expected: e.cloned(),
+            actual: a.cloned(),
+        }),
+    }
+}
+
+/// Escapes an object key for use in a JSON pointer, as described by RFC 6901.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Visits a JSON value, recording the paths of duplicated object keys.
+struct Keys<'a> {
+    path: String,
+    duplicates: &'a mut Vec<String>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for Keys<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<(), D::Error> {
+        de.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for Keys<'a> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut i = 0;
+        loop {
+            let item = Keys {
+                path: format!("{}/{}", self.path, i),
+                duplicates: &mut *self.duplicates,
+            };
+            if seq.next_element_seed(item)?.is_none() {
+                return Ok(());
+            }
+            i += 1;
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut seen = BTreeSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = format!("{}/{}", self.path, escape(&key));
+            if !seen.insert(key) {
+                self.duplicates.push(path.clone());
+            }
+            map.next_value_seed(Keys {
+                path,
+                duplicates: &mut *self.duplicates,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// === impl Report ===
+
+impl Report {
+    /// Returns true if no failures were encountered.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Panics with a description of all failures, if there are any.
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("{}", self);
+        }
+    }
+
+    /// Returns the kinds that this crate decodes of which no object was
+    /// checked.
+    pub fn unchecked_kinds(&self) -> Vec<&'static str> {
+        consts::KINDS
+            .iter()
+            .filter(|k| k.is_modeled() && !self.kinds.contains_key(k.kind))
+            .map(|k| k.kind)
+            .collect()
+    }
+
+    /// Adds the results of another report to this one.
+    pub fn extend(&mut self, other: Report) {
+        self.checked += other.checked;
+        self.skipped += other.skipped;
+        for (kind, n) in other.kinds {
+            *self.kinds.entry(kind).or_default() += n;
+        }
+        self.failures.extend(other.failures);
+    }
+
+    fn check_value(&mut self, source: String, value: Value) {
+        if value.is_null() {
+            return;
+        }
+        let api_version = value.get("apiVersion").and_then(Value::as_str);
+        if !api_version.map_or(false, manifest::is_gateway_api) {
+            self.skipped += 1;
+            return;
+        }
+
+        let object = describe(&value);
+        let decoded = match GatewayApiObject::from_value(value.clone()) {
+            Ok(decoded) => decoded,
+            Err(manifest::Error::UnknownKind { .. }) => {
+                self.skipped += 1;
+                return;
+            }
+            Err(e) => {
+                self.failures.push(Failure {
+                    source,
+                    object,
+                    problem: Problem::Decode(e),
+                });
+                return;
+            }
+        };
+        self.checked += 1;
+        *self.kinds.entry(decoded.kind()).or_default() += 1;
+
+        // Encode to bytes rather than to a `Value`, which would keep only one
+        // of a duplicated key's values.
+        let encoded = serde_json::to_vec(&decoded).and_then(|bytes| {
+            let duplicates = duplicate_keys(&bytes)?;
+            Ok((serde_json::from_slice::<Value>(&bytes)?, duplicates))
+        });
+        let mut encoded = match encoded {
+            Ok((_, duplicates)) if !duplicates.is_empty() => {
+                self.failures.push(Failure {
+                    source,
+                    object,
+                    problem: Problem::FlattenCollision(duplicates),
+                });
+                return;
+            }
+            Ok((encoded, _)) => encoded,
+            Err(source_err) => {
+                self.failures.push(Failure {
+                    source,
+                    object,
+                    problem: Problem::Decode(manifest::Error::Decode {
+                        kind: decoded.kind(),
+                        source: source_err,
+                    }),
+                });
+                return;
+            }
+        };
+
+        // Objects of all served versions are decoded into this crate's version
+        // of the kind, so only the group of the `apiVersion` is significant.
+        encoded["apiVersion"] = value["apiVersion"].clone();
+
+        let mut differences = Vec::new();
+        diff(
+            &mut String::new(),
+            Some(&value),
+            Some(&encoded),
+            &mut differences,
+        );
+        if !differences.is_empty() {
+            self.failures.push(Failure {
+                source,
+                object,
+                problem: Problem::Mismatch(differences),
+            });
+        }
+    }
+}
+
+/// Describes an object as `Kind namespace/name`.
+fn describe(value: &Value) -> Option<String> {
+    let kind = value.get("kind")?.as_str()?;
+    let name = value.pointer("/metadata/name")?.as_str()?;
+    match value.pointer("/metadata/namespace").and_then(Value::as_str) {
+        Some(ns) => Some(format!("{} {}/{}", kind, ns, name)),
+        None => Some(format!("{} {}", kind, name)),
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} objects checked, {} documents skipped, {} failures",
+            self.checked,
+            self.skipped,
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+// === impl Failure ===
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)?;
+        if let Some(object) = &self.object {
+            write!(f, " ({})", object)?;
+        }
+        match &self.problem {
+            Problem::Decode(e) => write!(f, ": {}", e),
+            Problem::Json(e) => write!(f, ": invalid JSON: {}", e),
+            Problem::DuplicateKeys(paths) => {
+                write!(f, ": duplicate keys: {}", paths.join(", "))
+            }
+            Problem::FlattenCollision(paths) => {
+                write!(f, ": encoded duplicate keys: {}", paths.join(", "))
+            }
+            Problem::Mismatch(differences) => {
+                write!(f, ": does not round-trip")?;
+                for d in differences {
+                    write!(f, "\n  {}", d)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// === impl Difference ===
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn show(v: &Option<Value>) -> String {
+            v.as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "<absent>".to_string())
+        }
+        write!(
+            f,
+            "{}: expected {}, got {}",
+            self.path,
+            show(&self.expected),
+            show(&self.actual)
+        )
+    }
+}
@@ -0,0 +1,339 @@
+//! Validation of Gateway listener TLS certificate references.
+//!
+//! [`validate_certificate_refs`] checks the references themselves. Once they
+//! are resolved to Secrets, [`check_certificate_hostnames`] checks that the
+//! certificates cover the listener's hostname, so that clients are not
+//! presented with certificates they reject.
+
+use crate::*;
+use k8s_openapi::api::core::v1::Secret;
+use std::fmt;
+
+mod x509;
+
+/// The maximum number of certificate references on a listener.
+pub const MAX_CERTIFICATE_REFS: usize = 64;
+
+/// The Secret type that holds a TLS certificate and its private key.
+pub const TLS_SECRET_TYPE: &str = "kubernetes.io/tls";
+
+/// The key of the PEM-encoded certificate chain in a TLS Secret's data.
+pub const TLS_CERT_KEY: &str = "tls.crt";
+
+/// A problem with a listener's certificate references.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CertificateRefError {
+    /// The listener terminates TLS but does not reference any certificates.
+    Missing,
+
+    /// The listener references more than [`MAX_CERTIFICATE_REFS`]
+    /// certificates.
+    TooMany(usize),
+
+    /// The reference is not to a core Secret.
+    InvalidKind {
+        name: String,
+        group: String,
+        kind: String,
+    },
+
+    /// The reference is to a Secret in another namespace and is not permitted
+    /// by a ReferenceGrant.
+    RefNotPermitted { namespace: String, name: String },
+}
+
+/// A problem with the certificates that a listener presents.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CertificateWarning {
+    /// The Secret does not hold a certificate that can be read.
+    InvalidCertificate {
+        namespace: String,
+        name: String,
+        error: InvalidCertificate,
+    },
+
+    /// None of the certificates' DNS names covers the listener's hostname,
+    /// so clients that connect with that hostname reject the certificate.
+    HostnameNotCovered {
+        hostname: String,
+        dns_names: Vec<String>,
+    },
+}
+
+/// The reason that a certificate could not be read.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct InvalidCertificate(&'static str);
+
+/// Validates the certificate references of a listener's TLS configuration.
+///
+/// References must be to core Secrets. References to Secrets outside of the
+/// Gateway's namespace must be permitted, as determined by `is_permitted`.
+/// When the `experimental` feature is enabled, `is_reference_permitted` may
+/// be used to check references against a set of ReferenceGrants:
+///
+/// ```ignore
+/// tls::validate_certificate_refs(ns, &tls, |r| is_reference_permitted(&grants, r))
+/// ```
+///
+/// The references of listeners in `Passthrough` mode are ignored.
+pub fn validate_certificate_refs<F>(
+    gateway_namespace: &str,
+    tls: &GatewayTlsConfig,
+    mut is_permitted: F,
+) -> Result<(), Vec<CertificateRefError>>
+where
+    F: FnMut(&CrossNamespaceReference<'_>) -> bool,
+{
+    if tls.mode.as_deref() == Some("Passthrough") {
+        return Ok(());
+    }
+
+    let refs = tls.certificate_refs.as_deref().unwrap_or_default();
+    if refs.is_empty() {
+        return Err(vec![CertificateRefError::Missing]);
+    }
+
+    let mut errors = Vec::new();
+    if refs.len() > MAX_CERTIFICATE_REFS {
+        errors.push(CertificateRefError::TooMany(refs.len()));
+    }
+    for cert in refs {
+        if !is_secret(cert) {
+            errors.push(CertificateRefError::InvalidKind {
+                name: cert.name.clone(),
+                group: cert.group.as_deref().unwrap_or("").to_string(),
+                kind: cert.kind.as_deref().unwrap_or("Secret").to_string(),
+            });
+            continue;
+        }
+
+        let namespace = cert.namespace.as_deref().unwrap_or(gateway_namespace);
+        if namespace != gateway_namespace {
+            let reference = CrossNamespaceReference {
+                from_group: consts::GROUP,
+                from_kind: "Gateway",
+                from_namespace: gateway_namespace,
+                to_group: "",
+                to_kind: "Secret",
+                to_namespace: namespace,
+                to_name: &cert.name,
+            };
+            if !is_permitted(&reference) {
+                errors.push(CertificateRefError::RefNotPermitted {
+                    namespace: namespace.to_string(),
+                    name: cert.name.clone(),
+                });
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Returns true if the reference is to a core Secret, which is the default
+/// when no group or kind is specified.
+pub fn is_secret(cert: &SecretObjectReference) -> bool {
+    cert.group.as_deref().unwrap_or("").is_empty()
+        && cert.kind.as_deref().unwrap_or("Secret") == "Secret"
+}
+
+/// Checks that the certificates of a listener cover its hostname.
+///
+/// `secrets` are the Secrets that the listener's certificate references
+/// resolve to; references that do not resolve are reported by the
+/// implementation's reference resolution. A hostname is covered if the first
+/// certificate of any Secret's chain covers it (see [`dns_name_covers`]), as
+/// implementations select among certificates by SNI.
+///
+/// Only listeners that terminate TLS with a hostname are checked: HTTPS
+/// listeners, and TLS listeners in `Terminate` mode. Listeners without a
+/// hostname accept every hostname, so no certificate can cover them.
+///
+/// Invalid certificates should be reported in the listener's "ResolvedRefs"
+/// condition with the `InvalidCertificateRef` reason. Uncovered hostnames
+/// do not invalidate the listener, but may be surfaced in its conditions'
+/// messages.
+pub fn check_certificate_hostnames(
+    listener: &Listener,
+    secrets: &[Secret],
+) -> Vec<CertificateWarning> {
+    let terminates = match listener.protocol.as_str() {
+        "HTTPS" => true,
+        "TLS" => {
+            let mode = listener.tls.as_ref().and_then(|tls| tls.mode.as_deref());
+            mode.unwrap_or("Terminate") == "Terminate"
+        }
+        _ => false,
+    };
+    let hostname = match listener.hostname.as_deref() {
+        Some(hostname) if terminates => hostname,
+        _ => return Vec::new(),
+    };
+
+    let mut warnings = Vec::new();
+    let mut dns_names = Vec::new();
+    let mut covered = false;
+    for secret in secrets {
+        let chain = secret
+            .data
+            .as_ref()
+            .and_then(|data| data.get(TLS_CERT_KEY))
+            .map(|chain| chain.0.as_slice())
+            .unwrap_or_default();
+        match certificate_dns_names(chain) {
+            Ok(names) => {
+                covered |= names.iter().any(|n| dns_name_covers(n, hostname));
+                dns_names.extend(names);
+            }
+            Err(error) => warnings.push(CertificateWarning::InvalidCertificate {
+                namespace: secret.metadata.namespace.clone().unwrap_or_default(),
+                name: secret.metadata.name.clone().unwrap_or_default(),
+                error,
+            }),
+        }
+    }
+    if covered {
+        return warnings;
+    }
+    dns_names.sort();
+    dns_names.dedup();
+    warnings.push(CertificateWarning::HostnameNotCovered {
+        hostname: hostname.to_string(),
+        dns_names,
+    });
+    warnings
+}
+
+/// Returns the DNS names in the subject alternative names of the first
+/// certificate of a PEM-encoded chain.
+///
+/// Clients do not fall back to a certificate's subject common name when it
+/// has subject alternative names, and most no longer do at all, so the
+/// common name is ignored.
+pub fn certificate_dns_names(pem: &[u8]) -> Result<Vec<String>, InvalidCertificate> {
+    x509::dns_names(pem)
+}
+
+/// Returns true if a certificate's DNS name covers a listener hostname.
+///
+/// Names are compared case-insensitively. A wildcard DNS name, such as
+/// `*.example.com`, covers hostnames with exactly one label in place of the
+/// `*`, as clients match them (RFC 6125). A wildcard hostname is only covered
+/// by the same wildcard DNS name.
+pub fn dns_name_covers(dns_name: &str, hostname: &str) -> bool {
+    if dns_name.eq_ignore_ascii_case(hostname) {
+        return true;
+    }
+    if hostname::is_wildcard(hostname) {
+        return false;
+    }
+    match (dns_name.strip_prefix("*."), hostname.split_once('.')) {
+        (Some(suffix), Some((label, rest))) => {
+            !label.is_empty() && rest.eq_ignore_ascii_case(suffix)
+        }
+        _ => false,
+    }
+}
+
+// === impl CertificateRefError ===
+
+impl CertificateRefError {
+    /// Returns the reason to set on the listener's "ResolvedRefs" condition.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::RefNotPermitted { .. } => "RefNotPermitted",
+            Self::Missing | Self::TooMany(_) | Self::InvalidKind { .. } => "InvalidCertificateRef",
+        }
+    }
+}
+
+impl fmt::Display for CertificateRefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "TLS termination requires a certificate reference"),
+            Self::TooMany(n) => write!(
+                f,
+                "{} certificate references exceeds the limit of {}",
+                n, MAX_CERTIFICATE_REFS
+            ),
+            Self::InvalidKind { name, group, kind } if group.is_empty() => {
+                write!(f, "certificate {} has unsupported kind {}", name, kind)
+            }
+            Self::InvalidKind { name, group, kind } => write!(
+                f,
+                "certificate {} has unsupported kind {}.{}",
+                name, kind, group
+            ),
+            Self::RefNotPermitted { namespace, name } => write!(
+                f,
+                "reference to Secret {}/{} is not permitted by any ReferenceGrant",
+                namespace, name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CertificateRefError {}
+
+impl From<CertificateRefError> for ReferenceError {
+    fn from(e: CertificateRefError) -> Self {
+        match e {
+            CertificateRefError::RefNotPermitted { namespace, name } => Self::RefNotPermitted {
+                kind: "Secret".to_string(),
+                namespace,
+                name,
+            },
+            e => Self::InvalidCertificateRef(e.to_string()),
+        }
+    }
+}
+
+// === impl CertificateWarning ===
+
+impl fmt::Display for CertificateWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCertificate {
+                namespace,
+                name,
+                error,
+            } => write!(
+                f,
+                "Secret {}/{} does not hold a valid certificate: {}",
+                namespace, name, error
+            ),
+            Self::HostnameNotCovered {
+                hostname,
+                dns_names,
+            } if dns_names.is_empty() => write!(
+                f,
+                "hostname {} is not covered by any certificate's DNS names",
+                hostname
+            ),
+            Self::HostnameNotCovered {
+                hostname,
+                dns_names,
+            } => write!(
+                f,
+                "hostname {} is not covered by the certificates' DNS names: {}",
+                hostname,
+                dns_names.join(", ")
+            ),
+        }
+    }
+}
+
+// === impl InvalidCertificate ===
+
+impl fmt::Display for InvalidCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for InvalidCertificate {}
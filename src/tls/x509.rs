@@ -0,0 +1,143 @@
+//! Just enough PEM and DER decoding to read the DNS names of a certificate.
+
+use super::InvalidCertificate;
+
+const SEQUENCE: u8 = 0x30;
+const OID: u8 = 0x06;
+const BOOLEAN: u8 = 0x01;
+const OCTET_STRING: u8 = 0x04;
+
+/// The context-specific, constructed tag of a TBSCertificate's extensions.
+const EXTENSIONS: u8 = 0xa3;
+
+/// The context-specific, primitive tag of a GeneralName's `dNSName`.
+const DNS_NAME: u8 = 0x82;
+
+/// The DER encoding of the subjectAltName extension's OID, 2.5.29.17.
+const SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+
+const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const END: &str = "-----END CERTIFICATE-----";
+
+/// Returns the DNS names of the first certificate in a PEM-encoded chain.
+pub(super) fn dns_names(pem: &[u8]) -> Result<Vec<String>, InvalidCertificate> {
+    let pem = std::str::from_utf8(pem).map_err(|_| InvalidCertificate("not PEM-encoded"))?;
+    let start = pem
+        .find(BEGIN)
+        .ok_or(InvalidCertificate("no PEM certificate"))?
+        + BEGIN.len();
+    let end = pem[start..]
+        .find(END)
+        .ok_or(InvalidCertificate("unterminated PEM certificate"))?;
+    let der = base64(&pem[start..start + end])?;
+
+    let mut cert = Der(&der).expect(SEQUENCE)?;
+    let mut tbs = cert.expect(SEQUENCE)?;
+    let mut names = Vec::new();
+    while !tbs.0.is_empty() {
+        let (tag, mut value) = tbs.next()?;
+        if tag != EXTENSIONS {
+            continue;
+        }
+        let mut extensions = value.expect(SEQUENCE)?;
+        while !extensions.0.is_empty() {
+            let mut extension = extensions.expect(SEQUENCE)?;
+            let oid = extension.expect(OID)?;
+            if oid.0 != SUBJECT_ALT_NAME {
+                continue;
+            }
+            let (mut tag, mut value) = extension.next()?;
+            if tag == BOOLEAN {
+                let (t, v) = extension.next()?;
+                tag = t;
+                value = v;
+            }
+            if tag != OCTET_STRING {
+                return Err(InvalidCertificate("malformed subjectAltName extension"));
+            }
+            let mut general_names = value.expect(SEQUENCE)?;
+            while !general_names.0.is_empty() {
+                let (tag, value) = general_names.next()?;
+                if tag == DNS_NAME {
+                    let name = std::str::from_utf8(value.0)
+                        .map_err(|_| InvalidCertificate("malformed DNS name"))?;
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// A sequence of DER-encoded values.
+struct Der<'a>(&'a [u8]);
+
+impl<'a> Der<'a> {
+    /// Reads the next value's tag and contents.
+    fn next(&mut self) -> Result<(u8, Der<'a>), InvalidCertificate> {
+        const TRUNCATED: InvalidCertificate = InvalidCertificate("truncated DER value");
+
+        let (&tag, rest) = self.0.split_first().ok_or(TRUNCATED)?;
+        let (&first, mut rest) = rest.split_first().ok_or(TRUNCATED)?;
+        let len = if first < 0x80 {
+            usize::from(first)
+        } else {
+            // Long-form lengths are followed by as many length bytes as the
+            // low bits indicate. Certificates are far smaller than 4GiB.
+            let n = usize::from(first & 0x7f);
+            if n == 0 || n > 4 || rest.len() < n {
+                return Err(InvalidCertificate("invalid DER length"));
+            }
+            let (bytes, r) = rest.split_at(n);
+            rest = r;
+            bytes.iter().fold(0, |len, b| (len << 8) | usize::from(*b))
+        };
+        if rest.len() < len {
+            return Err(TRUNCATED);
+        }
+        let (value, rest) = rest.split_at(len);
+        self.0 = rest;
+        Ok((tag, Der(value)))
+    }
+
+    /// Reads the contents of the next value, which must have the given tag.
+    fn expect(&mut self, tag: u8) -> Result<Der<'a>, InvalidCertificate> {
+        match self.next()? {
+            (t, value) if t == tag => Ok(value),
+            _ => Err(InvalidCertificate("unexpected DER value")),
+        }
+    }
+}
+
+/// Decodes standard base64, ignoring whitespace.
+fn base64(s: &str) -> Result<Vec<u8>, InvalidCertificate> {
+    fn sextet(c: u8) -> Option<u32> {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        Some(u32::from(v))
+    }
+
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in s.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'=' {
+            break;
+        }
+        let v = sextet(c).ok_or(InvalidCertificate("invalid base64"))?;
+        acc = (acc << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
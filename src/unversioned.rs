@@ -0,0 +1,330 @@
+//! Decoding of objects in any served API version into a single model.
+//!
+//! While the Gateway API CRDs are upgraded, objects may be stored or applied
+//! in any of the versions the CRDs serve, so a controller that watches one
+//! version may still be handed objects written in another, e.g. by an
+//! admission webhook or when reading manifests. The types in this crate serve
+//! as the version-independent model: every version served by the bundle this
+//! crate models (`v1alpha2` and `v1beta1`) shares their schema.
+//!
+//! Decoding is lossless: an object that sets a field the model cannot
+//! represent is rejected rather than silently truncated, and [`Unversioned`]
+//! records the version the object was written in so that it may be written
+//! back unchanged.
+//!
+//! ```ignore
+//! let route = unversioned::decode::<HttpRoute>(value)?;
+//! let patched = route.to_value()?; // in the version it was read in
+//! ```
+
+use crate::{
+    consts::{self, KindInfo},
+    manifest::{self, GatewayApiObject},
+};
+use kube::Resource;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+
+/// An object decoded into this crate's model, along with the API version it
+/// was served in.
+#[derive(Clone, Debug)]
+pub struct Unversioned<T> {
+    /// The API version the object was written in, e.g. `v1alpha2`.
+    pub served_version: String,
+
+    /// The decoded object.
+    pub object: T,
+
+    group: &'static str,
+}
+
+/// Errors encountered while decoding an object from a served version.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The object could not be decoded.
+    Decode(manifest::Error),
+
+    /// The object sets fields that are not represented by this crate's
+    /// model, identified by their JSON paths.
+    Lossy {
+        kind: String,
+        version: String,
+        fields: Vec<String>,
+    },
+}
+
+/// Returns the API versions in which a kind is served and decoded into this
+/// crate's types, or an empty slice if the kind is unknown.
+pub fn served_versions(kind: &str) -> &'static [&'static str] {
+    consts::find(kind)
+        .filter(|k| k.is_modeled())
+        .map_or(&[], |k| k.versions)
+}
+
+/// Decodes an object of kind `T` from any version in which the kind is
+/// served.
+pub fn decode<T>(value: serde_json::Value) -> Result<Unversioned<T>, Error>
+where
+    T: Resource<DynamicType = ()> + DeserializeOwned + Serialize,
+{
+    let (info, version) = type_meta(&value)?;
+    if info.kind != T::kind(&()) {
+        return Err(Error::Decode(manifest::Error::UnknownKind {
+            api_version: info.api_version(&version),
+            kind: info.kind.to_string(),
+        }));
+    }
+
+    let object = serde_json::from_value::<T>(value.clone()).map_err(|source| {
+        Error::Decode(manifest::Error::Decode {
+            kind: info.kind,
+            source,
+        })
+    })?;
+    check_lossless(info, &version, &value, &object)?;
+    Ok(Unversioned {
+        served_version: version,
+        object,
+        group: info.group,
+    })
+}
+
+/// Decodes an object of any kind known to this crate from any version in
+/// which the kind is served.
+pub fn decode_any(value: serde_json::Value) -> Result<Unversioned<GatewayApiObject>, Error> {
+    let (info, version) = type_meta(&value)?;
+    let object = GatewayApiObject::from_value(value.clone()).map_err(Error::Decode)?;
+    check_lossless(info, &version, &value, &object)?;
+    Ok(Unversioned {
+        served_version: version,
+        object,
+        group: info.group,
+    })
+}
+
+/// Returns the kind and the served version of a Gateway API object, failing
+/// if this crate does not decode the kind in that version.
+fn type_meta(value: &serde_json::Value) -> Result<(&'static KindInfo, String), Error> {
+    let api_version = value.get("apiVersion").and_then(|v| v.as_str());
+    let kind = value.get("kind").and_then(|v| v.as_str());
+    let (api_version, kind) = match (api_version, kind) {
+        (Some(api_version), Some(kind)) => (api_version, kind),
+        _ => return Err(Error::Decode(manifest::Error::MissingTypeMeta)),
+    };
+    match manifest::known_kind(api_version, kind) {
+        Some(info) => {
+            let version = api_version.split_once('/').map_or("", |(_, v)| v);
+            Ok((info, version.to_string()))
+        }
+        None => Err(Error::Decode(manifest::Error::UnknownKind {
+            api_version: api_version.to_string(),
+            kind: kind.to_string(),
+        })),
+    }
+}
+
+/// Returns the static name of a known kind, for decode errors.
+pub(crate) fn kind_name(kind: &str) -> &'static str {
+    consts::find(kind).map_or("object", |k| k.kind)
+}
+
+/// Fails if re-encoding `object` does not preserve every field set in
+/// `value`.
+fn check_lossless<T: Serialize>(
+    info: &KindInfo,
+    version: &str,
+    value: &serde_json::Value,
+    object: &T,
+) -> Result<(), Error> {
+    let encoded = serde_json::to_value(object).map_err(|source| {
+        Error::Decode(manifest::Error::Decode {
+            kind: info.kind,
+            source,
+        })
+    })?;
+    let mut fields = Vec::new();
+    dropped_fields(value, &encoded, &mut String::new(), &mut fields);
+    if fields.is_empty() {
+        return Ok(());
+    }
+    Err(Error::Lossy {
+        kind: info.kind.to_string(),
+        version: version.to_string(),
+        fields,
+    })
+}
+
+/// Collects the paths of the fields set in `input` that are missing from
+/// `output`. Null fields are equivalent to absent ones.
+fn dropped_fields(
+    input: &serde_json::Value,
+    output: &serde_json::Value,
+    path: &mut String,
+    fields: &mut Vec<String>,
+) {
+    use serde_json::Value;
+
+    match (input, output) {
+        (Value::Object(input), Value::Object(output)) => {
+            for (key, value) in input {
+                if value.is_null() {
+                    continue;
+                }
+                let len = path.len();
+                path.push('.');
+                path.push_str(key);
+                match output.get(key) {
+                    Some(out) if !out.is_null() => dropped_fields(value, out, path, fields),
+                    _ => fields.push(path.clone()),
+                }
+                path.truncate(len);
+            }
+        }
+        (Value::Array(input), Value::Array(output)) => {
+            for (i, (value, out)) in input.iter().zip(output).enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{}]", i));
+                dropped_fields(value, out, path, fields);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+// === impl Unversioned ===
+
+impl<T> Unversioned<T> {
+    /// Returns the decoded object, discarding the version it was served in.
+    pub fn into_inner(self) -> T {
+        self.object
+    }
+
+    /// Returns the `apiVersion` the object was written in.
+    pub fn api_version(&self) -> String {
+        format!("{}/{}", self.group, self.served_version)
+    }
+}
+
+impl<T: Serialize> Unversioned<T> {
+    /// Encodes the object in the version it was served in.
+    pub fn to_value(&self) -> serde_json::Result<serde_json::Value> {
+        let mut value = serde_json::to_value(&self.object)?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("apiVersion".to_string(), self.api_version().into());
+        }
+        Ok(value)
+    }
+}
+
+// === impl Error ===
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(e) => fmt::Display::fmt(e, f),
+            Self::Lossy {
+                kind,
+                version,
+                fields,
+            } => write!(
+                f,
+                "{} {} sets fields that cannot be represented: {}",
+                kind,
+                version,
+                fields.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(e) => Some(e),
+            Self::Lossy { .. } => None,
+        }
+    }
+}
+
+impl From<manifest::Error> for Error {
+    fn from(e: manifest::Error) -> Self {
+        Self::Decode(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn serves_the_versions_of_modeled_kinds() {
+        for info in consts::KINDS {
+            let expected: &[&str] = if info.is_modeled() {
+                info.versions
+            } else {
+                &[]
+            };
+            assert_eq!(served_versions(info.kind), expected, "{}", info.kind);
+            assert_eq!(kind_name(info.kind), info.kind);
+        }
+        assert_eq!(served_versions("Service"), [] as [&str; 0]);
+        assert_eq!(kind_name("Service"), "object");
+    }
+
+    #[test]
+    fn decodes_served_versions() {
+        let route = |api_version: &str| {
+            json!({
+                "apiVersion": api_version,
+                "kind": "HTTPRoute",
+                "metadata": { "name": "app", "namespace": "apps" },
+                "spec": {},
+            })
+        };
+
+        let decoded = decode::<HttpRoute>(route("gateway.networking.k8s.io/v1alpha2")).unwrap();
+        assert_eq!(decoded.api_version(), "gateway.networking.k8s.io/v1alpha2");
+
+        for api_version in [
+            "gateway.networking.k8s.io/v1alpha1",
+            "gateway.networking.x-k8s.io/v1beta1",
+        ] {
+            match decode::<HttpRoute>(route(api_version)) {
+                Err(Error::Decode(manifest::Error::UnknownKind { api_version: v, .. })) => {
+                    assert_eq!(v, api_version)
+                }
+                res => panic!("unexpected result: {:?}", res),
+            }
+        }
+
+        match decode::<Gateway>(route("gateway.networking.k8s.io/v1beta1")) {
+            Err(Error::Decode(manifest::Error::UnknownKind { kind, .. })) => {
+                assert_eq!(kind, "HTTPRoute")
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn keeps_the_group_of_experimental_kinds() {
+        let value = json!({
+            "apiVersion": "gateway.networking.x-k8s.io/v1alpha1",
+            "kind": "XBackendTrafficPolicy",
+            "metadata": { "name": "retries", "namespace": "apps" },
+            "spec": {
+                "targetRefs": [{ "group": "", "kind": "Service", "name": "app" }],
+            },
+        });
+        let decoded = decode_any(value.clone()).unwrap();
+        assert_eq!(decoded.served_version, "v1alpha1");
+        assert_eq!(
+            decoded.to_value().unwrap()["apiVersion"],
+            value["apiVersion"]
+        );
+    }
+}
@@ -0,0 +1,1392 @@
+//! Validation of Gateway API objects.
+//!
+//! Validation helpers report problems as [`ValidationError`]s, each of which
+//! identifies the offending field with a [`FieldPath`]. Errors can be rendered
+//! as JSON pointers (e.g. `/spec/listeners/0/name`) for tooling, or converted
+//! into the `causes` of a Kubernetes `Status` (e.g. `spec.listeners[0].name`)
+//! for admission responses.
+//!
+//! With the `regex-validate` feature, `RegularExpression` match values are
+//! compiled with the [`regex`](https://docs.rs/regex) crate and rejected if
+//! they are not valid in its RE2-like dialect.
+
+use crate::{feature_gate::FeatureGates, *};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::{collections::HashSet, fmt};
+
+/// The maximum number of listeners on a Gateway.
+pub const MAX_LISTENERS: usize = 64;
+
+/// The maximum number of hostnames on a route.
+pub const MAX_HOSTNAMES: usize = 16;
+
+/// The maximum number of parent references on a route.
+pub const MAX_PARENT_REFS: usize = 32;
+
+/// The maximum number of rules on a route.
+pub const MAX_RULES: usize = 16;
+
+/// The maximum number of matches on an HTTPRoute rule.
+pub const MAX_MATCHES: usize = 64;
+
+/// The maximum number of matches on a GRPCRoute rule.
+pub const MAX_GRPC_MATCHES: usize = 8;
+
+/// The maximum number of header or query parameter conditions in a match.
+pub const MAX_MATCH_CONDITIONS: usize = 16;
+
+/// The maximum number of filters on a route rule or backend.
+pub const MAX_FILTERS: usize = 16;
+
+/// The maximum number of backends on a route rule.
+pub const MAX_BACKEND_REFS: usize = 16;
+
+const PATH_MATCH_TYPES: &[&str] = &["Exact", "PathPrefix", "RegularExpression"];
+const VALUE_MATCH_TYPES: &[&str] = &["Exact", "RegularExpression"];
+const PATH_MODIFIER_TYPES: &[&str] = &["ReplaceFullPath", "ReplacePrefixMatch"];
+#[cfg(feature = "unknown-filters")]
+const FILTER_TYPES: &[&str] = &[
+    "RequestHeaderModifier",
+    "RequestMirror",
+    "RequestRedirect",
+    "URLRewrite",
+    "ExtensionRef",
+];
+
+/// Identifies a field within an object.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FieldPath(Vec<PathSegment>);
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum PathSegment {
+    Field(String),
+    Index(usize),
+    Key(String),
+}
+
+/// The kind of problem with a field.
+///
+/// These correspond to the Kubernetes `CauseType`s for field errors.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorReason {
+    /// A required field is missing.
+    Required,
+
+    /// A field's value is invalid.
+    Invalid,
+
+    /// A field refers to something that does not exist.
+    NotFound,
+
+    /// A field's value duplicates another value that must be unique.
+    Duplicate,
+
+    /// A field's value is valid but not supported.
+    NotSupported,
+
+    /// A field may not be set.
+    Forbidden,
+
+    /// A field's value is too long.
+    TooLong,
+
+    /// A list has too many items.
+    TooMany,
+}
+
+/// A problem with a field of an object.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationError {
+    /// The offending field.
+    pub path: FieldPath,
+
+    /// The kind of problem.
+    pub reason: ErrorReason,
+
+    /// A human-readable description of the problem.
+    pub message: String,
+}
+
+/// Types that can be validated beyond the constraints enforced by their
+/// types.
+pub trait Validate {
+    /// Appends any problems with `self`, located relative to `path`, to
+    /// `errors`.
+    fn validate_at(&self, path: &FieldPath, errors: &mut Vec<ValidationError>);
+
+    /// Appends an error for each field of `self`, located relative to `path`,
+    /// that requires a feature that `gates` does not enable. Types without
+    /// gated fields report none.
+    fn validate_features_at(
+        &self,
+        gates: &FeatureGates,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let _ = (gates, path, errors);
+    }
+
+    /// Validates `self` as a top-level object.
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.validate_at(&FieldPath::root(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validates `self` as a top-level object, also rejecting fields that
+    /// require features that `gates` does not enable.
+    fn validate_with_feature_gates(
+        &self,
+        gates: &FeatureGates,
+    ) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.validate_at(&FieldPath::root(), &mut errors);
+        self.validate_features_at(gates, &FieldPath::root(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Builds an `Invalid` (422) Kubernetes `Status` describing the errors.
+pub fn to_status(
+    group: &str,
+    kind: &str,
+    name: Option<&str>,
+    errors: &[ValidationError],
+) -> metav1::Status {
+    let object = match name {
+        Some(name) => format!("{} {:?}", kind, name),
+        None => kind.to_string(),
+    };
+    let message = errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    metav1::Status {
+        code: Some(422),
+        reason: Some("Invalid".to_string()),
+        status: Some("Failure".to_string()),
+        message: Some(format!("{} is invalid: {}", object, message)),
+        details: Some(metav1::StatusDetails {
+            group: Some(group.to_string()),
+            kind: Some(kind.to_string()),
+            name: name.map(Into::into),
+            causes: Some(
+                errors
+                    .iter()
+                    .map(ValidationError::to_status_cause)
+                    .collect(),
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+// === impl FieldPath ===
+
+impl FieldPath {
+    /// Returns the path of a top-level object.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Returns the path of a field within this path.
+    pub fn field(&self, name: impl Into<String>) -> Self {
+        self.push(PathSegment::Field(name.into()))
+    }
+
+    /// Returns the path of an item within the list at this path.
+    pub fn index(&self, index: usize) -> Self {
+        self.push(PathSegment::Index(index))
+    }
+
+    /// Returns the path of an entry within the map at this path.
+    pub fn key(&self, key: impl Into<String>) -> Self {
+        self.push(PathSegment::Key(key.into()))
+    }
+
+    /// Returns true if this is the path of a top-level object.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Renders the path as a JSON pointer, as described by RFC 6901, e.g.
+    /// `/spec/listeners/0/name`.
+    pub fn to_json_pointer(&self) -> String {
+        let mut ptr = String::new();
+        for segment in &self.0 {
+            ptr.push('/');
+            match segment {
+                PathSegment::Field(s) | PathSegment::Key(s) => {
+                    ptr.push_str(&s.replace('~', "~0").replace('/', "~1"))
+                }
+                PathSegment::Index(i) => ptr.push_str(&i.to_string()),
+            }
+        }
+        ptr
+    }
+
+    fn push(&self, segment: PathSegment) -> Self {
+        let mut segments = self.0.clone();
+        segments.push(segment);
+        Self(segments)
+    }
+}
+
+/// Formats the path as Kubernetes does in field errors, e.g.
+/// `spec.listeners[0].name` or `spec.tls.options[example.com/foo]`.
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Field(s) if i == 0 => write!(f, "{}", s)?,
+                PathSegment::Field(s) => write!(f, ".{}", s)?,
+                PathSegment::Index(i) => write!(f, "[{}]", i)?,
+                PathSegment::Key(k) => write!(f, "[{}]", k)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// === impl ErrorReason ===
+
+impl ErrorReason {
+    /// Returns the Kubernetes `CauseType` for this reason.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Required => "FieldValueRequired",
+            Self::Invalid => "FieldValueInvalid",
+            Self::NotFound => "FieldValueNotFound",
+            Self::Duplicate => "FieldValueDuplicate",
+            Self::NotSupported => "FieldValueNotSupported",
+            Self::Forbidden => "FieldValueForbidden",
+            Self::TooLong => "FieldValueTooLong",
+            Self::TooMany => "FieldValueTooMany",
+        }
+    }
+}
+
+impl fmt::Display for ErrorReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+// === impl ValidationError ===
+
+impl ValidationError {
+    /// Reports a problem with the field at `path`.
+    pub fn new(path: FieldPath, reason: ErrorReason, message: impl Into<String>) -> Self {
+        Self {
+            path,
+            reason,
+            message: message.into(),
+        }
+    }
+
+    /// Reports that a required field is missing.
+    pub fn required(path: FieldPath) -> Self {
+        Self::new(path, ErrorReason::Required, "Required value")
+    }
+
+    /// Reports that a field's value is invalid.
+    pub fn invalid(path: FieldPath, message: impl Into<String>) -> Self {
+        Self::new(path, ErrorReason::Invalid, message)
+    }
+
+    /// Reports that a field's value duplicates another.
+    pub fn duplicate(path: FieldPath, value: impl fmt::Debug) -> Self {
+        Self::new(
+            path,
+            ErrorReason::Duplicate,
+            format!("Duplicate value: {:?}", value),
+        )
+    }
+
+    /// Reports that a field's value is not one of the supported values.
+    pub fn not_supported(path: FieldPath, value: impl fmt::Debug, supported: &[&str]) -> Self {
+        Self::new(
+            path,
+            ErrorReason::NotSupported,
+            format!(
+                "Unsupported value: {:?}: supported values: {:?}",
+                value, supported
+            ),
+        )
+    }
+
+    /// Reports that a field may not be set.
+    pub fn forbidden(path: FieldPath, message: impl Into<String>) -> Self {
+        Self::new(path, ErrorReason::Forbidden, message)
+    }
+
+    /// Reports that a list has more than `max` items.
+    pub fn too_many(path: FieldPath, count: usize, max: usize) -> Self {
+        Self::new(
+            path,
+            ErrorReason::TooMany,
+            format!("Too many: {}: must have at most {} items", count, max),
+        )
+    }
+
+    /// Reports that a value does not satisfy the constraints of its type.
+    pub fn from_invalid_value(path: FieldPath, e: &InvalidValue) -> Self {
+        Self::invalid(
+            path,
+            format!("Invalid value: {:?}: {}", e.value(), e.reason()),
+        )
+    }
+
+    /// Converts the error into a cause of a Kubernetes `Status`.
+    pub fn to_status_cause(&self) -> metav1::StatusCause {
+        metav1::StatusCause {
+            field: Some(self.path.to_string()),
+            message: Some(self.message.clone()),
+            reason: Some(self.reason.as_str().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_root() {
+            self.message.fmt(f)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Serializes the error as a Kubernetes `StatusCause`.
+impl serde::Serialize for ValidationError {
+    fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.to_status_cause().serialize(ser)
+    }
+}
+
+// === impl Validate ===
+
+impl Validate for GatewayClass {
+    fn validate_at(&self, _: &FieldPath, _: &mut Vec<ValidationError>) {}
+}
+
+impl Validate for Gateway {
+    fn validate_at(&self, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+        let listeners = path.field("spec").field("listeners");
+        if self.spec.listeners.is_empty() {
+            errors.push(ValidationError::required(listeners.clone()));
+        }
+        validate_len(self.spec.listeners.len(), MAX_LISTENERS, &listeners, errors);
+        validate_len(
+            self.spec.addresses.as_ref().map_or(0, Vec::len),
+            addresses::MAX_ADDRESSES,
+            &path.field("spec").field("addresses"),
+            errors,
+        );
+
+        let mut names = HashSet::new();
+        for (i, listener) in self.spec.listeners.iter().enumerate() {
+            let path = listeners.index(i);
+            if !names.insert(&listener.name) {
+                errors.push(ValidationError::duplicate(
+                    path.field("name"),
+                    &listener.name,
+                ));
+            }
+            if let Some(tls) = &listener.tls {
+                validate_tls(tls, &path.field("tls"), errors);
+            }
+        }
+    }
+}
+
+fn validate_tls(tls: &GatewayTlsConfig, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+    const MODES: &[&str] = &["Terminate", "Passthrough"];
+
+    let mode = tls.mode.as_deref().unwrap_or("Terminate");
+    if !MODES.contains(&mode) {
+        errors.push(ValidationError::not_supported(
+            path.field("mode"),
+            mode,
+            MODES,
+        ));
+        return;
+    }
+    if mode == "Passthrough" {
+        return;
+    }
+
+    let refs_path = path.field("certificateRefs");
+    let refs = tls.certificate_refs.as_deref().unwrap_or_default();
+    if refs.is_empty() {
+        errors.push(ValidationError::required(refs_path));
+        return;
+    }
+    if refs.len() > tls::MAX_CERTIFICATE_REFS {
+        errors.push(ValidationError::too_many(
+            refs_path.clone(),
+            refs.len(),
+            tls::MAX_CERTIFICATE_REFS,
+        ));
+    }
+    for (i, cert) in refs.iter().enumerate() {
+        if !tls::is_secret(cert) {
+            errors.push(ValidationError::not_supported(
+                refs_path.index(i).field("kind"),
+                cert.kind.as_deref().unwrap_or("Secret"),
+                &["Secret"],
+            ));
+        }
+    }
+}
+
+/// Reports a list that is longer than `max`.
+fn validate_len(len: usize, max: usize, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+    if len > max {
+        errors.push(ValidationError::too_many(path.clone(), len, max));
+    }
+}
+
+/// Validates the fields common to all routes.
+fn validate_common_route(
+    spec: &CommonRouteSpec,
+    path: &FieldPath,
+    errors: &mut Vec<ValidationError>,
+) {
+    validate_len(
+        spec.parent_refs.as_ref().map_or(0, Vec::len),
+        MAX_PARENT_REFS,
+        &path.field("parentRefs"),
+        errors,
+    );
+}
+
+/// Validates the hostnames of a route. Hostnames are compared
+/// case-insensitively, so `Example.com` duplicates `example.com`.
+fn validate_hostnames(
+    hostnames: Option<&[Hostname]>,
+    path: &FieldPath,
+    errors: &mut Vec<ValidationError>,
+) {
+    let hostnames = hostnames.unwrap_or_default();
+    validate_len(hostnames.len(), MAX_HOSTNAMES, path, errors);
+
+    let mut seen = HashSet::new();
+    for (i, hostname) in hostnames.iter().enumerate() {
+        if !seen.insert(hostname.to_ascii_lowercase()) {
+            errors.push(ValidationError::duplicate(path.index(i), hostname));
+        }
+    }
+}
+
+impl Validate for HttpRoute {
+    fn validate_at(&self, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+        let spec_path = path.field("spec");
+        validate_common_route(&self.spec.inner, &spec_path, errors);
+        validate_hostnames(
+            self.spec.hostnames.as_deref(),
+            &spec_path.field("hostnames"),
+            errors,
+        );
+
+        let rules = self.spec.rules.as_deref().unwrap_or_default();
+        let rules_path = spec_path.field("rules");
+        validate_len(rules.len(), MAX_RULES, &rules_path, errors);
+        for (i, rule) in rules.iter().enumerate() {
+            let rule_path = rules_path.index(i);
+            let matches = rule.matches.as_deref().unwrap_or_default();
+            let matches_path = rule_path.field("matches");
+            validate_len(matches.len(), MAX_MATCHES, &matches_path, errors);
+            for (j, m) in matches.iter().enumerate() {
+                let match_path = matches_path.index(j);
+                if let Some(path_match) = &m.path {
+                    validate_path_match(path_match, &match_path.field("path"), errors);
+                }
+                let headers = m.headers.as_deref().unwrap_or_default();
+                let headers_path = match_path.field("headers");
+                validate_len(headers.len(), MAX_MATCH_CONDITIONS, &headers_path, errors);
+                for (k, h) in headers.iter().enumerate() {
+                    match h {
+                        HttpHeaderMatch::Exact { .. } => {}
+                        HttpHeaderMatch::RegularExpression { value, .. } => {
+                            validate_regex(value, &headers_path.index(k).field("value"), errors);
+                        }
+                        HttpHeaderMatch::Unknown(_) => validate_type(
+                            h.type_name(),
+                            VALUE_MATCH_TYPES,
+                            &headers_path.index(k),
+                            errors,
+                        ),
+                    }
+                }
+                let params = m.query_params.as_deref().unwrap_or_default();
+                let params_path = match_path.field("queryParams");
+                validate_len(params.len(), MAX_MATCH_CONDITIONS, &params_path, errors);
+                for (k, q) in params.iter().enumerate() {
+                    let param_path = params_path.index(k);
+                    let name = match q {
+                        HttpQueryParamMatch::Exact { name, .. } => name,
+                        HttpQueryParamMatch::RegularExpression { name, value } => {
+                            validate_regex(value, &param_path.field("value"), errors);
+                            name
+                        }
+                        HttpQueryParamMatch::Unknown(_) => {
+                            validate_type(q.type_name(), VALUE_MATCH_TYPES, &param_path, errors);
+                            continue;
+                        }
+                    };
+                    validate_query_param_name(name, &param_path.field("name"), errors);
+                }
+            }
+
+            let filters = rule.filters.as_deref().unwrap_or_default();
+            validate_filters(filters, &rule_path.field("filters"), errors);
+            let rule_ok = match filter::check_filters(filters) {
+                Ok(()) => true,
+                Err(e) => {
+                    let path = rule_path.field("filters").index(e.index());
+                    errors.push(ValidationError::invalid(path, e.to_string()));
+                    false
+                }
+            };
+
+            // Backend filters are checked as part of the effective chain, so
+            // that conflicts with the rule's filters are reported on the
+            // backend.
+            let backends = rule.backend_refs.as_deref().unwrap_or_default();
+            let backends_path = rule_path.field("backendRefs");
+            validate_len(backends.len(), MAX_BACKEND_REFS, &backends_path, errors);
+            for (j, backend) in backends.iter().enumerate() {
+                let backend_filters = backend.filters.as_deref().unwrap_or_default();
+                let filters_path = backends_path.index(j).field("filters");
+                validate_filters(backend_filters, &filters_path, errors);
+                let (offset, res) = if rule_ok {
+                    let res = filter::effective_filters(filters, backend_filters).map(|_| ());
+                    (filters.len(), res)
+                } else {
+                    (0, filter::check_filters(backend_filters))
+                };
+                if let Err(e) = res {
+                    let path = backends_path
+                        .index(j)
+                        .field("filters")
+                        .index(e.index() - offset);
+                    errors.push(ValidationError::invalid(path, e.to_string()));
+                }
+            }
+        }
+    }
+
+    fn validate_features_at(
+        &self,
+        gates: &FeatureGates,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        gates.validate_http_route(self, path, errors);
+    }
+}
+
+fn validate_filters(
+    filters: &[HttpRouteFilter],
+    path: &FieldPath,
+    errors: &mut Vec<ValidationError>,
+) {
+    validate_len(filters.len(), MAX_FILTERS, path, errors);
+    for (i, filter) in filters.iter().enumerate() {
+        let (modifier, path) = match filter {
+            HttpRouteFilter::RequestMirror { request_mirror } => {
+                let path = path.index(i).field("requestMirror");
+                validate_mirror(request_mirror, &path, errors);
+                continue;
+            }
+            HttpRouteFilter::RequestRedirect { request_redirect } => (
+                &request_redirect.path,
+                path.index(i).field("requestRedirect").field("path"),
+            ),
+            HttpRouteFilter::URLRewrite { url_rewrite } => (
+                &url_rewrite.path,
+                path.index(i).field("urlRewrite").field("path"),
+            ),
+            #[cfg(feature = "unknown-filters")]
+            HttpRouteFilter::Unknown { type_name, .. } => {
+                validate_type(type_name, FILTER_TYPES, &path.index(i), errors);
+                continue;
+            }
+            _ => continue,
+        };
+        if let Some(modifier @ HttpPathModifier::Unknown(_)) = modifier {
+            validate_type(modifier.type_name(), PATH_MODIFIER_TYPES, &path, errors);
+        }
+    }
+}
+
+fn validate_mirror(
+    mirror: &HttpRequestMirrorFilter,
+    path: &FieldPath,
+    errors: &mut Vec<ValidationError>,
+) {
+    if let Some(percent) = mirror.percent {
+        if !(0..=100).contains(&percent) {
+            errors.push(ValidationError::invalid(
+                path.field("percent"),
+                "must be between 0 and 100",
+            ));
+        }
+    }
+    if let Some(fraction) = &mirror.fraction {
+        if mirror.percent.is_some() {
+            errors.push(ValidationError::invalid(
+                path.field("fraction"),
+                "only one of percent or fraction may be specified",
+            ));
+        }
+        let denominator = fraction.denominator.unwrap_or(100);
+        if denominator < 1 {
+            errors.push(ValidationError::invalid(
+                path.field("fraction").field("denominator"),
+                "must be at least 1",
+            ));
+        }
+        if fraction.numerator < 0 || fraction.numerator > denominator {
+            errors.push(ValidationError::invalid(
+                path.field("fraction").field("numerator"),
+                "must be between 0 and the denominator",
+            ));
+        }
+    }
+}
+
+/// Reports a match, modifier, or filter of an unknown type.
+fn validate_type(
+    type_name: &str,
+    supported: &[&str],
+    path: &FieldPath,
+    errors: &mut Vec<ValidationError>,
+) {
+    errors.push(ValidationError::not_supported(
+        path.field("type"),
+        type_name,
+        supported,
+    ));
+}
+
+fn validate_path_match(m: &HttpPathMatch, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+    let value = match m {
+        HttpPathMatch::Exact { value } | HttpPathMatch::PathPrefix { value } => value,
+        HttpPathMatch::RegularExpression { value } => {
+            validate_regex(value, &path.field("value"), errors);
+            return;
+        }
+        HttpPathMatch::Unknown(_) => {
+            validate_type(m.type_name(), PATH_MATCH_TYPES, path, errors);
+            return;
+        }
+    };
+    if !value.starts_with('/') {
+        errors.push(ValidationError::invalid(
+            path.field("value"),
+            "must be an absolute path",
+        ));
+    } else if value.contains("//") {
+        errors.push(ValidationError::invalid(
+            path.field("value"),
+            "must not contain '//'",
+        ));
+    }
+}
+
+/// Checks that a `RegularExpression` match value compiles in the dialect of
+/// the `regex` crate, which is similar to RE2.
+#[cfg(feature = "regex-validate")]
+fn validate_regex(value: &str, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+    if let Err(e) = regex::Regex::new(value) {
+        errors.push(ValidationError::invalid(
+            path.clone(),
+            format!("invalid regular expression: {}", e),
+        ));
+    }
+}
+
+/// The dialect of regular expressions is implementation-specific, so they
+/// are only checked when the `regex-validate` feature is enabled.
+#[cfg(not(feature = "regex-validate"))]
+fn validate_regex(_: &str, _: &FieldPath, _: &mut Vec<ValidationError>) {}
+
+/// Query parameter names must be 1-256 characters long and consist of the
+/// characters permitted in HTTP tokens (RFC 7230).
+fn validate_query_param_name(name: &str, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+    const MAX_LEN: usize = 256;
+    if name.is_empty() {
+        errors.push(ValidationError::required(path.clone()));
+    } else if name.len() > MAX_LEN {
+        errors.push(ValidationError::invalid(
+            path.clone(),
+            format!("must be no more than {} characters", MAX_LEN),
+        ));
+    } else if !name.chars().all(is_token_char) {
+        errors.push(ValidationError::invalid(
+            path.clone(),
+            "must consist of alphanumeric characters or one of !#$%&'*+-.^_`|~",
+        ));
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+#[cfg(feature = "experimental")]
+impl Validate for BackendLbPolicy {
+    fn validate_at(&self, _: &FieldPath, _: &mut Vec<ValidationError>) {}
+
+    fn validate_features_at(
+        &self,
+        gates: &FeatureGates,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if self.spec.session_persistence.is_some() {
+            let path = path.field("spec").field("sessionPersistence");
+            gates.require(feature_gate::Feature::SessionPersistence, path, errors);
+        }
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl Validate for GrpcRoute {
+    fn validate_at(&self, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+        let spec_path = path.field("spec");
+        validate_common_route(&self.spec.inner, &spec_path, errors);
+        validate_hostnames(
+            self.spec.hostnames.as_deref(),
+            &spec_path.field("hostnames"),
+            errors,
+        );
+
+        let rules = self.spec.rules.as_deref().unwrap_or_default();
+        let rules_path = spec_path.field("rules");
+        validate_len(rules.len(), MAX_RULES, &rules_path, errors);
+        for (i, rule) in rules.iter().enumerate() {
+            let rule_path = rules_path.index(i);
+            let matches = rule.matches.as_deref().unwrap_or_default();
+            let matches_path = rule_path.field("matches");
+            validate_len(matches.len(), MAX_GRPC_MATCHES, &matches_path, errors);
+            for (j, m) in matches.iter().enumerate() {
+                let match_path = matches_path.index(j);
+                if let Some(method @ GrpcMethodMatch::Unknown(_)) = &m.method {
+                    let path = match_path.field("method");
+                    validate_type(method.type_name(), VALUE_MATCH_TYPES, &path, errors);
+                }
+                let headers = m.headers.as_deref().unwrap_or_default();
+                let headers_path = match_path.field("headers");
+                validate_len(headers.len(), MAX_MATCH_CONDITIONS, &headers_path, errors);
+                for (k, h) in headers.iter().enumerate() {
+                    if let GrpcHeaderMatch::Unknown(_) = h {
+                        let path = headers_path.index(k);
+                        validate_type(h.type_name(), VALUE_MATCH_TYPES, &path, errors);
+                    }
+                }
+            }
+
+            let filters = rule.filters.as_ref().map_or(0, Vec::len);
+            validate_len(filters, MAX_FILTERS, &rule_path.field("filters"), errors);
+            let backends = rule.backend_refs.as_deref().unwrap_or_default();
+            let backends_path = rule_path.field("backendRefs");
+            validate_len(backends.len(), MAX_BACKEND_REFS, &backends_path, errors);
+            for (j, backend) in backends.iter().enumerate() {
+                let filters = backend.filters.as_ref().map_or(0, Vec::len);
+                let path = backends_path.index(j).field("filters");
+                validate_len(filters, MAX_FILTERS, &path, errors);
+            }
+        }
+    }
+
+    fn validate_features_at(
+        &self,
+        gates: &FeatureGates,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        gates.validate_grpc_route(self, path, errors);
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl Validate for ReferenceGrant {
+    fn validate_at(&self, _: &FieldPath, _: &mut Vec<ValidationError>) {}
+}
+
+#[cfg(feature = "experimental")]
+impl Validate for XBackendTrafficPolicy {
+    fn validate_at(&self, _: &FieldPath, _: &mut Vec<ValidationError>) {}
+
+    fn validate_features_at(
+        &self,
+        gates: &FeatureGates,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if self.spec.session_persistence.is_some() {
+            let path = path.field("spec").field("sessionPersistence");
+            gates.require(feature_gate::Feature::SessionPersistence, path, errors);
+        }
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl Validate for TcpRoute {
+    fn validate_at(&self, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+        let spec_path = path.field("spec");
+        validate_common_route(&self.spec.inner, &spec_path, errors);
+        let rules = self.spec.rules.iter().map(|r| r.backend_refs.len());
+        validate_backend_rules(rules, &spec_path.field("rules"), errors);
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl Validate for TlsRoute {
+    fn validate_at(&self, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+        let spec_path = path.field("spec");
+        validate_common_route(&self.spec.inner, &spec_path, errors);
+        validate_hostnames(
+            self.spec.hostnames.as_deref(),
+            &spec_path.field("hostnames"),
+            errors,
+        );
+        let rules = self.spec.rules.iter().map(|r| r.backend_refs.len());
+        validate_backend_rules(rules, &spec_path.field("rules"), errors);
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl Validate for UdpRoute {
+    fn validate_at(&self, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+        let spec_path = path.field("spec");
+        validate_common_route(&self.spec.inner, &spec_path, errors);
+        let rules = self.spec.rules.iter().map(|r| r.backend_refs.len());
+        validate_backend_rules(rules, &spec_path.field("rules"), errors);
+    }
+}
+
+/// Validates the number of rules of a TCP, TLS, or UDP route, given the
+/// number of backends of each rule.
+#[cfg(feature = "experimental")]
+fn validate_backend_rules(
+    rules: impl ExactSizeIterator<Item = usize>,
+    path: &FieldPath,
+    errors: &mut Vec<ValidationError>,
+) {
+    validate_len(rules.len(), MAX_RULES, path, errors);
+    for (i, backends) in rules.enumerate() {
+        let path = path.index(i).field("backendRefs");
+        validate_len(backends, MAX_BACKEND_REFS, &path, errors);
+    }
+}
+
+impl Validate for manifest::GatewayApiObject {
+    fn validate_at(&self, path: &FieldPath, errors: &mut Vec<ValidationError>) {
+        match self {
+            Self::GatewayClass(o) => o.validate_at(path, errors),
+            Self::Gateway(o) => o.validate_at(path, errors),
+            Self::HttpRoute(o) => o.validate_at(path, errors),
+            #[cfg(feature = "experimental")]
+            Self::BackendLbPolicy(o) => o.validate_at(path, errors),
+            #[cfg(feature = "experimental")]
+            Self::GrpcRoute(o) => o.validate_at(path, errors),
+            #[cfg(feature = "experimental")]
+            Self::ReferenceGrant(o) => o.validate_at(path, errors),
+            #[cfg(feature = "experimental")]
+            Self::TcpRoute(o) => o.validate_at(path, errors),
+            #[cfg(feature = "experimental")]
+            Self::TlsRoute(o) => o.validate_at(path, errors),
+            #[cfg(feature = "experimental")]
+            Self::UdpRoute(o) => o.validate_at(path, errors),
+            #[cfg(feature = "experimental")]
+            Self::XBackendTrafficPolicy(o) => o.validate_at(path, errors),
+        }
+    }
+    fn validate_features_at(
+        &self,
+        gates: &FeatureGates,
+        path: &FieldPath,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match self {
+            Self::GatewayClass(o) => o.validate_features_at(gates, path, errors),
+            Self::Gateway(o) => o.validate_features_at(gates, path, errors),
+            Self::HttpRoute(o) => o.validate_features_at(gates, path, errors),
+            #[cfg(feature = "experimental")]
+            Self::BackendLbPolicy(o) => o.validate_features_at(gates, path, errors),
+            #[cfg(feature = "experimental")]
+            Self::GrpcRoute(o) => o.validate_features_at(gates, path, errors),
+            #[cfg(feature = "experimental")]
+            Self::ReferenceGrant(o) => o.validate_features_at(gates, path, errors),
+            #[cfg(feature = "experimental")]
+            Self::TcpRoute(o) => o.validate_features_at(gates, path, errors),
+            #[cfg(feature = "experimental")]
+            Self::TlsRoute(o) => o.validate_features_at(gates, path, errors),
+            #[cfg(feature = "experimental")]
+            Self::UdpRoute(o) => o.validate_features_at(gates, path, errors),
+            #[cfg(feature = "experimental")]
+            Self::XBackendTrafficPolicy(o) => o.validate_features_at(gates, path, errors),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gateway(listeners: serde_json::Value) -> Gateway {
+        serde_json::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "Gateway",
+            "metadata": { "name": "web", "namespace": "infra" },
+            "spec": { "gatewayClassName": "acme", "listeners": listeners },
+        }))
+        .unwrap()
+    }
+
+    fn route(spec: serde_json::Value) -> HttpRoute {
+        serde_json::from_value(json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": "app", "namespace": "apps" },
+            "spec": spec,
+        }))
+        .unwrap()
+    }
+
+    fn route_with_match(m: serde_json::Value) -> HttpRoute {
+        route(json!({ "rules": [{ "matches": [m] }] }))
+    }
+
+    fn listener(name: &str) -> serde_json::Value {
+        json!({ "name": name, "port": 80, "protocol": "HTTP" })
+    }
+
+    /// Returns the path and reason of each error.
+    fn errors(obj: &impl Validate) -> Vec<(String, ErrorReason)> {
+        match obj.validate() {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .into_iter()
+                .map(|e| (e.path.to_string(), e.reason))
+                .collect(),
+        }
+    }
+
+    fn error(path: &str, reason: ErrorReason) -> Vec<(String, ErrorReason)> {
+        vec![(path.to_string(), reason)]
+    }
+
+    #[test]
+    fn formats_field_paths() {
+        let path = FieldPath::root()
+            .field("spec")
+            .field("tls")
+            .field("options")
+            .key("example.com/a~b");
+        assert_eq!(path.to_string(), "spec.tls.options[example.com/a~b]");
+        assert_eq!(
+            path.to_json_pointer(),
+            "/spec/tls/options/example.com~1a~0b"
+        );
+
+        let path = FieldPath::root().field("spec").field("listeners").index(0);
+        assert_eq!(path.to_string(), "spec.listeners[0]");
+        assert_eq!(path.to_json_pointer(), "/spec/listeners/0");
+
+        assert!(FieldPath::root().is_root());
+        assert_eq!(FieldPath::root().to_string(), "");
+        assert_eq!(FieldPath::root().to_json_pointer(), "");
+    }
+
+    #[test]
+    fn formats_errors() {
+        let path = FieldPath::root().field("spec").field("listeners");
+        let cases = [
+            (
+                ValidationError::required(path.clone()),
+                ErrorReason::Required,
+                "FieldValueRequired",
+                "spec.listeners: Required value",
+            ),
+            (
+                ValidationError::invalid(path.clone(), "bad"),
+                ErrorReason::Invalid,
+                "FieldValueInvalid",
+                "spec.listeners: bad",
+            ),
+            (
+                ValidationError::new(path.clone(), ErrorReason::NotFound, "missing"),
+                ErrorReason::NotFound,
+                "FieldValueNotFound",
+                "spec.listeners: missing",
+            ),
+            (
+                ValidationError::duplicate(path.clone(), "a"),
+                ErrorReason::Duplicate,
+                "FieldValueDuplicate",
+                "spec.listeners: Duplicate value: \"a\"",
+            ),
+            (
+                ValidationError::not_supported(path.clone(), "a", &["b", "c"]),
+                ErrorReason::NotSupported,
+                "FieldValueNotSupported",
+                "spec.listeners: Unsupported value: \"a\": supported values: [\"b\", \"c\"]",
+            ),
+            (
+                ValidationError::forbidden(path.clone(), "not here"),
+                ErrorReason::Forbidden,
+                "FieldValueForbidden",
+                "spec.listeners: not here",
+            ),
+            (
+                ValidationError::new(path.clone(), ErrorReason::TooLong, "too long"),
+                ErrorReason::TooLong,
+                "FieldValueTooLong",
+                "spec.listeners: too long",
+            ),
+            (
+                ValidationError::too_many(path, 3, 2),
+                ErrorReason::TooMany,
+                "FieldValueTooMany",
+                "spec.listeners: Too many: 3: must have at most 2 items",
+            ),
+        ];
+        for (error, reason, cause, display) in cases {
+            assert_eq!(error.reason, reason);
+            assert_eq!(reason.to_string(), cause);
+            assert_eq!(error.to_string(), display);
+            assert_eq!(error.to_status_cause().reason.as_deref(), Some(cause));
+        }
+
+        let root = ValidationError::invalid(FieldPath::root(), "bad");
+        assert_eq!(root.to_string(), "bad");
+    }
+
+    #[test]
+    fn builds_statuses() {
+        let errors = [
+            ValidationError::required(FieldPath::root().field("spec").field("listeners")),
+            ValidationError::invalid(FieldPath::root().field("metadata"), "bad"),
+        ];
+        let status = to_status(consts::GROUP, "Gateway", Some("web"), &errors);
+        assert_eq!(status.code, Some(422));
+        assert_eq!(status.reason.as_deref(), Some("Invalid"));
+        assert_eq!(status.status.as_deref(), Some("Failure"));
+        assert_eq!(
+            status.message.as_deref(),
+            Some("Gateway \"web\" is invalid: spec.listeners: Required value, metadata: bad")
+        );
+        assert_eq!(
+            serde_json::to_value(&status.details).unwrap(),
+            json!({
+                    "group": "gateway.networking.k8s.io",
+                    "kind": "Gateway",
+                    "name": "web",
+                    "causes": [
+                        {
+                            "field": "spec.listeners",
+                            "message": "Required value",
+                            "reason": "FieldValueRequired",
+                        },
+                        {
+                            "field": "metadata",
+                            "message": "bad",
+                            "reason": "FieldValueInvalid",
+                        },
+                    ],
+            })
+        );
+
+        let status = to_status(consts::GROUP, "Gateway", None, &errors[..1]);
+        assert_eq!(
+            status.message.as_deref(),
+            Some("Gateway is invalid: spec.listeners: Required value")
+        );
+        assert_eq!(status.details.unwrap().name, None);
+    }
+
+    #[test]
+    fn validates_gateways() {
+        assert_eq!(errors(&gateway(json!([listener("a")]))), []);
+        assert_eq!(
+            errors(&gateway(json!([]))),
+            error("spec.listeners", ErrorReason::Required)
+        );
+        assert_eq!(
+            errors(&gateway(json!([listener("a"), listener("a")]))),
+            error("spec.listeners[1].name", ErrorReason::Duplicate)
+        );
+        assert_eq!(
+            errors(&gateway(json!([{
+                "name": "a",
+                "port": 443,
+                "protocol": "HTTPS",
+                "tls": { "mode": "Terminate" },
+            }]))),
+            error(
+                "spec.listeners[0].tls.certificateRefs",
+                ErrorReason::Required
+            )
+        );
+        assert_eq!(
+            errors(&gateway(json!([{
+                "name": "a",
+                "port": 443,
+                "protocol": "HTTPS",
+                "tls": { "mode": "Reencrypt" },
+            }]))),
+            error("spec.listeners[0].tls.mode", ErrorReason::NotSupported)
+        );
+    }
+
+    #[test]
+    fn limits_list_lengths() {
+        let listeners = (0..=MAX_LISTENERS)
+            .map(|i| listener(&format!("l{}", i)))
+            .collect::<Vec<_>>();
+        assert_eq!(errors(&gateway(json!(listeners[..MAX_LISTENERS]))), []);
+        assert_eq!(
+            errors(&gateway(json!(listeners))),
+            error("spec.listeners", ErrorReason::TooMany)
+        );
+
+        let hostnames = (0..=MAX_HOSTNAMES)
+            .map(|i| format!("h{}.example.com", i))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors(&route(json!({ "hostnames": hostnames }))),
+            error("spec.hostnames", ErrorReason::TooMany)
+        );
+
+        let parents = vec![json!({ "name": "web" }); MAX_PARENT_REFS + 1];
+        assert_eq!(
+            errors(&route(json!({ "parentRefs": parents }))),
+            error("spec.parentRefs", ErrorReason::TooMany)
+        );
+
+        let rules = vec![json!({}); MAX_RULES + 1];
+        assert_eq!(
+            errors(&route(json!({ "rules": rules }))),
+            error("spec.rules", ErrorReason::TooMany)
+        );
+
+        let matches = vec![json!({}); MAX_MATCHES + 1];
+        assert_eq!(
+            errors(&route(json!({ "rules": [{ "matches": matches }] }))),
+            error("spec.rules[0].matches", ErrorReason::TooMany)
+        );
+
+        let headers = (0..=MAX_MATCH_CONDITIONS)
+            .map(|i| json!({ "name": format!("h{}", i), "value": "v" }))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors(&route_with_match(json!({ "headers": headers }))),
+            error("spec.rules[0].matches[0].headers", ErrorReason::TooMany)
+        );
+        assert_eq!(
+            errors(&route_with_match(json!({ "queryParams": headers }))),
+            error("spec.rules[0].matches[0].queryParams", ErrorReason::TooMany)
+        );
+
+        let filters = vec![
+            json!({ "type": "RequestMirror", "requestMirror": { "backendRef": { "name": "m" } } });
+            MAX_FILTERS + 1
+        ];
+        assert_eq!(
+            errors(&route(json!({ "rules": [{ "filters": filters }] }))),
+            error("spec.rules[0].filters", ErrorReason::TooMany)
+        );
+
+        let backends = vec![json!({ "name": "app", "port": 8080 }); MAX_BACKEND_REFS + 1];
+        assert_eq!(
+            errors(&route(json!({ "rules": [{ "backendRefs": backends }] }))),
+            error("spec.rules[0].backendRefs", ErrorReason::TooMany)
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_hostnames_case_insensitively() {
+        assert_eq!(
+            errors(&route(json!({
+                "hostnames": ["example.com", "a.example.com", "Example.com"],
+            }))),
+            error("spec.hostnames[2]", ErrorReason::Duplicate)
+        );
+    }
+
+    #[test]
+    fn validates_path_matches() {
+        let path = |ty: &str, value: &str| {
+            errors(&route_with_match(
+                json!({ "path": { "type": ty, "value": value } }),
+            ))
+        };
+        assert_eq!(path("PathPrefix", "/foo"), []);
+        assert_eq!(
+            path("PathPrefix", "foo"),
+            error("spec.rules[0].matches[0].path.value", ErrorReason::Invalid)
+        );
+        assert_eq!(
+            path("Exact", "/foo//bar"),
+            error("spec.rules[0].matches[0].path.value", ErrorReason::Invalid)
+        );
+    }
+
+    #[test]
+    fn validates_query_param_names() {
+        let name = |name: &str| {
+            errors(&route_with_match(
+                json!({ "queryParams": [{ "name": name, "value": "v" }] }),
+            ))
+        };
+        let path = "spec.rules[0].matches[0].queryParams[0].name";
+        assert_eq!(name("a!#$%&'*+-.^_`|~9"), []);
+        assert_eq!(name(""), error(path, ErrorReason::Required));
+        assert_eq!(name("a b"), error(path, ErrorReason::Invalid));
+        assert_eq!(name("a=b"), error(path, ErrorReason::Invalid));
+        assert_eq!(name(&"a".repeat(256)), []);
+        assert_eq!(name(&"a".repeat(257)), error(path, ErrorReason::Invalid));
+    }
+
+    #[test]
+    fn validates_regular_expressions() {
+        let regex = |value: &str| {
+            errors(&route_with_match(json!({
+                "path": { "type": "RegularExpression", "value": value },
+                "headers": [{ "type": "RegularExpression", "name": "h", "value": value }],
+                "queryParams": [{ "type": "RegularExpression", "name": "q", "value": value }],
+            })))
+        };
+        assert_eq!(regex("/a.*"), []);
+
+        let expected = if cfg!(feature = "regex-validate") {
+            vec![
+                (
+                    "spec.rules[0].matches[0].path.value".to_string(),
+                    ErrorReason::Invalid,
+                ),
+                (
+                    "spec.rules[0].matches[0].headers[0].value".to_string(),
+                    ErrorReason::Invalid,
+                ),
+                (
+                    "spec.rules[0].matches[0].queryParams[0].value".to_string(),
+                    ErrorReason::Invalid,
+                ),
+            ]
+        } else {
+            Vec::new()
+        };
+        assert_eq!(regex("/a("), expected);
+    }
+
+    #[test]
+    fn locates_backend_filter_conflicts() {
+        let header = json!({
+            "type": "RequestHeaderModifier",
+            "requestHeaderModifier": { "set": [{ "name": "a", "value": "b" }] },
+        });
+        let rewrite = json!({ "type": "URLRewrite", "urlRewrite": { "hostname": "example.com" } });
+        let redirect =
+            json!({ "type": "RequestRedirect", "requestRedirect": { "statusCode": 302 } });
+        let rule = |filters: serde_json::Value, backend_filters: serde_json::Value| {
+            errors(&route(json!({
+                "rules": [{
+                    "filters": filters,
+                    "backendRefs": [
+                        { "name": "a", "port": 8080 },
+                        { "name": "b", "port": 8080, "filters": backend_filters },
+                    ],
+                }],
+            })))
+        };
+
+        // A backend may repeat a filter type that its rule configures.
+        assert_eq!(rule(json!([header, rewrite]), json!([header])), []);
+
+        // Conflicts with the rule's filters are reported on the backend's
+        // filter, offset by the rule's filters.
+        assert_eq!(
+            rule(json!([header, rewrite]), json!([header, redirect])),
+            error(
+                "spec.rules[0].backendRefs[1].filters[1]",
+                ErrorReason::Invalid
+            )
+        );
+
+        // If the rule's filters are invalid, the backend's are checked alone.
+        assert_eq!(
+            rule(json!([rewrite, redirect]), json!([header, header])),
+            vec![
+                ("spec.rules[0].filters[1]".to_string(), ErrorReason::Invalid),
+                (
+                    "spec.rules[0].backendRefs[1].filters[1]".to_string(),
+                    ErrorReason::Invalid
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn schemas_bound_lists_by_limits() {
+        let max_items = |schema: &serde_json::Value, pointer: &str| {
+            schema
+                .pointer(&format!("{}/maxItems", pointer))
+                .and_then(|v| v.as_u64())
+                .unwrap_or_else(|| panic!("{} has no maxItems", pointer))
+        };
+
+        let gateway = serde_json::to_value(schemars::schema_for!(GatewaySpec)).unwrap();
+        assert_eq!(
+            max_items(&gateway, "/properties/listeners"),
+            MAX_LISTENERS as u64
+        );
+        assert_eq!(
+            max_items(&gateway, "/properties/addresses"),
+            crate::addresses::MAX_ADDRESSES as u64
+        );
+
+        let route = serde_json::to_value(schemars::schema_for!(HttpRouteSpec)).unwrap();
+        assert_eq!(
+            max_items(&route, "/properties/hostnames"),
+            MAX_HOSTNAMES as u64
+        );
+        assert_eq!(
+            max_items(&route, "/properties/parentRefs"),
+            MAX_PARENT_REFS as u64
+        );
+        assert_eq!(max_items(&route, "/properties/rules"), MAX_RULES as u64);
+        let rule = "/definitions/HttpRouteRule/properties";
+        assert_eq!(
+            max_items(&route, &format!("{}/matches", rule)),
+            MAX_MATCHES as u64
+        );
+        assert_eq!(
+            max_items(&route, &format!("{}/filters", rule)),
+            MAX_FILTERS as u64
+        );
+        assert_eq!(
+            max_items(&route, &format!("{}/backendRefs", rule)),
+            MAX_BACKEND_REFS as u64
+        );
+    }
+}
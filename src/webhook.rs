@@ -0,0 +1,282 @@
+//! A validating admission webhook for Gateway API resources.
+//!
+//! [`handle`] decodes an `AdmissionReview`, validates the submitted object with
+//! the [`validation`](crate::validation) module, and encodes the response. It
+//! may be served with any hyper-compatible server; TLS termination, which the
+//! Kubernetes API server requires of webhooks, is left to the caller:
+//!
+//! ```ignore
+//! let make_svc = hyper::service::make_service_fn(|_| async {
+//!     Ok::<_, std::convert::Infallible>(hyper::service::service_fn(webhook::handle))
+//! });
+//! hyper::Server::builder(tls_acceptor).serve(make_svc).await?;
+//! ```
+
+use crate::{
+    feature_gate::FeatureGates,
+    manifest::{self, GatewayApiObject},
+    patch,
+    validation::{self, Validate, ValidationError},
+};
+use hyper::{header, Body, Method, Request, Response, StatusCode};
+use kube::core::DynamicObject;
+use std::convert::Infallible;
+
+pub use kube::core::admission::{AdmissionRequest, AdmissionResponse, AdmissionReview, Operation};
+
+/// Handles an HTTP request containing an `AdmissionReview`.
+///
+/// Requests that are not `POST`s are rejected with `405 Method Not Allowed`.
+/// Reviews that cannot be decoded are answered with a response that denies
+/// the request.
+pub async fn handle(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::POST {
+        return Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .expect("response must be valid"));
+    }
+
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) => body,
+        Err(e) => {
+            return Ok(Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(e.to_string()))
+                .expect("response must be valid"))
+        }
+    };
+
+    let review = match serde_json::from_slice::<AdmissionReview<DynamicObject>>(&body) {
+        Ok(review) => self::review(review),
+        Err(e) => AdmissionResponse::invalid(e).into_review(),
+    };
+    let body = serde_json::to_vec(&review).expect("admission review must serialize");
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("response must be valid"))
+}
+
+/// Answers an `AdmissionReview`.
+pub fn review(review: AdmissionReview<DynamicObject>) -> AdmissionReview<DynamicObject> {
+    let rsp = match review.try_into() {
+        Ok(req) => validate(&req),
+        Err(e) => AdmissionResponse::invalid(e),
+    };
+    rsp.into_review()
+}
+
+/// Validates the object submitted with an admission request.
+///
+/// Deletions, and objects of kinds that are not known to this crate, are
+/// always admitted.
+pub fn validate(req: &AdmissionRequest<DynamicObject>) -> AdmissionResponse {
+    validate_object(req, None)
+}
+
+/// Validates the object submitted with an admission request, also denying
+/// objects that set fields requiring features that are not enabled by
+/// `gates`.
+pub fn validate_with_feature_gates(
+    req: &AdmissionRequest<DynamicObject>,
+    gates: &FeatureGates,
+) -> AdmissionResponse {
+    validate_object(req, Some(gates))
+}
+
+fn validate_object(
+    req: &AdmissionRequest<DynamicObject>,
+    gates: Option<&FeatureGates>,
+) -> AdmissionResponse {
+    let rsp = AdmissionResponse::from(req);
+    if req.operation == Operation::Delete {
+        return rsp;
+    }
+    let obj = match &req.object {
+        Some(obj) => obj,
+        None => return rsp,
+    };
+
+    let value = match serde_json::to_value(obj) {
+        Ok(value) => value,
+        Err(e) => return rsp.deny(e),
+    };
+    let obj = match GatewayApiObject::from_value(value) {
+        Ok(obj) => obj,
+        Err(manifest::Error::UnknownKind { .. }) => return rsp,
+        Err(e) => return rsp.deny(e),
+    };
+
+    let validated = match gates {
+        Some(gates) => obj.validate_with_feature_gates(gates),
+        None => obj.validate(),
+    };
+    match validated {
+        Ok(()) => rsp,
+        Err(errors) => deny(rsp, &req.kind.group, &obj, &errors),
+    }
+}
+
+/// Adds the JSON Patch that transforms `before` into `after` to a response,
+/// so that a mutating webhook may return an object that it has mutated.
+///
+/// The request is denied if the objects cannot be serialized.
+pub fn with_patch<K: serde::Serialize>(
+    rsp: AdmissionResponse,
+    before: &K,
+    after: &K,
+) -> AdmissionResponse {
+    let ops = match patch::diff(before, after) {
+        Ok(ops) => ops,
+        Err(e) => return rsp.deny(e),
+    };
+    if ops.is_empty() {
+        return rsp;
+    }
+    let ops = serde_json::to_value(ops).expect("patch operations must serialize");
+    let patch = serde_json::from_value::<json_patch::Patch>(ops)
+        .expect("patch operations must be valid JSON Patch operations");
+    match rsp.clone().with_patch(patch) {
+        Ok(rsp) => rsp,
+        Err(e) => rsp.deny(e),
+    }
+}
+
+/// Denies a request as `Invalid`, identifying each offending field.
+fn deny(
+    mut rsp: AdmissionResponse,
+    group: &str,
+    obj: &GatewayApiObject,
+    errors: &[ValidationError],
+) -> AdmissionResponse {
+    let mut status = validation::to_status(group, obj.kind(), obj.name(), errors);
+    if let Some(details) = status.details.as_mut() {
+        details.uid = obj.metadata().uid.clone();
+    }
+    let status = serde_json::to_value(status).expect("status must serialize");
+
+    rsp.allowed = false;
+    rsp.result = serde_json::from_value(status).expect("status must be a valid Status");
+    rsp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts;
+    use serde_json::json;
+
+    fn admission_review(object: serde_json::Value) -> serde_json::Value {
+        let (group, version) = object["apiVersion"]
+            .as_str()
+            .unwrap()
+            .split_once('/')
+            .unwrap();
+        let kind = consts::find(object["kind"].as_str().unwrap()).unwrap();
+        json!({
+            "apiVersion": "admission.k8s.io/v1",
+            "kind": "AdmissionReview",
+            "request": {
+                "uid": "00000000-0000-0000-0000-000000000001",
+                "kind": { "group": group, "version": version, "kind": kind.kind },
+                "resource": { "group": group, "version": version, "resource": kind.plural },
+                "name": object["metadata"]["name"],
+                "namespace": object["metadata"]["namespace"],
+                "operation": "CREATE",
+                "userInfo": {},
+                "object": object,
+            },
+        })
+    }
+
+    fn request(object: serde_json::Value) -> AdmissionRequest<DynamicObject> {
+        serde_json::from_value::<AdmissionReview<DynamicObject>>(admission_review(object))
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    async fn post(body: Vec<u8>) -> (StatusCode, serde_json::Value) {
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let rsp = handle(req).await.unwrap();
+        let status = rsp.status();
+        let body = hyper::body::to_bytes(rsp.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn handles_reviews() {
+        let route = json!({
+            "apiVersion": "gateway.networking.k8s.io/v1beta1",
+            "kind": "HTTPRoute",
+            "metadata": { "name": "web", "namespace": "default", "uid": "1234" },
+            "spec": {
+                "rules": [{
+                    "matches": [{ "path": { "type": "PathPrefix", "value": "web" } }],
+                }],
+            },
+        });
+        let errors = GatewayApiObject::from_value(route.clone())
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        let mut status = validation::to_status(consts::GROUP, "HTTPRoute", Some("web"), &errors);
+        status.details.as_mut().unwrap().uid = Some("1234".to_string());
+
+        let (code, review) = post(serde_json::to_vec(&admission_review(route)).unwrap()).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(review["kind"], "AdmissionReview");
+        let rsp = &review["response"];
+        assert_eq!(rsp["uid"], "00000000-0000-0000-0000-000000000001");
+        assert_eq!(rsp["allowed"], false);
+        assert_eq!(rsp["status"]["code"], 422);
+        assert_eq!(rsp["status"]["reason"], "Invalid");
+        assert_eq!(rsp["status"]["message"], json!(status.message));
+        assert_eq!(rsp["status"]["details"], json!(status.details));
+        assert_eq!(
+            rsp["status"]["details"]["causes"][0]["field"],
+            "spec.rules[0].matches[0].path.value"
+        );
+
+        let (code, review) = post(b"{}".to_vec()).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(review["response"]["allowed"], false);
+
+        let req = Request::builder()
+            .method(Method::GET)
+            .body(Body::empty())
+            .unwrap();
+        let rsp = handle(req).await.unwrap();
+        assert_eq!(rsp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn validates_experimental_group() {
+        let req = request(json!({
+            "apiVersion": "gateway.networking.x-k8s.io/v1alpha1",
+            "kind": "XBackendTrafficPolicy",
+            "metadata": { "name": "retries", "namespace": "default" },
+            "spec": {
+                "targetRefs": [{ "group": "", "kind": "Service", "name": "web" }],
+                "sessionPersistence": { "sessionName": "web" },
+            },
+        }));
+        assert!(validate(&req).allowed);
+        assert!(validate_with_feature_gates(&req, &FeatureGates::experimental()).allowed);
+
+        let rsp = validate_with_feature_gates(&req, &FeatureGates::default());
+        assert!(!rsp.allowed);
+        let details = rsp.result.details.unwrap();
+        assert_eq!(details.group, consts::EXPERIMENTAL_GROUP);
+        assert_eq!(details.kind, "XBackendTrafficPolicy");
+        assert_eq!(details.causes.len(), 1);
+        assert_eq!(details.causes[0].field, "spec.sessionPersistence");
+    }
+}
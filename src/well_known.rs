@@ -0,0 +1,264 @@
+//! Well-known Gateway API labels and annotations.
+//!
+//! Implementations that provision infrastructure for a Gateway (e.g. a
+//! Service and a Deployment) label the generated resources with the name of
+//! the Gateway so that they may be discovered consistently across
+//! implementations. The Gateway API CRDs themselves are annotated with the
+//! bundle version and release channel they were generated from.
+//!
+//! [`MetadataExt`] provides typed accessors for these keys on any
+//! `ObjectMeta`:
+//!
+//! ```
+//! # use k8s_gateway_api::well_known::MetadataExt;
+//! # use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+//! let mut meta = ObjectMeta::default();
+//! meta.set_gateway_name("public");
+//! assert_eq!(meta.gateway_name(), Some("public"));
+//! ```
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as metav1;
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// Label set on resources generated for a Gateway, whose value is the name
+/// of the Gateway.
+pub const GATEWAY_NAME_LABEL: &str = "gateway.networking.k8s.io/gateway-name";
+
+/// Annotation set on Gateway API CRDs, whose value is the version of the
+/// Gateway API bundle the CRD was generated from, e.g. `v0.5.0`.
+pub const BUNDLE_VERSION_ANNOTATION: &str = "gateway.networking.k8s.io/bundle-version";
+
+/// Annotation set on Gateway API CRDs, whose value is the release
+/// [`Channel`] the CRD was generated from.
+pub const CHANNEL_ANNOTATION: &str = "gateway.networking.k8s.io/channel";
+
+/// A Gateway API release channel.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Channel {
+    /// The standard channel includes only resources and fields that have
+    /// graduated to beta or GA.
+    Standard,
+
+    /// The experimental channel additionally includes alpha resources and
+    /// fields.
+    Experimental,
+}
+
+/// Indicates that a channel annotation has an unknown value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnknownChannel(pub String);
+
+/// A Gateway API bundle version, e.g. `v1.1.0` or `v0.5.0-rc1`, as recorded
+/// by the [`BUNDLE_VERSION_ANNOTATION`].
+///
+/// Versions are ordered as semantic versions: a pre-release precedes its
+/// release, and pre-releases of the same version are ordered by their
+/// identifiers.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BundleVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+
+    /// The pre-release identifiers, e.g. `rc1`.
+    pub pre: Option<String>,
+}
+
+/// Indicates that a bundle version annotation is not a valid version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvalidBundleVersion(pub String);
+
+/// Typed accessors for well-known labels and annotations.
+pub trait MetadataExt {
+    /// Returns the value of the [`GATEWAY_NAME_LABEL`] label.
+    fn gateway_name(&self) -> Option<&str>;
+
+    /// Sets the [`GATEWAY_NAME_LABEL`] label.
+    fn set_gateway_name(&mut self, name: impl Into<String>);
+
+    /// Returns the value of the [`BUNDLE_VERSION_ANNOTATION`] annotation.
+    fn bundle_version(&self) -> Option<&str>;
+
+    /// Sets the [`BUNDLE_VERSION_ANNOTATION`] annotation.
+    fn set_bundle_version(&mut self, version: impl Into<String>);
+
+    /// Returns the value of the [`CHANNEL_ANNOTATION`] annotation, if it is
+    /// set.
+    fn channel(&self) -> Option<Result<Channel, UnknownChannel>>;
+
+    /// Sets the [`CHANNEL_ANNOTATION`] annotation.
+    fn set_channel(&mut self, channel: Channel);
+}
+
+// === impl MetadataExt ===
+
+impl MetadataExt for metav1::ObjectMeta {
+    fn gateway_name(&self) -> Option<&str> {
+        get(&self.labels, GATEWAY_NAME_LABEL)
+    }
+
+    fn set_gateway_name(&mut self, name: impl Into<String>) {
+        set(&mut self.labels, GATEWAY_NAME_LABEL, name.into())
+    }
+
+    fn bundle_version(&self) -> Option<&str> {
+        get(&self.annotations, BUNDLE_VERSION_ANNOTATION)
+    }
+
+    fn set_bundle_version(&mut self, version: impl Into<String>) {
+        set(
+            &mut self.annotations,
+            BUNDLE_VERSION_ANNOTATION,
+            version.into(),
+        )
+    }
+
+    fn channel(&self) -> Option<Result<Channel, UnknownChannel>> {
+        get(&self.annotations, CHANNEL_ANNOTATION).map(str::parse)
+    }
+
+    fn set_channel(&mut self, channel: Channel) {
+        set(
+            &mut self.annotations,
+            CHANNEL_ANNOTATION,
+            channel.as_str().to_string(),
+        )
+    }
+}
+
+type Map = Option<std::collections::BTreeMap<String, String>>;
+
+fn get<'m>(map: &'m Map, key: &str) -> Option<&'m str> {
+    map.as_ref()?.get(key).map(String::as_str)
+}
+
+fn set(map: &mut Map, key: &str, value: String) {
+    map.get_or_insert_with(Default::default)
+        .insert(key.to_string(), value);
+}
+
+// === impl BundleVersion ===
+
+impl BundleVersion {
+    /// Returns the release version with the given components.
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: None,
+        }
+    }
+}
+
+impl fmt::Display for BundleVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a version with an optional leading `v`.
+impl FromStr for BundleVersion {
+    type Err = InvalidBundleVersion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidBundleVersion(s.to_string());
+        let version = s.strip_prefix('v').unwrap_or(s);
+        let (version, pre) = match version.split_once('-') {
+            Some((_, "")) => return Err(invalid()),
+            Some((version, pre)) => (version, Some(pre.to_string())),
+            None => (version, None),
+        };
+
+        let mut parts = version.split('.').map(|p| {
+            if p.is_empty() || !p.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            p.parse().map_err(|_| invalid())
+        });
+        let mut next = || parts.next().unwrap_or_else(|| Err(invalid()));
+        let (major, minor, patch) = (next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl PartialOrd for BundleVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BundleVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+// === impl InvalidBundleVersion ===
+
+impl fmt::Display for InvalidBundleVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid bundle version {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidBundleVersion {}
+
+// === impl Channel ===
+
+impl Channel {
+    /// Returns the value of the channel annotation for this channel.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Experimental => "experimental",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Channel {
+    type Err = UnknownChannel;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Self::Standard),
+            "experimental" => Ok(Self::Experimental),
+            s => Err(UnknownChannel(s.to_string())),
+        }
+    }
+}
+
+// === impl UnknownChannel ===
+
+impl fmt::Display for UnknownChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown release channel {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownChannel {}